@@ -4,25 +4,33 @@
 mod access;
 mod block;
 mod call;
+mod chunk;
+mod differential;
 mod execution;
+mod gas_audit;
 mod input_state_ref;
+mod prefilter;
+mod state_override;
 #[cfg(test)]
 mod tracer_tests;
 mod transaction;
 mod withdrawal;
+mod witness_input;
 
 use self::access::gen_state_access_trace;
 use crate::{
     error::Error,
     evm::opcodes::{gen_associated_ops, gen_associated_steps},
-    operation::{CallContextField, Operation, RWCounter, StartOp, RW},
+    operation::{AccountField, CallContextField, Operation, RWCounter, StartOp, RW},
     rpc::GethClient,
     state_db::{self, CodeDB, StateDB},
 };
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
 pub use block::{Block, BlockContext};
 pub use call::{Call, CallContext, CallKind};
+pub use chunk::Chunk;
 use core::fmt::Debug;
+pub use differential::{diff_block, Divergence, DivergenceKind};
 use eth_types::{
     self, geth_types,
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
@@ -32,20 +40,31 @@ use ethers_providers::JsonRpcClient;
 pub use execution::{
     CopyDataType, CopyEvent, CopyStep, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
 };
+use futures::{stream, Future, StreamExt, TryStreamExt};
+pub use gas_audit::{audit_gas, GasAuditReport, GasMismatch, GasMismatchKind};
 pub use input_state_ref::CircuitInputStateRef;
 use itertools::Itertools;
 use log::warn;
+pub use prefilter::{
+    check_block_support, is_opcode_supported, is_precompile_supported, UnsupportedFeature,
+    UnsupportedReport, MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS,
+    MAX_DESTRUCTED_ACCOUNTS, SUPPORTED_TX_TYPES,
+};
+use serde::{Deserialize, Serialize};
+pub use state_override::apply_state_override;
 use std::{
     collections::{HashMap, HashSet},
     ops::Deref,
 };
 pub use transaction::{Transaction, TransactionContext};
 pub use withdrawal::{Withdrawal, WithdrawalContext};
+pub use witness_input::{WitnessAccount, WitnessInput, WITNESS_INPUT_VERSION};
 
 /// Runtime Config
 ///
 /// Default to mainnet block
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FeatureConfig {
     /// Zero difficulty
     pub zero_difficulty: bool,
@@ -59,6 +78,9 @@ pub struct FeatureConfig {
     /// shouldn't be included in a mainnet block. However, rollup developers might want to
     /// include invalid tx in the L2 block to support forced exit feature.
     pub invalid_tx: bool,
+    /// Compare the witness of each handled block with its traces, and fail with the
+    /// divergences, see [`diff_block`]
+    pub differential: bool,
 }
 
 impl Default for FeatureConfig {
@@ -68,6 +90,7 @@ impl Default for FeatureConfig {
             free_first_tx: false,
             enable_eip1559: true,
             invalid_tx: false,
+            differential: false,
         }
     }
 }
@@ -80,7 +103,7 @@ impl FeatureConfig {
 }
 
 /// Circuit Setup Parameters
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct FixedCParams {
     /// Maximum number of rw operations in the state circuit (RwTable length /
     /// nummber of rows). This must be at least the number of rw operations
@@ -93,6 +116,8 @@ pub struct FixedCParams {
     pub max_withdrawals: usize,
     /// Maximum number of bytes from all txs calldata in the Tx Circuit
     pub max_calldata: usize,
+    /// Maximum number of ecRecover signatures verified in the Tx Circuit
+    pub max_ecrecover: usize,
     /// Max amount of rows that the CopyCircuit can have.
     pub max_copy_rows: usize,
     /// Max number of steps that the ExpCircuit can have. Each step is further
@@ -146,6 +171,7 @@ impl Default for FixedCParams {
             max_txs: 1,
             max_withdrawals: 1,
             max_calldata: 256,
+            max_ecrecover: 0,
             // TODO: Check whether this value is correct or we should increase/decrease based on
             // this lib tests
             max_copy_rows: 1000,
@@ -256,6 +282,7 @@ impl<'a, C: CircuitsParams> CircuitInputBuilder<C> {
             &self.sdb,
             &mut self.code_db,
             eth_tx,
+            self.block.base_fee,
             is_success,
         )
     }
@@ -370,6 +397,35 @@ impl CircuitInputBuilder<FixedCParams> {
             )?;
         }
 
+        // Credit the withdrawals (EIP-4895) after the last transaction.  The credits are not
+        // reversible and, like any transfer, create the accounts that don't exist, which is
+        // proven by the read of their code hash.
+        for wd in state.block.withdrawals() {
+            if wd.amount == 0 {
+                continue;
+            }
+            let (_, account) = state.sdb.get_account(&wd.address);
+            let exists = !account.is_empty();
+            state.account_read(
+                &mut end_block_last,
+                wd.address,
+                AccountField::CodeHash,
+                if exists {
+                    account.code_hash.to_word()
+                } else {
+                    Word::zero()
+                },
+            )?;
+            state.transfer_to(
+                &mut end_block_last,
+                wd.address,
+                exists,
+                false,
+                wd.amount_in_wei(),
+                false,
+            )?;
+        }
+
         let mut push_op = |step: &mut ExecStep, rwc: RWCounter, rw: RW, op: StartOp| {
             let op_ref = state.block.container.insert(Operation::new(rwc, rw, op));
             step.bus_mapping_instance.push(op_ref);
@@ -431,8 +487,40 @@ impl<C: CircuitsParams> CircuitInputBuilder<C> {
         // set eth_block
         self.block.eth_block = eth_block.clone();
         self.set_value_ops_call_context_rwc_eor();
+        if self.feature_config.differential {
+            let divergences = diff_block(&self.block, geth_traces);
+            if !divergences.is_empty() {
+                return Err(Error::WitnessDivergence(divergences));
+            }
+        }
         Ok(())
     }
+
+    /// Split the handled transactions into the fewest chunks of consecutive transactions whose
+    /// operations fit in `max_rws` rows.  Returns an error if a transaction doesn't fit.
+    pub fn chunks(&self, max_rws: usize) -> Result<Vec<Chunk>, Error> {
+        chunk::split_into_chunks(&self.block, self.block_ctx.rwc.0, max_rws)
+    }
+
+    /// Number of rws of the withdrawal credits done in `set_end_block`: a code hash read and a
+    /// balance write per withdrawal with a non-zero amount, plus a code hash write for each
+    /// account that the credits create.
+    fn withdrawal_rws(&self) -> usize {
+        let mut created = HashSet::new();
+        self.block
+            .withdrawals()
+            .iter()
+            .filter(|wd| wd.amount != 0)
+            .map(|wd| {
+                let (_, account) = self.sdb.get_account(&wd.address);
+                if account.is_empty() && created.insert(wd.address) {
+                    3
+                } else {
+                    2
+                }
+            })
+            .sum()
+    }
 }
 impl CircuitInputBuilder<DynamicCParams> {
     /// Handle a block by handling each transaction to generate all the
@@ -455,6 +543,7 @@ impl CircuitInputBuilder<DynamicCParams> {
                 .transactions
                 .iter()
                 .fold(0, |acc, tx| acc + tx.input.len());
+            let max_ecrecover = self.block.ecrecover_events.len();
             let max_exp_steps = self
                 .block
                 .exp_events
@@ -476,7 +565,8 @@ impl CircuitInputBuilder<DynamicCParams> {
                 <RWCounter as Into<usize>>::into(self.block_ctx.rwc) - 1; // -1 since rwc start from index `1`
             let max_rws_after_padding = total_rws_before_padding
                 + 1 // fill 1 to have exactly one StartOp padding in below `set_end_block`
-                + if total_rws_before_padding > 0 { 1 /*end_block -> CallContextFieldTag::TxId lookup*/ } else { 0 };
+                + if total_rws_before_padding > 0 { 1 /*end_block -> CallContextFieldTag::TxId lookup*/ } else { 0 }
+                + self.withdrawal_rws();
             // Computing the number of rows for the EVM circuit requires the size of ExecStep,
             // which is determined in the code of zkevm-circuits and cannot be imported here.
             // When the evm circuit receives a 0 value it dynamically computes the minimum
@@ -492,6 +582,7 @@ impl CircuitInputBuilder<DynamicCParams> {
                 max_txs,
                 max_withdrawals,
                 max_calldata,
+                max_ecrecover,
                 max_copy_rows,
                 max_exp_steps,
                 max_bytecode,
@@ -530,6 +621,10 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
     for input in &block.sha3_inputs {
         keccak_inputs.insert(input.clone());
     }
+    // ecRecover signatures, verified in the Tx Circuit
+    for input in keccak_inputs_sign_verify(&block.ecrecover_events) {
+        keccak_inputs.insert(input);
+    }
     // MPT Circuit
     // TODO https://github.com/privacy-scaling-explorations/zkevm-circuits/issues/696
     Ok(keccak_inputs.into_iter().collect_vec())
@@ -609,6 +704,49 @@ pub fn get_call_memory_offset_length(step: &GethExecStep, nth: usize) -> Result<
 
 type EthBlock = eth_types::Block<eth_types::Transaction>;
 
+/// Configuration of the queries of the pre-state sent by
+/// [`BuilderClient::get_state`].
+#[derive(Debug, Clone, Copy)]
+pub struct StateFetchConfig {
+    /// Maximum number of queries in flight.  This bounds the request rate
+    /// against the node.
+    pub max_concurrency: usize,
+    /// Number of times a query that fails with a JSON-RPC error is retried.
+    /// For retries with backoff on rate limit errors, wrap the provider in a
+    /// `ethers_providers::RetryClient` instead.
+    pub max_retries: usize,
+}
+
+impl Default for StateFetchConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 16,
+            max_retries: 2,
+        }
+    }
+}
+
+/// Run `query` until it succeeds, or until it fails with a JSON-RPC error
+/// more than `max_retries` times.
+async fn with_retries<T, Fut: Future<Output = Result<T, Error>>>(
+    max_retries: usize,
+    mut query: impl FnMut() -> Fut,
+) -> Result<T, Error> {
+    let mut retries = 0;
+    loop {
+        match query().await {
+            Err(Error::JSONRpcError(err)) if retries < max_retries => {
+                retries += 1;
+                warn!(
+                    "JSON-RPC query failed ({}), retry {}/{}",
+                    err, retries, max_retries
+                );
+            }
+            result => return result,
+        }
+    }
+}
+
 /// Struct that wraps a GethClient and contains methods to perform all the steps
 /// necessary to generate the circuit inputs for a block by querying geth for
 /// the necessary information and using the CircuitInputBuilder.
@@ -617,6 +755,7 @@ pub struct BuilderClient<P: JsonRpcClient> {
     chain_id: Word,
     circuits_params: FixedCParams,
     feature_config: FeatureConfig,
+    state_fetch_config: StateFetchConfig,
 }
 
 /// Get State Accesses from TxExecTraces
@@ -638,6 +777,16 @@ pub fn get_state_accesses(
         let tx_access_trace = gen_state_access_trace(eth_block, tx, geth_trace)?;
         block_access_trace.extend(tx_access_trace);
     }
+    // The withdrawals are credited after the transactions.
+    for wd in eth_block.withdrawals.iter().flatten() {
+        block_access_trace.push(Access::new(
+            None,
+            RW::WRITE,
+            AccessValue::Account {
+                address: wd.address,
+            },
+        ));
+    }
 
     Ok(AccessSet::from(block_access_trace))
 }
@@ -690,9 +839,16 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             chain_id: chain_id.into(),
             circuits_params,
             feature_config,
+            state_fetch_config: StateFetchConfig::default(),
         })
     }
 
+    /// Set the configuration of the queries of the pre-state.
+    pub fn with_state_fetch_config(mut self, state_fetch_config: StateFetchConfig) -> Self {
+        self.state_fetch_config = state_fetch_config;
+        self
+    }
+
     /// Step 1. Query geth for Block, Txs, TxExecTraces, history block hashes
     /// and previous state root.
     pub async fn get_block(
@@ -701,7 +857,18 @@ impl<P: JsonRpcClient> BuilderClient<P> {
     ) -> Result<(EthBlock, Vec<eth_types::GethExecTrace>, Vec<Word>, Word), Error> {
         let eth_block = self.cli.get_block_by_number(block_num.into()).await?;
         let geth_traces = self.cli.trace_block_by_number(block_num.into()).await?;
+        let (history_hashes, prev_state_root) = self.get_history(block_num, &eth_block).await?;
+
+        Ok((eth_block, geth_traces, history_hashes, prev_state_root))
+    }
 
+    /// Query geth for the hashes of up to 256 blocks before the block and the
+    /// previous state root.
+    async fn get_history(
+        &self,
+        block_num: u64,
+        eth_block: &EthBlock,
+    ) -> Result<(Vec<Word>, Word), Error> {
         // fetch up to 256 blocks
         let mut n_blocks = std::cmp::min(256, block_num as usize);
         let mut next_hash = eth_block.parent_hash;
@@ -729,12 +896,17 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             next_hash = header.parent_hash;
         }
 
-        Ok((
-            eth_block,
-            geth_traces,
-            history_hashes,
-            prev_state_root.unwrap_or_default(),
-        ))
+        Ok((history_hashes, prev_state_root.unwrap_or_default()))
+    }
+
+    /// Scan the block from step 1 for features the circuits don't support, so
+    /// that the block can be skipped before querying the state.
+    pub fn check_block_support(
+        &self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+    ) -> UnsupportedReport {
+        check_block_support(eth_block, geth_traces, self.circuits_params.max_bytecode)
     }
 
     /// Step 2. Get State Accesses from TxExecTraces
@@ -746,7 +918,8 @@ impl<P: JsonRpcClient> BuilderClient<P> {
     }
 
     /// Step 3. Query geth for all accounts, storage keys, and codes from
-    /// Accesses
+    /// Accesses.  The queries are sent concurrently, see
+    /// [`StateFetchConfig`].
     pub async fn get_state(
         &self,
         block_num: u64,
@@ -758,26 +931,33 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         ),
         Error,
     > {
-        let mut proofs = Vec::new();
-        for (address, key_set) in access_set.state {
-            let mut keys: Vec<Word> = key_set.iter().cloned().collect();
-            keys.sort();
-            let proof = self
-                .cli
-                .get_proof(address, keys, (block_num - 1).into())
-                .await
-                .unwrap();
-            proofs.push(proof);
-        }
-        let mut codes: HashMap<Address, Vec<u8>> = HashMap::new();
-        for address in access_set.code {
-            let code = self
-                .cli
-                .get_code(address, (block_num - 1).into())
+        let StateFetchConfig {
+            max_concurrency,
+            max_retries,
+        } = self.state_fetch_config;
+        let proofs: Vec<_> = stream::iter(access_set.state)
+            .map(|(address, key_set)| async move {
+                let keys: Vec<Word> = key_set.into_iter().sorted().collect();
+                with_retries(max_retries, || {
+                    self.cli
+                        .get_proof(address, keys.clone(), (block_num - 1).into())
+                })
                 .await
-                .unwrap();
-            codes.insert(address, code);
-        }
+            })
+            .buffer_unordered(max_concurrency)
+            .try_collect()
+            .await?;
+        let codes: HashMap<Address, Vec<u8>> = stream::iter(access_set.code)
+            .map(|address| async move {
+                let code = with_retries(max_retries, || {
+                    self.cli.get_code(address, (block_num - 1).into())
+                })
+                .await?;
+                Ok::<_, Error>((address, code))
+            })
+            .buffer_unordered(max_concurrency)
+            .try_collect()
+            .await?;
         Ok((proofs, codes))
     }
 
@@ -789,6 +969,41 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         build_state_code_db(proofs, codes)
     }
 
+    /// Perform the steps 1 to 3, to generate the witness of the block from the returned input
+    /// on another machine, see [`WitnessInput`].
+    pub async fn gen_witness_input(&self, block_num: u64) -> Result<WitnessInput, Error> {
+        let (eth_block, geth_traces, history_hashes, prev_state_root) =
+            self.get_block(block_num).await?;
+        let access_set = Self::get_state_accesses(&eth_block, &geth_traces)?;
+        let (proofs, codes) = self.get_state(block_num, access_set).await?;
+        let accounts = proofs
+            .into_iter()
+            .map(|proof| WitnessAccount {
+                address: proof.address,
+                nonce: proof.nonce.as_u64(),
+                balance: proof.balance,
+                code_hash: proof.code_hash,
+                storage: proof
+                    .storage_proof
+                    .into_iter()
+                    .map(|storage_proof| (storage_proof.key, storage_proof.value))
+                    .collect(),
+            })
+            .collect();
+        Ok(WitnessInput {
+            version: WITNESS_INPUT_VERSION,
+            chain_id: self.chain_id,
+            history_hashes,
+            prev_state_root,
+            eth_block,
+            geth_traces,
+            accounts,
+            codes: codes.into_values().map(Into::into).collect(),
+            circuits_params: self.circuits_params,
+            feature_config: self.feature_config,
+        })
+    }
+
     /// Step 5. For each step in TxExecTraces, gen the associated ops and state
     /// circuit inputs
     pub fn gen_inputs_from_state(
@@ -838,4 +1053,41 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         )?;
         Ok((builder, eth_block))
     }
+
+    /// Perform all the steps to generate the circuit inputs with the state
+    /// override applied on top of the pre-state of the block.  The node
+    /// traces the block without the override, so `geth_traces` must be the
+    /// traces of the block executed with the override applied, for example
+    /// produced by a local tracer.  The previous state root is still the one
+    /// reported by the node.
+    pub async fn gen_inputs_with_state_override(
+        &self,
+        block_num: u64,
+        state_override: &geth_types::StateOverride,
+        geth_traces: &[eth_types::GethExecTrace],
+    ) -> Result<
+        (
+            CircuitInputBuilder<FixedCParams>,
+            eth_types::Block<eth_types::Transaction>,
+        ),
+        Error,
+    > {
+        let eth_block = self.cli.get_block_by_number(block_num.into()).await?;
+        let (history_hashes, prev_state_root) = self.get_history(block_num, &eth_block).await?;
+        let access_set = Self::get_state_accesses(&eth_block, geth_traces)?;
+        let (proofs, codes) = self.get_state(block_num, access_set.clone()).await?;
+        let (mut state_db, mut code_db) = Self::build_state_code_db(proofs, codes);
+        apply_state_override(&mut state_db, &mut code_db, state_override)?;
+        // The override may add accounts and code that the block doesn't touch.
+        let (state_db, code_db) = access_set.prune_state_code_db(&state_db, &code_db);
+        let builder = self.gen_inputs_from_state(
+            state_db,
+            code_db,
+            &eth_block,
+            geth_traces,
+            history_hashes,
+            prev_state_root,
+        )?;
+        Ok((builder, eth_block))
+    }
 }
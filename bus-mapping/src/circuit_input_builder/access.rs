@@ -1,4 +1,8 @@
-use crate::{operation::RW, Error};
+use crate::{
+    operation::RW,
+    state_db::{self, CodeDB, StateDB},
+    Error,
+};
 use eth_types::{evm_types::OpcodeId, Address, GethExecStep, GethExecTrace, ToAddress, Word};
 use ethers_core::utils::get_contract_address;
 use std::collections::{hash_map::Entry, HashMap, HashSet};
@@ -57,7 +61,7 @@ fn get_call_result(trace: &[GethExecStep]) -> Option<Word> {
 }
 
 /// State and Code Access set.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AccessSet {
     /// Set of accounts
     pub state: HashMap<Address, HashSet<Word>>,
@@ -94,6 +98,42 @@ impl From<Vec<Access>> for AccessSet {
     }
 }
 
+impl AccessSet {
+    /// Return the part of the pre-state that is accessed: the accounts of the
+    /// set with only the accessed storage slots, and the code of the accounts
+    /// whose code is accessed.  Slots and accounts missing from the pre-state
+    /// are left out, they read as zero.
+    pub fn prune_state_code_db(&self, sdb: &StateDB, code_db: &CodeDB) -> (StateDB, CodeDB) {
+        let mut pruned_sdb = StateDB::new();
+        let mut pruned_code_db = CodeDB::default();
+        for (address, keys) in &self.state {
+            let (found, account) = sdb.get_account(address);
+            if !found {
+                continue;
+            }
+            let storage = account
+                .storage
+                .iter()
+                .filter(|(key, _)| keys.contains(key))
+                .map(|(key, value)| (*key, *value))
+                .collect();
+            pruned_sdb.set_account(
+                address,
+                state_db::Account {
+                    storage,
+                    ..account.clone()
+                },
+            );
+            if self.code.contains(address) {
+                if let Some(code) = code_db.get_from_h256(&account.code_hash) {
+                    pruned_code_db.insert(code.code());
+                }
+            }
+        }
+        (pruned_sdb, pruned_code_db)
+    }
+}
+
 /// Source of the code in the EVM execution.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CodeSource {
@@ -275,3 +315,48 @@ pub fn gen_state_access_trace<TX>(
     }
     Ok(accs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_set_prune_state() {
+        let [touched, untouched] = [1, 2].map(Address::repeat_byte);
+        let mut code_db = CodeDB::default();
+        let mut sdb = StateDB::new();
+        for (address, code) in [(touched, vec![0x00]), (untouched, vec![0x60, 0x00])] {
+            sdb.set_account(
+                &address,
+                state_db::Account {
+                    nonce: 1,
+                    balance: Word::from(1),
+                    storage: HashMap::from([(Word::from(1), Word::from(1)), (2.into(), 2.into())]),
+                    code_hash: code_db.insert(code),
+                },
+            );
+        }
+
+        let access_set = AccessSet::from(vec![
+            Access::new(None, READ, Code { address: touched }),
+            Access::new(
+                None,
+                WRITE,
+                Storage {
+                    address: touched,
+                    key: Word::from(2),
+                },
+            ),
+        ]);
+        let (pruned_sdb, pruned_code_db) = access_set.prune_state_code_db(&sdb, &code_db);
+
+        let (found, account) = pruned_sdb.get_account(&touched);
+        assert!(found);
+        assert_eq!(
+            account.storage,
+            HashMap::from([(Word::from(2), Word::from(2))])
+        );
+        assert!(!pruned_sdb.get_account(&untouched).0);
+        assert_eq!(pruned_code_db.num_rows_required_for_bytecode_table(), 2);
+    }
+}
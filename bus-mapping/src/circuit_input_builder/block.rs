@@ -7,7 +7,7 @@ use crate::{
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{evm_unimplemented, Address, Word, H256};
+use eth_types::{evm_unimplemented, sign_types::SignData, Address, Word, H256};
 use itertools::Itertools;
 use std::collections::HashMap;
 
@@ -87,6 +87,8 @@ pub struct Block {
     pub sha3_inputs: Vec<Vec<u8>>,
     /// Exponentiation events in the block.
     pub exp_events: Vec<ExpEvent>,
+    /// Signatures verified by the ecRecover calls in the block.
+    pub ecrecover_events: Vec<SignData>,
     /// Original block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
 }
@@ -144,6 +146,7 @@ impl Block {
             },
             copy_events: Vec::new(),
             exp_events: Vec::new(),
+            ecrecover_events: Vec::new(),
             sha3_inputs: Vec::new(),
             eth_block: eth_block.clone(),
         })
@@ -161,7 +164,7 @@ impl Block {
 
     /// Return the list of withdrawals of this block.
     pub fn withdrawals(&self) -> Vec<Withdrawal> {
-        let eth_withdrawals = self.eth_block.withdrawals.clone().unwrap();
+        let eth_withdrawals = self.eth_block.withdrawals.clone().unwrap_or_default();
         eth_withdrawals
             .iter()
             .map({
@@ -191,4 +194,8 @@ impl Block {
     pub fn add_exp_event(&mut self, event: ExpEvent) {
         self.exp_events.push(event);
     }
+    /// Push an ecRecover signature to the block.
+    pub fn add_ecrecover_event(&mut self, event: SignData) {
+        self.ecrecover_events.push(event);
+    }
 }
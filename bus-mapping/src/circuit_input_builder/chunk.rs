@@ -0,0 +1,149 @@
+//! Split of the execution of a block at transaction boundaries into chunks that each fit the
+//! capacity of a circuit.
+//!
+//! A [`Chunk`] is a range of consecutive transactions with the range of rw counters of their
+//! operations: the end of a chunk is the start of the next one, and is the value that a chunk
+//! carries over to the next.
+
+use super::Block;
+use crate::Error;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Consecutive transactions of a block, executed in one circuit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Chunk {
+    /// Index of the chunk in the block
+    pub index: usize,
+    /// Indexes of the transactions of the chunk in the block
+    pub txs: Range<usize>,
+    /// Rw counters of the operations of the chunk
+    pub rws: Range<usize>,
+}
+
+impl Chunk {
+    /// Number of operations of the chunk.
+    pub fn num_rws(&self) -> usize {
+        self.rws.len()
+    }
+}
+
+/// Split the transactions of `block` into the fewest chunks of consecutive transactions whose
+/// operations fit `max_rws` rows, with the `Start` row of the padding.  `rwc_end` is the rw
+/// counter after the last transaction.  Returns an error if the operations of a single
+/// transaction don't fit.
+pub(super) fn split_into_chunks(
+    block: &Block,
+    rwc_end: usize,
+    max_rws: usize,
+) -> Result<Vec<Chunk>, Error> {
+    let starts = block
+        .txs
+        .iter()
+        .map(|tx| tx.steps().first().map_or(rwc_end, |step| step.rwc.0))
+        .chain([rwc_end])
+        .collect::<Vec<_>>();
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for (tx_index, rws) in starts.windows(2).enumerate() {
+        let rws = rws[0]..rws[1];
+        if rws.len() + 1 > max_rws {
+            return Err(Error::RwsNotEnough(max_rws, rws.len() + 1));
+        }
+        match chunks.last_mut() {
+            Some(chunk) if chunk.num_rws() + rws.len() < max_rws => {
+                chunk.txs.end = tx_index + 1;
+                chunk.rws.end = rws.end;
+            }
+            _ => chunks.push(Chunk {
+                index: chunks.len(),
+                txs: tx_index..tx_index + 1,
+                rws,
+            }),
+        }
+    }
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, Word};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    #[test]
+    fn split_block_into_chunks() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<3, 3>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[2].address).to(accs[0].address);
+                txs[2]
+                    .from(accs[1].address)
+                    .to(accs[2].address)
+                    .value(Word::from(1));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let rwc_end = builder.block_ctx.rwc.0;
+        let block = &builder.block;
+
+        let rws = |tx_index: usize| {
+            let start = block.txs[tx_index].steps()[0].rwc.0;
+            let end = block
+                .txs
+                .get(tx_index + 1)
+                .map_or(rwc_end, |tx| tx.steps()[0].rwc.0);
+            start..end
+        };
+        let max_rws = rws(0).len() + rws(1).len() + 1;
+        assert_eq!(
+            split_into_chunks(block, rwc_end, max_rws).unwrap(),
+            vec![
+                Chunk {
+                    index: 0,
+                    txs: 0..2,
+                    rws: rws(0).start..rws(1).end,
+                },
+                Chunk {
+                    index: 1,
+                    txs: 2..3,
+                    rws: rws(2),
+                },
+            ]
+        );
+        // The chunks cover all the operations
+        assert_eq!(
+            split_into_chunks(block, rwc_end, rwc_end).unwrap(),
+            vec![Chunk {
+                index: 0,
+                txs: 0..3,
+                rws: 1..rwc_end,
+            }]
+        );
+
+        let max_rws = rws(0).len();
+        assert!(matches!(
+            split_into_chunks(block, rwc_end, max_rws),
+            Err(Error::RwsNotEnough(max, needed)) if max == max_rws && needed == max_rws + 1
+        ));
+    }
+}
@@ -0,0 +1,369 @@
+//! Differential check of the generated witness against the traces it was generated from.
+//!
+//! Each opcode step of the witness is matched with its step of the geth trace, and the stack,
+//! memory and storage operations of the step are compared with the state recorded by geth: a
+//! read must return the value of the trace before the step, and a write the value of the trace
+//! after the step.  A divergence points to a bug in the witness generation of the opcode, which
+//! would otherwise only surface as an unsatisfied constraint when proving the block.
+//!
+//! The check runs after each block handled with [`FeatureConfig::differential`] set, or can be
+//! called with [`diff_block`].
+//!
+//! [`FeatureConfig::differential`]: super::FeatureConfig::differential
+
+use super::{Block, Call, ExecState};
+use crate::operation::{OperationRef, Target};
+use eth_types::{evm_types::OpcodeId, GethExecStep, GethExecTrace, Word};
+use serde::Serialize;
+
+/// Difference between a step of the witness and its step of the trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DivergenceKind {
+    /// The witness and the trace have a different number of opcode steps.  The steps after the
+    /// shortest are not compared.
+    StepCount {
+        /// Opcode steps of the trace
+        trace: usize,
+        /// Opcode steps of the witness
+        witness: usize,
+    },
+    /// The step of the witness is at another program counter or opcode than the trace.
+    Step {
+        /// Program counter of the witness
+        pc: u64,
+        /// Opcode of the witness
+        opcode: OpcodeId,
+    },
+    /// Value of a stack operation.
+    Stack {
+        /// Stack address, 1023 being the bottom of the stack
+        address: usize,
+        /// Whether the operation is a write
+        is_write: bool,
+        /// Value of the trace, if the address is in its stack
+        trace: Option<Word>,
+        /// Value of the witness
+        witness: Word,
+    },
+    /// Value of a memory operation.
+    Memory {
+        /// Memory address
+        address: usize,
+        /// Whether the operation is a write
+        is_write: bool,
+        /// Value of the trace, if the address is in its memory
+        trace: Option<u8>,
+        /// Value of the witness
+        witness: u8,
+    },
+    /// Value of a storage operation.
+    Storage {
+        /// Storage key
+        key: Word,
+        /// Value of the trace
+        trace: Word,
+        /// Value of the witness
+        witness: Word,
+    },
+}
+
+/// Difference found by the differential check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Divergence {
+    /// Index of the transaction in the block
+    pub tx_index: usize,
+    /// Index of the step in the `struct_logs` of the trace
+    pub step_index: usize,
+    /// Program counter of the step of the trace, if any
+    pub pc: Option<u64>,
+    /// Opcode of the step of the trace, if any
+    pub opcode: Option<OpcodeId>,
+    /// Difference
+    pub kind: DivergenceKind,
+}
+
+/// Compare the opcode steps of the witness of `block` with the `geth_traces` of its
+/// transactions, and return the divergences in the order of the steps.
+///
+/// The memory is only compared for the traces that captured it, and the storage only for the
+/// `SLOAD` and `SSTORE` steps, which are the ones geth records it for.
+pub fn diff_block(block: &Block, geth_traces: &[GethExecTrace]) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    for (tx_index, (tx, trace)) in block.txs.iter().zip(geth_traces).enumerate() {
+        let steps = tx
+            .steps()
+            .iter()
+            .filter_map(|step| match step.exec_state {
+                ExecState::Op(opcode) => Some((step, opcode)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let logs = &trace.struct_logs;
+        let has_memory = logs.iter().any(|log| !log.memory.0.is_empty());
+        let mut push = |step_index: usize, kind| {
+            let log = logs.get(step_index);
+            divergences.push(Divergence {
+                tx_index,
+                step_index,
+                pc: log.map(|log| log.pc),
+                opcode: log.map(|log| log.op),
+                kind,
+            });
+        };
+
+        for (step_index, ((step, opcode), log)) in steps.iter().zip(logs).enumerate() {
+            if step.pc != log.pc || *opcode != log.op {
+                push(
+                    step_index,
+                    DivergenceKind::Step {
+                        pc: step.pc,
+                        opcode: *opcode,
+                    },
+                );
+                continue;
+            }
+            // The state after the step is only in the trace when the next step runs in the
+            // same call.
+            let next = logs
+                .get(step_index + 1)
+                .filter(|next| next.depth == log.depth);
+            let call = &tx.calls()[step.call_index];
+            for kind in diff_step(
+                block,
+                &step.bus_mapping_instance,
+                log,
+                next,
+                has_memory,
+                call,
+            ) {
+                push(step_index, kind);
+            }
+        }
+
+        if steps.len() != logs.len() {
+            push(
+                steps.len().min(logs.len()),
+                DivergenceKind::StepCount {
+                    trace: logs.len(),
+                    witness: steps.len(),
+                },
+            );
+        }
+    }
+    divergences
+}
+
+/// Compare the operations of a step of the witness with the state of the trace before the step,
+/// `log`, and after the step, `next`.  Only the operations of the call of the step are compared:
+/// the other calls aren't in the state of the trace.
+fn diff_step(
+    block: &Block,
+    ops: &[OperationRef],
+    log: &GethExecStep,
+    next: Option<&GethExecStep>,
+    has_memory: bool,
+    call: &Call,
+) -> Vec<DivergenceKind> {
+    let container = &block.container;
+    let mut kinds = Vec::new();
+    for op_ref in ops {
+        match op_ref.0 {
+            Target::Stack => {
+                let operation = &container.stack[op_ref.1];
+                let op = operation.op();
+                if op.call_id != call.call_id {
+                    continue;
+                }
+                let address = op.address.0;
+                let is_write = operation.rw().is_write();
+                let trace = if is_write {
+                    match next {
+                        Some(next) => stack_value(next, address),
+                        None => continue,
+                    }
+                } else {
+                    stack_value(log, address)
+                };
+                if trace != Some(op.value) {
+                    kinds.push(DivergenceKind::Stack {
+                        address,
+                        is_write,
+                        trace,
+                        witness: op.value,
+                    });
+                }
+            }
+            Target::Memory if has_memory => {
+                let operation = &container.memory[op_ref.1];
+                let op = operation.op();
+                if op.call_id != call.call_id {
+                    continue;
+                }
+                let address = op.address.0;
+                let is_write = operation.rw().is_write();
+                let trace = if is_write {
+                    match next {
+                        Some(next) => next.memory.0.get(address).copied(),
+                        None => continue,
+                    }
+                } else {
+                    // The memory expanded by the step reads as zeros
+                    Some(log.memory.0.get(address).copied().unwrap_or_default())
+                };
+                if trace != Some(op.value) {
+                    kinds.push(DivergenceKind::Memory {
+                        address,
+                        is_write,
+                        trace,
+                        witness: op.value,
+                    });
+                }
+            }
+            Target::Storage if matches!(log.op, OpcodeId::SLOAD | OpcodeId::SSTORE) => {
+                let op = container.storage[op_ref.1].op();
+                if op.address != call.address {
+                    continue;
+                }
+                // geth records the loaded value for `SLOAD`, and the stored value for `SSTORE`
+                if let Some(trace) = log.storage.0.get(&op.key) {
+                    if *trace != op.value {
+                        kinds.push(DivergenceKind::Storage {
+                            key: op.key,
+                            trace: *trace,
+                            witness: op.value,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    kinds
+}
+
+/// Value at the stack `address` of the step, with the top of the geth stack at its end.
+fn stack_value(step: &GethExecStep, address: usize) -> Option<Word> {
+    let stack = &step.stack.0;
+    1023usize
+        .checked_sub(address)
+        .and_then(|index| stack.get(index))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_input_builder::FeatureConfig, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{
+        test_ctx::{helpers::*, LoggerConfig},
+        TestContext,
+    };
+
+    fn block_with_traces() -> (Block, GethData) {
+        let code = bytecode! {
+            PUSH1(0x42)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x00)
+            MLOAD
+            PUSH1(0x01)
+            SSTORE
+            PUSH1(0x01)
+            SLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+            LoggerConfig::enable_memory(),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone())
+            .new_circuit_input_builder_with_feature(FeatureConfig {
+                differential: true,
+                ..Default::default()
+            });
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        (builder.block, block)
+    }
+
+    fn find(logs: &[GethExecStep], op: OpcodeId) -> usize {
+        logs.iter().position(|log| log.op == op).unwrap()
+    }
+
+    #[test]
+    fn differential_no_divergence() {
+        let (block, geth_data) = block_with_traces();
+        assert_eq!(diff_block(&block, &geth_data.geth_traces), vec![]);
+    }
+
+    #[test]
+    fn differential_divergences() {
+        let (block, mut geth_data) = block_with_traces();
+        let logs = &mut geth_data.geth_traces[0].struct_logs;
+
+        // The value pushed by `MLOAD` is on the stack of the `PUSH1` after it
+        let mload = find(logs, OpcodeId::MLOAD);
+        *logs[mload + 1].stack.0.last_mut().unwrap() = Word::from(0x43);
+        // The byte stored by `MSTORE` is in the memory of the `PUSH1` after it
+        let mstore = find(logs, OpcodeId::MSTORE);
+        logs[mstore + 1].memory.0[31] = 0x43;
+        let sload = find(logs, OpcodeId::SLOAD);
+        logs[sload]
+            .storage
+            .0
+            .insert(Word::from(1), Word::from(0x43));
+        logs.truncate(sload + 1);
+
+        let divergences = diff_block(&block, &geth_data.geth_traces);
+        let kinds = divergences
+            .iter()
+            .map(|divergence| (divergence.step_index, divergence.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    mstore,
+                    DivergenceKind::Memory {
+                        address: 31,
+                        is_write: true,
+                        trace: Some(0x43),
+                        witness: 0x42,
+                    }
+                ),
+                (
+                    mload,
+                    DivergenceKind::Stack {
+                        address: 1023,
+                        is_write: true,
+                        trace: Some(Word::from(0x43)),
+                        witness: Word::from(0x42),
+                    }
+                ),
+                (
+                    sload,
+                    DivergenceKind::Storage {
+                        key: Word::from(1),
+                        trace: Word::from(0x43),
+                        witness: Word::from(0x42),
+                    }
+                ),
+                (
+                    sload + 1,
+                    DivergenceKind::StepCount {
+                        trace: sload + 1,
+                        witness: sload + 2,
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Audit of the gas of the generated witness against the gas rules of the EVM.
+//!
+//! The gas cost of each opcode step is recomputed from its operands, read from the stack
+//! operations of the step, and compared with the `gas_cost` of the witness, and the `gas_left`
+//! of the next step of the call with the gas left after the cost.  A gas mismatch otherwise
+//! only shows up as an unsatisfied constraint of the gadget of the opcode.
+//!
+//! The costs that depend on the state (the access lists of EIP-2929, the storage and the
+//! created or called accounts) aren't recomputed: the steps of these opcodes are counted as
+//! unchecked.
+
+use super::{Block, ExecState, ExecStep};
+use crate::operation::Target;
+use eth_types::{
+    evm_types::{
+        gas_utils::{memory_copier_gas_cost, memory_expansion_gas_cost},
+        GasCost, OpcodeId,
+    },
+    Word,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Gas value of the witness that differs from the gas rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GasMismatchKind {
+    /// `gas_cost` of the step
+    Cost,
+    /// `gas_left` of the next step of the call
+    GasLeft,
+}
+
+/// Step of the witness with a gas value that differs from the gas rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GasMismatch {
+    /// Index of the transaction in the block
+    pub tx_index: usize,
+    /// Index of the step in the steps of the transaction
+    pub step_index: usize,
+    /// Program counter of the step
+    pub pc: u64,
+    /// Opcode of the step
+    pub opcode: OpcodeId,
+    /// Gas value that differs
+    pub kind: GasMismatchKind,
+    /// Gas value of the gas rules
+    pub expected: u64,
+    /// Gas value of the witness
+    pub witness: u64,
+}
+
+/// Report of a gas audit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GasAuditReport {
+    /// Number of checked steps
+    pub checked: u64,
+    /// Number of steps that weren't checked, by opcode
+    pub unchecked: BTreeMap<String, u64>,
+    /// Mismatches, in the order of the steps
+    pub mismatches: Vec<GasMismatch>,
+}
+
+impl GasAuditReport {
+    /// Number of mismatches by opcode.
+    pub fn mismatches_by_opcode(&self) -> BTreeMap<String, u64> {
+        let mut counts = BTreeMap::new();
+        for mismatch in self.mismatches.iter() {
+            *counts.entry(mismatch.opcode.to_string()).or_default() += 1;
+        }
+        counts
+    }
+}
+
+/// Value of the `n`th item from the top of the stack before the step, read by the step.
+fn operand(block: &Block, step: &ExecStep, n: usize) -> Option<Word> {
+    let address = 1024usize.checked_sub(step.stack_size)? + n;
+    step.bus_mapping_instance
+        .iter()
+        .filter(|op_ref| op_ref.0 == Target::Stack)
+        .map(|op_ref| &block.container.stack[op_ref.1])
+        .find(|op| !op.rw().is_write() && op.op().address.0 == address)
+        .map(|op| op.op().value)
+}
+
+/// Memory size in words after an access of `size` bytes at `offset`, from `curr` words.
+fn next_memory_word_size(curr: u64, offset: Word, size: Word) -> u64 {
+    if size.is_zero() {
+        curr
+    } else {
+        curr.max((offset.low_u64() + size.low_u64() + 31) / 32)
+    }
+}
+
+/// Recompute the gas cost of the step of `opcode`, or return `None` if it depends on the state.
+fn gas_cost(block: &Block, step: &ExecStep, opcode: OpcodeId) -> Option<u64> {
+    let arg = |n| operand(block, step, n);
+    let memory_word_size = (step.memory_size as u64 + 31) / 32;
+    let memory_expansion = |offset, size| {
+        memory_expansion_gas_cost(
+            memory_word_size,
+            next_memory_word_size(memory_word_size, offset, size),
+        )
+    };
+    let constant = opcode.constant_gas_cost();
+    let cost = match opcode {
+        OpcodeId::SLOAD
+        | OpcodeId::SSTORE
+        | OpcodeId::BALANCE
+        | OpcodeId::EXTCODESIZE
+        | OpcodeId::EXTCODECOPY
+        | OpcodeId::EXTCODEHASH
+        | OpcodeId::CALL
+        | OpcodeId::CALLCODE
+        | OpcodeId::DELEGATECALL
+        | OpcodeId::STATICCALL
+        | OpcodeId::CREATE
+        | OpcodeId::CREATE2
+        | OpcodeId::SELFDESTRUCT => return None,
+        OpcodeId::MLOAD | OpcodeId::MSTORE => constant + memory_expansion(arg(0)?, Word::from(32)),
+        OpcodeId::MSTORE8 => constant + memory_expansion(arg(0)?, Word::one()),
+        OpcodeId::RETURN | OpcodeId::REVERT => constant + memory_expansion(arg(0)?, arg(1)?),
+        OpcodeId::SHA3 => {
+            let size = arg(1)?;
+            constant
+                + GasCost::COPY_SHA3 * ((size.low_u64() + 31) / 32)
+                + memory_expansion(arg(0)?, size)
+        }
+        OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => {
+            let (offset, size) = (arg(0)?, arg(2)?);
+            constant
+                + memory_copier_gas_cost(
+                    memory_word_size,
+                    next_memory_word_size(memory_word_size, offset, size),
+                    size.low_u64(),
+                )
+        }
+        OpcodeId::EXP => {
+            let exponent_bytes = (arg(1)?.bits() as u64 + 7) / 8;
+            constant + GasCost::EXP_BYTE_TIMES * exponent_bytes
+        }
+        _ if opcode.is_log() => {
+            let (offset, size) = (arg(0)?, arg(1)?);
+            let topics = opcode.postfix().expect("LOG has a postfix") as u64;
+            GasCost::LOG * (1 + topics) + 8 * size.low_u64() + memory_expansion(offset, size)
+        }
+        _ => constant,
+    };
+    Some(cost)
+}
+
+/// Audit the gas of the opcode steps of `block`.  The steps that fail are skipped, since their
+/// gas is handled by the error gadgets.
+pub fn audit_gas(block: &Block) -> GasAuditReport {
+    let mut report = GasAuditReport::default();
+    for (tx_index, tx) in block.txs.iter().enumerate() {
+        let steps = tx.steps();
+        for (step_index, step) in steps.iter().enumerate() {
+            let ExecState::Op(opcode) = step.exec_state else {
+                continue;
+            };
+            if step.error.is_some() {
+                continue;
+            }
+            let Some(expected) = gas_cost(block, step, opcode) else {
+                *report.unchecked.entry(opcode.to_string()).or_default() += 1;
+                continue;
+            };
+            report.checked += 1;
+            let mut mismatch = |kind, expected, witness| {
+                report.mismatches.push(GasMismatch {
+                    tx_index,
+                    step_index,
+                    pc: step.pc,
+                    opcode,
+                    kind,
+                    expected,
+                    witness,
+                })
+            };
+            if step.gas_cost != expected {
+                mismatch(GasMismatchKind::Cost, expected, step.gas_cost);
+            }
+            let next = steps
+                .get(step_index + 1)
+                .filter(|next| next.call_index == step.call_index)
+                .filter(|next| matches!(next.exec_state, ExecState::Op(_)));
+            if let Some(next) = next {
+                let gas_left = step.gas_left.saturating_sub(expected);
+                if next.gas_left != gas_left {
+                    mismatch(GasMismatchKind::GasLeft, gas_left, next.gas_left);
+                }
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::TestContext;
+
+    #[test]
+    fn gas_audit() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH1(0x40)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH1(0x60)
+            CALLDATACOPY
+            PUSH1(0x60)
+            PUSH1(0x10)
+            SHA3
+            PUSH2(0x0102)
+            PUSH1(0x03)
+            EXP
+            PUSH1(0x20)
+            PUSH1(0x40)
+            LOG1
+            PUSH1(0x00)
+            SLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let report = audit_gas(&builder.block);
+        assert_eq!(report.mismatches, vec![]);
+        // All the opcodes but SLOAD
+        assert_eq!(report.checked, 18);
+        assert_eq!(report.unchecked, BTreeMap::from([("SLOAD".to_string(), 1)]));
+
+        let steps = builder.block.txs[0].steps_mut();
+        let sha3 = steps
+            .iter()
+            .position(|step| step.exec_state == ExecState::Op(OpcodeId::SHA3))
+            .unwrap();
+        let cost = steps[sha3].gas_cost;
+        steps[sha3].gas_cost += 1;
+        let report = audit_gas(&builder.block);
+        let mismatch = |kind, expected, witness| GasMismatch {
+            tx_index: 0,
+            step_index: sha3,
+            pc: builder.block.txs[0].steps()[sha3].pc,
+            opcode: OpcodeId::SHA3,
+            kind,
+            expected,
+            witness,
+        };
+        assert_eq!(
+            report.mismatches,
+            vec![mismatch(GasMismatchKind::Cost, cost, cost + 1)]
+        );
+        assert_eq!(
+            report.mismatches_by_opcode(),
+            BTreeMap::from([("SHA3".to_string(), 1)])
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! Cheap scan of a block and its traces for features that the circuits don't
+//! support yet, so that such blocks can be skipped or routed elsewhere before
+//! the circuit inputs are generated.
+
+use super::EthBlock;
+use crate::precompile::{is_precompiled, PrecompileCalls};
+use eth_types::{evm_types::OpcodeId, Address, GethExecStep, GethExecTrace, Word};
+use serde::Serialize;
+
+/// EIP-2718 transaction types supported by the circuits: legacy transactions, whose signature
+/// is verified with the EIP-155 signing hash, EIP-2930 access list transactions and EIP-1559
+/// fee market transactions.
+pub const SUPPORTED_TX_TYPES: &[u64] = &[0, 1, 2];
+
+/// Maximum number of addresses in the access list of a tx (EIP-2930).  Each tx has this many
+/// address slots in the tx table, which BeginTx pre-warms one lookup at a time.
+pub const MAX_ACCESS_LIST_ADDRESSES: usize = 4;
+/// Maximum number of storage keys in the access list of a tx (EIP-2930), counting the ones of
+/// all its addresses.
+pub const MAX_ACCESS_LIST_STORAGE_KEYS: usize = 4;
+/// Maximum number of accounts self destructed by a tx.  EndTx has this many slots to delete
+/// them, one deletion at a time.
+pub const MAX_DESTRUCTED_ACCOUNTS: usize = 4;
+
+/// Feature found in a block that the circuits can't prove.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UnsupportedFeature {
+    /// Transaction of a type other than legacy, EIP-2930 (access list) or EIP-1559 (fee market).
+    TxType {
+        /// Index of the transaction in the block
+        tx_index: usize,
+        /// EIP-2718 transaction type
+        tx_type: u64,
+    },
+    /// Call to a precompile without a gadget in the EVM circuit.
+    Precompile {
+        /// Index of the transaction in the block
+        tx_index: usize,
+        /// Address of the precompile
+        address: Address,
+        /// Name of the precompile
+        name: String,
+    },
+    /// Opcode without a complete gadget in the EVM circuit.
+    Opcode {
+        /// Index of the transaction in the block
+        tx_index: usize,
+        /// Program counter of the step
+        pc: u64,
+        /// Opcode
+        opcode: OpcodeId,
+    },
+    /// Access list with more entries than the tx table has slots for.
+    AccessList {
+        /// Index of the transaction in the block
+        tx_index: usize,
+        /// Number of addresses
+        addresses: usize,
+        /// Number of storage keys, counting the ones of all the addresses
+        storage_keys: usize,
+    },
+    /// Code that doesn't fit in the bytecode circuit, together with the codes before it.
+    OversizedCode {
+        /// Index of the transaction in the block
+        tx_index: usize,
+        /// Rows of the bytecode circuit used by the codes up to this one
+        size: usize,
+        /// Rows of the bytecode circuit
+        limit: usize,
+    },
+}
+
+/// Report of the unsupported features found in a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UnsupportedReport {
+    /// Block number
+    pub block_number: Option<u64>,
+    /// Unsupported features, in the order they appear in the block
+    pub features: Vec<UnsupportedFeature>,
+}
+
+impl UnsupportedReport {
+    /// Return true if no unsupported feature was found.
+    pub fn is_supported(&self) -> bool {
+        self.features.is_empty()
+    }
+}
+
+/// Return true if the EVM circuit proves the opcode completely.  SELFDESTRUCT is rejected:
+/// EndTx deletes the nonce, balance and code hash of a destructed account but not its
+/// storage, which can't be seen from the traces alone.
+pub fn is_opcode_supported(opcode: OpcodeId) -> bool {
+    !matches!(opcode, OpcodeId::SELFDESTRUCT)
+}
+
+/// Return true if the EVM circuit has a gadget for the precompile.
+pub fn is_precompile_supported(precompile: PrecompileCalls) -> bool {
+    matches!(
+        precompile,
+        PrecompileCalls::ECRecover | PrecompileCalls::Identity
+    )
+}
+
+/// Scan a block and its traces for unsupported features.  `max_bytecode` is the
+/// capacity of the bytecode circuit, which the codes created in the block share: the
+/// first code that doesn't fit together with the ones before it is reported.  The scan
+/// doesn't need the state, so the code of existing contracts is not counted, and a code
+/// created more than once is counted each time.
+pub fn check_block_support(
+    eth_block: &EthBlock,
+    geth_traces: &[GethExecTrace],
+    max_bytecode: usize,
+) -> UnsupportedReport {
+    let mut features = Vec::new();
+    let mut bytecode_rows = 0;
+
+    for (tx_index, (tx, geth_trace)) in eth_block
+        .transactions
+        .iter()
+        .zip(geth_traces.iter())
+        .enumerate()
+    {
+        let tx_type = tx.transaction_type.map(|t| t.as_u64()).unwrap_or_default();
+        if !SUPPORTED_TX_TYPES.contains(&tx_type) {
+            features.push(UnsupportedFeature::TxType { tx_index, tx_type });
+        }
+
+        let (addresses, storage_keys) = tx.access_list.as_ref().map_or((0, 0), |access_list| {
+            (
+                access_list.0.len(),
+                access_list
+                    .0
+                    .iter()
+                    .map(|item| item.storage_keys.len())
+                    .sum::<usize>(),
+            )
+        });
+        if addresses > MAX_ACCESS_LIST_ADDRESSES || storage_keys > MAX_ACCESS_LIST_STORAGE_KEYS {
+            features.push(UnsupportedFeature::AccessList {
+                tx_index,
+                addresses,
+                storage_keys,
+            });
+        }
+
+        match tx.to {
+            None => add_code(
+                &mut features,
+                &mut bytecode_rows,
+                max_bytecode,
+                tx_index,
+                Word::from(tx.input.len()),
+            ),
+            Some(to) => check_precompile(&mut features, tx_index, to),
+        }
+
+        // Whether each call of the stack is a creation, to count the code it deploys.
+        let mut is_create = vec![tx.to.is_none()];
+        let mut prev_step: Option<&GethExecStep> = None;
+        for step in geth_trace.struct_logs.iter() {
+            if let Some(prev_step) = prev_step {
+                if step.depth > prev_step.depth {
+                    is_create.push(matches!(prev_step.op, OpcodeId::CREATE | OpcodeId::CREATE2));
+                } else if step.depth < prev_step.depth {
+                    is_create.pop();
+                }
+            }
+            prev_step = Some(step);
+
+            if !is_opcode_supported(step.op) {
+                features.push(UnsupportedFeature::Opcode {
+                    tx_index,
+                    pc: step.pc,
+                    opcode: step.op,
+                });
+            }
+            // Steps that fail before the stack is complete are skipped.
+            match step.op {
+                OpcodeId::CALL
+                | OpcodeId::CALLCODE
+                | OpcodeId::DELEGATECALL
+                | OpcodeId::STATICCALL => {
+                    if let Ok(address) = step.stack.nth_last(1) {
+                        check_precompile(&mut features, tx_index, word_to_address(address));
+                    }
+                }
+                OpcodeId::CREATE | OpcodeId::CREATE2 => {
+                    if let Ok(length) = step.stack.nth_last(2) {
+                        add_code(
+                            &mut features,
+                            &mut bytecode_rows,
+                            max_bytecode,
+                            tx_index,
+                            length,
+                        );
+                    }
+                }
+                OpcodeId::RETURN if is_create.last() == Some(&true) => {
+                    if let Ok(length) = step.stack.nth_last(1) {
+                        add_code(
+                            &mut features,
+                            &mut bytecode_rows,
+                            max_bytecode,
+                            tx_index,
+                            length,
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    UnsupportedReport {
+        block_number: eth_block.number.map(|n| n.as_u64()),
+        features,
+    }
+}
+
+/// Add a code of `length` bytes to the rows of the bytecode circuit, a row per byte plus a
+/// header row, and report it if it's the first one that doesn't fit.
+fn add_code(
+    features: &mut Vec<UnsupportedFeature>,
+    bytecode_rows: &mut usize,
+    max_bytecode: usize,
+    tx_index: usize,
+    length: Word,
+) {
+    let fits = *bytecode_rows <= max_bytecode;
+    let length = length.min(Word::from(u32::MAX)).as_usize();
+    *bytecode_rows = bytecode_rows.saturating_add(length + 1);
+    if fits && *bytecode_rows > max_bytecode {
+        features.push(UnsupportedFeature::OversizedCode {
+            tx_index,
+            size: *bytecode_rows,
+            limit: max_bytecode,
+        });
+    }
+}
+
+fn word_to_address(word: Word) -> Address {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    Address::from_slice(&bytes[12..])
+}
+
+fn check_precompile(features: &mut Vec<UnsupportedFeature>, tx_index: usize, address: Address) {
+    if !is_precompiled(&address) {
+        return;
+    }
+    let precompile = PrecompileCalls::from(address.0[19]);
+    if !is_precompile_supported(precompile) {
+        features.push(UnsupportedFeature::Precompile {
+            tx_index,
+            address,
+            name: format!("{:?}", precompile),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{bytecode, H256};
+    use ethers_core::types::transaction::eip2930::{AccessList, AccessListItem};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn check(code: eth_types::Bytecode) -> UnsupportedReport {
+        let block = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+        check_block_support(&block.eth_block, &block.geth_traces, 1000)
+    }
+
+    #[test]
+    fn prefilter_supported_block() {
+        let report = check(bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH1(0x00)
+            PUSH1(0x04)
+            PUSH2(0xffff)
+            CALL
+            STOP
+        });
+        assert!(report.is_supported(), "{:?}", report);
+    }
+
+    #[test]
+    fn prefilter_unsupported_precompile() {
+        let report = check(bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH1(0x00)
+            PUSH1(0x05)
+            PUSH2(0xffff)
+            CALL
+            STOP
+        });
+        assert_eq!(
+            report.features,
+            vec![UnsupportedFeature::Precompile {
+                tx_index: 0,
+                address: PrecompileCalls::Modexp.into(),
+                name: "Modexp".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn prefilter_selfdestruct() {
+        let report = check(bytecode! {
+            PUSH1(0x00)
+            SELFDESTRUCT
+        });
+        assert_eq!(
+            report.features,
+            vec![UnsupportedFeature::Opcode {
+                tx_index: 0,
+                pc: 2,
+                opcode: OpcodeId::SELFDESTRUCT,
+            }]
+        );
+    }
+
+    #[test]
+    fn prefilter_oversized_create() {
+        let report = check(bytecode! {
+            PUSH2(0x1000)
+            PUSH1(0x00)
+            PUSH1(0x00)
+            CREATE
+            STOP
+        });
+        assert_eq!(
+            report.features,
+            vec![UnsupportedFeature::OversizedCode {
+                tx_index: 0,
+                size: 0x1001,
+                limit: 1000,
+            }]
+        );
+    }
+
+    #[test]
+    fn prefilter_cumulative_bytecode() {
+        // Each creation code fits on its own, but not both.
+        let report = check(bytecode! {
+            PUSH2(600)
+            PUSH1(0x00)
+            PUSH1(0x00)
+            CREATE
+            PUSH2(600)
+            PUSH1(0x00)
+            PUSH1(0x00)
+            CREATE
+            STOP
+        });
+        assert_eq!(
+            report.features,
+            vec![UnsupportedFeature::OversizedCode {
+                tx_index: 0,
+                size: 1202,
+                limit: 1000,
+            }]
+        );
+    }
+
+    #[test]
+    fn prefilter_access_list() {
+        // One more address than the limit, each with a storage key.
+        let access_list = AccessList(
+            (0..=MAX_ACCESS_LIST_ADDRESSES)
+                .map(|i| AccessListItem {
+                    address: Address::repeat_byte(0x10 + i as u8),
+                    storage_keys: vec![H256::zero()],
+                })
+                .collect(),
+        );
+        let block = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .transaction_type(1)
+                    .access_list(access_list);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+        let report = check_block_support(&block.eth_block, &block.geth_traces, 1000);
+        assert_eq!(
+            report.features,
+            vec![UnsupportedFeature::AccessList {
+                tx_index: 0,
+                addresses: MAX_ACCESS_LIST_ADDRESSES + 1,
+                storage_keys: MAX_ACCESS_LIST_ADDRESSES + 1,
+            }]
+        );
+    }
+}
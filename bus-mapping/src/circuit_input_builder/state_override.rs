@@ -0,0 +1,153 @@
+//! State overrides applied on top of the pre-state of a block, so that
+//! synthetic scenarios can be proven without building a whole test chain.
+
+use crate::{
+    state_db::{Account, CodeDB, StateDB},
+    Error,
+};
+use eth_types::geth_types::StateOverride;
+
+/// Apply the state override to the pre-state of a block.  Overridden
+/// accounts that don't exist in the state are created.
+///
+/// The traces used to generate the circuit inputs must come from the
+/// execution of the block with the same override applied, otherwise the
+/// witness won't match the state.
+pub fn apply_state_override(
+    sdb: &mut StateDB,
+    code_db: &mut CodeDB,
+    state_override: &StateOverride,
+) -> Result<(), Error> {
+    for (address, account_override) in state_override {
+        if account_override.is_conflicting() {
+            return Err(Error::InvalidStateOverride(*address));
+        }
+        let (_, account) = sdb.get_account_mut(address);
+        let Account {
+            nonce,
+            balance,
+            storage,
+            code_hash,
+        } = account;
+        if let Some(value) = account_override.nonce {
+            *nonce = value.as_u64();
+        }
+        if let Some(value) = account_override.balance {
+            *balance = value;
+        }
+        if let Some(code) = &account_override.code {
+            *code_hash = code_db.insert(code.to_vec());
+        }
+        if account_override.state.is_some() {
+            storage.clear();
+        }
+        storage.extend(account_override.storage_slots());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{geth_types::AccountOverride, Address, Bytes, Hash, Word};
+    use std::collections::HashMap;
+
+    fn slot(value: u64) -> Hash {
+        Hash::from_low_u64_be(value)
+    }
+
+    #[test]
+    fn state_override_apply() {
+        let address = Address::repeat_byte(0x01);
+        let mut sdb = StateDB::new();
+        sdb.set_account(
+            &address,
+            Account {
+                nonce: 1,
+                balance: Word::from(100),
+                storage: HashMap::from([(Word::from(1), Word::from(1)), (2.into(), 2.into())]),
+                code_hash: CodeDB::empty_code_hash(),
+            },
+        );
+        let mut code_db = CodeDB::default();
+
+        let new_address = Address::repeat_byte(0x02);
+        let code = Bytes::from(vec![0x60, 0x00, 0x00]);
+        let state_override = StateOverride::from([
+            (
+                address,
+                AccountOverride {
+                    balance: Some(Word::from(200)),
+                    state_diff: Some(HashMap::from([(slot(2), slot(3))])),
+                    ..Default::default()
+                },
+            ),
+            (
+                new_address,
+                AccountOverride {
+                    nonce: Some(5.into()),
+                    code: Some(code.clone()),
+                    state: Some(HashMap::from([(slot(7), slot(8))])),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        apply_state_override(&mut sdb, &mut code_db, &state_override).unwrap();
+
+        let (_, account) = sdb.get_account(&address);
+        assert_eq!(account.nonce, 1);
+        assert_eq!(account.balance, Word::from(200));
+        assert_eq!(account.storage[&Word::from(1)], Word::from(1));
+        assert_eq!(account.storage[&Word::from(2)], Word::from(3));
+
+        let (found, account) = sdb.get_account(&new_address);
+        assert!(found);
+        assert_eq!(account.nonce, 5);
+        assert_eq!(account.code_hash, CodeDB::hash(&code));
+        assert!(code_db.get_from_h256(&account.code_hash).is_some());
+        assert_eq!(
+            account.storage,
+            HashMap::from([(Word::from(7), Word::from(8))])
+        );
+    }
+
+    #[test]
+    fn state_override_conflicting() {
+        let address = Address::repeat_byte(0x01);
+        let state_override = StateOverride::from([(
+            address,
+            AccountOverride {
+                state: Some(HashMap::new()),
+                state_diff: Some(HashMap::new()),
+                ..Default::default()
+            },
+        )]);
+        let err =
+            apply_state_override(&mut StateDB::new(), &mut CodeDB::default(), &state_override)
+                .unwrap_err();
+        assert!(matches!(err, Error::InvalidStateOverride(a) if a == address));
+    }
+
+    #[test]
+    fn state_override_deserialize() {
+        let state_override: StateOverride = serde_json::from_str(
+            r#"{
+                "0x0101010101010101010101010101010101010101": {
+                    "balance": "0x10",
+                    "stateDiff": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                        "0x0000000000000000000000000000000000000000000000000000000000000002"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let account_override = &state_override[&Address::repeat_byte(0x01)];
+        assert_eq!(account_override.balance, Some(Word::from(0x10)));
+        assert_eq!(account_override.nonce, None);
+        assert_eq!(
+            account_override.storage_slots().collect::<Vec<_>>(),
+            vec![(Word::from(1), Word::from(2))]
+        );
+    }
+}
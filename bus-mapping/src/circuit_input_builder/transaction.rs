@@ -2,7 +2,7 @@
 
 use std::collections::BTreeMap;
 
-use eth_types::{evm_types::Memory, geth_types, GethExecTrace};
+use eth_types::{evm_types::Memory, geth_types, GethExecTrace, Word};
 use ethers_core::utils::get_contract_address;
 
 use crate::{
@@ -190,13 +190,15 @@ pub struct Transaction {
 }
 
 impl Transaction {
-    /// Create a new Self.
+    /// Create a new Self.  The gas price of an EIP-1559 transaction is its effective gas price
+    /// in the block of base fee `base_fee`.
     pub fn new(
         id: u64,
         call_id: usize,
         sdb: &StateDB,
         code_db: &mut CodeDB,
         eth_tx: &eth_types::Transaction,
+        base_fee: Word,
         is_success: bool,
     ) -> Result<Self, Error> {
         let (found, _) = sdb.get_account(&eth_tx.from);
@@ -246,9 +248,12 @@ impl Transaction {
             }
         };
 
+        let mut tx: geth_types::Transaction = eth_tx.into();
+        tx.gas_price = tx.effective_gas_price(base_fee);
+
         Ok(Self {
             id,
-            tx: eth_tx.into(),
+            tx,
             calls: vec![call],
             steps: Vec::new(),
         })
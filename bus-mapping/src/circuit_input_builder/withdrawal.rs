@@ -1,6 +1,6 @@
 //! Withdrawal & WithdrawalContext utility module.
 
-use eth_types::Address;
+use eth_types::{Address, Word};
 
 use crate::Error;
 
@@ -42,9 +42,9 @@ impl Withdrawal {
             amount,
         })
     }
-    /// Return the amount in this withdrawal
-    pub fn amount_in_wei(&self) -> u64 {
-        self.amount * (10 ^ 9)
+    /// Return the amount of this withdrawal in Wei
+    pub fn amount_in_wei(&self) -> Word {
+        Word::from(self.amount) * Word::exp10(9)
     }
 
     /// Constructor for padding withdrawal in withdrawal circuit
@@ -55,3 +55,104 @@ impl Withdrawal {
         }
     }
 }
+
+#[cfg(test)]
+mod withdrawal_tests {
+    use crate::{
+        mock::BlockData,
+        operation::{AccountField, AccountOp, Target, RW},
+        state_db::CodeDB,
+    };
+    use eth_types::{geth_types::GethData, Address, ToWord, Word};
+    use mock::{eth, test_ctx::helpers::tx_from_1_to_0, TestContext2, MOCK_ACCOUNTS};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn withdrawal_credits() {
+        let new_account = Address::repeat_byte(0xad);
+        let block: GethData = TestContext2::<2, 1, 3>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(1));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            tx_from_1_to_0,
+            |mut wds| {
+                wds[0].id(10).address(MOCK_ACCOUNTS[0]).amount(5);
+                wds[1].id(11).address(new_account).amount(7);
+                wds[2].id(12).address(new_account).amount(0);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let builder = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let (_, account) = builder.sdb.get_account(&MOCK_ACCOUNTS[0]);
+        let balance_prev = account.balance - Word::from(5_000_000_000u64);
+        let end_block = &builder.block.block_steps.end_block_last;
+        let credits = end_block
+            .bus_mapping_instance
+            .iter()
+            .filter(|op| op.target() == Target::Account)
+            .map(|op| {
+                let op = &builder.block.container.account[op.as_usize()];
+                (op.rw(), op.op().clone())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            credits,
+            vec![
+                (
+                    RW::READ,
+                    AccountOp::new(
+                        MOCK_ACCOUNTS[0],
+                        AccountField::CodeHash,
+                        account.code_hash.to_word(),
+                        account.code_hash.to_word(),
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp::new(
+                        MOCK_ACCOUNTS[0],
+                        AccountField::Balance,
+                        account.balance,
+                        balance_prev,
+                    )
+                ),
+                (
+                    RW::READ,
+                    AccountOp::new(
+                        new_account,
+                        AccountField::CodeHash,
+                        Word::zero(),
+                        Word::zero(),
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp::new(
+                        new_account,
+                        AccountField::CodeHash,
+                        CodeDB::empty_code_hash().to_word(),
+                        Word::zero(),
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp::new(
+                        new_account,
+                        AccountField::Balance,
+                        Word::from(7_000_000_000u64),
+                        Word::zero(),
+                    )
+                ),
+            ]
+        );
+    }
+}
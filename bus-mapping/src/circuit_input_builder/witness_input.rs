@@ -0,0 +1,241 @@
+//! Serializable input of the witness generation of a block.
+//!
+//! The witness of a block is generated from the block, its traces, and the state it reads, all
+//! fetched from a node.  A [`WitnessInput`] carries all of them, so that they can be fetched by a
+//! machine next to the node, and the witness generated by the machine of the prover from the
+//! input alone, with [`WitnessInput::into_builder`].
+//!
+//! The input is encoded in JSON, with the serde encoding of its fields, and has a
+//! [`WITNESS_INPUT_VERSION`].  A field added to the format has a default value, so that the
+//! inputs written before it can still be read, and doesn't change the version.  Any other change
+//! increases the version, and an input of another version is rejected.
+
+use super::{get_state_accesses, Block, CircuitInputBuilder, FeatureConfig, FixedCParams};
+use crate::{
+    state_db::{self, CodeDB, StateDB},
+    Error,
+};
+use eth_types::{geth_types::GethData, Address, Bytes, GethExecTrace, Word, H256};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Read, Write},
+};
+
+/// Version of the witness input format.  Increase it whenever a change of the format isn't the
+/// addition of a field with a default value.
+pub const WITNESS_INPUT_VERSION: u32 = 1;
+
+/// Account of the state read by the block, before the block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessAccount {
+    /// Address
+    pub address: Address,
+    /// Nonce
+    pub nonce: u64,
+    /// Balance
+    pub balance: Word,
+    /// Hash of the code
+    pub code_hash: H256,
+    /// Storage slots read by the block
+    pub storage: BTreeMap<Word, Word>,
+}
+
+/// Everything the witness generation of a block reads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WitnessInput {
+    /// Version of the format
+    pub version: u32,
+    /// Chain id
+    pub chain_id: Word,
+    /// Hashes of the up to 256 blocks before the block, the latest last
+    pub history_hashes: Vec<Word>,
+    /// State root before the block
+    pub prev_state_root: Word,
+    /// Block
+    pub eth_block: eth_types::Block<eth_types::Transaction>,
+    /// Traces of the transactions of the block
+    pub geth_traces: Vec<GethExecTrace>,
+    /// Accounts read by the block
+    pub accounts: Vec<WitnessAccount>,
+    /// Codes of the accounts read by the block
+    pub codes: Vec<Bytes>,
+    /// Parameters of the circuits
+    pub circuits_params: FixedCParams,
+    /// Features of the circuits
+    pub feature_config: FeatureConfig,
+}
+
+impl WitnessInput {
+    /// Input of the data of a block generated by the mock context of the tests, with the
+    /// accounts the block accesses that aren't in the data initialized to zero.
+    pub fn from_geth_data(
+        geth_data: GethData,
+        circuits_params: FixedCParams,
+        feature_config: FeatureConfig,
+    ) -> Result<Self, Error> {
+        let access_set = get_state_accesses(&geth_data.eth_block, &geth_data.geth_traces)?;
+        let mut accounts: BTreeMap<Address, WitnessAccount> = access_set
+            .state
+            .keys()
+            .map(|address| {
+                let account = WitnessAccount {
+                    address: *address,
+                    code_hash: CodeDB::empty_code_hash(),
+                    ..Default::default()
+                };
+                (*address, account)
+            })
+            .collect();
+        for account in geth_data.accounts.iter() {
+            accounts.insert(
+                account.address,
+                WitnessAccount {
+                    address: account.address,
+                    nonce: account.nonce.as_u64(),
+                    balance: account.balance,
+                    code_hash: CodeDB::hash(&account.code),
+                    storage: account.storage.clone().into_iter().collect(),
+                },
+            );
+        }
+        Ok(Self {
+            version: WITNESS_INPUT_VERSION,
+            chain_id: geth_data.chain_id,
+            history_hashes: geth_data.history_hashes,
+            prev_state_root: Word::zero(),
+            eth_block: geth_data.eth_block,
+            geth_traces: geth_data.geth_traces,
+            accounts: accounts.into_values().collect(),
+            codes: geth_data
+                .accounts
+                .into_iter()
+                .map(|account| account.code)
+                .collect(),
+            circuits_params,
+            feature_config,
+        })
+    }
+
+    /// State and codes read by the block.
+    pub fn state_code_db(&self) -> (StateDB, CodeDB) {
+        let mut sdb = StateDB::new();
+        for account in self.accounts.iter() {
+            sdb.set_account(
+                &account.address,
+                state_db::Account {
+                    nonce: account.nonce,
+                    balance: account.balance,
+                    storage: HashMap::from_iter(account.storage.clone()),
+                    code_hash: account.code_hash,
+                },
+            );
+        }
+        let code_db = CodeDB::from(
+            self.codes
+                .iter()
+                .map(|code| code.to_vec())
+                .collect::<Vec<_>>(),
+        );
+        (sdb, code_db)
+    }
+
+    /// Generate the witness of the block.
+    pub fn into_builder(self) -> Result<CircuitInputBuilder<FixedCParams>, Error> {
+        let (sdb, code_db) = self.state_code_db();
+        let block = Block::new(
+            self.chain_id,
+            self.history_hashes,
+            self.prev_state_root,
+            &self.eth_block,
+        )?;
+        let mut builder = CircuitInputBuilder::new(
+            sdb,
+            code_db,
+            block,
+            self.circuits_params,
+            self.feature_config,
+        );
+        builder.handle_block(&self.eth_block, &self.geth_traces)?;
+        Ok(builder)
+    }
+
+    /// Write the input as JSON.
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), Error> {
+        serde_json::to_writer(writer, self).map_err(Error::SerdeError)
+    }
+
+    /// Read an input from JSON, and check that it is of the current version of the format.
+    pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+        let input: Self = serde_json::from_reader(reader).map_err(Error::SerdeError)?;
+        if input.version != WITNESS_INPUT_VERSION {
+            return Err(Error::UnsupportedWitnessInputVersion(input.version));
+        }
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::bytecode;
+    use mock::TestContext;
+
+    #[test]
+    fn witness_input_roundtrip() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            PUSH1(0x00)
+            SLOAD
+            STOP
+        };
+        let geth_data: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let input = WitnessInput::from_geth_data(
+            geth_data.clone(),
+            FixedCParams::default(),
+            FeatureConfig::default(),
+        )
+        .unwrap();
+
+        let mut buf = Vec::new();
+        input.write(&mut buf).unwrap();
+        let builder = WitnessInput::read(buf.as_slice())
+            .unwrap()
+            .into_builder()
+            .unwrap();
+
+        // The witness is the one generated from the data in memory
+        let mut expected =
+            BlockData::new_from_geth_data_with_params(geth_data.clone(), FixedCParams::default())
+                .new_circuit_input_builder();
+        expected
+            .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
+            .unwrap();
+        assert_eq!(builder.block.container, expected.block.container);
+        assert_eq!(
+            builder.block.txs[0].steps().len(),
+            expected.block.txs[0].steps().len()
+        );
+
+        // An input of another version is rejected
+        let mut value: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        value["version"] = (WITNESS_INPUT_VERSION + 1).into();
+        assert!(matches!(
+            WitnessInput::read(value.to_string().as_bytes()),
+            Err(Error::UnsupportedWitnessInputVersion(version))
+                if version == WITNESS_INPUT_VERSION + 1
+        ));
+        // A field added with a default value can be left out
+        value["version"] = WITNESS_INPUT_VERSION.into();
+        value["feature_config"]
+            .as_object_mut()
+            .unwrap()
+            .remove("differential");
+        assert!(WitnessInput::read(value.to_string().as_bytes()).is_ok());
+    }
+}
@@ -5,9 +5,12 @@ use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
-use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+use crate::{
+    circuit_input_builder::Divergence,
+    geth_errors::{
+        GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
+        GETH_ERR_STACK_UNDERFLOW,
+    },
 };
 
 /// Error type for any BusMapping related failure.
@@ -42,6 +45,26 @@ pub enum Error {
     InternalError(&'static str),
     /// Rw number overflow
     RwsNotEnough(usize, usize),
+    /// Tx with more addresses in its access list than
+    /// [`MAX_ACCESS_LIST_ADDRESSES`](crate::circuit_input_builder::MAX_ACCESS_LIST_ADDRESSES)
+    TooManyAccessListAddresses(usize, usize),
+    /// Tx with more storage keys in its access list than
+    /// [`MAX_ACCESS_LIST_STORAGE_KEYS`](crate::circuit_input_builder::MAX_ACCESS_LIST_STORAGE_KEYS)
+    TooManyAccessListStorageKeys(usize, usize),
+    /// Tx that self destructs more accounts than
+    /// [`MAX_DESTRUCTED_ACCOUNTS`](crate::circuit_input_builder::MAX_DESTRUCTED_ACCOUNTS)
+    TooManyDestructedAccounts(usize, usize),
+    /// State override of the account sets both `state` and `stateDiff`
+    InvalidStateOverride(Address),
+    /// Block of a batch whose parent hash or previous state root don't match the previous
+    /// block of the batch
+    UnchainedBlock(Word),
+    /// Witness that diverges from the traces it was generated from, found with
+    /// [`FeatureConfig::differential`](crate::circuit_input_builder::FeatureConfig::differential)
+    WitnessDivergence(Vec<Divergence>),
+    /// Witness input of another version of the format than
+    /// [`WITNESS_INPUT_VERSION`](crate::circuit_input_builder::WITNESS_INPUT_VERSION)
+    UnsupportedWitnessInputVersion(u32),
 }
 
 impl From<eth_types::Error> for Error {
@@ -1,7 +1,13 @@
 use super::TxExecSteps;
 use crate::{
-    circuit_input_builder::{Call, CircuitInputStateRef, ExecState, ExecStep},
-    operation::{AccountField, AccountOp, CallContextField, TxReceiptField, TxRefundOp, RW},
+    circuit_input_builder::{
+        Call, CircuitInputStateRef, ExecState, ExecStep, MAX_ACCESS_LIST_ADDRESSES,
+        MAX_ACCESS_LIST_STORAGE_KEYS, MAX_DESTRUCTED_ACCOUNTS,
+    },
+    operation::{
+        AccountField, AccountOp, CallContextField, TxAccessListAccountStorageOp,
+        TxDestructedAccountOp, TxReceiptField, TxRefundOp, RW,
+    },
     state_db::CodeDB,
     Error,
 };
@@ -86,6 +92,47 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         )?;
     }
 
+    // Add the addresses and then the storage keys of the access list (EIP-2930), in the order
+    // of their slots in the tx table.
+    let addresses = state.tx.access_list_addresses();
+    if addresses.len() > MAX_ACCESS_LIST_ADDRESSES {
+        return Err(Error::TooManyAccessListAddresses(
+            addresses.len(),
+            MAX_ACCESS_LIST_ADDRESSES,
+        ));
+    }
+    let storage_keys = state.tx.access_list_storage_keys();
+    if storage_keys.len() > MAX_ACCESS_LIST_STORAGE_KEYS {
+        return Err(Error::TooManyAccessListStorageKeys(
+            storage_keys.len(),
+            MAX_ACCESS_LIST_STORAGE_KEYS,
+        ));
+    }
+    for address in addresses {
+        let is_warm_prev = !state.sdb.add_account_to_access_list(address);
+        state.tx_accesslist_account_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            address,
+            true,
+            is_warm_prev,
+        )?;
+    }
+    for (address, key) in storage_keys {
+        let is_warm_prev = !state.sdb.add_account_storage_to_access_list((address, key));
+        state.push_op(
+            &mut exec_step,
+            RW::WRITE,
+            TxAccessListAccountStorageOp {
+                tx_id: state.tx_ctx.id(),
+                address,
+                key,
+                is_warm: true,
+                is_warm_prev,
+            },
+        )?;
+    }
+
     let init_code_gas_cost = if state.tx.is_create() {
         // Calculate gas cost of init code for EIP-3860.
         (state.tx.call_data.len() as u64 + 31) / 32 * eth_types::evm_types::INIT_CODE_WORD_GAS
@@ -98,6 +145,7 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
     } else {
         GasCost::TX
     } + state.tx.call_data_gas_cost()
+        + state.tx.access_list_gas_cost()
         + init_code_gas_cost;
     exec_step.gas_cost = intrinsic_gas_cost;
 
@@ -315,6 +363,43 @@ fn gen_end_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error>
         false,
     )?;
 
+    // Delete the accounts self destructed in the transaction, burning any balance they received
+    // after SELFDESTRUCT, and clear their marks.
+    let destructed_accounts = state.sdb.destructed_accounts();
+    if destructed_accounts.len() > MAX_DESTRUCTED_ACCOUNTS {
+        return Err(Error::TooManyDestructedAccounts(
+            destructed_accounts.len(),
+            MAX_DESTRUCTED_ACCOUNTS,
+        ));
+    }
+    for address in destructed_accounts {
+        state.push_op(
+            &mut exec_step,
+            RW::WRITE,
+            TxDestructedAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address,
+                is_destructed: false,
+                is_destructed_prev: true,
+            },
+        )?;
+        let account = state.sdb.get_account(&address).1.clone();
+        for (field, value_prev) in [
+            (AccountField::Nonce, Word::from(account.nonce)),
+            (AccountField::Balance, account.balance),
+            (AccountField::CodeHash, account.code_hash.to_word()),
+        ] {
+            state.account_write(
+                &mut exec_step,
+                address,
+                field,
+                Word::zero(),
+                value_prev,
+                false,
+            )?;
+        }
+    }
+
     end_tx(state, &mut exec_step, &call)?;
 
     Ok(exec_step)
@@ -8,7 +8,10 @@ use crate::{
         precompiles::gen_associated_ops as precompile_associated_ops,
     },
     operation::{AccountField, CallContextField, TxAccessListAccountOp},
-    precompile::{execute_precompiled, is_precompiled, PrecompileCalls},
+    precompile::{
+        ecrecover_sign_data, execute_precompiled, is_precompiled, PrecompileAuxData,
+        PrecompileCalls,
+    },
     state_db::CodeDB,
     Error,
 };
@@ -360,28 +363,31 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                     state.call_context_write(&mut exec_step, current_call.call_id, field, value)?;
                 }
 
-                // insert a copy event (input) for this step and generate memory op
+                // insert a copy event (input) for this step and generate memory op.  The
+                // precompiles with a fixed input length take exactly that many bytes, the call
+                // data being padded with zeros when shorter.
                 let rw_counter_start = state.block_ctx.rwc;
-                if call.call_data_length > 0 {
-                    let n_input_bytes = if let Some(input_len) = precompile_call.input_len() {
-                        min(input_len, call.call_data_length as usize)
-                    } else {
-                        call.call_data_length as usize
-                    };
-
-                    let input_bytes = state.gen_copy_steps_for_precompile_calldata(
+                let n_input_bytes = precompile_call
+                    .input_len()
+                    .unwrap_or(call.call_data_length as usize);
+                let mut input_bytes = if call.call_data_length > 0 {
+                    state.gen_copy_steps_for_precompile_calldata(
                         &mut exec_step,
                         call.call_data_offset,
-                        n_input_bytes as u64,
-                    )?;
-
+                        min(n_input_bytes, call.call_data_length as usize) as u64,
+                    )?
+                } else {
+                    vec![]
+                };
+                input_bytes.resize(n_input_bytes, 0);
+                if call.call_data_length > 0 {
                     state.push_copy(
                         &mut exec_step,
                         CopyEvent {
                             src_id: NumberOrHash::Number(call.caller_id),
                             src_type: CopyDataType::Memory,
                             src_addr: call.call_data_offset,
-                            src_addr_end: call.call_data_offset + n_input_bytes as u64,
+                            src_addr_end: call.call_data_offset + call.call_data_length,
                             dst_id: NumberOrHash::Number(call.call_id),
                             dst_type: CopyDataType::RlcAcc,
                             dst_addr: 0,
@@ -467,6 +473,15 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                     // Set gas left and gas cost for precompile step.
                     precompile_step.gas_left = callee_gas_left_with_stipend;
                     precompile_step.gas_cost = precompile_call_gas_cost;
+                    if precompile_call == PrecompileCalls::ECRecover && !result.is_empty() {
+                        let sign_data = ecrecover_sign_data(&input_bytes)
+                            .expect("ecRecover returned an address for an invalid signature");
+                        state.block.add_ecrecover_event(sign_data);
+                    }
+                    precompile_step.aux_data = Some(PrecompileAuxData {
+                        input_bytes,
+                        output_bytes: result,
+                    });
                     // Make the Precompile execution step to handle return logic and restore to
                     // caller context (similar as STOP and RETURN).
                     state.handle_return(
@@ -0,0 +1,308 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word, H256};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+///
+/// The balance of the account is moved to the beneficiary right away, but
+/// the account itself is only marked as destructed, and deleted at the end of
+/// the transaction, in the EndTx step, so that it keeps its code until then.
+/// The tree follows Shanghai: the EIP-6780 restriction of the deletion to the
+/// accounts created in the same transaction isn't applied.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Selfdestruct;
+
+impl Opcode for Selfdestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let call = state.call()?.clone();
+        let sender = call.address;
+
+        for (field, value) in [
+            (CallContextField::TxId, Word::from(state.tx_ctx.id())),
+            (CallContextField::IsStatic, Word::from(call.is_static as u8)),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                Word::from(call.rw_counter_end_of_reversion),
+            ),
+            (
+                CallContextField::IsPersistent,
+                Word::from(call.is_persistent as u8),
+            ),
+            (CallContextField::CalleeAddress, sender.to_word()),
+            (CallContextField::IsSuccess, Word::one()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value)?;
+        }
+
+        let beneficiary_word = geth_step.stack.last()?;
+        let beneficiary = beneficiary_word.to_address();
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary_word,
+        )?;
+
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op_reversible(
+            &mut exec_step,
+            TxAccessListAccountOp {
+                tx_id: state.tx_ctx.id(),
+                address: beneficiary,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        let beneficiary_account = state.sdb.get_account(&beneficiary).1;
+        let beneficiary_exists = !beneficiary_account.is_empty();
+        let beneficiary_code_hash = if beneficiary_exists {
+            beneficiary_account.code_hash
+        } else {
+            H256::zero()
+        };
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            beneficiary_code_hash.to_word(),
+        )?;
+
+        // The balance is burnt when the beneficiary is the account itself.
+        let value = state.sdb.get_account(&sender).1.balance;
+        state.account_write(
+            &mut exec_step,
+            sender,
+            AccountField::Balance,
+            Word::zero(),
+            value,
+            true,
+        )?;
+        if beneficiary != sender {
+            state.transfer_to(
+                &mut exec_step,
+                beneficiary,
+                beneficiary_exists,
+                false,
+                value,
+                true,
+            )?;
+        }
+
+        // Reverted together with the call, as the other writes.
+        state.tx_destructed_account_write(&mut exec_step, sender)?;
+
+        state.handle_return(&mut [&mut exec_step], geth_steps, !call.is_root)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use crate::{
+        circuit_input_builder::{ExecState, ExecStep, MAX_DESTRUCTED_ACCOUNTS},
+        mock::BlockData,
+        operation::{AccountField, AccountOp, Target, RW},
+        state_db::CodeDB,
+        Error,
+    };
+    use eth_types::{
+        bytecode, evm_types::OpcodeId, geth_types::GethData, Address, Bytecode, ToWord, Word, U256,
+    };
+    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn selfdestruct_opcode_impl() {
+        let beneficiary = Address::repeat_byte(0xbe);
+        let code = bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .nonce(1)
+                    .balance(Word::from(1000))
+                    .code(code);
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+            },
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let builder = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let tx = &builder.block.txs()[0];
+        let account_ops = |step: &ExecStep| {
+            step.bus_mapping_instance
+                .iter()
+                .filter(|op| op.target() == Target::Account)
+                .map(|op| {
+                    let op = &builder.block.container.account[op.as_usize()];
+                    (op.rw(), op.op().clone())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap();
+        assert_eq!(
+            account_ops(step),
+            vec![
+                (
+                    RW::READ,
+                    AccountOp::new(
+                        beneficiary,
+                        AccountField::CodeHash,
+                        Word::zero(),
+                        Word::zero()
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp::new(
+                        MOCK_ACCOUNTS[0],
+                        AccountField::Balance,
+                        Word::zero(),
+                        Word::from(1000)
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp::new(
+                        beneficiary,
+                        AccountField::CodeHash,
+                        CodeDB::empty_code_hash().to_word(),
+                        Word::zero()
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    AccountOp::new(
+                        beneficiary,
+                        AccountField::Balance,
+                        Word::from(1000),
+                        Word::zero()
+                    )
+                ),
+            ]
+        );
+
+        // The account is marked as destructed, then deleted at the end of the transaction, with
+        // its mark cleared.
+        let destructed_ops = |step: &ExecStep| {
+            step.bus_mapping_instance
+                .iter()
+                .filter(|op| op.target() == Target::TxDestructedAccount)
+                .map(|op| builder.block.container.tx_destructed_account[op.as_usize()].clone())
+                .map(|op| {
+                    (
+                        op.op().is_destructed_prev,
+                        op.op().is_destructed,
+                        op.reversible(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(destructed_ops(step), vec![(false, true, true)]);
+        let end_tx = tx.steps().last().unwrap();
+        assert_eq!(end_tx.exec_state, ExecState::EndTx);
+        assert_eq!(destructed_ops(end_tx), vec![(true, false, false)]);
+        let deletion = account_ops(end_tx)
+            .into_iter()
+            .filter(|(_, op)| op.address == MOCK_ACCOUNTS[0])
+            .map(|(_, op)| (op.field, op.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deletion,
+            vec![
+                (AccountField::Nonce, Word::zero()),
+                (AccountField::Balance, Word::zero()),
+                (AccountField::CodeHash, Word::zero()),
+            ]
+        );
+        let (_, account) = builder.sdb.get_account(&MOCK_ACCOUNTS[0]);
+        assert_eq!(account.balance, U256::zero());
+        assert!(account.is_empty());
+    }
+
+    #[test]
+    fn selfdestruct_too_many_accounts() {
+        // The tx calls one more contract than EndTx can delete, each of which self destructs.
+        let destructed = (0..=MAX_DESTRUCTED_ACCOUNTS)
+            .map(|i| Address::repeat_byte(0x10 + i as u8))
+            .collect::<Vec<_>>();
+        let mut code = Bytecode::default();
+        for address in destructed.iter() {
+            code.append(&bytecode! {
+                PUSH1(0x00)
+                PUSH1(0x00)
+                PUSH1(0x00)
+                PUSH1(0x00)
+                PUSH1(0x00)
+                PUSH20(address.to_word())
+                PUSH2(0xffff)
+                CALL
+                POP
+            });
+        }
+        code.append(&bytecode! { STOP });
+        let block: GethData = TestContext::<{ MAX_DESTRUCTED_ACCOUNTS + 3 }, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code);
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(10u64.pow(19)));
+                for (acc, address) in accs[2..].iter_mut().zip(destructed.iter()) {
+                    acc.address(*address).code(bytecode! {
+                        PUSH1(0x00)
+                        SELFDESTRUCT
+                    });
+                }
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::TooManyDestructedAccounts(len, MAX_DESTRUCTED_ACCOUNTS)
+                    if len == MAX_DESTRUCTED_ACCOUNTS + 1
+            ),
+            "{:?}",
+            err
+        );
+    }
+}
@@ -24,6 +24,7 @@ impl fmt::Debug for OperationRef {
                 Target::CallContext => "CallContext",
                 Target::TxReceipt => "TxReceipt",
                 Target::TxLog => "TxLog",
+                Target::TxDestructedAccount => "TxDestructedAccount",
             },
             self.1
         ))
@@ -116,6 +116,8 @@ pub enum Target {
     TxReceipt,
     /// Means the target of the operation is the TxLog.
     TxLog,
+    /// Means the target of the operation is the TxDestructedAccount.
+    TxDestructedAccount,
 }
 
 impl_expr!(Target);
@@ -133,6 +135,7 @@ impl Target {
             self,
             Target::TxAccessListAccount
                 | Target::TxAccessListAccountStorage
+                | Target::TxDestructedAccount
                 | Target::TxRefund
                 | Target::Account
                 | Target::Storage
@@ -456,6 +459,56 @@ impl Op for TxAccessListAccountOp {
     }
 }
 
+/// Represents a change in the set of accounts destructed by a transaction, implied by a
+/// `SELFDESTRUCT` step, which marks its callee, or by the `EndTx` step, which deletes the marked
+/// accounts and clears their marks.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TxDestructedAccountOp {
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+    /// Account Address
+    pub address: Address,
+    /// Whether the account is marked to be deleted at the end of the transaction.
+    pub is_destructed: bool,
+    /// Whether the account was marked before the operation.
+    pub is_destructed_prev: bool,
+}
+
+impl fmt::Debug for TxDestructedAccountOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TxDestructedAccountOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, is_destructed_prev: {:?}, is_destructed: {:?}",
+            self.tx_id, self.address, self.is_destructed_prev, self.is_destructed
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl PartialOrd for TxDestructedAccountOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxDestructedAccountOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address).cmp(&(&other.tx_id, &other.address))
+    }
+}
+
+impl Op for TxDestructedAccountOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxDestructedAccount(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.is_destructed, &mut rev.is_destructed_prev);
+        rev
+    }
+}
+
 /// Represents a change in the Storage AccessList implied by an `SSTORE` or
 /// `SLOAD` step of the [`ExecStep`](crate::circuit_input_builder::ExecStep).
 #[derive(Clone, PartialEq, Eq)]
@@ -954,6 +1007,8 @@ pub enum OpEnum {
     TxReceipt(TxReceiptOp),
     /// TxLog
     TxLog(TxLogOp),
+    /// TxDestructedAccount
+    TxDestructedAccount(TxDestructedAccountOp),
     /// Start
     Start(StartOp),
 }
@@ -2,7 +2,12 @@
 
 use eth_types::{
     evm_types::{GasCost, OpcodeId},
-    Address, Bytecode, Word,
+    sign_types::{recover_pk, SignData},
+    Address, Bytecode, ToLittleEndian, Word,
+};
+use halo2_proofs::halo2curves::{
+    group::ff::{FromUniformBytes, PrimeField},
+    secp256k1,
 };
 #[cfg(not(target_arch = "wasm32"))]
 use revm_precompile::{Precompile, PrecompileError, Precompiles};
@@ -149,6 +154,41 @@ impl PrecompileCalls {
     }
 }
 
+/// Input and output bytes of a precompile call, kept in the precompile execution step for the
+/// gadget witnessing it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrecompileAuxData {
+    /// Input bytes of the call.  For the precompiles with a fixed input length they are padded
+    /// with zeros, or truncated, to that length.
+    pub input_bytes: Vec<u8>,
+    /// Output bytes of the call.
+    pub output_bytes: Vec<u8>,
+}
+
+/// Return the signature verified for an ecRecover call from its (padded) input
+/// `msg_hash || v || r || s`, or `None` if no public key can be recovered from it.
+pub fn ecrecover_sign_data(input: &[u8]) -> Option<SignData> {
+    let input: &[u8; 128] = input.try_into().ok()?;
+    let msg_hash: [u8; 32] = input[..32].try_into().unwrap();
+    let [sig_v, sig_r, sig_s] = [32, 64, 96].map(|i| Word::from_big_endian(&input[i..i + 32]));
+    if sig_v != 27.into() && sig_v != 28.into() {
+        return None;
+    }
+    let [r, s] = [sig_r, sig_s]
+        .map(|word| Option::<secp256k1::Fq>::from(secp256k1::Fq::from_repr(word.to_le_bytes())));
+    let pk = recover_pk(sig_v.as_u64() as u8 - 27, &sig_r, &sig_s, &msg_hash).ok()?;
+    // msg_hash = msg_hash % q, as for the transaction signatures
+    let mut msg_hash_le = [0u8; 64];
+    msg_hash_le[..32].copy_from_slice(&msg_hash);
+    msg_hash_le[..32].reverse();
+    let msg_hash = secp256k1::Fq::from_uniform_bytes(&msg_hash_le);
+    Some(SignData {
+        signature: (r?, s?),
+        pk,
+        msg_hash,
+    })
+}
+
 /// Precompile call args
 pub struct PrecompileCallArgs {
     /// description for the instance of a precompile call.
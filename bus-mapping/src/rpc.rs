@@ -10,6 +10,9 @@ pub use ethers_core::types::BlockNumber;
 use ethers_providers::JsonRpcClient;
 use serde::Serialize;
 
+mod cache;
+pub use cache::{CacheError, CachedClient};
+
 /// Serialize a type.
 ///
 /// # Panics
@@ -0,0 +1,239 @@
+//! Disk-backed cache of the JSON-RPC responses of a node.
+//!
+//! Generating the witness of a block queries the same block, traces, codes and proofs on every
+//! run.  Wrapping the provider of a [`GethClient`](super::GethClient) in a [`CachedClient`]
+//! stores the responses of the queries pinned to a block on disk, so that repeated runs over the
+//! same blocks during development only hit the node once:
+//!
+//! ```ignore
+//! let provider = CachedClient::new(Http::from_str(url)?, "rpc-cache")?;
+//! let cli = GethClient::new(provider);
+//! ```
+
+use async_trait::async_trait;
+use eth_types::H256;
+use ethers_core::utils::keccak256;
+use ethers_providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Methods whose response only depends on their params, as long as the block is not given by
+/// one of the [`MOVING_BLOCK_TAGS`].
+const CACHEABLE_METHODS: &[&str] = &[
+    "eth_chainId",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getCode",
+    "eth_getProof",
+    "debug_traceBlockByHash",
+    "debug_traceBlockByNumber",
+];
+
+/// Block tags that resolve to a different block as the chain advances.
+const MOVING_BLOCK_TAGS: &[&str] = &["latest", "pending", "safe", "finalized"];
+
+/// Error of a [`CachedClient`].
+#[derive(Debug)]
+pub enum CacheError {
+    /// Error of the wrapped client.
+    Client(ProviderError),
+    /// Error reading or writing the cache.
+    Io(io::Error),
+    /// Error de/serializing a request or response.
+    Serde(serde_json::Error),
+}
+
+impl Display for CacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+impl RpcError for CacheError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Self::Client(ProviderError::JsonRpcClientError(err)) => err.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Self::Client(ProviderError::JsonRpcClientError(err)) => err.as_serde_error(),
+            Self::Client(ProviderError::SerdeJson(err)) | Self::Serde(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CacheError> for ProviderError {
+    fn from(err: CacheError) -> Self {
+        match err {
+            CacheError::Client(err) => err,
+            err => ProviderError::JsonRpcClientError(Box::new(err)),
+        }
+    }
+}
+
+/// [`JsonRpcClient`] that caches on disk the responses of the wrapped client to the requests
+/// pinned to a block.  Requests that use a moving block tag such as `latest`, and methods not
+/// listed as cacheable, are always forwarded to the wrapped client.
+///
+/// Responses are stored as one JSON file per request, keyed by the method and its params (which
+/// include the block), so the cache of a block can be dropped by deleting its files.
+#[derive(Debug)]
+pub struct CachedClient<P> {
+    inner: P,
+    dir: PathBuf,
+}
+
+impl<P: JsonRpcClient> CachedClient<P> {
+    /// Wrap `inner` with a cache stored in `dir`, which is created if it doesn't exist.
+    pub fn new(inner: P, dir: impl Into<PathBuf>) -> Result<Self, io::Error> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { inner, dir })
+    }
+
+    /// Return the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return the wrapped client.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    fn path(&self, method: &str, params: &Value) -> PathBuf {
+        let key = H256(keccak256(format!("{}:{}", method, params)));
+        self.dir.join(format!("{}-{:x}.json", method, key))
+    }
+
+    async fn forward(&self, method: &str, params: Value) -> Result<Value, CacheError> {
+        self.inner
+            .request(method, params)
+            .await
+            .map_err(|e| CacheError::Client(e.into()))
+    }
+}
+
+fn is_cacheable(method: &str, params: &Value) -> bool {
+    let uses_moving_tag = params.as_array().map_or(false, |params| {
+        params.iter().any(|param| {
+            matches!(param, Value::String(tag) if MOVING_BLOCK_TAGS.contains(&tag.as_str()))
+        })
+    });
+    CACHEABLE_METHODS.contains(&method) && !uses_moving_tag
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<P: JsonRpcClient> JsonRpcClient for CachedClient<P> {
+    type Error = CacheError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params).map_err(CacheError::Serde)?;
+        if !is_cacheable(method, &params) {
+            let resp = self.forward(method, params).await?;
+            return serde_json::from_value(resp).map_err(CacheError::Serde);
+        }
+
+        let path = self.path(method, &params);
+        let resp = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(CacheError::Serde)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let resp = self.forward(method, params).await?;
+                // `null` is returned for blocks that the node doesn't have yet, which may exist
+                // later on.
+                if !resp.is_null() {
+                    // Write to a temporary file first so that an interrupted run doesn't leave a
+                    // truncated response in the cache.
+                    let tmp = path.with_extension("tmp");
+                    let bytes = serde_json::to_vec(&resp).map_err(CacheError::Serde)?;
+                    fs::write(&tmp, bytes).map_err(CacheError::Io)?;
+                    fs::rename(&tmp, &path).map_err(CacheError::Io)?;
+                }
+                resp
+            }
+            Err(err) => return Err(CacheError::Io(err)),
+        };
+        serde_json::from_value(resp).map_err(CacheError::Serde)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Client that echoes the requests and counts them.
+    #[derive(Debug, Default)]
+    struct CountingClient(AtomicUsize);
+
+    #[async_trait]
+    impl JsonRpcClient for CountingClient {
+        type Error = CacheError;
+
+        async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+        where
+            T: Debug + Serialize + Send + Sync,
+            R: DeserializeOwned + Send,
+        {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            serde_json::from_value(json!([method, params])).map_err(CacheError::Serde)
+        }
+    }
+
+    #[test]
+    fn cached_client_requests() {
+        let dir = std::env::temp_dir().join("bus-mapping-rpc-cache-test");
+        let _ = fs::remove_dir_all(&dir);
+        let cli = CachedClient::new(CountingClient::default(), &dir).unwrap();
+        let request = |method: &str, params: Value| -> Value {
+            block_on(cli.request(method, params)).unwrap()
+        };
+
+        let pinned = json!(["0x0101010101010101010101010101010101010101", "0x10"]);
+        let resp = request("eth_getCode", pinned.clone());
+        assert_eq!(resp, json!(["eth_getCode", pinned]));
+        assert_eq!(request("eth_getCode", pinned.clone()), resp);
+        assert_eq!(cli.inner.0.load(Ordering::SeqCst), 1);
+
+        // Another block is another entry.
+        request(
+            "eth_getCode",
+            json!(["0x0101010101010101010101010101010101010101", "0x11"]),
+        );
+        assert_eq!(cli.inner.0.load(Ordering::SeqCst), 2);
+
+        // Moving block tags and non cacheable methods are always forwarded.
+        let latest = json!(["0x0101010101010101010101010101010101010101", "latest"]);
+        request("eth_getCode", latest.clone());
+        request("eth_getCode", latest);
+        request("miner_stop", json!([]));
+        request("miner_stop", json!([]));
+        assert_eq!(cli.inner.0.load(Ordering::SeqCst), 6);
+
+        // The cache survives the client.
+        let cli = CachedClient::new(CountingClient::default(), &dir).unwrap();
+        let resp: Value = block_on(cli.request("eth_getCode", pinned.clone())).unwrap();
+        assert_eq!(resp, json!(["eth_getCode", pinned]));
+        assert_eq!(cli.inner.0.load(Ordering::SeqCst), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -140,8 +140,8 @@ pub struct StateDB {
     // state before current transaction, to calculate gas cost for some opcodes like sstore.
     // So both dirty storage and committed storage are needed.
     dirty_storage: HashMap<(Address, Word), Word>,
-    // Accounts that have been through `SELFDESTRUCT` under the situation that `is_persistent` is
-    // `true`. These accounts will be reset once `commit_tx` is called.
+    // Accounts that have been through `SELFDESTRUCT` in a call that hasn't been reverted. These
+    // accounts will be reset once `commit_tx` is called.
     destructed_account: HashSet<Address>,
     refund: u64,
 }
@@ -286,6 +286,22 @@ impl StateDB {
         self.destructed_account.insert(addr);
     }
 
+    /// Check whether `addr` has been self destructed in the current transaction.
+    pub fn check_account_destructed(&self, addr: &Address) -> bool {
+        self.destructed_account.contains(addr)
+    }
+
+    /// Unset account as self destructed, when the `SELFDESTRUCT` is reverted.
+    pub fn remove_destructed_account(&mut self, addr: &Address) {
+        let exist = self.destructed_account.remove(addr);
+        debug_assert!(exist);
+    }
+
+    /// Return the accounts self destructed in the current transaction, sorted by address.
+    pub fn destructed_accounts(&self) -> Vec<Address> {
+        self.destructed_account.iter().copied().sorted().collect()
+    }
+
     /// Retrieve refund.
     pub fn refund(&self) -> u64 {
         self.refund
@@ -307,7 +323,7 @@ impl StateDB {
             *ptr = value;
         }
         self.dirty_storage = HashMap::new();
-        for addr in self.destructed_account.clone() {
+        for addr in std::mem::take(&mut self.destructed_account) {
             let (_, account) = self.get_account_mut(&addr);
             *account = ACCOUNT_ZERO.clone();
         }
@@ -0,0 +1,486 @@
+//! Proving benchmarks over sweeps of circuits, degrees and thread counts
+//!
+//! A [`BenchSweep`] is the product of a list of circuits, of degrees and of thread counts.  The
+//! `bench_sweep` bench proves each [`BenchCase`] of the sweep in a thread pool of its thread
+//! count, and writes a [`BenchRun`] with the [`MachineInfo`] of the run and a [`ProvingReport`]
+//! per case to `REPORT_PATH`.  The `compare_bench_runs` bench prints the [`RunComparison`] of the
+//! runs at `BASE_REPORT` and `NEW_REPORT`, so that the runs of two commits can be compared case
+//! by case:
+//!
+//! ```text
+//! git checkout main
+//! BENCH_CIRCUITS=exp,bytecode BENCH_DEGREES=16,18 BENCH_THREADS=8,16 REPORT_PATH=base.json make bench_sweep
+//! git checkout my-optimization
+//! BENCH_CIRCUITS=exp,bytecode BENCH_DEGREES=16,18 BENCH_THREADS=8,16 REPORT_PATH=new.json make bench_sweep
+//! BASE_REPORT=base.json NEW_REPORT=new.json make bench_compare
+//! ```
+
+use crate::pk_comparison::{ProvingReport, ReportComparison};
+use serde::{Deserialize, Serialize};
+use std::{env, fmt, fs, io, path::Path, process::Command, str::FromStr, thread};
+use zkevm_circuits::{
+    estimate::proc_memory_kb,
+    local_prover::{prove_many_local, Partition},
+};
+
+/// Machine and commit of a benchmark run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineInfo {
+    /// Operating system
+    pub os: String,
+    /// CPU architecture
+    pub arch: String,
+    /// Threads available to the process
+    pub threads: usize,
+    /// Total memory, in kB.  Only measured on Linux.
+    pub memory_kb: Option<u64>,
+    /// Commit of the benchmarked circuits, if run in a git checkout
+    pub commit: Option<String>,
+}
+
+impl MachineInfo {
+    /// Info of this machine and of the commit of the working directory.
+    pub fn current() -> Self {
+        Self {
+            os: env::consts::OS.to_string(),
+            arch: env::consts::ARCH.to_string(),
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            memory_kb: memory_kb(),
+            commit: git_commit(),
+        }
+    }
+
+    /// Whether the runs of both machines are comparable, whatever their commits.
+    pub fn same_machine(&self, other: &Self) -> bool {
+        (&self.os, &self.arch, self.threads, self.memory_kb)
+            == (&other.os, &other.arch, other.threads, other.memory_kb)
+    }
+}
+
+/// Total memory of the machine, in kB.
+fn memory_kb() -> Option<u64> {
+    proc_memory_kb("/proc/meminfo", "MemTotal")
+}
+
+/// Commit of the working directory.
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
+
+/// Circuit, degree and thread count of a benchmark.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchCase {
+    /// Name of the circuit
+    pub circuit: String,
+    /// Degree of the circuit
+    pub degree: u32,
+    /// Threads of the pool the proof runs on
+    pub threads: usize,
+}
+
+impl fmt::Display for BenchCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} with degree {} on {} threads",
+            self.circuit, self.degree, self.threads
+        )
+    }
+}
+
+/// Product of circuits, degrees and thread counts to benchmark.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchSweep {
+    /// Names of the circuits
+    pub circuits: Vec<String>,
+    /// Degrees of the circuits
+    pub degrees: Vec<u32>,
+    /// Thread counts
+    pub threads: Vec<usize>,
+}
+
+impl BenchSweep {
+    /// Sweep of the comma separated lists of `BENCH_CIRCUITS`, `BENCH_DEGREES` and
+    /// `BENCH_THREADS`, by default the circuits and degrees given and the threads of the machine.
+    pub fn from_env(circuits: &str, degrees: &str) -> Self {
+        let threads = MachineInfo::current().threads.to_string();
+        Self {
+            circuits: list("BENCH_CIRCUITS", circuits),
+            degrees: list("BENCH_DEGREES", degrees),
+            threads: list("BENCH_THREADS", &threads),
+        }
+    }
+
+    /// Cases of the sweep, by circuit, then by degree, then by thread count.
+    pub fn cases(&self) -> Vec<BenchCase> {
+        let mut cases = Vec::new();
+        for circuit in &self.circuits {
+            for &degree in &self.degrees {
+                for &threads in &self.threads {
+                    cases.push(BenchCase {
+                        circuit: circuit.clone(),
+                        degree,
+                        threads,
+                    });
+                }
+            }
+        }
+        cases
+    }
+}
+
+/// Values of the comma separated list of the environment variable `var`, or of `default`.
+fn list<T: FromStr>(var: &str, default: &str) -> Vec<T> {
+    env::var(var)
+        .unwrap_or(default.to_string())
+        .split(',')
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("Cannot parse {} env var", var))
+        })
+        .collect()
+}
+
+/// Report of a case of a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchResult {
+    /// Benchmarked case
+    pub case: BenchCase,
+    /// Time and peak memory of the phases of the case
+    pub report: ProvingReport,
+}
+
+/// Reports of the cases of a sweep on a machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchRun {
+    /// Machine and commit of the run
+    pub machine: MachineInfo,
+    /// Reports, in the order of the cases
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchRun {
+    /// Run `bench` on each case of the sweep, in a thread pool of the threads of the case, which
+    /// the parallel work of the prover runs on.  `bench` records its phases in the report.
+    pub fn run(sweep: &BenchSweep, bench: impl Fn(&BenchCase, &mut ProvingReport) + Sync) -> Self {
+        let results = sweep
+            .cases()
+            .into_iter()
+            .map(|case| {
+                let partition = Partition {
+                    jobs: 1,
+                    threads_per_job: case.threads,
+                };
+                let report = ProvingReport::new(&case.circuit, case.degree);
+                let report = prove_many_local(vec![report], &partition, |mut report| {
+                    bench(&case, &mut report);
+                    report
+                })
+                .expect("cannot build the thread pool")
+                .remove(0);
+                BenchResult { case, report }
+            })
+            .collect();
+        Self {
+            machine: MachineInfo::current(),
+            results,
+        }
+    }
+
+    /// Read a run written by [`BenchRun::write`].
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Write the run as JSON.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// Comparison of a case between two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseComparison {
+    /// Compared case
+    pub case: BenchCase,
+    /// Report of the case in the base run, if it has it
+    pub base: Option<ProvingReport>,
+    /// Report of the case in the new run, if it has it
+    pub new: Option<ProvingReport>,
+}
+
+impl CaseComparison {
+    /// Comparison of the phases of the case, if both runs have it.
+    pub fn reports(&self) -> Option<ReportComparison> {
+        Some(ReportComparison::new(self.base.clone()?, self.new.clone()?))
+    }
+}
+
+/// Comparison of a base and a new run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunComparison {
+    /// Machine of the base run
+    pub base: MachineInfo,
+    /// Machine of the new run
+    pub new: MachineInfo,
+    /// Cases of both runs, in the order of the base run
+    pub cases: Vec<CaseComparison>,
+}
+
+impl RunComparison {
+    /// Compare the reports of two runs by case.
+    pub fn new(base: BenchRun, new: BenchRun) -> Self {
+        let mut cases: Vec<CaseComparison> = base
+            .results
+            .into_iter()
+            .map(|result| CaseComparison {
+                case: result.case,
+                base: Some(result.report),
+                new: None,
+            })
+            .collect();
+        for result in new.results {
+            match cases
+                .iter_mut()
+                .find(|comparison| comparison.case == result.case)
+            {
+                Some(comparison) => comparison.new = Some(result.report),
+                None => cases.push(CaseComparison {
+                    case: result.case,
+                    base: None,
+                    new: Some(result.report),
+                }),
+            }
+        }
+        Self {
+            base: base.machine,
+            new: new.machine,
+            cases,
+        }
+    }
+}
+
+impl fmt::Display for RunComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn commit(machine: &MachineInfo) -> &str {
+            machine.commit.as_deref().unwrap_or("unknown commit")
+        }
+
+        if !self.base.same_machine(&self.new) {
+            writeln!(
+                f,
+                "WARNING: comparing runs on different machines: {:?} and {:?}",
+                self.base, self.new
+            )?;
+        }
+        writeln!(f, "{} -> {}", commit(&self.base), commit(&self.new))?;
+        for comparison in &self.cases {
+            writeln!(f)?;
+            writeln!(f, "{}", comparison.case)?;
+            match (&comparison.base, &comparison.new) {
+                (Some(_), None) => writeln!(f, "only in the base run")?,
+                (None, Some(_)) => writeln!(f, "only in the new run")?,
+                _ => {}
+            }
+            if let Some(reports) = comparison.reports() {
+                write!(f, "{}", reports)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pk_comparison::PhaseReport;
+    use bus_mapping::state_db::CodeDB;
+    use halo2_proofs::{
+        halo2curves::bn256::{Bn256, Fr, G1Affine},
+        plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use std::env::var;
+    use zkevm_circuits::{
+        bytecode_circuit::TestBytecodeCircuit, evm_circuit::witness::RwMap,
+        exp_circuit::TestExpCircuit, state_circuit::TestStateCircuit, util::SubCircuit,
+    };
+
+    fn result(circuit: &str, degree: u32, threads: usize, proof_ms: u128) -> BenchResult {
+        let mut report = ProvingReport::new(circuit, degree);
+        report.phases = vec![PhaseReport {
+            name: "proof generation".to_string(),
+            duration_ms: proof_ms,
+            peak_rss_kb: None,
+        }];
+        BenchResult {
+            case: BenchCase {
+                circuit: circuit.to_string(),
+                degree,
+                threads,
+            },
+            report,
+        }
+    }
+
+    #[test]
+    fn bench_sweep_comparison() {
+        let sweep = BenchSweep {
+            circuits: vec!["exp".to_string(), "bytecode".to_string()],
+            degrees: vec![16, 18],
+            threads: vec![8],
+        };
+        let cases: Vec<_> = sweep.cases().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            cases,
+            [
+                "exp with degree 16 on 8 threads",
+                "exp with degree 18 on 8 threads",
+                "bytecode with degree 16 on 8 threads",
+                "bytecode with degree 18 on 8 threads",
+            ]
+        );
+
+        let machine = MachineInfo {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            threads: 8,
+            memory_kb: Some(1 << 20),
+            commit: Some("base".to_string()),
+        };
+        let base = BenchRun {
+            machine: machine.clone(),
+            results: vec![result("exp", 16, 8, 300), result("exp", 18, 8, 1200)],
+        };
+        let new = BenchRun {
+            machine: MachineInfo {
+                commit: Some("new".to_string()),
+                ..machine
+            },
+            results: vec![result("exp", 18, 8, 800), result("bytecode", 16, 8, 500)],
+        };
+
+        let comparison = RunComparison::new(base, new);
+        assert!(comparison.base.same_machine(&comparison.new));
+        assert_eq!(comparison.cases.len(), 3);
+        assert!(comparison.cases[0].reports().is_none());
+        assert_eq!(
+            comparison.cases[1].reports().unwrap().phases[0].speedup(),
+            Some(1.5)
+        );
+        assert!(comparison.cases[2].base.is_none());
+        let display = comparison.to_string();
+        assert!(display.starts_with("base -> new"));
+        assert!(display.contains("only in the new run"));
+        assert!(display.contains("1.50x"));
+    }
+
+    /// Prove the circuit at `degree` and record the phases in the report.
+    fn bench_circuit<C: Circuit<Fr>>(
+        degree: u32,
+        circuit: C,
+        instance: Vec<Vec<Fr>>,
+        report: &mut ProvingReport,
+    ) {
+        let instance_refs: Vec<&[Fr]> = instance.iter().map(|v| &v[..]).collect();
+        // The same seed gives the same params in every run
+        let mut rng = ChaChaRng::seed_from_u64(2);
+
+        let params = report.measure("setup", || ParamsKZG::<Bn256>::setup(degree, &mut rng));
+        let pk = report.measure("keygen", || {
+            let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+            keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail")
+        });
+        let proof = report.measure("proof generation", || {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit],
+                &[&instance_refs],
+                &mut rng,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            transcript.finalize()
+        });
+        report.proof_bytes = proof.len();
+        report.measure("verification", || {
+            let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+            verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&instance_refs],
+                &mut transcript,
+            )
+            .expect("failed to verify bench circuit");
+        });
+    }
+
+    fn bench_case(case: &BenchCase, report: &mut ProvingReport) {
+        let degree = case.degree;
+        match case.circuit.as_str() {
+            "exp" => {
+                let circuit = TestExpCircuit::<Fr>::new(vec![], (1 << degree) / 16);
+                let instance = circuit.instance();
+                bench_circuit(degree, circuit, instance, report);
+            }
+            "bytecode" => {
+                let circuit = TestBytecodeCircuit::<Fr>::new(CodeDB::default(), 1 << degree);
+                let instance = circuit.instance();
+                bench_circuit(degree, circuit, instance, report);
+            }
+            "state" => {
+                // The state circuit loads a table of 2^16 rows
+                assert!(
+                    degree >= 17,
+                    "the state circuit needs a degree of 17 or more"
+                );
+                let circuit = TestStateCircuit::<Fr>::new(RwMap::default(), 1 << (degree - 2));
+                let instance = circuit.instance();
+                bench_circuit(degree, circuit, instance, report);
+            }
+            circuit => panic!("Unknown circuit {} in BENCH_CIRCUITS", circuit),
+        }
+    }
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn bench_sweep() {
+        let report_path = var("REPORT_PATH").unwrap_or("bench_run.json".to_string());
+        let sweep = BenchSweep::from_env("exp,bytecode", "16");
+        let run = BenchRun::run(&sweep, bench_case);
+        run.write(&report_path).expect("cannot write report");
+        println!("{}", serde_json::to_string_pretty(&run).unwrap());
+    }
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn compare_bench_runs() {
+        let base = BenchRun::read(var("BASE_REPORT").expect("BASE_REPORT is not set"))
+            .expect("cannot read the base run");
+        let new = BenchRun::read(var("NEW_REPORT").expect("NEW_REPORT is not set"))
+            .expect("cannot read the new run");
+        println!("{}", RunComparison::new(base, new));
+    }
+}
@@ -4,6 +4,15 @@ pub mod evm_circuit;
 #[cfg(feature = "benches")]
 pub mod state_circuit;
 
+#[cfg(feature = "benches")]
+pub mod pk_comparison;
+
+#[cfg(feature = "benches")]
+pub mod witness_size;
+
+#[cfg(feature = "benches")]
+pub mod bench;
+
 #[cfg(test)]
 #[cfg(feature = "benches")]
 pub mod tx_circuit;
@@ -0,0 +1,458 @@
+//! Comparison of the proving performance of two proving keys
+//!
+//! An optimization of the circuit layout changes the proving key, and the key of one version of
+//! the circuits can only be read by the same version, as reading a key configures the circuit
+//! again.  The comparison is thus made in two steps:
+//!
+//! 1. The `bench_super_circuit_pk` bench is run on each version.  It proves the same witness with
+//!    the proving key stored at `PK_PATH`, generating and storing it if the file doesn't exist,
+//!    and writes a [`ProvingReport`] with the time and peak memory of each phase to
+//!    `REPORT_PATH`.
+//! 2. The `compare_pk_reports` bench reads the reports at `BASE_REPORT` and `NEW_REPORT` and
+//!    prints their [`ReportComparison`].
+//!
+//! ```text
+//! git checkout main
+//! PK_PATH=base.pk REPORT_PATH=base.json make pk_bench
+//! git checkout my-optimization
+//! PK_PATH=new.pk REPORT_PATH=new.json make pk_bench
+//! BASE_REPORT=base.json NEW_REPORT=new.json make pk_compare
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs, io,
+    path::Path,
+    time::{Duration, Instant},
+};
+use zkevm_circuits::estimate::proc_memory_kb;
+
+/// Time and peak memory of a proving phase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseReport {
+    /// Name of the phase
+    pub name: String,
+    /// Wall time of the phase, in milliseconds
+    pub duration_ms: u128,
+    /// Peak resident memory during the phase, in kB.  Only measured on Linux.
+    pub peak_rss_kb: Option<u64>,
+}
+
+/// Proving performance of a proving key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvingReport {
+    /// Name of the benchmarked circuit
+    pub circuit: String,
+    /// Degree of the circuit
+    pub degree: u32,
+    /// Size of the serialized proving key, in bytes
+    pub pk_bytes: u64,
+    /// Proof size, in bytes
+    pub proof_bytes: usize,
+    /// Phases, in the order they run
+    pub phases: Vec<PhaseReport>,
+}
+
+impl ProvingReport {
+    /// Create an empty report.
+    pub fn new(circuit: &str, degree: u32) -> Self {
+        Self {
+            circuit: circuit.to_string(),
+            degree,
+            pk_bytes: 0,
+            proof_bytes: 0,
+            phases: vec![],
+        }
+    }
+
+    /// Run a phase and record its time and peak memory.
+    pub fn measure<T>(&mut self, name: &str, phase: impl FnOnce() -> T) -> T {
+        reset_peak_rss();
+        let start = Instant::now();
+        let result = phase();
+        let duration = start.elapsed();
+        self.phases.push(PhaseReport {
+            name: name.to_string(),
+            duration_ms: duration.as_millis(),
+            peak_rss_kb: peak_rss_kb(),
+        });
+        result
+    }
+
+    /// Read a report written by [`ProvingReport::write`].
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Write the report as JSON.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// Comparison of a phase between two reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseComparison {
+    /// Name of the phase
+    pub name: String,
+    /// Phase in the base report, if it has it
+    pub base: Option<PhaseReport>,
+    /// Phase in the new report, if it has it
+    pub new: Option<PhaseReport>,
+}
+
+impl PhaseComparison {
+    /// Ratio of the base time to the new time: above 1 when the new key proves faster.
+    pub fn speedup(&self) -> Option<f64> {
+        match (&self.base, &self.new) {
+            (Some(base), Some(new)) if new.duration_ms > 0 => {
+                Some(base.duration_ms as f64 / new.duration_ms as f64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Difference of the new peak memory with the base peak memory, in kB.
+    pub fn peak_rss_diff_kb(&self) -> Option<i64> {
+        let base = self.base.as_ref()?.peak_rss_kb?;
+        let new = self.new.as_ref()?.peak_rss_kb?;
+        Some(new as i64 - base as i64)
+    }
+}
+
+/// Comparison of the reports of a base and a new proving key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportComparison {
+    /// Base report
+    pub base: ProvingReport,
+    /// New report
+    pub new: ProvingReport,
+    /// Phases of both reports, in the order of the base report
+    pub phases: Vec<PhaseComparison>,
+}
+
+impl ReportComparison {
+    /// Compare the phases of two reports by name.
+    pub fn new(base: ProvingReport, new: ProvingReport) -> Self {
+        let find = |report: &ProvingReport, name: &str| {
+            report
+                .phases
+                .iter()
+                .find(|phase| phase.name == name)
+                .cloned()
+        };
+        let mut names: Vec<&str> = base
+            .phases
+            .iter()
+            .map(|phase| phase.name.as_str())
+            .collect();
+        for phase in &new.phases {
+            if !names.contains(&phase.name.as_str()) {
+                names.push(&phase.name);
+            }
+        }
+        let phases = names
+            .into_iter()
+            .map(|name| PhaseComparison {
+                name: name.to_string(),
+                base: find(&base, name),
+                new: find(&new, name),
+            })
+            .collect();
+        Self { base, new, phases }
+    }
+}
+
+impl fmt::Display for ReportComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn duration(phase: &Option<PhaseReport>) -> String {
+            phase.as_ref().map_or("-".to_string(), |phase| {
+                format!("{:?}", Duration::from_millis(phase.duration_ms as u64))
+            })
+        }
+        fn memory(phase: &Option<PhaseReport>) -> String {
+            phase
+                .as_ref()
+                .and_then(|phase| phase.peak_rss_kb)
+                .map_or("-".to_string(), |kb| format!("{} MB", kb / 1024))
+        }
+
+        if self.base.circuit != self.new.circuit || self.base.degree != self.new.degree {
+            writeln!(
+                f,
+                "WARNING: comparing {} with degree {} to {} with degree {}",
+                self.base.circuit, self.base.degree, self.new.circuit, self.new.degree
+            )?;
+        }
+        writeln!(
+            f,
+            "{} with degree {}: pk {} -> {} bytes, proof {} -> {} bytes",
+            self.new.circuit,
+            self.new.degree,
+            self.base.pk_bytes,
+            self.new.pk_bytes,
+            self.base.proof_bytes,
+            self.new.proof_bytes
+        )?;
+        writeln!(
+            f,
+            "{:<20} {:>12} {:>12} {:>8} {:>10} {:>10}",
+            "phase", "base time", "new time", "speedup", "base mem", "new mem"
+        )?;
+        for phase in &self.phases {
+            writeln!(
+                f,
+                "{:<20} {:>12} {:>12} {:>8} {:>10} {:>10}",
+                phase.name,
+                duration(&phase.base),
+                duration(&phase.new),
+                phase
+                    .speedup()
+                    .map_or("-".to_string(), |speedup| format!("{:.2}x", speedup)),
+                memory(&phase.base),
+                memory(&phase.new),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Reset the peak resident memory of the process, so that it is measured per phase.
+fn reset_peak_rss() {
+    // Writing 5 to clear_refs resets VmHWM on Linux.  Nothing to do on other systems, where the
+    // peak memory isn't measured.
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Peak resident memory of the process since the last reset, in kB.
+fn peak_rss_kb() -> Option<u64> {
+    proc_memory_kb("/proc/self/status", "VmHWM")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::{end_timer, start_timer};
+    use bus_mapping::circuit_input_builder::FixedCParams;
+    use eth_types::{address, bytecode, geth_types::GethData, Word};
+    use ethers_signers::{LocalWallet, Signer};
+    use halo2_proofs::{
+        halo2curves::bn256::{Bn256, Fr, G1Affine},
+        plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+        SerdeFormat,
+    };
+    use mock::{TestContext, MOCK_CHAIN_ID};
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use std::{collections::HashMap, env::var, fs::File, io::BufReader};
+    use zkevm_circuits::super_circuit::SuperCircuit;
+
+    fn phase(name: &str, duration_ms: u128, peak_rss_kb: Option<u64>) -> PhaseReport {
+        PhaseReport {
+            name: name.to_string(),
+            duration_ms,
+            peak_rss_kb,
+        }
+    }
+
+    #[test]
+    fn report_comparison() {
+        let mut base = ProvingReport::new("Super Circuit", 19);
+        base.phases = vec![
+            phase("witness", 100, Some(2048)),
+            phase("proof generation", 3000, Some(8192)),
+        ];
+        let mut new = ProvingReport::new("Super Circuit", 19);
+        new.phases = vec![
+            phase("witness", 100, Some(2048)),
+            phase("proof generation", 2000, Some(6144)),
+            phase("verification", 10, None),
+        ];
+
+        let comparison = ReportComparison::new(base, new);
+        let names: Vec<_> = comparison.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["witness", "proof generation", "verification"]);
+        assert_eq!(comparison.phases[1].speedup(), Some(1.5));
+        assert_eq!(comparison.phases[1].peak_rss_diff_kb(), Some(-2048));
+        assert_eq!(comparison.phases[2].speedup(), None);
+        assert!(comparison.to_string().contains("1.50x"));
+    }
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn bench_super_circuit_pk() {
+        // Unique string used by bench results module for parsing the result
+        const BENCHMARK_ID: &str = "Super Circuit";
+
+        let degree: u32 = var("DEGREE")
+            .unwrap_or("19".to_string())
+            .parse()
+            .expect("Cannot parse DEGREE env var as u32");
+        let pk_path = var("PK_PATH").expect("PK_PATH is not set");
+        let report_path = var("REPORT_PATH").unwrap_or("pk_report.json".to_string());
+        let mut report = ProvingReport::new(BENCHMARK_ID, degree);
+
+        // The same seed gives the same witness and params in every version
+        let mut rng = ChaChaRng::seed_from_u64(2);
+
+        let (circuit, instance) = report.measure("witness", || {
+            let wallet_a = LocalWallet::new(&mut rng).with_chain_id((*MOCK_CHAIN_ID).as_u64());
+
+            let addr_a = wallet_a.address();
+            let addr_b = address!("0x000000000000000000000000000000000000BBBB");
+
+            let mut wallets = HashMap::new();
+            wallets.insert(wallet_a.address(), wallet_a);
+
+            let mut block: GethData = TestContext::<2, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(addr_b)
+                        .balance(Word::from(1u64 << 20))
+                        .code(bytecode! { STOP });
+                    accs[1].address(addr_a).balance(Word::from(1u64 << 20));
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[1].address)
+                        .to(accs[0].address)
+                        .gas(Word::from(1_000_000u64));
+                },
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap()
+            .into();
+            block.sign(&wallets);
+
+            let circuits_params = FixedCParams {
+                max_txs: 1,
+                max_withdrawals: 1,
+                max_calldata: 32,
+                max_ecrecover: 0,
+                max_rws: 256,
+                max_copy_rows: 256,
+                max_exp_steps: 256,
+                max_bytecode: 512,
+                max_evm_rows: 0,
+                max_keccak_rows: 0,
+            };
+            let (_, circuit, instance, _) =
+                SuperCircuit::build(block, circuits_params, Fr::from(0x100)).unwrap();
+            (circuit, instance)
+        });
+        let instance_refs: Vec<&[Fr]> = instance.iter().map(|v| &v[..]).collect();
+
+        let start1 = start_timer!(|| format!(
+            "{} {} with degree = {}",
+            BENCHMARK_ID,
+            crate::constants::SETUP_PREFIX,
+            degree
+        ));
+        let general_params =
+            report.measure("setup", || ParamsKZG::<Bn256>::setup(degree, &mut rng));
+        let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
+        end_timer!(start1);
+
+        let pk = if Path::new(&pk_path).exists() {
+            report.measure("pk read", || {
+                let mut reader = BufReader::new(File::open(&pk_path).expect("cannot open pk"));
+                ProvingKey::<G1Affine>::read::<_, SuperCircuit<Fr>>(
+                    &mut reader,
+                    SerdeFormat::RawBytes,
+                    circuit.params(),
+                )
+                .expect("cannot read pk, was it generated by another version of the circuits?")
+            })
+        } else {
+            let pk = report.measure("keygen", || {
+                let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
+                keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail")
+            });
+            pk.write(
+                &mut File::create(&pk_path).expect("cannot create pk"),
+                SerdeFormat::RawBytes,
+            )
+            .expect("cannot write pk");
+            pk
+        };
+        report.pk_bytes = fs::metadata(&pk_path).expect("pk is written").len();
+
+        let start2 = start_timer!(|| format!(
+            "{} {} with degree = {}",
+            BENCHMARK_ID,
+            crate::constants::PROOFGEN_PREFIX,
+            degree
+        ));
+        let proof = report.measure("proof generation", || {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof::<
+                KZGCommitmentScheme<Bn256>,
+                ProverSHPLONK<'_, Bn256>,
+                Challenge255<G1Affine>,
+                ChaChaRng,
+                Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+                SuperCircuit<Fr>,
+            >(
+                &general_params,
+                &pk,
+                &[circuit],
+                &[&instance_refs],
+                rng,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            transcript.finalize()
+        });
+        report.proof_bytes = proof.len();
+        end_timer!(start2);
+
+        let start3 =
+            start_timer!(|| format!("{} {}", BENCHMARK_ID, crate::constants::PROOFVER_PREFIX));
+        report.measure("verification", || {
+            let mut verifier_transcript =
+                Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+            let strategy = SingleStrategy::new(&general_params);
+
+            verify_proof::<
+                KZGCommitmentScheme<Bn256>,
+                VerifierSHPLONK<'_, Bn256>,
+                Challenge255<G1Affine>,
+                Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+                SingleStrategy<'_, Bn256>,
+            >(
+                &verifier_params,
+                pk.get_vk(),
+                strategy,
+                &[&instance_refs],
+                &mut verifier_transcript,
+            )
+            .expect("failed to verify bench circuit");
+        });
+        end_timer!(start3);
+
+        report.write(&report_path).expect("cannot write report");
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    }
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn compare_pk_reports() {
+        let base = ProvingReport::read(var("BASE_REPORT").expect("BASE_REPORT is not set"))
+            .expect("cannot read the base report");
+        let new = ProvingReport::read(var("NEW_REPORT").expect("NEW_REPORT is not set"))
+            .expect("cannot read the new report");
+        println!("{}", ReportComparison::new(base, new));
+    }
+}
@@ -83,6 +83,7 @@ mod tests {
             max_txs: 1,
             max_withdrawals: 1,
             max_calldata: 32,
+            max_ecrecover: 0,
             max_rws: 256,
             max_copy_rows: 256,
             max_exp_steps: 256,
@@ -0,0 +1,308 @@
+//! Serialized size of the witness of a block and its compression
+//!
+//! A [`WitnessSizeReport`] measures the size of each component of the witness of a block, as
+//! serialized in the [`WitnessDump`] format plus the txs of the block, and how well each
+//! [`Codec`] compresses it.  The `bench_witness_size` bench prints the report of a block with
+//! `CALLDATA_BYTES` bytes of calldata, copied `COPY_EVENTS` times and hashed:
+//!
+//! ```text
+//! CALLDATA_BYTES=10000 COPY_EVENTS=100 make witness_size_bench
+//! ```
+
+use eth_types::Field;
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, Write},
+    ops::Deref,
+    time::Instant,
+};
+use zkevm_circuits::witness::{Block, WitnessDump};
+
+/// Compression codec with its level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Codec {
+    /// Gzip, with a level from 0 to 9
+    Gzip(u32),
+    /// Zstandard, with a level from 1 to 22
+    Zstd(i32),
+}
+
+impl Codec {
+    /// Codecs evaluated by default: the fast, default and best levels of each codec.
+    pub const DEFAULT: [Codec; 6] = [
+        Codec::Gzip(1),
+        Codec::Gzip(6),
+        Codec::Gzip(9),
+        Codec::Zstd(1),
+        Codec::Zstd(3),
+        Codec::Zstd(19),
+    ];
+
+    /// Compress the data.
+    pub fn compress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip(level) => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::new(*level));
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Self::Zstd(level) => zstd::encode_all(data, *level),
+        }
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gzip(level) => write!(f, "gzip-{}", level),
+            Self::Zstd(level) => write!(f, "zstd-{}", level),
+        }
+    }
+}
+
+/// Size of a component compressed with a codec.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressedSize {
+    /// Codec used
+    pub codec: Codec,
+    /// Size of the compressed component, in bytes
+    pub bytes: usize,
+    /// Compression time, in microseconds
+    pub duration_us: u128,
+}
+
+/// Size of a component of the witness.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentSize {
+    /// Name of the component
+    pub name: String,
+    /// Number of rows of the component
+    pub rows: usize,
+    /// Size of the serialized component, in bytes
+    pub bytes: usize,
+    /// Size of the serialized component compressed with each codec
+    pub compressed: Vec<CompressedSize>,
+}
+
+impl ComponentSize {
+    fn new(name: &str, rows: usize, data: &[u8], codecs: &[Codec]) -> io::Result<Self> {
+        let compressed = codecs
+            .iter()
+            .map(|codec| {
+                let start = Instant::now();
+                let bytes = codec.compress(data)?.len();
+                Ok(CompressedSize {
+                    codec: *codec,
+                    bytes,
+                    duration_us: start.elapsed().as_micros(),
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            rows,
+            bytes: data.len(),
+            compressed,
+        })
+    }
+}
+
+/// Serialized size of the components of the witness of a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessSizeReport {
+    /// Codecs evaluated, in the order of the compressed sizes of each component
+    pub codecs: Vec<Codec>,
+    /// Components of the witness: the txs and the tables of the [`WitnessDump`]
+    pub components: Vec<ComponentSize>,
+}
+
+impl WitnessSizeReport {
+    /// Measure the size of the witness of the block, compressed with each codec.
+    pub fn new<F: Field>(block: &Block<F>, codecs: &[Codec]) -> io::Result<Self> {
+        let txs = block
+            .txs
+            .iter()
+            .map(|tx| tx.deref().clone())
+            .collect::<Vec<_>>();
+        let mut components = vec![ComponentSize::new(
+            "tx",
+            txs.len(),
+            &serde_json::to_vec(&txs)?,
+            codecs,
+        )?];
+        let dump = WitnessDump::from_block(block)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+        for (name, rows) in dump.tables {
+            components.push(ComponentSize::new(
+                &name,
+                rows.len(),
+                &serde_json::to_vec(&rows)?,
+                codecs,
+            )?);
+        }
+        Ok(Self {
+            codecs: codecs.to_vec(),
+            components,
+        })
+    }
+
+    /// Total size of the serialized witness, in bytes.
+    pub fn bytes(&self) -> usize {
+        self.components
+            .iter()
+            .map(|component| component.bytes)
+            .sum()
+    }
+
+    /// Total size of the serialized witness compressed with each codec, in bytes.
+    pub fn compressed_bytes(&self) -> Vec<usize> {
+        (0..self.codecs.len())
+            .map(|i| {
+                self.components
+                    .iter()
+                    .map(|component| component.compressed[i].bytes)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for WitnessSizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<16} {:>10} {:>12}", "component", "rows", "bytes")?;
+        for codec in &self.codecs {
+            write!(f, " {:>12}", codec.to_string())?;
+        }
+        writeln!(f)?;
+        for component in &self.components {
+            write!(
+                f,
+                "{:<16} {:>10} {:>12}",
+                component.name, component.rows, component.bytes
+            )?;
+            for compressed in &component.compressed {
+                write!(
+                    f,
+                    " {:>12}",
+                    format!(
+                        "{:.1}%",
+                        100.0 * compressed.bytes as f64 / component.bytes.max(1) as f64
+                    )
+                )?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{:<16} {:>10} {:>12}", "total", "", self.bytes())?;
+        for bytes in self.compressed_bytes() {
+            write!(f, " {:>12}", bytes)?;
+        }
+        writeln!(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::test_ctx::{helpers::*, TestContext};
+    use std::env::var;
+    use zkevm_circuits::witness::block_convert;
+
+    /// Block with a tx of `calldata_bytes` bytes of calldata, which is copied to memory and hashed
+    /// `copy_events` times.
+    fn block(calldata_bytes: usize, copy_events: usize) -> Block<Fr> {
+        let calldata = (0..calldata_bytes).map(|i| i as u8).collect::<Vec<_>>();
+        let code = bytecode! {
+            PUSH32(Word::from(copy_events))     // config loop counter
+            JUMPDEST                            // PC offset 0x21
+            CALLDATASIZE
+            PUSH1(0x00)
+            PUSH1(0x00)
+            CALLDATACOPY
+            CALLDATASIZE
+            PUSH1(0x00)
+            SHA3
+            POP
+            PUSH1(0x01)
+            SWAP1
+            SUB
+            DUP1
+            PUSH1(0x21)
+            JUMPI                               // goto JUMPDEST(0x21) if (--copy_events > 0)
+            STOP
+        };
+        let test_ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(calldata.into())
+                    .gas((1e16 as u64).into());
+            },
+            |block, _txs| block.number(0xcafeu64),
+        )
+        .unwrap();
+        let block: GethData = test_ctx.into();
+        // The circuits parameters are derived from the block, so that the tables are not padded
+        let builder = BlockData::new_from_geth_data(block.clone())
+            .new_circuit_input_builder()
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        block_convert(&builder).unwrap()
+    }
+
+    #[test]
+    fn witness_size_report() {
+        let block = block(64, 2);
+        let codecs = [Codec::Gzip(6), Codec::Zstd(3)];
+        let report = WitnessSizeReport::new(&block, &codecs).unwrap();
+
+        let names = report
+            .components
+            .iter()
+            .map(|component| component.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "tx",
+                "block",
+                "bytecode",
+                "copy",
+                "exp",
+                "keccak_inputs",
+                "rw"
+            ]
+        );
+        for component in &report.components {
+            assert_eq!(component.compressed.len(), codecs.len());
+        }
+        // the hex encoded rw rows are highly redundant
+        let rw = report.components.last().unwrap();
+        assert!(rw.compressed.iter().all(|size| size.bytes < rw.bytes));
+        assert_eq!(report.compressed_bytes().len(), codecs.len());
+    }
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn bench_witness_size() {
+        let calldata_bytes: usize = var("CALLDATA_BYTES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .expect("Cannot parse CALLDATA_BYTES env var as usize");
+        let copy_events: usize = var("COPY_EVENTS")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse()
+            .expect("Cannot parse COPY_EVENTS env var as usize");
+
+        let block = block(calldata_bytes, copy_events);
+        let report = WitnessSizeReport::new(&block, &Codec::DEFAULT).unwrap();
+        println!("{}", report);
+    }
+}
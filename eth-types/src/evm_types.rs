@@ -111,6 +111,10 @@ impl GasCost {
     pub const TX: u64 = 21000;
     /// Constant cost for a creation transaction
     pub const CREATION_TX: u64 = 53000;
+    /// Constant cost for each address in the access list of a transaction (EIP-2930)
+    pub const ACCESS_LIST_PER_ADDRESS: u64 = 2400;
+    /// Constant cost for each storage key in the access list of a transaction (EIP-2930)
+    pub const ACCESS_LIST_PER_STORAGE_KEY: u64 = 1900;
     /// Constant cost for calling with non-zero value
     pub const CALL_WITH_VALUE: u64 = 9000;
     /// Constant cost for turning empty account into non-empty account
@@ -2,20 +2,25 @@
 
 use crate::{
     evm_types::{self, GasCost},
-    keccak256,
     sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
     AccessList, Address, Block, Bytecode, Bytes, Error, GethExecTrace, Hash, ToBigEndian,
     ToLittleEndian, ToWord, Word, U64,
 };
 use ethers_core::{
-    types::{transaction::response, NameOrAddress, TransactionRequest},
+    types::{
+        transaction::{
+            eip1559::Eip1559TransactionRequest, eip2718::TypedTransaction,
+            eip2930::Eip2930TransactionRequest, response,
+        },
+        NameOrAddress, TransactionRequest,
+    },
     utils::get_contract_address,
 };
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
 use num::Integer;
 use num_bigint::BigUint;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use serde_with::serde_as;
 use std::collections::HashMap;
 
@@ -69,6 +74,67 @@ impl Account {
     }
 }
 
+/// Override of the fields of an account, in the format of the `stateOverrides`
+/// parameter of geth's `eth_call`.  Unset fields are left untouched.
+#[derive(PartialEq, Eq, Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    /// Nonce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    /// Balance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<Word>,
+    /// EVM Code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Storage that replaces the whole storage of the account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<HashMap<Hash, Hash>>,
+    /// Storage slots that are overridden, the others are kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<HashMap<Hash, Hash>>,
+}
+
+impl AccountOverride {
+    /// Return true if both `state` and `state_diff` are set.  geth rejects
+    /// such an override since the two are contradictory.
+    pub fn is_conflicting(&self) -> bool {
+        self.state.is_some() && self.state_diff.is_some()
+    }
+
+    /// Apply the override to an account.
+    pub fn apply(&self, account: &mut Account) {
+        if let Some(nonce) = self.nonce {
+            account.nonce = nonce;
+        }
+        if let Some(balance) = self.balance {
+            account.balance = balance;
+        }
+        if let Some(code) = &self.code {
+            account.code = code.clone();
+        }
+        if self.state.is_some() {
+            account.storage.clear();
+        }
+        for (key, value) in self.storage_slots() {
+            account.storage.insert(key, value);
+        }
+    }
+
+    /// Return the storage slots set by the override, as words.
+    pub fn storage_slots(&self) -> impl Iterator<Item = (Word, Word)> + '_ {
+        self.state
+            .iter()
+            .chain(self.state_diff.iter())
+            .flatten()
+            .map(|(key, value)| (key.to_word(), value.to_word()))
+    }
+}
+
+/// State overrides indexed by account address.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
 fn serde_account_storage<S: Serializer>(
     to_serialize: &HashMap<Word, Word>,
     serializer: S,
@@ -169,16 +235,18 @@ pub struct Transaction {
     pub gas_limit: U64,
     /// Transfered value
     pub value: Word,
-    /// Gas Price
+    /// Gas Price, the effective gas price for EIP-1559 transactions
     pub gas_price: Word,
-    /// Gas fee cap
+    /// Gas fee cap (EIP-1559 max_fee_per_gas)
     pub gas_fee_cap: Word,
-    /// Gas tip cap
+    /// Gas tip cap (EIP-1559 max_priority_fee_per_gas)
     pub gas_tip_cap: Word,
     /// The compiled code of a contract OR the first 4 bytes of the hash of the
     /// invoked method signature and encoded parameters. For details see
     /// Ethereum Contract ABI
     pub call_data: Bytes,
+    /// Transaction type (EIP-2718), 0 for legacy transactions
+    pub transaction_type: U64,
     /// Access list
     pub access_list: Option<AccessList>,
 
@@ -202,6 +270,7 @@ impl From<&Transaction> for crate::Transaction {
             max_priority_fee_per_gas: Some(tx.gas_tip_cap),
             max_fee_per_gas: Some(tx.gas_fee_cap),
             input: tx.call_data.clone(),
+            transaction_type: Some(tx.transaction_type),
             access_list: tx.access_list.clone(),
             v: tx.v.into(),
             r: tx.r,
@@ -223,6 +292,7 @@ impl From<&crate::Transaction> for Transaction {
             gas_tip_cap: tx.max_priority_fee_per_gas.unwrap_or_default(),
             gas_fee_cap: tx.max_fee_per_gas.unwrap_or_default(),
             call_data: tx.input.clone(),
+            transaction_type: tx.transaction_type.unwrap_or_default(),
             access_list: tx.access_list.clone(),
             v: tx.v.as_u64(),
             r: tx.r,
@@ -259,14 +329,20 @@ impl Transaction {
             secp256k1::Fq::from_repr(sig_s_le),
             Error::Signature(libsecp256k1::Error::InvalidSignature),
         )?;
-        // msg = rlp([nonce, gasPrice, gas, to, value, data, sig_v, r, s])
-        let req: TransactionRequest = self.into();
-        let msg = req.chain_id(chain_id).rlp();
-        let msg_hash: [u8; 32] = keccak256(&msg);
-        let v = self
-            .v
-            .checked_sub(35 + chain_id * 2)
-            .ok_or(Error::Signature(libsecp256k1::Error::InvalidSignature))? as u8;
+        // legacy: msg = rlp([nonce, gasPrice, gas, to, value, data, chain_id, 0, 0])
+        // EIP-2930: msg = 0x01 || rlp([chain_id, nonce, gasPrice, gas, to, value, data,
+        // access_list])
+        // EIP-1559: msg = 0x02 || rlp([chain_id, nonce, max_priority_fee_per_gas,
+        // max_fee_per_gas, gas, to, value, data, access_list])
+        let msg_hash: [u8; 32] = self.typed_tx(chain_id).sighash().to_fixed_bytes();
+        // Typed transactions returned by a node carry the y parity as `v`, while the ones
+        // signed with ethers carry the EIP-155 value.
+        let v = match self.v {
+            v @ (0 | 1) if !self.transaction_type.is_zero() => v,
+            v => v
+                .checked_sub(35 + chain_id * 2)
+                .ok_or(Error::Signature(libsecp256k1::Error::InvalidSignature))?,
+        } as u8;
         let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
         // msg_hash = msg_hash % q
         let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
@@ -283,6 +359,62 @@ impl Transaction {
         })
     }
 
+    /// Return the transaction to be signed, with the signing fields of its type.
+    pub fn typed_tx(&self, chain_id: u64) -> TypedTransaction {
+        let req = TransactionRequest::from(self).chain_id(chain_id);
+        match self.transaction_type.as_u64() {
+            1 => {
+                let access_list = self.access_list.clone().unwrap_or_default();
+                Eip2930TransactionRequest::new(req, access_list).into()
+            }
+            2 => Eip1559TransactionRequest {
+                from: req.from,
+                to: req.to,
+                gas: req.gas,
+                value: req.value,
+                data: req.data,
+                nonce: req.nonce,
+                access_list: self.access_list.clone().unwrap_or_default(),
+                max_priority_fee_per_gas: Some(self.gas_tip_cap),
+                max_fee_per_gas: Some(self.gas_fee_cap),
+                chain_id: req.chain_id,
+            }
+            .into(),
+            _ => req.into(),
+        }
+    }
+
+    /// Return the max fee per gas of the transaction, which is the gas price for the transactions
+    /// before EIP-1559.
+    pub fn max_fee_per_gas(&self) -> Word {
+        if self.transaction_type.as_u64() == 2 {
+            self.gas_fee_cap
+        } else {
+            self.gas_price
+        }
+    }
+
+    /// Return the max priority fee per gas of the transaction, which is the gas price for the
+    /// transactions before EIP-1559.
+    pub fn max_priority_fee_per_gas(&self) -> Word {
+        if self.transaction_type.as_u64() == 2 {
+            self.gas_tip_cap
+        } else {
+            self.gas_price
+        }
+    }
+
+    /// Return the gas price paid by the transaction in a block of base fee `base_fee`:
+    /// min(max_fee_per_gas, base_fee + max_priority_fee_per_gas) for EIP-1559 transactions.
+    pub fn effective_gas_price(&self, base_fee: Word) -> Word {
+        if self.transaction_type.as_u64() == 2 {
+            self.gas_fee_cap
+                .min(base_fee.saturating_add(self.gas_tip_cap))
+        } else {
+            self.gas_price
+        }
+    }
+
     /// Compute call data gas cost from call data
     pub fn call_data_gas_cost(&self) -> u64 {
         self.call_data
@@ -290,6 +422,52 @@ impl Transaction {
             .fold(0, |acc, byte| acc + if *byte == 0 { 4 } else { 16 })
     }
 
+    /// Number of addresses in the access list
+    pub fn access_list_addresses_len(&self) -> u64 {
+        self.access_list
+            .as_ref()
+            .map_or(0, |access_list| access_list.0.len() as u64)
+    }
+
+    /// Number of storage keys in the access list, counting the ones of all its addresses
+    pub fn access_list_storage_keys_len(&self) -> u64 {
+        self.access_list.as_ref().map_or(0, |access_list| {
+            access_list
+                .0
+                .iter()
+                .map(|item| item.storage_keys.len() as u64)
+                .sum()
+        })
+    }
+
+    /// Addresses of the access list, in order
+    pub fn access_list_addresses(&self) -> Vec<Address> {
+        self.access_list.as_ref().map_or(vec![], |access_list| {
+            access_list.0.iter().map(|item| item.address).collect()
+        })
+    }
+
+    /// Storage keys of the access list with the address they belong to, in order
+    pub fn access_list_storage_keys(&self) -> Vec<(Address, Word)> {
+        self.access_list.as_ref().map_or(vec![], |access_list| {
+            access_list
+                .0
+                .iter()
+                .flat_map(|item| {
+                    item.storage_keys
+                        .iter()
+                        .map(|key| (item.address, key.to_word()))
+                })
+                .collect()
+        })
+    }
+
+    /// Compute the access list gas cost (EIP-2930)
+    pub fn access_list_gas_cost(&self) -> u64 {
+        self.access_list_addresses_len() * GasCost::ACCESS_LIST_PER_ADDRESS
+            + self.access_list_storage_keys_len() * GasCost::ACCESS_LIST_PER_STORAGE_KEY
+    }
+
     /// Compute the intrinsic gas cost
     pub fn intrinsic_gas_cost(&self) -> u64 {
         let is_create = self.is_create() as u64;
@@ -299,6 +477,7 @@ impl Transaction {
         is_create * (GasCost::CREATION_TX + init_code_gas_cost)
             + (1 - is_create) * GasCost::TX
             + self.call_data_gas_cost()
+            + self.access_list_gas_cost()
     }
 
     /// Get the "to" address. If `to` is None then zero address
@@ -328,6 +507,7 @@ impl Transaction {
             value: self.value,
             input: self.call_data.clone(),
             gas_price: Some(self.gas_price),
+            transaction_type: Some(self.transaction_type),
             access_list: self.access_list.clone(),
             nonce: self.nonce.to_word(),
             gas: self.gas_limit.to_word(),
@@ -369,9 +549,8 @@ impl GethData {
             let wallet = wallets.get(&tx.from).unwrap();
             assert_eq!(Word::from(wallet.chain_id()), self.chain_id);
             let geth_tx: Transaction = (&*tx).into();
-            let req: TransactionRequest = (&geth_tx).into();
             let sig = wallet
-                .sign_transaction_sync(&req.chain_id(self.chain_id.as_u64()).into())
+                .sign_transaction_sync(&geth_tx.typed_tx(self.chain_id.as_u64()))
                 .unwrap();
             tx.v = U64::from(sig.v);
             tx.r = sig.r;
@@ -15,12 +15,19 @@ use halo2_proofs::{
     },
     poly::{
         commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy as IPASingleStrategy,
+        },
         kzg::{
             commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG},
-            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
             strategy::SingleStrategy,
         },
+        VerificationStrategy,
     },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
 };
 use lazy_static::lazy_static;
 use mock::TestContext;
@@ -34,6 +41,7 @@ use zkevm_circuits::{
     evm_circuit::TestEvmCircuit,
     exp_circuit::TestExpCircuit,
     keccak_circuit::TestKeccakCircuit,
+    local_prover::{prove, verify},
     pi_circuit::TestPiCircuit,
     root_circuit::{
         compile, Config, EvmTranscript, NativeLoader, PoseidonTranscript, RootCircuit, Shplonk,
@@ -45,6 +53,8 @@ use zkevm_circuits::{
     witness::{block_convert, Block},
 };
 
+pub use zkevm_circuits::local_prover::ProvingScheme;
+
 /// TEST_MOCK_RANDOMNESS
 const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 
@@ -72,6 +82,7 @@ const CIRCUITS_PARAMS: FixedCParams = FixedCParams {
     max_txs: MAX_TXS,
     max_withdrawals: MAX_WITHDRAWALS,
     max_calldata: MAX_CALLDATA,
+    max_ecrecover: 0,
     max_bytecode: MAX_BYTECODE,
     max_copy_rows: MAX_COPY_ROWS,
     max_evm_rows: MAX_EVM_ROWS,
@@ -103,6 +114,8 @@ lazy_static! {
 
 lazy_static! {
     static ref GEN_PARAMS: Mutex<HashMap<u32, ParamsKZG<Bn256>>> = Mutex::new(HashMap::new());
+    static ref GEN_PARAMS_IPA: Mutex<HashMap<u32, ParamsIPA<G1Affine>>> =
+        Mutex::new(HashMap::new());
 }
 
 lazy_static! {
@@ -148,49 +161,119 @@ lazy_static! {
     static ref PROOF_CACHE: TokioMutex<HashMap<String, Vec<u8>>> = TokioMutex::new(HashMap::new());
 }
 
-/// Generate a real proof of a Circuit with Poseidon transcript and Shplonk accumulation scheme.
-/// Verify the proof and return it.  The proof is suitable to be verified by the Root Circuit.
+/// Generate a real proof of a Circuit with the `scheme`, verify it and return it.  KZG proofs
+/// use the Poseidon transcript, which makes the SHPLONK ones suitable to be verified by the Root
+/// Circuit, and IPA proofs the Blake2b transcript.
 fn test_actual_circuit<C: Circuit<Fr>>(
     circuit: C,
     degree: u32,
     instance: Vec<Vec<Fr>>,
     proving_key: ProvingKey<G1Affine>,
+    scheme: ProvingScheme,
 ) -> Vec<u8> {
-    let general_params = get_general_params(degree);
-    let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
-
-    let mut transcript = PoseidonTranscript::new(Vec::new());
-
-    // change instace to slice
-    let instance: Vec<&[Fr]> = instance.iter().map(|v| v.as_slice()).collect();
-
-    log::info!("gen circuit proof");
-    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
-        &general_params,
-        &proving_key,
-        &[circuit],
-        &[&instance],
-        RNG.clone(),
-        &mut transcript,
-    )
-    .expect("proof generation should not fail");
-    let proof = transcript.finalize();
-
-    log::info!("verify circuit proof");
+    log::info!("gen circuit proof ({:?})", scheme);
     let verifying_key = proving_key.get_vk();
-    let mut verifier_transcript = PoseidonTranscript::new(proof.as_slice());
-    let strategy = SingleStrategy::new(&general_params);
-
-    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
-        &verifier_params,
-        verifying_key,
-        strategy,
-        &[&instance],
-        &mut verifier_transcript,
-    )
-    .expect("failed to verify circuit");
-
-    proof
+    match scheme {
+        ProvingScheme::Kzg => {
+            let general_params = get_general_params(degree);
+            let proof = prove::<
+                KZGCommitmentScheme<Bn256>,
+                ProverSHPLONK<'_, Bn256>,
+                PoseidonTranscript<G1Affine, _>,
+                _,
+                _,
+            >(
+                &general_params,
+                &proving_key,
+                circuit,
+                &instance,
+                RNG.clone(),
+            )
+            .expect("proof generation should not fail");
+            log::info!("verify circuit proof ({:?})", scheme);
+            verify::<
+                KZGCommitmentScheme<Bn256>,
+                VerifierSHPLONK<'_, Bn256>,
+                PoseidonTranscript<G1Affine, _>,
+                _,
+                _,
+            >(
+                general_params.verifier_params(),
+                verifying_key,
+                SingleStrategy::new(&general_params),
+                &instance,
+                &proof,
+            )
+            .expect("failed to verify circuit");
+            proof
+        }
+        ProvingScheme::KzgGwc => {
+            let general_params = get_general_params(degree);
+            let proof = prove::<
+                KZGCommitmentScheme<Bn256>,
+                ProverGWC<'_, Bn256>,
+                PoseidonTranscript<G1Affine, _>,
+                _,
+                _,
+            >(
+                &general_params,
+                &proving_key,
+                circuit,
+                &instance,
+                RNG.clone(),
+            )
+            .expect("proof generation should not fail");
+            log::info!("verify circuit proof ({:?})", scheme);
+            verify::<
+                KZGCommitmentScheme<Bn256>,
+                VerifierGWC<'_, Bn256>,
+                PoseidonTranscript<G1Affine, _>,
+                _,
+                _,
+            >(
+                general_params.verifier_params(),
+                verifying_key,
+                SingleStrategy::new(&general_params),
+                &instance,
+                &proof,
+            )
+            .expect("failed to verify circuit");
+            proof
+        }
+        ProvingScheme::IpaBn256 => {
+            let general_params = get_general_params_ipa(degree);
+            let proof = prove::<
+                IPACommitmentScheme<G1Affine>,
+                ProverIPA<'_, G1Affine>,
+                Blake2bWrite<_, G1Affine, Challenge255<_>>,
+                _,
+                _,
+            >(
+                &general_params,
+                &proving_key,
+                circuit,
+                &instance,
+                RNG.clone(),
+            )
+            .expect("proof generation should not fail");
+            log::info!("verify circuit proof ({:?})", scheme);
+            verify::<
+                IPACommitmentScheme<G1Affine>,
+                VerifierIPA<'_, G1Affine>,
+                Blake2bRead<_, G1Affine, Challenge255<_>>,
+                _,
+                _,
+            >(
+                &general_params,
+                verifying_key,
+                IPASingleStrategy::new(&general_params),
+                &instance,
+                &proof,
+            )
+            .expect("failed to verify circuit");
+            proof
+        }
+    }
 }
 
 /// Generate a real proof of the RootCircuit with Keccak transcript and Shplonk accumulation
@@ -249,6 +332,7 @@ pub struct IntegrationTest<C: SubCircuit<Fr> + Circuit<Fr>> {
     degree: u32,
     root_degree: u32,
     key: Option<ProvingKey<G1Affine>>,
+    ipa_key: Option<ProvingKey<G1Affine>>,
     root_key: Option<ProvingKey<G1Affine>>,
     fixed: Option<Vec<Vec<CellValue<Fr>>>>,
     permutation: Option<Assembly>,
@@ -266,6 +350,7 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
             degree,
             root_degree,
             key: None,
+            ipa_key: None,
             root_key: None,
             fixed: None,
             permutation: None,
@@ -297,6 +382,24 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
         }
     }
 
+    fn get_ipa_key(&mut self) -> ProvingKey<G1Affine> {
+        match self.ipa_key.clone() {
+            Some(key) => key,
+            None => {
+                let block = new_empty_block();
+                let circuit = C::new_from_block(&block);
+                let general_params = get_general_params_ipa(self.degree);
+
+                let verifying_key =
+                    keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
+                let key = keygen_pk(&general_params, verifying_key, &circuit)
+                    .expect("keygen_pk should not fail");
+                self.ipa_key = Some(key.clone());
+                key
+            }
+        }
+    }
+
     fn get_root_key(&mut self) -> ProvingKey<G1Affine> {
         match self.root_key.clone() {
             Some(key) => key,
@@ -396,13 +499,27 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
     }
 
     /// Run integration test at a block identified by a tag.
-    pub async fn test_at_block_tag(&mut self, block_tag: &str, root: bool, actual: bool) {
+    ///
+    /// The `scheme` is only used by the real prover.  Aggregation with the Root Circuit is only
+    /// supported for [`ProvingScheme::Kzg`].
+    pub async fn test_at_block_tag(
+        &mut self,
+        block_tag: &str,
+        root: bool,
+        actual: bool,
+        scheme: ProvingScheme,
+    ) {
+        assert!(
+            !root || scheme == ProvingScheme::Kzg,
+            "root circuit only supports KZG with SHPLONK, got {:?}",
+            scheme
+        );
         let block_num = *GEN_DATA.blocks.get(block_tag).unwrap();
         let proof_name = self.proof_name(block_tag);
         let (builder, _) = gen_inputs(block_num).await;
 
         log::info!(
-            "test {} circuit{}, {} prover, block: #{} - {}",
+            "test {} circuit{}, {} prover ({:?}), block: #{} - {}",
             self.name,
             if root {
                 " with aggregation (root circuit)"
@@ -410,6 +527,7 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
                 ""
             },
             if actual { "real" } else { "mock" },
+            scheme,
             block_num,
             block_tag,
         );
@@ -437,7 +555,13 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
                 } else {
                     let key = self.get_key();
                     log::info!("circuit proof generation (no proof in the cache)");
-                    let proof = test_actual_circuit(circuit, self.degree, instance.clone(), key);
+                    let proof = test_actual_circuit(
+                        circuit,
+                        self.degree,
+                        instance.clone(),
+                        key,
+                        ProvingScheme::Kzg,
+                    );
                     proof_cache.insert(proof_name, proof.clone());
                     proof
                 }
@@ -470,11 +594,17 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
             }
         } else {
             if actual {
-                let key = self.get_key();
+                let key = match scheme {
+                    ProvingScheme::Kzg | ProvingScheme::KzgGwc => self.get_key(),
+                    ProvingScheme::IpaBn256 => self.get_ipa_key(),
+                };
                 log::info!("circuit proof generation");
-                let proof = test_actual_circuit(circuit, self.degree, instance, key);
-                let mut proof_cache = PROOF_CACHE.lock().await;
-                proof_cache.insert(proof_name, proof);
+                let proof = test_actual_circuit(circuit, self.degree, instance, key, scheme);
+                // Only the SHPLONK proofs can be aggregated by the Root Circuit
+                if scheme == ProvingScheme::Kzg {
+                    let mut proof_cache = PROOF_CACHE.lock().await;
+                    proof_cache.insert(proof_name, proof);
+                }
             } else {
                 log::info!("circuit mock prover verification");
                 self.test_mock(&circuit, instance);
@@ -507,6 +637,18 @@ fn get_general_params(degree: u32) -> ParamsKZG<Bn256> {
     }
 }
 
+fn get_general_params_ipa(degree: u32) -> ParamsIPA<G1Affine> {
+    let mut map = GEN_PARAMS_IPA.lock().unwrap();
+    match map.get(&degree) {
+        Some(params) => params.clone(),
+        None => {
+            let params = ParamsIPA::<G1Affine>::new(degree);
+            map.insert(degree, params.clone());
+            params
+        }
+    }
+}
+
 /// returns gen_inputs for a block number
 async fn gen_inputs(
     block_num: u64,
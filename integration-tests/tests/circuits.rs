@@ -1,59 +1,59 @@
 macro_rules! run_test {
-    ($test_instance:expr, $block_tag:expr, $root:expr, $real_prover:expr) => {
+    ($test_instance:expr, $block_tag:expr, $root:expr, $real_prover:expr, $scheme:expr) => {
         log_init();
 
         let mut test = $test_instance.lock().await;
-        test.test_at_block_tag($block_tag, $root, $real_prover)
+        test.test_at_block_tag($block_tag, $root, $real_prover, $scheme)
             .await;
     };
 }
 
 macro_rules! declare_tests {
-    (($name:ident, $block_tag:expr),$root:expr,$real_prover:expr) => {
+    (($name:ident, $block_tag:expr),$root:expr,$real_prover:expr,$scheme:expr) => {
         paste! {
             #[tokio::test]
             async fn [<serial_test_evm_ $name>]() {
-                run_test! (EVM_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (EVM_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_state_ $name>]() {
-                run_test! (STATE_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (STATE_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_tx_ $name>]() {
-                run_test! (TX_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (TX_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_bytecode_ $name>]() {
-                run_test! (BYTECODE_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (BYTECODE_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_copy_ $name>]() {
-                run_test! (COPY_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (COPY_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_keccak_ $name>]() {
-                run_test! (KECCAK_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (KECCAK_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_super_ $name>]() {
-                run_test! (SUPER_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (SUPER_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_exp_ $name>]() {
-                run_test! (EXP_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (EXP_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
 
             #[tokio::test]
             async fn [<serial_test_pi_ $name>]() {
-                run_test! (PI_CIRCUIT_TEST, $block_tag, $root, $real_prover);
+                run_test! (PI_CIRCUIT_TEST, $block_tag, $root, $real_prover, $scheme);
             }
         }
     };
@@ -72,6 +72,7 @@ macro_rules! unroll_tests {
             SUPER_CIRCUIT_TEST,
             EXP_CIRCUIT_TEST,
             PI_CIRCUIT_TEST,
+            ProvingScheme,
         };
         use integration_tests::log_init;
         // NOTE: The SubCircuits include all well known SubCircuits and the SuperCircuit.
@@ -80,7 +81,23 @@ macro_rules! unroll_tests {
         mod sub_real_prover {
             use super::*;
             $(
-                declare_tests! ($arg, false, true) ;
+                declare_tests! ($arg, false, true, ProvingScheme::Kzg) ;
+            )*
+        }
+
+        // SubCircuit tests with real prover using IPA commitments over BN256
+        mod ipa_sub_real_prover {
+            use super::*;
+            $(
+                declare_tests! ($arg, false, true, ProvingScheme::IpaBn256) ;
+            )*
+        }
+
+        // SubCircuit tests with real prover using the GWC multiopen scheme
+        mod gwc_sub_real_prover {
+            use super::*;
+            $(
+                declare_tests! ($arg, false, true, ProvingScheme::KzgGwc) ;
             )*
         }
 
@@ -88,7 +105,7 @@ macro_rules! unroll_tests {
         mod sub_mock_prover {
             use super::*;
             $(
-                declare_tests! ($arg, false, false) ;
+                declare_tests! ($arg, false, false, ProvingScheme::Kzg) ;
             )*
         }
 
@@ -96,7 +113,7 @@ macro_rules! unroll_tests {
         mod root_real_prover {
             use super::*;
             $(
-                declare_tests! ($arg, true, true) ;
+                declare_tests! ($arg, true, true, ProvingScheme::Kzg) ;
             )*
         }
 
@@ -104,7 +121,7 @@ macro_rules! unroll_tests {
         mod root_mock_prover {
             use super::*;
             $(
-                declare_tests! ($arg, true, false) ;
+                declare_tests! ($arg, true, false, ProvingScheme::Kzg) ;
             )*
         }
     }
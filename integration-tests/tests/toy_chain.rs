@@ -0,0 +1,181 @@
+#![cfg(feature = "toy_chain")]
+
+//! End-to-end proving of a fixed block of a toy chain, with capacities reduced so that the
+//! `SuperCircuit` has a tiny degree.  The test goes through the whole pipeline, as an executable
+//! specification of it:
+//!
+//! 1. witness generation of the block
+//! 2. real `SuperCircuit` proof, with the Poseidon transcript so that it can be aggregated
+//! 3. aggregation of the proof by the `RootCircuit`
+//! 4. real `RootCircuit` proof, with the EVM transcript, and its native verification
+//! 5. verification of the `RootCircuit` proof by the EVM verifier, which requires `solc`
+//!
+//! The `RootCircuit` has the same size for any `SuperCircuit`, so its proof dominates the run.
+
+use bus_mapping::circuit_input_builder::FixedCParams;
+use eth_types::{bytecode, geth_types::GethData};
+use halo2_proofs::{
+    circuit::Value,
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof},
+    poly::{
+        commitment::Params,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+};
+use integration_tests::log_init;
+use mock::{
+    test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+    TestContext,
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use zkevm_circuits::{
+    instance::PublicInputs,
+    root_circuit::{
+        compile, evm_verify, gen_evm_verifier, Config, EvmTranscript, NativeLoader,
+        PoseidonTranscript, RootCircuit, Shplonk,
+    },
+    super_circuit::SuperCircuit,
+};
+
+/// Capacities of the toy chain, enough for a block with a single transfer.
+const TOY_CIRCUITS_PARAMS: FixedCParams = FixedCParams {
+    max_txs: 1,
+    max_withdrawals: 1,
+    max_calldata: 32,
+    max_ecrecover: 0,
+    max_rws: 256,
+    max_copy_rows: 256,
+    max_exp_steps: 256,
+    max_bytecode: 512,
+    max_evm_rows: 0,
+    max_keccak_rows: 0,
+};
+/// Maximum degree of the `SuperCircuit` of the toy chain
+const TOY_SUPER_CIRCUIT_DEGREE: u32 = 12;
+/// Degree of the `RootCircuit` aggregating a `SuperCircuit` proof
+const ROOT_CIRCUIT_DEGREE: u32 = 26;
+
+const TEST_MOCK_RANDOMNESS: u64 = 0x100;
+
+/// Fixed block of the toy chain: a transfer to an account that stops.
+fn toy_block() -> GethData {
+    TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(bytecode! { STOP }),
+        tx_from_1_to_0,
+        |block, _txs| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into()
+}
+
+#[test]
+fn toy_chain_end_to_end() {
+    log_init();
+    let rng = ChaCha20Rng::seed_from_u64(42);
+    let block = toy_block();
+
+    log::info!("witness generation");
+    let (k, circuit, instance, _) = SuperCircuit::<Fr>::build(
+        block.clone(),
+        TOY_CIRCUITS_PARAMS,
+        TEST_MOCK_RANDOMNESS.into(),
+    )
+    .unwrap();
+    assert!(k <= TOY_SUPER_CIRCUIT_DEGREE, "degree {} is too big", k);
+    let public_inputs = PublicInputs::from_instance(&instance[0]).unwrap();
+    assert_eq!(public_inputs.state_root, block.eth_block.state_root);
+
+    // The params of both circuits must come from the same setup
+    let root_params = ParamsKZG::<Bn256>::setup(ROOT_CIRCUIT_DEGREE, rng.clone());
+    let mut params = root_params.clone();
+    params.downsize(k);
+
+    log::info!("super circuit proof");
+    let pk = keygen_pk(&params, keygen_vk(&params, &circuit).unwrap(), &circuit).unwrap();
+    let instance_refs = instance.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let proof = {
+        let mut transcript = PoseidonTranscript::new(Vec::new());
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&instance_refs],
+            rng.clone(),
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    };
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        &params,
+        pk.get_vk(),
+        SingleStrategy::new(&params),
+        &[&instance_refs],
+        &mut PoseidonTranscript::new(proof.as_slice()),
+    )
+    .unwrap();
+
+    log::info!("aggregation");
+    let protocol = compile(
+        &params,
+        pk.get_vk(),
+        Config::kzg().with_num_instance(instance.iter().map(Vec::len).collect()),
+    );
+    let root_circuit = RootCircuit::<Bn256, Shplonk<_>>::new(
+        &params,
+        &protocol,
+        Value::known(&instance),
+        Value::known(&proof),
+    )
+    .unwrap();
+    let root_instance = root_circuit.instance();
+    let root_instance_refs = root_instance.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+    log::info!("root circuit proof");
+    let root_pk = keygen_pk(
+        &root_params,
+        keygen_vk(&root_params, &root_circuit).unwrap(),
+        &root_circuit,
+    )
+    .unwrap();
+    let num_instance = root_circuit.num_instance();
+    let accumulator_indices = root_circuit.accumulator_indices();
+    let root_proof = {
+        let mut transcript = EvmTranscript::<_, NativeLoader, _, _>::new(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+            &root_params,
+            &root_pk,
+            &[root_circuit],
+            &[&root_instance_refs],
+            rng,
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    };
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        &root_params,
+        root_pk.get_vk(),
+        SingleStrategy::new(&root_params),
+        &[&root_instance_refs],
+        &mut EvmTranscript::<_, NativeLoader, _, _>::new(root_proof.as_slice()),
+    )
+    .unwrap();
+
+    log::info!("evm verification");
+    let deployment_code = gen_evm_verifier(
+        &root_params,
+        root_pk.get_vk(),
+        num_instance,
+        accumulator_indices,
+    );
+    let gas_cost = evm_verify(deployment_code, root_instance, root_proof).unwrap();
+    log::info!("evm verification gas cost: {}", gas_cost);
+}
@@ -0,0 +1,126 @@
+//! Time and memory metrics of the proving phases: the key generation, the proof and its
+//! verification.
+//!
+//! While a phase runs, the resident memory of the process is sampled from `/proc/self/status`.
+//! With `PROVER_MEMORY_BUDGET` set to a number of GiB, a phase with a sample over it fails, with
+//! the phase in the error, so that a job that needs more memory than planned is reported as such.
+//! The phase can't be interrupted, so it fails once it returns.  With `PROVER_METRICS` set to a
+//! path, the metrics of each phase are appended to this file as a JSON line.
+
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fmt, fs,
+    io::Write,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+use zkevm_circuits::estimate::proc_memory_kb;
+
+/// Interval between two samples of the resident memory.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+const GIB: f64 = (1u64 << 30) as f64;
+
+/// Metrics of a proving phase.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseMetrics {
+    /// Name of the phase
+    pub phase: String,
+    /// Duration of the phase, in seconds
+    pub elapsed_secs: f64,
+    /// Most resident memory sampled during the phase, in bytes, if it can be read
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl fmt::Display for PhaseMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} time: {:.3}s", self.phase, self.elapsed_secs)?;
+        if let Some(peak) = self.peak_rss_bytes {
+            write!(f, ", peak RSS: {:.2} GiB", peak as f64 / GIB)?;
+        }
+        Ok(())
+    }
+}
+
+/// Resident memory of the process, in bytes, or `None` where `/proc` isn't available.
+pub fn rss_bytes() -> Option<u64> {
+    proc_memory_kb("/proc/self/status", "VmRSS").map(|kb| kb * 1024)
+}
+
+/// Parse a memory budget in GiB, like `180` or `0.5`.
+pub fn parse_budget_bytes(budget: &str) -> Option<u64> {
+    let gib: f64 = budget.trim().parse().ok()?;
+    (gib > 0.0).then(|| (gib * GIB) as u64)
+}
+
+/// Memory budget of the `PROVER_MEMORY_BUDGET` environment variable, in bytes.
+fn budget_from_env() -> Option<u64> {
+    let budget = env::var("PROVER_MEMORY_BUDGET").ok()?;
+    let bytes = parse_budget_bytes(&budget);
+    if bytes.is_none() {
+        eprintln!("ignoring the invalid PROVER_MEMORY_BUDGET {:?}", budget);
+    }
+    bytes
+}
+
+/// Run the phase `f`, sampling the resident memory while it runs, then print its metrics and
+/// export them to `PROVER_METRICS`.
+///
+/// # Errors
+///
+/// The error of `f`, or an error if the resident memory exceeds the `PROVER_MEMORY_BUDGET`
+/// during the phase.
+pub fn measure<T>(phase: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let budget = budget_from_env();
+    let peak = AtomicU64::new(rss_bytes().unwrap_or_default());
+    let done = AtomicBool::new(false);
+    let start = Instant::now();
+
+    let result = thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                if let Some(rss) = rss_bytes() {
+                    peak.fetch_max(rss, Ordering::Relaxed);
+                }
+                thread::sleep(SAMPLE_INTERVAL);
+            }
+        });
+        let result = f();
+        done.store(true, Ordering::Relaxed);
+        result
+    });
+
+    let peak = peak.into_inner();
+    let metrics = PhaseMetrics {
+        phase: phase.to_string(),
+        elapsed_secs: start.elapsed().as_secs_f64(),
+        peak_rss_bytes: Some(peak).filter(|peak| *peak > 0),
+    };
+    println!("{}", metrics);
+    if let Ok(path) = env::var("PROVER_METRICS") {
+        if let Err(err) = export(&path, &metrics) {
+            eprintln!("exporting the metrics to {} failed: {}", path, err);
+        }
+    }
+    if let Some(budget) = budget.filter(|budget| peak > *budget) {
+        return Err(eyre!(
+            "{} exceeds the {} GiB memory budget ({:.2} GiB resident)",
+            phase,
+            budget as f64 / GIB,
+            peak as f64 / GIB
+        ));
+    }
+    result
+}
+
+/// Append the metrics to the file at `path` as a JSON line.
+fn export(path: &str, metrics: &PhaseMetrics) -> eyre::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(metrics)?)?;
+    Ok(())
+}
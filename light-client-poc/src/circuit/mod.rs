@@ -1,9 +1,11 @@
 mod equal_words;
+mod metrics;
 mod prover;
 mod state_update;
 mod witness;
 
-pub use prover::StateUpdateCircuitKeys;
+pub use metrics::{measure, parse_budget_bytes, rss_bytes, PhaseMetrics};
+pub use prover::{ProofManifest, ProofTranscript, Reproduction, StateUpdateCircuitKeys};
 
 pub use state_update::{
     StateUpdateCircuit, DEFAULT_CIRCUIT_DEGREE, DEFAULT_MAX_NODES, DEFAULT_MAX_PROOF_COUNT,
@@ -1,10 +1,16 @@
-use eyre::Result;
+use ethers::utils::keccak256;
+use eyre::{eyre, Result};
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit, SerdeFormat};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
-use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+use zkevm_circuits::root_circuit::{vk_digest, PoseidonTranscript};
 
-use super::witness::PublicInputs;
+use super::{metrics::measure, witness::PublicInputs};
 use halo2_proofs::{
     halo2curves::bn256::{Bn256, G1Affine},
     plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, ProvingKey},
@@ -23,6 +29,20 @@ use halo2_proofs::{
 
 use super::state_update::StateUpdateCircuit;
 
+/// Seed of the randomness of the prover
+const PROOF_SEED: u64 = 42;
+
+/// Transcript of the proofs of the `StateUpdateCircuit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofTranscript {
+    /// Blake2b transcript, cheap to verify natively
+    #[default]
+    Blake2b,
+    /// Poseidon transcript, cheap to verify inside a halo2 circuit, so that the proof can be
+    /// aggregated by the `RootCircuit`
+    Poseidon,
+}
+
 #[derive(Clone)]
 pub struct StateUpdateCircuitKeys {
     general_params: ParamsKZG<Bn256>,
@@ -31,27 +51,27 @@ pub struct StateUpdateCircuitKeys {
 }
 
 impl StateUpdateCircuitKeys {
-    pub fn new(circuit: &StateUpdateCircuit<Fr>) -> StateUpdateCircuitKeys {
+    pub fn new(circuit: &StateUpdateCircuit<Fr>) -> Result<StateUpdateCircuitKeys> {
         let mut rng = ChaCha20Rng::seed_from_u64(42);
 
-        let start = Instant::now();
-
         // let circuit = StateUpdateCircuit::default();
 
-        let general_params = ParamsKZG::<Bn256>::setup(circuit.degree as u32, &mut rng);
-        let verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
-
-        // Initialize the proving key
-        let vk = keygen_vk(&general_params, circuit).expect("keygen_vk should not fail");
-        let pk = keygen_pk(&general_params, vk, circuit).expect("keygen_pk should not fail");
+        let (general_params, verifier_params, pk) = measure("key generation", || {
+            let general_params = ParamsKZG::<Bn256>::setup(circuit.degree as u32, &mut rng);
+            let verifier_params: ParamsVerifierKZG<Bn256> =
+                general_params.verifier_params().clone();
 
-        println!("key generation time: {:?}", start.elapsed());
+            // Initialize the proving key
+            let vk = keygen_vk(&general_params, circuit)?;
+            let pk = keygen_pk(&general_params, vk, circuit)?;
+            Ok((general_params, verifier_params, pk))
+        })?;
 
-        StateUpdateCircuitKeys {
+        Ok(StateUpdateCircuitKeys {
             general_params,
             verifier_params,
             pk,
-        }
+        })
     }
 
     pub fn serialize(&self) -> Result<Vec<u8>> {
@@ -83,13 +103,17 @@ impl StateUpdateCircuitKeys {
 }
 
 impl StateUpdateCircuit<Fr> {
-    pub fn assert_satisfied(&self) {
-        let num_rows: usize = self
-            .mpt_circuit
+    /// Number of rows used by the nodes of the MPT circuit
+    fn num_rows(&self) -> usize {
+        self.mpt_circuit
             .nodes
             .iter()
             .map(|node| node.values.len())
-            .sum();
+            .sum()
+    }
+
+    pub fn assert_satisfied(&self) {
+        let num_rows = self.num_rows();
 
         let public_inputs: PublicInputs<Fr> = (&self.lc_witness).into();
 
@@ -102,61 +126,288 @@ impl StateUpdateCircuit<Fr> {
         prover.assert_satisfied_at_rows(0..num_rows, 0..num_rows);
     }
 
-    pub fn prove(self, keys: &StateUpdateCircuitKeys) -> Result<Vec<u8>> {
-        let rng = ChaCha20Rng::seed_from_u64(42);
+    /// Check that the circuit is satisfied like [`Self::assert_satisfied`], but return an error
+    /// instead of panicking when it isn't.
+    pub fn check_satisfied(&self) -> Result<()> {
+        let num_rows = self.num_rows();
+        let public_inputs: PublicInputs<Fr> = (&self.lc_witness).into();
+        let prover = MockProver::<Fr>::run(self.degree as u32, self, vec![public_inputs.0])?;
+        prover
+            .verify_at_rows(0..num_rows, 0..num_rows)
+            .map_err(|failures| {
+                eyre!(
+                    "the circuit is not satisfied, {} failures, the first one: {}",
+                    failures.len(),
+                    failures[0]
+                )
+            })
+    }
 
-        // Create a proof
-        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    pub fn prove(self, keys: &StateUpdateCircuitKeys) -> Result<Vec<u8>> {
+        self.prove_with_transcript(keys, ProofTranscript::Blake2b)
+    }
 
-        let public_inputs: PublicInputs<Fr> = (&self.lc_witness).into();
+    pub fn prove_with_transcript(
+        self,
+        keys: &StateUpdateCircuitKeys,
+        transcript: ProofTranscript,
+    ) -> Result<Vec<u8>> {
+        self.prove_with_seed(keys, transcript, PROOF_SEED)
+    }
 
-        // Bench proof generation time
-        let start = Instant::now();
-        create_proof::<
-            KZGCommitmentScheme<Bn256>,
-            ProverSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            ChaCha20Rng,
-            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-            StateUpdateCircuit<Fr>,
-        >(
-            &keys.general_params,
-            &keys.pk,
-            &[self],
-            &[&[&public_inputs]],
-            rng,
-            &mut transcript,
-        )?;
+    /// Prove with the randomness of the prover seeded with `seed`, so that the proof is
+    /// reproducible byte for byte.
+    pub fn prove_with_seed(
+        self,
+        keys: &StateUpdateCircuitKeys,
+        transcript: ProofTranscript,
+        seed: u64,
+    ) -> Result<Vec<u8>> {
+        let rng = ChaCha20Rng::seed_from_u64(seed);
 
-        let proof = transcript.finalize();
+        let public_inputs: PublicInputs<Fr> = (&self.lc_witness).into();
 
-        println!("proof generation time: {:?}", start.elapsed());
+        // Bench proof generation time and memory
+        let proof = measure("proof generation", || -> Result<_> {
+            Ok(match transcript {
+                ProofTranscript::Blake2b => {
+                    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+                    create_proof::<
+                        KZGCommitmentScheme<Bn256>,
+                        ProverSHPLONK<'_, Bn256>,
+                        Challenge255<G1Affine>,
+                        ChaCha20Rng,
+                        Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+                        StateUpdateCircuit<Fr>,
+                    >(
+                        &keys.general_params,
+                        &keys.pk,
+                        &[self],
+                        &[&[&public_inputs]],
+                        rng,
+                        &mut transcript,
+                    )?;
+                    transcript.finalize()
+                }
+                ProofTranscript::Poseidon => {
+                    let mut transcript = PoseidonTranscript::<G1Affine, _>::new(Vec::new());
+                    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+                        &keys.general_params,
+                        &keys.pk,
+                        &[self],
+                        &[&[&public_inputs]],
+                        rng,
+                        &mut transcript,
+                    )?;
+                    transcript.finalize()
+                }
+            })
+        })?;
 
         Ok(proof)
     }
 
     pub fn verify(proof: &[u8], public_inputs: &[Fr], keys: &StateUpdateCircuitKeys) -> Result<()> {
-        // Bench verification time
-        let start = Instant::now();
-        let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
-        let strategy = SingleStrategy::new(&keys.general_params);
-
-        verify_proof::<
-            KZGCommitmentScheme<Bn256>,
-            VerifierSHPLONK<'_, Bn256>,
-            Challenge255<G1Affine>,
-            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-            SingleStrategy<'_, Bn256>,
-        >(
-            &keys.verifier_params,
-            keys.pk.get_vk(),
-            strategy,
-            &[&[public_inputs]],
-            &mut verifier_transcript,
-        )?;
+        Self::verify_with_transcript(proof, public_inputs, keys, ProofTranscript::Blake2b)
+    }
+
+    pub fn verify_with_transcript(
+        proof: &[u8],
+        public_inputs: &[Fr],
+        keys: &StateUpdateCircuitKeys,
+        transcript: ProofTranscript,
+    ) -> Result<()> {
+        // Bench verification time and memory
+        measure("verification", || -> Result<()> {
+            let strategy = SingleStrategy::new(&keys.general_params);
+
+            match transcript {
+                ProofTranscript::Blake2b => {
+                    let mut verifier_transcript =
+                        Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+                    verify_proof::<
+                        KZGCommitmentScheme<Bn256>,
+                        VerifierSHPLONK<'_, Bn256>,
+                        Challenge255<G1Affine>,
+                        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+                        SingleStrategy<'_, Bn256>,
+                    >(
+                        &keys.verifier_params,
+                        keys.pk.get_vk(),
+                        strategy,
+                        &[&[public_inputs]],
+                        &mut verifier_transcript,
+                    )?;
+                }
+                ProofTranscript::Poseidon => {
+                    let mut verifier_transcript = PoseidonTranscript::<G1Affine, _>::new(proof);
+                    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+                        &keys.verifier_params,
+                        keys.pk.get_vk(),
+                        strategy,
+                        &[&[public_inputs]],
+                        &mut verifier_transcript,
+                    )?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// Everything needed to reproduce a proof of the `StateUpdateCircuit` byte for byte, besides the
+/// witness.  It's written next to the proof, see [`ProofManifest::path`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofManifest {
+    /// Version of this crate
+    pub version: String,
+    /// Whether the keccak circuit is disabled
+    pub disable_keccak: bool,
+    /// Transcript of the proof
+    pub transcript: ProofTranscript,
+    /// Seed of the randomness of the prover
+    pub seed: u64,
+    /// Degree of the circuit
+    pub degree: usize,
+    /// Maximum number of MPT proofs of the circuit
+    pub max_proof_count: usize,
+    /// Keccak hash of the general params, in `SerdeFormat::RawBytes`
+    pub params_hash: String,
+    /// Keccak hash of the verifying key, in `SerdeFormat::RawBytes`
+    pub vk_hash: String,
+    /// Digest of the verifying key stored by an EVM verifier, see `vk_digest`
+    #[serde(default)]
+    pub vk_digest: String,
+    /// Public inputs, hex encoded
+    pub public_inputs: Vec<String>,
+    /// Keccak hash of the proof
+    pub proof_hash: String,
+}
+
+impl ProofManifest {
+    fn new(
+        circuit: &StateUpdateCircuit<Fr>,
+        keys: &StateUpdateCircuitKeys,
+        transcript: ProofTranscript,
+        seed: u64,
+    ) -> Result<Self> {
+        let mut params = Vec::new();
+        keys.general_params
+            .write_custom(&mut params, SerdeFormat::RawBytes)?;
+        let public_inputs: PublicInputs<Fr> = (&circuit.lc_witness).into();
+        Ok(Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            disable_keccak: cfg!(feature = "disable-keccak"),
+            transcript,
+            seed,
+            degree: circuit.degree,
+            max_proof_count: circuit.max_proof_count,
+            params_hash: hex::encode(keccak256(params)),
+            vk_hash: hex::encode(keccak256(keys.pk.get_vk().to_bytes(SerdeFormat::RawBytes))),
+            vk_digest: hex::encode(vk_digest(keys.pk.get_vk())),
+            public_inputs: public_inputs
+                .iter()
+                .map(|input| format!("{:?}", input))
+                .collect(),
+            proof_hash: String::new(),
+        })
+    }
+
+    /// Path of the manifest of the proof at `proof_path`.
+    pub fn path(proof_path: &Path) -> PathBuf {
+        let mut path = proof_path.as_os_str().to_owned();
+        path.push(".manifest.json");
+        path.into()
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> Result<()> {
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
 
-        println!("verification time: {:?}", start.elapsed());
+    pub fn read<R: Read>(reader: R) -> Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Return the fields that differ from the `other` manifest, with both values.
+    pub fn diff(&self, other: &Self) -> Vec<(&'static str, String, String)> {
+        let fields = |m: &Self| {
+            [
+                ("version", m.version.clone()),
+                ("disable_keccak", m.disable_keccak.to_string()),
+                ("transcript", format!("{:?}", m.transcript)),
+                ("seed", m.seed.to_string()),
+                ("degree", m.degree.to_string()),
+                ("max_proof_count", m.max_proof_count.to_string()),
+                ("params_hash", m.params_hash.clone()),
+                ("vk_hash", m.vk_hash.clone()),
+                ("vk_digest", m.vk_digest.clone()),
+                ("public_inputs", m.public_inputs.join(",")),
+                ("proof_hash", m.proof_hash.clone()),
+            ]
+        };
+        fields(self)
+            .into_iter()
+            .zip(fields(other))
+            .filter(|((_, a), (_, b))| a != b)
+            .map(|((name, a), (_, b))| (name, a, b))
+            .collect()
+    }
+}
 
-        Ok(())
+/// Result of the reproduction of a proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reproduction {
+    /// Fields of the manifest of the reproduced proof that differ from the recorded one
+    pub diff: Vec<(&'static str, String, String)>,
+    /// Offset of the first byte of the transcript of the reproduced proof that differs from the
+    /// recorded one, if the proof was given
+    pub first_transcript_diff: Option<usize>,
+}
+
+impl Reproduction {
+    /// Whether the proof was reproduced byte for byte.
+    pub fn is_identical(&self) -> bool {
+        self.diff.is_empty() && self.first_transcript_diff.is_none()
+    }
+}
+
+impl StateUpdateCircuit<Fr> {
+    /// Prove, and record the manifest needed to reproduce the proof.
+    pub fn prove_reproducible(
+        self,
+        keys: &StateUpdateCircuitKeys,
+        transcript: ProofTranscript,
+        seed: u64,
+    ) -> Result<(Vec<u8>, ProofManifest)> {
+        let mut manifest = ProofManifest::new(&self, keys, transcript, seed)?;
+        let proof = self.prove_with_seed(keys, transcript, seed)?;
+        manifest.proof_hash = hex::encode(keccak256(&proof));
+        Ok((proof, manifest))
+    }
+
+    /// Replay the proof recorded in the `manifest` with the circuit of the same witness, and
+    /// compare the outcome with the manifest and, if given, with the recorded `proof`.
+    pub fn reproduce(
+        self,
+        keys: &StateUpdateCircuitKeys,
+        manifest: &ProofManifest,
+        proof: Option<&[u8]>,
+    ) -> Result<Reproduction> {
+        let (reproduced, reproduced_manifest) =
+            self.prove_reproducible(keys, manifest.transcript, manifest.seed)?;
+        let first_transcript_diff = proof.and_then(|proof| {
+            proof
+                .iter()
+                .zip(reproduced.iter())
+                .position(|(a, b)| a != b)
+                .or_else(|| {
+                    (proof.len() != reproduced.len()).then_some(proof.len().min(reproduced.len()))
+                })
+        });
+        Ok(Reproduction {
+            diff: manifest.diff(&reproduced_manifest),
+            first_transcript_diff,
+        })
     }
 }
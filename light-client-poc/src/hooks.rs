@@ -0,0 +1,112 @@
+//! Hooks run by the server after each block is proven.
+//!
+//! Each hook receives a [`ProofEvent`] as JSON, so that integrations like the submission of the
+//! proof to a contract don't have to poll the server.  The hooks are configured with environment
+//! variables:
+//!
+//! - `PROOF_WEBHOOKS`: comma separated URLs that receive the event in a POST request
+//! - `PROOF_COMMANDS`: semicolon separated shell commands that receive the event in their stdin;
+//!   for example `aws s3 cp - s3://bucket/proof.json` uploads it to S3
+
+use eyre::{eyre, Result};
+use halo2_proofs::halo2curves::{bn256::Fr, ff::PrimeField};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Proof of the state update of a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEvent {
+    /// Number of the proven block
+    pub block_number: u64,
+    /// Public inputs of the proof, as big-endian hex scalars
+    pub public_inputs: Vec<String>,
+    /// Proof, hex encoded
+    pub proof: String,
+}
+
+impl ProofEvent {
+    pub fn new(block_number: u64, public_inputs: &[Fr], proof: &[u8]) -> Self {
+        Self {
+            block_number,
+            public_inputs: public_inputs
+                .iter()
+                .map(|input| {
+                    let mut bytes = input.to_repr();
+                    bytes.reverse();
+                    hex::encode(bytes)
+                })
+                .collect(),
+            proof: hex::encode(proof),
+        }
+    }
+}
+
+/// Hook run after a block is proven.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofHook {
+    /// POST the event to the URL
+    Webhook(String),
+    /// Run the shell command with the event in its stdin
+    Command(String),
+}
+
+impl ProofHook {
+    /// Read the hooks from the `PROOF_WEBHOOKS` and `PROOF_COMMANDS` environment variables.
+    pub fn from_env() -> Vec<Self> {
+        let split = |var: &str, sep: char| {
+            env::var(var)
+                .unwrap_or_default()
+                .split(sep)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        split("PROOF_WEBHOOKS", ',')
+            .into_iter()
+            .map(Self::Webhook)
+            .chain(split("PROOF_COMMANDS", ';').into_iter().map(Self::Command))
+            .collect()
+    }
+
+    pub async fn run(&self, event: &ProofEvent) -> Result<()> {
+        let payload = serde_json::to_vec(event)?;
+        match self {
+            Self::Webhook(url) => {
+                reqwest::Client::new()
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(payload)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            Self::Command(command) => {
+                let mut child = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+                child.stdin.take().unwrap().write_all(&payload)?;
+                let status = child.wait()?;
+                if !status.success() {
+                    return Err(eyre!("`{}` failed with {}", command, status));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run all the hooks on the event.  A failing hook doesn't stop the others.
+pub async fn run_hooks(hooks: &[ProofHook], event: &ProofEvent) {
+    for hook in hooks {
+        if let Err(err) = hook.run(event).await {
+            eprintln!("proof hook {:?} failed: {}", hook, err);
+        }
+    }
+}
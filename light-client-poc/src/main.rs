@@ -1,11 +1,22 @@
 use eyre::Result;
+use std::{env, path::Path};
 
 pub mod circuit;
+pub mod hooks;
+pub mod prover_server;
 pub mod server;
 pub mod tests;
 pub mod utils;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    server::serve().await
+    match env::args().nth(1).as_deref() {
+        Some("prover-server") => {
+            let addr =
+                env::var("PROVER_SERVER_ADDR").unwrap_or_else(|_| "127.0.0.1:3000".to_string());
+            let state = env::var("PROVER_SERVER_STATE").ok();
+            prover_server::serve(addr.parse()?, state.as_deref().map(Path::new)).await
+        }
+        _ => server::serve().await,
+    }
 }
@@ -0,0 +1,391 @@
+//! HTTP API to prove the state update of blocks on request, instead of following the chain like
+//! [`serve`](crate::server::serve):
+//!
+//! - `POST /jobs` with `{"block_number": 123}` queues the proof of the block and returns the id of
+//!   its job, `{"id": 0}`.  The request may also have a `priority`, 0 by default, and a `deadline`,
+//!   in seconds since the UNIX epoch, see [`JobRequest`].
+//! - `GET /jobs/<id>` returns the status of the job, for example `{"status": "proving"}`
+//! - `GET /jobs/<id>/proof` returns the [`ProofEvent`] of a done job
+//!
+//! The jobs are proven one after the other by a single worker, since a proof uses all the cores,
+//! in the order of [`Jobs::next`].  The hooks of [`ProofHook::from_env`] run after each proof,
+//! like in the server.
+//!
+//! With `PROVER_SERVER_STATE` set to a path, the jobs are saved to this file after each change,
+//! and loaded from it on start, so that the queue survives restarts.  Each proof is saved to its
+//! own file, `<id>.json` in the directory of the state path with the `proofs` extension, so that
+//! the state file only has the job metadata.
+
+use eth_types::U64;
+use eyre::Result;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+use tokio::sync::Notify;
+
+use crate::{
+    hooks::{run_hooks, ProofEvent, ProofHook},
+    server::{prove_block, PROVIDER_URL, PVK},
+};
+
+/// Status of a proof job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for the previous jobs
+    Queued,
+    /// Being proven
+    Proving,
+    /// Proven, the proof can be fetched
+    Done,
+    /// Failed with the error
+    Failed { error: String },
+}
+
+/// Body of a `POST /jobs` request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRequest {
+    pub block_number: u64,
+    /// Jobs with a higher priority are proven first
+    #[serde(default)]
+    pub priority: u32,
+    /// Time after which the job fails instead of starting, in seconds since the UNIX epoch.
+    /// Between jobs of the same priority, the earliest deadline is proven first.
+    #[serde(default)]
+    pub deadline: Option<u64>,
+}
+
+/// Body of the response to a `POST /jobs` request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobId {
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Job {
+    request: JobRequest,
+    status: JobStatus,
+}
+
+/// Jobs of the server, shared by the API and the worker.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Jobs {
+    next_id: u64,
+    jobs: HashMap<u64, Job>,
+    /// File the jobs are saved to
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Proofs of the done jobs, when they aren't saved to files
+    #[serde(skip)]
+    proofs: HashMap<u64, ProofEvent>,
+}
+
+/// Proof of a done job.
+#[derive(Debug, Clone)]
+pub enum StoredProof {
+    /// File the proof is saved to
+    File(PathBuf),
+    /// Proof kept in memory, when the jobs aren't saved
+    Memory(ProofEvent),
+}
+
+impl StoredProof {
+    /// Read the proof, from its file without blocking the runtime.
+    pub async fn read(self) -> Result<ProofEvent> {
+        match self {
+            Self::File(path) => Ok(serde_json::from_slice(&tokio::fs::read(path).await?)?),
+            Self::Memory(proof) => Ok(proof),
+        }
+    }
+}
+
+/// Write then rename, so that a crash doesn't leave a truncated file
+fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+impl Jobs {
+    /// Load the jobs saved to `path`, or start without jobs if it doesn't exist.  The jobs are
+    /// saved to `path` after each change.  A job that was being proven is queued again.
+    pub fn persisted(path: &Path) -> Result<Self> {
+        let mut jobs = if path.exists() {
+            serde_json::from_slice::<Self>(&fs::read(path)?)?
+        } else {
+            Self::default()
+        };
+        for job in jobs.jobs.values_mut() {
+            if job.status == JobStatus::Proving {
+                job.status = JobStatus::Queued;
+            }
+        }
+        jobs.path = Some(path.to_path_buf());
+        Ok(jobs)
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(err) = self.write(path) {
+            eprintln!("saving the jobs to {} failed: {}", path.display(), err);
+        }
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        write_file(path, &serde_json::to_vec(self)?)
+    }
+
+    /// File the proof of the job `id` is saved to, for the jobs saved to `path`
+    fn proof_path(path: &Path, id: u64) -> PathBuf {
+        path.with_extension("proofs").join(format!("{}.json", id))
+    }
+
+    fn save_proof(&mut self, id: u64, proof: ProofEvent) -> Result<()> {
+        match &self.path {
+            Some(path) => {
+                let proof_path = Self::proof_path(path, id);
+                if let Some(dir) = proof_path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                write_file(&proof_path, &serde_json::to_vec(&proof)?)
+            }
+            None => {
+                self.proofs.insert(id, proof);
+                Ok(())
+            }
+        }
+    }
+
+    /// Add a queued job, and return its id.
+    pub fn push(&mut self, request: JobRequest) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.insert(
+            id,
+            Job {
+                request,
+                status: JobStatus::Queued,
+            },
+        );
+        self.save();
+        id
+    }
+
+    /// Start the next queued job: the one with the highest priority, then with the earliest
+    /// deadline, then the oldest.  The queued jobs with a deadline before `now`, in seconds since
+    /// the UNIX epoch, fail.  Returns the id and the block number of the started job.
+    pub fn next(&mut self, now: u64) -> Option<(u64, u64)> {
+        for job in self.jobs.values_mut() {
+            if job.status == JobStatus::Queued
+                && job.request.deadline.is_some_and(|deadline| deadline < now)
+            {
+                job.status = JobStatus::Failed {
+                    error: "deadline expired".to_string(),
+                };
+            }
+        }
+        let (id, job) = self
+            .jobs
+            .iter_mut()
+            .filter(|(_, job)| job.status == JobStatus::Queued)
+            .min_by_key(|(id, job)| {
+                (
+                    std::cmp::Reverse(job.request.priority),
+                    job.request.deadline.unwrap_or(u64::MAX),
+                    **id,
+                )
+            })?;
+        job.status = JobStatus::Proving;
+        let next = (*id, job.request.block_number);
+        self.save();
+        Some(next)
+    }
+
+    pub fn status(&self, id: u64) -> Option<&JobStatus> {
+        self.jobs.get(&id).map(|job| &job.status)
+    }
+
+    /// Return where the proof of the job is, `None` if the job isn't done.  The proof is read
+    /// with [`StoredProof::read`], once the jobs are unlocked.
+    pub fn proof(&self, id: u64) -> Option<StoredProof> {
+        if self.status(id) != Some(&JobStatus::Done) {
+            return None;
+        }
+        match &self.path {
+            Some(path) => Some(StoredProof::File(Self::proof_path(path, id))),
+            None => self.proofs.get(&id).cloned().map(StoredProof::Memory),
+        }
+    }
+
+    /// Mark the job as done with the proof, or as failed with the error.  The job also fails if
+    /// its proof can't be saved.
+    pub fn finish(&mut self, id: u64, result: Result<ProofEvent, String>) {
+        if !self.jobs.contains_key(&id) {
+            return;
+        }
+        let status = match result.and_then(|proof| {
+            self.save_proof(id, proof)
+                .map_err(|err| format!("saving the proof failed: {}", err))
+        }) {
+            Ok(()) => JobStatus::Done,
+            Err(error) => JobStatus::Failed { error },
+        };
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.status = status;
+        }
+        self.save();
+    }
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(body).unwrap()))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, error: &str) -> Response<Body> {
+    json_response(status, &serde_json::json!({ "error": error }))
+}
+
+async fn handle(
+    request: Request<Body>,
+    jobs: Arc<Mutex<Jobs>>,
+    queued: Arc<Notify>,
+) -> Result<Response<Body>, Infallible> {
+    let path = request
+        .uri()
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let path = path.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let response = match (request.method(), path.as_slice()) {
+        (&Method::POST, ["jobs"]) => {
+            let body = match hyper::body::to_bytes(request.into_body()).await {
+                Ok(body) => body,
+                Err(err) => return Ok(error_response(StatusCode::BAD_REQUEST, &err.to_string())),
+            };
+            match serde_json::from_slice::<JobRequest>(&body) {
+                Ok(request) => {
+                    let id = jobs.lock().unwrap().push(request);
+                    queued.notify_one();
+                    json_response(StatusCode::CREATED, &JobId { id })
+                }
+                Err(err) => error_response(StatusCode::BAD_REQUEST, &err.to_string()),
+            }
+        }
+        (&Method::GET, ["jobs", id]) => match id.parse() {
+            Ok(id) => match jobs.lock().unwrap().status(id) {
+                Some(status) => json_response(StatusCode::OK, status),
+                None => error_response(StatusCode::NOT_FOUND, "unknown job"),
+            },
+            Err(_) => error_response(StatusCode::BAD_REQUEST, "invalid job id"),
+        },
+        (&Method::GET, ["jobs", id, "proof"]) => match id.parse() {
+            Ok(id) => {
+                // The lock is released before reading the proof file.
+                let (exists, proof) = {
+                    let jobs = jobs.lock().unwrap();
+                    (jobs.status(id).is_some(), jobs.proof(id))
+                };
+                match (exists, proof) {
+                    (_, Some(proof)) => match proof.read().await {
+                        Ok(proof) => json_response(StatusCode::OK, &proof),
+                        Err(err) => {
+                            error_response(StatusCode::INTERNAL_SERVER_ERROR, &err.to_string())
+                        }
+                    },
+                    (true, None) => error_response(StatusCode::NOT_FOUND, "job not done"),
+                    (false, None) => error_response(StatusCode::NOT_FOUND, "unknown job"),
+                }
+            }
+            Err(_) => error_response(StatusCode::BAD_REQUEST, "invalid job id"),
+        },
+        _ => error_response(StatusCode::NOT_FOUND, "not found"),
+    };
+    Ok(response)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Prove the queued jobs one after the other.
+async fn work(jobs: Arc<Mutex<Jobs>>, queued: Arc<Notify>) -> Result<()> {
+    let client = crate::utils::new_eth_signer_client(PROVIDER_URL, PVK).await?;
+    let hooks = ProofHook::from_env();
+    let mut keys = None;
+
+    loop {
+        let next = jobs.lock().unwrap().next(now());
+        let Some((id, block_number)) = next else {
+            queued.notified().await;
+            continue;
+        };
+        println!("Proving block {} for job {}", block_number, id);
+
+        let result = match prove_block(client.clone(), &mut keys, U64::from(block_number)).await {
+            Ok(Some((public_inputs, proof))) => {
+                Ok(ProofEvent::new(block_number, &public_inputs, &proof))
+            }
+            Ok(None) => Err(format!("block {} doesn't update the state", block_number)),
+            Err(err) => Err(err.to_string()),
+        };
+        if let Ok(event) = &result {
+            run_hooks(&hooks, event).await;
+        }
+        jobs.lock().unwrap().finish(id, result);
+    }
+}
+
+/// Serve the API on `addr`, saving the jobs to `state` if any.
+pub async fn serve(addr: SocketAddr, state: Option<&Path>) -> Result<()> {
+    let jobs = match state {
+        Some(path) => Jobs::persisted(path)?,
+        None => Jobs::default(),
+    };
+    let jobs = Arc::new(Mutex::new(jobs));
+    let queued = Arc::new(Notify::new());
+
+    let (service_jobs, service_queued) = (jobs.clone(), queued.clone());
+    let make_service = make_service_fn(move |_| {
+        let jobs = service_jobs.clone();
+        let queued = service_queued.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(request, jobs.clone(), queued.clone())
+            }))
+        }
+    });
+    println!("Prover server listening on {}", addr);
+    // The server runs in its own task, so that it keeps answering while the worker waits for a
+    // proof
+    let server = tokio::spawn(Server::bind(&addr).serve(make_service));
+
+    tokio::select! {
+        result = server => result??,
+        result = work(jobs, queued) => result?,
+    }
+    Ok(())
+}
@@ -5,19 +5,76 @@ use ethers::{
 };
 use eyre::Result;
 use halo2_proofs::halo2curves::bn256::Fr;
-use std::{collections::HashMap, str::FromStr, time::SystemTime};
-
-use crate::circuit::{
-    PublicInputs, StateUpdateCircuit, StateUpdateCircuitKeys, StateUpdateWitness,
-    DEFAULT_CIRCUIT_DEGREE, DEFAULT_MAX_NODES, DEFAULT_MAX_PROOF_COUNT,
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::SystemTime};
+
+use crate::{
+    circuit::{
+        PublicInputs, StateUpdateCircuit, StateUpdateCircuitKeys, StateUpdateWitness,
+        DEFAULT_CIRCUIT_DEGREE, DEFAULT_MAX_NODES, DEFAULT_MAX_PROOF_COUNT,
+    },
+    hooks::{run_hooks, ProofEvent, ProofHook},
+    utils::MM,
 };
 
-pub async fn serve() -> Result<()> {
-    const PROVIDER_URL: &str = "http://localhost:8545";
-    const PVK: &str = "7ccb34dc5fd31fd0aa7860de89a4adc37ccb34dc5fd31fd0aa7860de89a4adc3";
+pub const PROVIDER_URL: &str = "http://localhost:8545";
+pub const PVK: &str = "7ccb34dc5fd31fd0aa7860de89a4adc37ccb34dc5fd31fd0aa7860de89a4adc3";
+
+/// Prove the state update of a block, creating the keys with its circuit if there are none yet.
+/// Returns `None` if the block doesn't update the state, and an error if the circuit isn't
+/// satisfied by its witness.
+pub async fn prove_block(
+    client: Arc<MM>,
+    keys: &mut Option<StateUpdateCircuitKeys>,
+    block_number: U64,
+) -> Result<Option<(PublicInputs<Fr>, Vec<u8>)>> {
+    let witness = StateUpdateWitness::<Fr>::build(client, PROVIDER_URL, block_number, None).await?;
+
+    let Some(witness) = witness else {
+        return Ok(None);
+    };
+
+    let circuit = StateUpdateCircuit::new(
+        witness,
+        DEFAULT_CIRCUIT_DEGREE,
+        DEFAULT_MAX_NODES,
+        DEFAULT_MAX_PROOF_COUNT,
+    )?;
+
+    // The check and the proof take minutes of CPU, so they run on a blocking thread instead of
+    // stalling the tasks of the runtime.  The keys are moved to the thread and back.
+    let mut blocking_keys = keys.take();
+    let (blocking_keys, result) = tokio::task::spawn_blocking(move || {
+        let result = prove_circuit(circuit, &mut blocking_keys);
+        (blocking_keys, result)
+    })
+    .await?;
+    *keys = blocking_keys;
+    result.map(Some)
+}
 
+/// Check that the circuit is satisfied, then prove it and verify the proof.
+fn prove_circuit(
+    circuit: StateUpdateCircuit<Fr>,
+    keys: &mut Option<StateUpdateCircuitKeys>,
+) -> Result<(PublicInputs<Fr>, Vec<u8>)> {
+    let public_inputs: PublicInputs<Fr> = (&circuit.lc_witness).into();
+    circuit.check_satisfied()?;
+
+    let new_keys = match keys.take() {
+        Some(keys) => keys,
+        None => StateUpdateCircuitKeys::new(&circuit)?,
+    };
+    let keys = keys.insert(new_keys);
+    let proof = circuit.prove(keys)?;
+    StateUpdateCircuit::verify(&proof, &public_inputs, keys)?;
+
+    Ok((public_inputs, proof))
+}
+
+pub async fn serve() -> Result<()> {
     let client = crate::utils::new_eth_signer_client(PROVIDER_URL, PVK).await?;
 
+    let hooks = ProofHook::from_env();
     let mut keys = None;
 
     let mut storage = HashMap::new();
@@ -49,36 +106,14 @@ pub async fn serve() -> Result<()> {
 
         last_processed_block = last_processed_block + 1;
 
-        let witness = StateUpdateWitness::<Fr>::build(
-            client.clone(),
-            PROVIDER_URL,
-            last_processed_block,
-            None,
-        )
-        .await?;
-
-        let Some(witness) = witness else {
-                continue;
+        let Some((public_inputs, proof)) =
+            prove_block(client.clone(), &mut keys, last_processed_block).await?
+        else {
+            continue;
         };
 
-        let public_inputs: PublicInputs<Fr> = (&witness.lc_witness).into();
-        let circuit = StateUpdateCircuit::new(
-            witness,
-            DEFAULT_CIRCUIT_DEGREE,
-            DEFAULT_MAX_NODES,
-            DEFAULT_MAX_PROOF_COUNT,
-        )?;
-
-        println!("trns: {:#?}", circuit.transforms);
-
-        circuit.assert_satisfied();
-
-        if keys.is_none() {
-            keys = Some(StateUpdateCircuitKeys::new(&circuit));
-        }
-
-        let proof = circuit.prove(keys.as_ref().unwrap())?;
-        StateUpdateCircuit::verify(&proof, &public_inputs, keys.as_ref().unwrap())?;
+        let event = ProofEvent::new(last_processed_block.as_u64(), &public_inputs, &proof);
+        run_hooks(&hooks, &event).await;
 
         storage.insert(last_processed_block, proof);
     }
@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod test {
+    use eyre::Result;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use std::{env, fs};
+
+    use crate::hooks::{ProofEvent, ProofHook};
+
+    #[tokio::test]
+    async fn test_command_hook() -> Result<()> {
+        let event = ProofEvent::new(7, &[Fr::from(0x1234)], &[0xab, 0xcd]);
+        assert_eq!(event.public_inputs, vec![format!("{:0>64}", "1234")]);
+        assert_eq!(event.proof, "abcd");
+
+        let path = env::temp_dir().join(format!("proof_hook_{}.json", std::process::id()));
+        ProofHook::Command(format!("cat > {}", path.display()))
+            .run(&event)
+            .await?;
+        let written: ProofEvent = serde_json::from_slice(&fs::read(&path)?)?;
+        fs::remove_file(&path)?;
+        assert_eq!(written, event);
+
+        assert!(ProofHook::Command("exit 1".to_string())
+            .run(&event)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
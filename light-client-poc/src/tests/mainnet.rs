@@ -9,8 +9,8 @@ mod test {
     use std::{collections::HashMap, str::FromStr};
 
     use crate::circuit::{
-        PublicInputs, StateUpdateCircuit, StateUpdateCircuitKeys, StateUpdateWitness,
-        DEFAULT_CIRCUIT_DEGREE, DEFAULT_MAX_NODES, DEFAULT_MAX_PROOF_COUNT,
+        ProofManifest, ProofTranscript, PublicInputs, StateUpdateCircuit, StateUpdateCircuitKeys,
+        StateUpdateWitness, DEFAULT_CIRCUIT_DEGREE, DEFAULT_MAX_NODES, DEFAULT_MAX_PROOF_COUNT,
     };
 
     #[ctor::ctor]
@@ -194,7 +194,7 @@ mod test {
         .await?;
         let public_inputs: PublicInputs<Fr> = (&circuit.lc_witness).into();
 
-        let keys = StateUpdateCircuitKeys::new(&circuit);
+        let keys = StateUpdateCircuitKeys::new(&circuit)?;
         let proof = circuit.prove(&keys)?;
 
         let keys = StateUpdateCircuitKeys::unserialize(&keys.serialize()?)?;
@@ -232,6 +232,82 @@ mod test {
         Ok(())
     }
 
+    #[ignore]
+    #[tokio::test]
+    async fn test_poseidon_transcript() -> Result<()> {
+        let block_no = 107;
+        let access_list = blocks().get(&block_no).unwrap().clone();
+
+        let circuit = mock_prove(
+            block_no,
+            &access_list,
+            15,
+            DEFAULT_MAX_NODES,
+            DEFAULT_MAX_PROOF_COUNT,
+        )
+        .await?;
+        let public_inputs: PublicInputs<Fr> = (&circuit.lc_witness).into();
+
+        let keys = StateUpdateCircuitKeys::new(&circuit)?;
+        let proof = circuit.prove_with_transcript(&keys, ProofTranscript::Poseidon)?;
+
+        StateUpdateCircuit::verify_with_transcript(
+            &proof,
+            &public_inputs,
+            &keys,
+            ProofTranscript::Poseidon,
+        )?;
+        // the challenges of a Blake2b transcript differ
+        let _ = StateUpdateCircuit::verify(&proof, &public_inputs, &keys)
+            .err()
+            .unwrap();
+
+        Ok(())
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_reproduce_proof() -> Result<()> {
+        let block_no = 107;
+        let access_list = blocks().get(&block_no).unwrap().clone();
+        let circuit = || {
+            mock_prove(
+                block_no,
+                &access_list,
+                15,
+                DEFAULT_MAX_NODES,
+                DEFAULT_MAX_PROOF_COUNT,
+            )
+        };
+
+        let keys = StateUpdateCircuitKeys::new(&circuit().await?)?;
+        let (proof, manifest) =
+            circuit()
+                .await?
+                .prove_reproducible(&keys, ProofTranscript::Blake2b, 7)?;
+
+        let reproduction = circuit().await?.reproduce(&keys, &manifest, Some(&proof))?;
+        assert!(reproduction.is_identical(), "{:?}", reproduction);
+
+        // a proof with another seed only differs by its hash and its transcript
+        let manifest = ProofManifest {
+            seed: 8,
+            ..manifest
+        };
+        let reproduction = circuit().await?.reproduce(&keys, &manifest, Some(&proof))?;
+        assert_eq!(
+            reproduction
+                .diff
+                .iter()
+                .map(|(name, _, _)| *name)
+                .collect::<Vec<_>>(),
+            vec!["proof_hash"]
+        );
+        assert!(reproduction.first_transcript_diff.is_some());
+
+        Ok(())
+    }
+
     #[ignore]
     #[tokio::test]
     async fn test_block_2000007() -> Result<()> {
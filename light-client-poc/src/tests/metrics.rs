@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use crate::circuit::{measure, parse_budget_bytes, PhaseMetrics};
+
+    #[test]
+    fn test_parse_budget() {
+        assert_eq!(parse_budget_bytes("180"), Some(180 << 30));
+        assert_eq!(parse_budget_bytes(" 0.5 "), Some(1 << 29));
+        assert_eq!(parse_budget_bytes("0"), None);
+        assert_eq!(parse_budget_bytes("180GiB"), None);
+    }
+
+    #[test]
+    fn test_measure_exports_metrics() {
+        let path = env::temp_dir().join(format!("prover_metrics_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        env::set_var("PROVER_METRICS", &path);
+        assert_eq!(measure("first", || Ok(1 + 1)).unwrap(), 2);
+        measure("second", || Ok(())).unwrap();
+        env::remove_var("PROVER_METRICS");
+
+        let metrics = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<PhaseMetrics>(line).unwrap())
+            .collect::<Vec<_>>();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            metrics.iter().map(|m| m.phase.as_str()).collect::<Vec<_>>(),
+            vec!["first", "second"]
+        );
+        if cfg!(target_os = "linux") {
+            assert!(metrics[0].peak_rss_bytes.unwrap() > 0);
+        }
+    }
+}
@@ -1,3 +1,6 @@
 pub mod contract;
+pub mod hooks;
 pub mod local;
 pub mod mainnet;
+pub mod metrics;
+pub mod prover_server;
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test {
+    use eyre::Result;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use std::{env, fs};
+
+    use crate::{
+        hooks::ProofEvent,
+        prover_server::{JobRequest, JobStatus, Jobs},
+    };
+
+    fn request(block_number: u64, priority: u32, deadline: Option<u64>) -> JobRequest {
+        JobRequest {
+            block_number,
+            priority,
+            deadline,
+        }
+    }
+
+    #[test]
+    fn test_jobs() {
+        let mut jobs = Jobs::default();
+        let a = jobs.push(request(7, 0, None));
+        let b = jobs.push(request(8, 0, None));
+        assert_ne!(a, b);
+        assert_eq!(jobs.status(a), Some(&JobStatus::Queued));
+        assert_eq!(jobs.status(b + 1), None);
+
+        // the oldest job of the same priority first
+        assert_eq!(jobs.next(0), Some((a, 7)));
+        assert_eq!(
+            serde_json::to_string(jobs.status(a).unwrap()).unwrap(),
+            r#"{"status":"proving"}"#
+        );
+        let event = ProofEvent::new(7, &[Fr::from(1)], &[0xab]);
+        jobs.finish(a, Ok(event.clone()));
+        assert_eq!(jobs.status(a), Some(&JobStatus::Done));
+        assert_eq!(jobs.proof(a).unwrap(), Some(event));
+
+        assert_eq!(jobs.next(0), Some((b, 8)));
+        jobs.finish(b, Err("no state update".to_string()));
+        assert_eq!(
+            jobs.status(b),
+            Some(&JobStatus::Failed {
+                error: "no state update".to_string()
+            })
+        );
+        assert_eq!(jobs.proof(b).unwrap(), None);
+        assert_eq!(jobs.next(0), None);
+    }
+
+    #[test]
+    fn test_jobs_scheduling() {
+        let mut jobs = Jobs::default();
+        let low = jobs.push(request(1, 0, None));
+        let late = jobs.push(request(2, 1, Some(200)));
+        let soon = jobs.push(request(3, 1, Some(100)));
+        let expired = jobs.push(request(4, 2, Some(10)));
+
+        assert_eq!(jobs.next(50), Some((soon, 3)));
+        assert_eq!(
+            jobs.status(expired),
+            Some(&JobStatus::Failed {
+                error: "deadline expired".to_string()
+            })
+        );
+        assert_eq!(jobs.next(50), Some((late, 2)));
+        assert_eq!(jobs.next(50), Some((low, 1)));
+    }
+
+    #[test]
+    fn test_jobs_persisted() -> Result<()> {
+        let path = env::temp_dir().join(format!("prover_server_{}.json", std::process::id()));
+        let proofs = path.with_extension("proofs");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_dir_all(&proofs);
+
+        let mut jobs = Jobs::persisted(&path)?;
+        let a = jobs.push(request(7, 0, None));
+        let b = jobs.push(request(8, 0, None));
+        let c = jobs.push(request(9, 0, None));
+        assert_eq!(jobs.next(0), Some((a, 7)));
+        let event = ProofEvent::new(7, &[Fr::from(1)], &[0xab; 32]);
+        jobs.finish(a, Ok(event.clone()));
+        assert_eq!(jobs.next(0), Some((b, 8)));
+
+        // the proof is in its own file, not in the state file
+        assert!(proofs.join(format!("{}.json", a)).exists());
+        assert!(!String::from_utf8(fs::read(&path)?)?.contains(&event.proof));
+
+        // the job being proven when the server stopped is queued again
+        let mut jobs = Jobs::persisted(&path)?;
+        assert_eq!(jobs.status(a), Some(&JobStatus::Done));
+        assert_eq!(jobs.proof(a)?, Some(event));
+        assert_eq!(jobs.status(b), Some(&JobStatus::Queued));
+        assert_eq!(jobs.status(c), Some(&JobStatus::Queued));
+        assert_ne!(jobs.push(request(10, 0, None)), c);
+        fs::remove_file(&path)?;
+        fs::remove_dir_all(&proofs)?;
+
+        Ok(())
+    }
+}
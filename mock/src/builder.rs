@@ -0,0 +1,185 @@
+//! Fluent builder of a [`TestContext`], which adds the accounts and the transactions of the
+//! block one at a time instead of through closures over arrays of a fixed size.
+
+use crate::{MockAccount, MockBlock, MockTransaction, TestContext};
+use eth_types::{Error, Word};
+use external_tracer::LoggerConfig;
+
+/// Builder of the accounts, the transactions and the block of a [`TestContext`].
+///
+/// Accounts are set up with [`MockAccount`] and transactions with [`MockTransaction`], whose
+/// nonces and indexes are filled in by [`TestContext`] as usual.  The expected outcomes of the
+/// block are checked by the `CircuitTestBuilder` of the circuits.
+///
+/// ## Example
+/// ```rust
+/// use eth_types::bytecode;
+/// use mock::{builder::BlockBuilder, eth, MOCK_ACCOUNTS};
+///
+/// let ctx = BlockBuilder::new()
+///     .account(|acc| {
+///         acc.address(MOCK_ACCOUNTS[0])
+///             .balance(eth(10))
+///             .code(bytecode! { STOP });
+///     })
+///     .account(|acc| {
+///         acc.address(MOCK_ACCOUNTS[1]).balance(eth(10));
+///     })
+///     .tx(|tx| {
+///         tx.from(MOCK_ACCOUNTS[1]).to(MOCK_ACCOUNTS[0]);
+///     })
+///     .block(|block| {
+///         block.number(0xcafeu64);
+///     })
+///     .build::<2, 1>()
+///     .unwrap();
+/// ```
+pub struct BlockBuilder {
+    history_hashes: Option<Vec<Word>>,
+    accounts: Vec<MockAccount>,
+    txs: Vec<MockTransaction>,
+    block_fns: Vec<Box<dyn FnOnce(&mut MockBlock)>>,
+    logger_config: LoggerConfig,
+}
+
+impl Default for BlockBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockBuilder {
+    /// Create a builder of a block without accounts nor transactions.
+    pub fn new() -> Self {
+        Self {
+            history_hashes: None,
+            accounts: Vec::new(),
+            txs: Vec::new(),
+            block_fns: Vec::new(),
+            logger_config: LoggerConfig::default(),
+        }
+    }
+
+    /// Set the history hashes of the block, the most recent one last.
+    pub fn history_hashes(mut self, history_hashes: Vec<Word>) -> Self {
+        self.history_hashes = Some(history_hashes);
+        self
+    }
+
+    /// Add an account to the state before the block, set up by `account_fn`.
+    pub fn account(mut self, account_fn: impl FnOnce(&mut MockAccount)) -> Self {
+        let mut account = MockAccount::default();
+        account_fn(&mut account);
+        self.accounts.push(account);
+        self
+    }
+
+    /// Add a transaction to the block, set up by `tx_fn`.
+    pub fn tx(mut self, tx_fn: impl FnOnce(&mut MockTransaction)) -> Self {
+        let mut tx = MockTransaction::default();
+        tx_fn(&mut tx);
+        self.txs.push(tx);
+        self
+    }
+
+    /// Modify the block with `block_fn`, once its transactions are set.
+    pub fn block(mut self, block_fn: impl FnOnce(&mut MockBlock) + 'static) -> Self {
+        self.block_fns.push(Box::new(block_fn));
+        self
+    }
+
+    /// Set the config of the logger of the tracer.
+    pub fn logger_config(mut self, logger_config: LoggerConfig) -> Self {
+        self.logger_config = logger_config;
+        self
+    }
+
+    /// Build the [`TestContext`] of the `NACC` accounts and the `NTX` transactions added to the
+    /// builder, then trace the block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of accounts isn't `NACC` or the number of transactions isn't `NTX`.
+    pub fn build<const NACC: usize, const NTX: usize>(
+        self,
+    ) -> Result<TestContext<NACC, NTX>, Error> {
+        assert_eq!(self.accounts.len(), NACC, "number of accounts");
+        assert_eq!(self.txs.len(), NTX, "number of transactions");
+        let Self {
+            history_hashes,
+            accounts,
+            txs,
+            block_fns,
+            logger_config,
+        } = self;
+
+        TestContext::new_with_logger_config(
+            history_hashes,
+            |account_refs| {
+                for (account_ref, account) in account_refs.into_iter().zip(accounts) {
+                    *account_ref = account;
+                }
+            },
+            |tx_refs, _| {
+                for (tx_ref, tx) in tx_refs.into_iter().zip(txs) {
+                    *tx_ref = tx;
+                }
+            },
+            |block, _| {
+                for block_fn in block_fns {
+                    block_fn(block);
+                }
+                block
+            },
+            logger_config,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockBuilder;
+    use crate::{eth, MOCK_ACCOUNTS};
+    use eth_types::{U256, U64};
+
+    #[test]
+    fn block_builder() {
+        let ctx = BlockBuilder::new()
+            .account(|acc| {
+                acc.address(MOCK_ACCOUNTS[0]).balance(eth(10)).nonce(7);
+            })
+            .account(|acc| {
+                acc.address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            })
+            .tx(|tx| {
+                tx.from(MOCK_ACCOUNTS[0]).to(MOCK_ACCOUNTS[1]).value(eth(1));
+            })
+            .tx(|tx| {
+                tx.from(MOCK_ACCOUNTS[0]).to(MOCK_ACCOUNTS[1]);
+            })
+            .block(|block| {
+                block.number(0xcafeu64);
+            })
+            .build::<2, 2>()
+            .unwrap();
+
+        assert_eq!(ctx.accounts[0].address, MOCK_ACCOUNTS[0]);
+        assert_eq!(ctx.accounts[1].balance, eth(10));
+        assert_eq!(ctx.eth_block.number, Some(U64::from(0xcafe)));
+        assert_eq!(ctx.eth_block.transactions[0].value, eth(1));
+        // The nonces follow the one of the sender
+        assert_eq!(ctx.eth_block.transactions[0].nonce, U256::from(7));
+        assert_eq!(ctx.eth_block.transactions[1].nonce, U256::from(8));
+        assert_eq!(ctx.geth_traces.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "number of transactions")]
+    fn block_builder_wrong_len() {
+        let _ = BlockBuilder::new()
+            .account(|acc| {
+                acc.address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            })
+            .build::<1, 1>();
+    }
+}
@@ -9,6 +9,7 @@ use rand::{random, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 mod account;
 mod block;
+pub mod builder;
 mod sha3;
 pub mod test_ctx;
 pub mod test_ctx2;
@@ -7,7 +7,13 @@ use eth_types::{
 };
 use ethers_core::{
     rand::{CryptoRng, RngCore},
-    types::{OtherFields, TransactionRequest},
+    types::{
+        transaction::{
+            eip1559::Eip1559TransactionRequest, eip2718::TypedTransaction,
+            eip2930::Eip2930TransactionRequest,
+        },
+        OtherFields, TransactionRequest,
+    },
 };
 use ethers_signers::{LocalWallet, Signer};
 use lazy_static::lazy_static;
@@ -317,6 +323,22 @@ impl MockTransaction {
             .gas(self.gas)
             .gas_price(self.gas_price)
             .chain_id(self.chain_id.low_u64());
+        let tx: TypedTransaction = match self.transaction_type.as_u64() {
+            1 => Eip2930TransactionRequest::new(tx, self.access_list.clone()).into(),
+            2 => Eip1559TransactionRequest::new()
+                .from(self.from.address())
+                .to(self.to.clone().unwrap_or_default().address())
+                .nonce(self.nonce.unwrap_or_default())
+                .value(self.value)
+                .data(self.input.clone())
+                .gas(self.gas)
+                .access_list(self.access_list.clone())
+                .max_priority_fee_per_gas(self.max_priority_fee_per_gas)
+                .max_fee_per_gas(self.max_fee_per_gas)
+                .chain_id(self.chain_id.low_u64())
+                .into(),
+            _ => tx.into(),
+        };
 
         match (self.v, self.r, self.s) {
             (None, None, None) => {
@@ -326,7 +348,7 @@ impl MockTransaction {
                         .from
                         .as_wallet()
                         .with_chain_id(self.chain_id.low_u64())
-                        .sign_transaction_sync(&tx.into())
+                        .sign_transaction_sync(&tx)
                         .unwrap();
                     // Set sig parameters
                     self.sig_data((sig.v, sig.r, sig.s));
@@ -15,6 +15,7 @@ use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use std::{collections::HashMap, str::FromStr};
 use thiserror::Error;
 use zkevm_circuits::{
+    evm_circuit::param::MAX_DESTRUCTED_ACCOUNTS,
     super_circuit::SuperCircuit,
     test_util::{CircuitTestBuilder, CircuitTestError},
     witness::Block,
@@ -45,8 +46,8 @@ pub enum StateTestError {
     SkipTestMaxGasLimit(u64),
     #[error("SkipTestMaxSteps({0})")]
     SkipTestMaxSteps(usize),
-    #[error("SkipTestSelfDestruct")]
-    SkipTestSelfDestruct,
+    #[error("SkipTestSelfDestruct({0})")]
+    SkipTestSelfDestruct(usize),
     #[error("SkipTestDifficulty")]
     SkipTestDifficulty,
     #[error("SkipTestBalanceOverflow")]
@@ -66,7 +67,7 @@ impl StateTestError {
             self,
             StateTestError::SkipTestMaxSteps(_)
                 | StateTestError::SkipTestMaxGasLimit(_)
-                | StateTestError::SkipTestSelfDestruct
+                | StateTestError::SkipTestSelfDestruct(_)
         )
     }
 }
@@ -180,6 +181,7 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 gas_fee_cap: U256::zero(),
                 gas_tip_cap: U256::zero(),
                 call_data: st.data,
+                transaction_type: U64::zero(),
                 access_list: None,
                 v: sig.v,
                 r: sig.r,
@@ -206,13 +208,20 @@ fn check_geth_traces(
     suite: &TestSuite,
     verbose: bool,
 ) -> Result<(), StateTestError> {
-    if geth_traces.iter().any(|gt| {
-        gt.struct_logs.iter().any(|sl| {
-            sl.op == eth_types::evm_types::OpcodeId::SELFDESTRUCT
-                || sl.op == eth_types::evm_types::OpcodeId::INVALID(0xff)
-        })
-    }) {
-        return Err(StateTestError::SkipTestSelfDestruct);
+    // EndTx can only delete MAX_DESTRUCTED_ACCOUNTS accounts, so skip the txs that may self
+    // destruct more, counting the SELFDESTRUCT steps that are reverted.
+    for gt in geth_traces {
+        let num_selfdestructs = gt
+            .struct_logs
+            .iter()
+            .filter(|sl| {
+                sl.op == eth_types::evm_types::OpcodeId::SELFDESTRUCT
+                    || sl.op == eth_types::evm_types::OpcodeId::INVALID(0xff)
+            })
+            .count();
+        if num_selfdestructs > MAX_DESTRUCTED_ACCOUNTS {
+            return Err(StateTestError::SkipTestSelfDestruct(num_selfdestructs));
+        }
     }
 
     if geth_traces[0].struct_logs.len() as u64 > suite.max_steps {
@@ -332,6 +341,7 @@ pub fn run_test(
             max_withdrawals: 1,
             max_rws: 55000,
             max_calldata: 5000,
+            max_ecrecover: 0,
             max_bytecode: 5000,
             max_copy_rows: 55000,
             max_evm_rows: 0,
@@ -369,6 +379,7 @@ pub fn run_test(
             max_txs: 1,
             max_withdrawals: 1,
             max_calldata: 32,
+            max_ecrecover: 0,
             max_rws: 256,
             max_copy_rows: 256,
             max_exp_steps: 256,
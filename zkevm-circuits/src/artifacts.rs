@@ -0,0 +1,696 @@
+//! Disk space management of the artifacts of a prover: proofs, params and keys.
+//!
+//! A long-running prover accumulates the proofs of old blocks, and the keys and params of
+//! circuits it doesn't prove anymore.  An [`ArtifactStore`] tracks the files of a directory with
+//! their kind, size and last use in an index next to them, [`INDEX_FILE`], and removes them:
+//!
+//! - with [`ArtifactStore::gc`], the least recently used first, until the artifacts fit a size
+//! - with [`ArtifactStore::retain`], the ones a [`RetentionPolicy`] doesn't keep
+//!
+//! The files of the directory that aren't in the index when the store is opened, like the ones
+//! written by another tool, are tracked as [`ArtifactKind::Other`], last used when they were last
+//! modified.
+//!
+//! ```text
+//! cargo run --bin artifacts -- <dir> gc <max bytes>
+//! ```
+//!
+//! An [`ArtifactsDir`] locates the directory of the artifacts on Linux, macOS and Windows,
+//! [`ARTIFACTS_DIR_VAR`] first, with a subdirectory per circuit, and writes the artifacts with
+//! [`write_atomic`], so that two runs writing the same proof never leave a truncated or
+//! interleaved file.  The paths of the index are stored with `/` separators, so that a directory
+//! of artifacts can be copied between platforms.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// Name of the index of the artifacts, in the directory of the store.
+pub const INDEX_FILE: &str = "artifacts.json";
+
+/// Environment variable overriding the directory of the artifacts.
+pub const ARTIFACTS_DIR_VAR: &str = "ZKEVM_ARTIFACTS_DIR";
+
+/// Name of the directory of the artifacts in the data directory of the user.
+const APP_DIR: &str = "zkevm-circuits";
+
+/// Error of an [`ArtifactStore`].
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    /// Error reading or writing the artifacts or the index.
+    #[error("artifacts io error: {0}")]
+    Io(#[from] io::Error),
+    /// The index can't be decoded.
+    #[error("invalid artifacts index: {0}")]
+    Serde(#[from] serde_json::Error),
+    /// The path isn't in the directory of the store.
+    #[error("{} is not in the artifacts directory", .0.display())]
+    OutsideDir(PathBuf),
+    /// The artifact isn't tracked by the store.
+    #[error("untracked artifact {}", .0.display())]
+    Untracked(PathBuf),
+    /// The name of the circuit can't be the name of a directory.
+    #[error("invalid circuit name {0:?}")]
+    InvalidCircuit(String),
+}
+
+/// Kind of an artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// Proof of a circuit
+    Proof {
+        /// Name of the circuit, or digest of its verifying key
+        circuit: String,
+    },
+    /// KZG params
+    Params {
+        /// Degree of the params
+        degree: u32,
+    },
+    /// Proving or verifying key of a circuit
+    Key {
+        /// Name of the circuit, or digest of its verifying key
+        circuit: String,
+    },
+    /// Any other file
+    Other,
+}
+
+/// Artifact tracked by an [`ArtifactStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Kind of the artifact
+    pub kind: ArtifactKind,
+    /// Size of the file, in bytes
+    pub size: u64,
+    /// Last use, in milliseconds since the unix epoch
+    pub last_use_ms: u64,
+    /// Number of the last use in the uses of the store, to order the uses of a same millisecond
+    pub last_use_seq: u64,
+}
+
+impl Artifact {
+    fn last_use(&self) -> (u64, u64) {
+        (self.last_use_ms, self.last_use_seq)
+    }
+}
+
+/// Serialized index of an [`ArtifactStore`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    /// Number of the last use
+    seq: u64,
+    /// Artifacts by path relative to the directory of the store
+    #[serde(with = "portable_paths")]
+    artifacts: BTreeMap<PathBuf, Artifact>,
+}
+
+/// Paths of the index with `/` separators on every platform.
+mod portable_paths {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(
+        artifacts: &BTreeMap<PathBuf, Artifact>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(artifacts.iter().map(|(path, artifact)| {
+            let components = path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>();
+            (components.join("/"), artifact)
+        }))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<PathBuf, Artifact>, D::Error> {
+        let artifacts = BTreeMap::<String, Artifact>::deserialize(deserializer)?;
+        Ok(artifacts
+            .into_iter()
+            .map(|(path, artifact)| (path.split('/').collect(), artifact))
+            .collect())
+    }
+}
+
+/// Artifacts to keep with [`ArtifactStore::retain`].  Only the artifacts of the kinds the policy
+/// restricts are removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Number of proofs to keep per circuit, the most recently used ones
+    pub proofs_per_circuit: Option<usize>,
+    /// Degrees of the params to keep
+    pub active_degrees: Option<BTreeSet<u32>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Write `bytes` to `path` atomically: to a temporary file of the same directory, unique to the
+/// process and the call, renamed to `path` once complete.  Concurrent writers of the same path
+/// each replace the file with a complete one, and an interrupted writer leaves the previous one.
+pub fn write_atomic(path: impl AsRef<Path>, bytes: &[u8]) -> io::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+
+    let path = path.as_ref();
+    let mut tmp_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?
+        .to_os_string();
+    tmp_name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = path.with_file_name(tmp_name);
+
+    let write = || {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        // Replaces an existing file on Windows too
+        fs::rename(&tmp, path)
+    };
+    write().map_err(|err| {
+        let _ = fs::remove_file(&tmp);
+        err
+    })
+}
+
+/// Paths of the files under `dir`, relative to `root`, but the index and the temporary files.
+fn collect_files(root: &Path, dir: &Path, files: &mut BTreeSet<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if path != root.join(INDEX_FILE) && path.extension().map_or(true, |ext| ext != "tmp")
+        {
+            files.insert(path.strip_prefix(root).unwrap().to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Artifacts of a directory, with their kind, size and last use.
+#[derive(Debug)]
+pub struct ArtifactStore {
+    dir: PathBuf,
+    index: Index,
+}
+
+impl ArtifactStore {
+    /// Open the store of the artifacts in `dir`, which is created if it doesn't exist.  The
+    /// removed files are dropped from the index, the new ones are tracked as
+    /// [`ArtifactKind::Other`], and the sizes are updated.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, ArtifactError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut index: Index = match fs::read(dir.join(INDEX_FILE)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Index::default(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut paths = BTreeSet::new();
+        collect_files(&dir, &dir, &mut paths)?;
+        index.artifacts.retain(|path, _| paths.contains(path));
+        for path in paths {
+            let metadata = fs::metadata(dir.join(&path))?;
+            match index.artifacts.get_mut(&path) {
+                Some(artifact) => artifact.size = metadata.len(),
+                None => {
+                    let modified_ms = metadata
+                        .modified()?
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |duration| duration.as_millis() as u64);
+                    let artifact = Artifact {
+                        kind: ArtifactKind::Other,
+                        size: metadata.len(),
+                        last_use_ms: modified_ms,
+                        last_use_seq: 0,
+                    };
+                    index.artifacts.insert(path, artifact);
+                }
+            }
+        }
+
+        let store = Self { dir, index };
+        store.save()?;
+        Ok(store)
+    }
+
+    /// Return the directory of the store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return the artifacts, by path relative to the directory of the store.
+    pub fn artifacts(&self) -> &BTreeMap<PathBuf, Artifact> {
+        &self.index.artifacts
+    }
+
+    /// Return the total size of the artifacts, in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.index
+            .artifacts
+            .values()
+            .map(|artifact| artifact.size)
+            .sum()
+    }
+
+    /// Path of an artifact relative to the directory of the store, from a path relative to the
+    /// directory or prefixed by it.
+    fn relative(&self, path: &Path) -> Result<PathBuf, ArtifactError> {
+        let relative = path.strip_prefix(&self.dir).unwrap_or(path);
+        // Components below the directory only: a root without a drive, like `\elsewhere`, isn't
+        // absolute on Windows
+        let mut normal = PathBuf::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(name) => normal.push(name),
+                Component::CurDir => {}
+                _ => return Err(ArtifactError::OutsideDir(path.to_path_buf())),
+            }
+        }
+        Ok(normal)
+    }
+
+    fn next_use(&mut self) -> (u64, u64) {
+        self.index.seq += 1;
+        (now_ms(), self.index.seq)
+    }
+
+    /// Track the file at `path`, relative to the directory of the store or prefixed by it, as an
+    /// artifact of `kind` used now.
+    pub fn track(
+        &mut self,
+        path: impl AsRef<Path>,
+        kind: ArtifactKind,
+    ) -> Result<(), ArtifactError> {
+        let path = self.relative(path.as_ref())?;
+        let size = fs::metadata(self.dir.join(&path))?.len();
+        let (last_use_ms, last_use_seq) = self.next_use();
+        self.index.artifacts.insert(
+            path,
+            Artifact {
+                kind,
+                size,
+                last_use_ms,
+                last_use_seq,
+            },
+        );
+        self.save()
+    }
+
+    /// Record a use of the artifact at `path` now.
+    pub fn touch(&mut self, path: impl AsRef<Path>) -> Result<(), ArtifactError> {
+        let path = self.relative(path.as_ref())?;
+        if !self.index.artifacts.contains_key(&path) {
+            return Err(ArtifactError::Untracked(path));
+        }
+        let (last_use_ms, last_use_seq) = self.next_use();
+        let artifact = self.index.artifacts.get_mut(&path).unwrap();
+        artifact.last_use_ms = last_use_ms;
+        artifact.last_use_seq = last_use_seq;
+        self.save()
+    }
+
+    /// Remove the least recently used artifacts until the total size is at most `max_bytes`.
+    /// Returns the paths of the removed artifacts, relative to the directory of the store.
+    pub fn gc(&mut self, max_bytes: u64) -> Result<Vec<PathBuf>, ArtifactError> {
+        let mut artifacts = self.index.artifacts.iter().collect::<Vec<_>>();
+        artifacts.sort_by_key(|(_, artifact)| artifact.last_use());
+        let mut total_bytes = self.total_bytes();
+        let mut removed = Vec::new();
+        for (path, artifact) in artifacts {
+            if total_bytes <= max_bytes {
+                break;
+            }
+            total_bytes -= artifact.size;
+            removed.push(path.clone());
+        }
+        self.remove(removed)
+    }
+
+    /// Remove the artifacts the policy doesn't keep.  Returns the paths of the removed
+    /// artifacts, relative to the directory of the store.
+    pub fn retain(&mut self, policy: &RetentionPolicy) -> Result<Vec<PathBuf>, ArtifactError> {
+        let mut removed = Vec::new();
+        let mut proofs: HashMap<&str, Vec<(&PathBuf, &Artifact)>> = HashMap::new();
+        for (path, artifact) in self.index.artifacts.iter() {
+            match &artifact.kind {
+                ArtifactKind::Proof { circuit } => proofs
+                    .entry(circuit.as_str())
+                    .or_default()
+                    .push((path, artifact)),
+                ArtifactKind::Params { degree } => {
+                    if let Some(degrees) = &policy.active_degrees {
+                        if !degrees.contains(degree) {
+                            removed.push(path.clone());
+                        }
+                    }
+                }
+                ArtifactKind::Key { .. } | ArtifactKind::Other => {}
+            }
+        }
+        if let Some(proofs_per_circuit) = policy.proofs_per_circuit {
+            for mut proofs in proofs.into_values() {
+                // Most recently used first
+                proofs.sort_by_key(|(_, artifact)| std::cmp::Reverse(artifact.last_use()));
+                removed.extend(
+                    proofs
+                        .into_iter()
+                        .skip(proofs_per_circuit)
+                        .map(|(path, _)| path.clone()),
+                );
+            }
+        }
+        removed.sort();
+        self.remove(removed)
+    }
+
+    fn remove(&mut self, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, ArtifactError> {
+        for path in paths.iter() {
+            match fs::remove_file(self.dir.join(path)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    // Keep the index of the files removed so far
+                    self.save()?;
+                    return Err(err.into());
+                }
+                _ => {
+                    self.index.artifacts.remove(path);
+                }
+            }
+        }
+        self.save()?;
+        Ok(paths)
+    }
+
+    fn save(&self) -> Result<(), ArtifactError> {
+        write_atomic(
+            self.dir.join(INDEX_FILE),
+            &serde_json::to_vec_pretty(&self.index)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Directory of the artifacts of a prover, with a subdirectory per circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactsDir {
+    root: PathBuf,
+}
+
+impl ArtifactsDir {
+    /// Artifacts in `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Artifacts in the directory of [`ARTIFACTS_DIR_VAR`] if it is set, else in the data
+    /// directory of the user: `$XDG_DATA_HOME/zkevm-circuits` if it is set,
+    /// `~/Library/Application Support/zkevm-circuits` on macOS,
+    /// `%LOCALAPPDATA%\zkevm-circuits` on Windows and `~/.local/share/zkevm-circuits` on the
+    /// other platforms, else in `artifacts` in the working directory.
+    pub fn from_env() -> Self {
+        Self::new(root_from(|var| env::var_os(var)))
+    }
+
+    /// Return the directory of the artifacts.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Return the directory of the artifacts of a circuit, created if it doesn't exist.  The
+    /// name of the circuit must be a single component of a path.
+    pub fn circuit_dir(&self, circuit: &str) -> Result<PathBuf, ArtifactError> {
+        if !is_file_name(circuit) {
+            return Err(ArtifactError::InvalidCircuit(circuit.to_string()));
+        }
+        let dir = self.root.join(circuit);
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Write the artifact `name` of a circuit atomically with [`write_atomic`], and return its
+    /// path.
+    pub fn write(&self, circuit: &str, name: &str, bytes: &[u8]) -> Result<PathBuf, ArtifactError> {
+        let path = self.circuit_dir(circuit)?.join(name);
+        if !is_file_name(name) {
+            return Err(ArtifactError::OutsideDir(path));
+        }
+        write_atomic(&path, bytes)?;
+        Ok(path)
+    }
+
+    /// Open the [`ArtifactStore`] of the directory.
+    pub fn store(&self) -> Result<ArtifactStore, ArtifactError> {
+        ArtifactStore::open(&self.root)
+    }
+}
+
+/// Whether `name` is a single normal component of a path.
+fn is_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(component)), None) if component == name
+    )
+}
+
+/// Directory of the artifacts from the environment variables returned by `var`.
+fn root_from(var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    // Empty or relative XDG directories are invalid and ignored
+    let absolute = |name: &str| var(name).map(PathBuf::from).filter(|dir| dir.is_absolute());
+    if let Some(dir) = var(ARTIFACTS_DIR_VAR).filter(|dir| !dir.is_empty()) {
+        return dir.into();
+    }
+    if let Some(dir) = absolute("XDG_DATA_HOME") {
+        return dir.join(APP_DIR);
+    }
+    let data_dir = if cfg!(windows) {
+        absolute("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        absolute("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        absolute("HOME").map(|home| home.join(".local").join("share"))
+    };
+    data_dir.map_or_else(|| PathBuf::from("artifacts"), |dir| dir.join(APP_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, path: &str, size: usize) {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0; size]).unwrap();
+    }
+
+    fn paths(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn artifacts_gc() {
+        let dir = std::env::temp_dir().join(format!("artifacts_gc_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        write(&dir, "untracked", 1);
+        let mut store = ArtifactStore::open(&dir).unwrap();
+        assert_eq!(
+            store.artifacts()[Path::new("untracked")].kind,
+            ArtifactKind::Other
+        );
+
+        for (path, size) in [("a.pk", 100), ("params/20", 50), ("proofs/b", 10)] {
+            write(&dir, path, size);
+        }
+        let circuit = "super".to_string();
+        store
+            .track(
+                "a.pk",
+                ArtifactKind::Key {
+                    circuit: circuit.clone(),
+                },
+            )
+            .unwrap();
+        store
+            .track(dir.join("params/20"), ArtifactKind::Params { degree: 20 })
+            .unwrap();
+        store
+            .track("proofs/b", ArtifactKind::Proof { circuit })
+            .unwrap();
+        store.touch("a.pk").unwrap();
+        assert_eq!(store.total_bytes(), 161);
+        assert!(matches!(
+            store.touch("missing"),
+            Err(ArtifactError::Untracked(_))
+        ));
+        assert!(matches!(
+            store.track("/elsewhere", ArtifactKind::Other),
+            Err(ArtifactError::OutsideDir(_))
+        ));
+
+        // The index survives the store, and forgets the removed files
+        fs::remove_file(dir.join("untracked")).unwrap();
+        let mut store = ArtifactStore::open(&dir).unwrap();
+        assert_eq!(store.total_bytes(), 160);
+
+        // The least recently used first
+        assert_eq!(store.gc(120).unwrap(), paths(&["params/20"]));
+        assert_eq!(store.gc(120).unwrap(), paths(&[]));
+        assert_eq!(store.gc(100).unwrap(), paths(&["proofs/b"]));
+        assert!(dir.join("a.pk").exists() && !dir.join("proofs/b").exists());
+        assert_eq!(store.total_bytes(), 100);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn artifacts_retention_policy() {
+        let dir = std::env::temp_dir().join(format!("artifacts_retain_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut store = ArtifactStore::open(&dir).unwrap();
+        for (path, kind) in [
+            ("params/19", ArtifactKind::Params { degree: 19 }),
+            ("params/20", ArtifactKind::Params { degree: 20 }),
+            (
+                "a/1",
+                ArtifactKind::Proof {
+                    circuit: "a".to_string(),
+                },
+            ),
+            (
+                "a/2",
+                ArtifactKind::Proof {
+                    circuit: "a".to_string(),
+                },
+            ),
+            (
+                "a/3",
+                ArtifactKind::Proof {
+                    circuit: "a".to_string(),
+                },
+            ),
+            (
+                "b/1",
+                ArtifactKind::Proof {
+                    circuit: "b".to_string(),
+                },
+            ),
+        ] {
+            write(&dir, path, 1);
+            store.track(path, kind).unwrap();
+        }
+        store.touch("a/1").unwrap();
+
+        // Restricting nothing keeps everything
+        assert_eq!(
+            store.retain(&RetentionPolicy::default()).unwrap(),
+            paths(&[])
+        );
+        let policy = RetentionPolicy {
+            proofs_per_circuit: Some(2),
+            active_degrees: Some(BTreeSet::from([20])),
+        };
+        assert_eq!(store.retain(&policy).unwrap(), paths(&["a/2", "params/19"]));
+        assert_eq!(
+            store.artifacts().keys().cloned().collect::<Vec<_>>(),
+            paths(&["a/1", "a/3", "b/1", "params/20"])
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn artifacts_dir() {
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |var: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == var)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert_eq!(
+            root_from(vars(&[
+                (ARTIFACTS_DIR_VAR, "proofs"),
+                ("XDG_DATA_HOME", "/data")
+            ])),
+            PathBuf::from("proofs")
+        );
+        if !cfg!(windows) {
+            assert_eq!(
+                root_from(vars(&[("XDG_DATA_HOME", "/data"), ("HOME", "/home")])),
+                Path::new("/data").join(APP_DIR)
+            );
+        }
+        // Empty and relative directories are ignored
+        assert_eq!(
+            root_from(vars(&[(ARTIFACTS_DIR_VAR, ""), ("XDG_DATA_HOME", "data")])),
+            PathBuf::from("artifacts")
+        );
+
+        let root = std::env::temp_dir().join(format!("artifacts_dir_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let dir = ArtifactsDir::new(&root);
+        for circuit in ["", "..", "a/b", "a/"] {
+            assert!(matches!(
+                dir.circuit_dir(circuit),
+                Err(ArtifactError::InvalidCircuit(_))
+            ));
+        }
+        assert!(matches!(
+            dir.write("super", "../proof", b"proof"),
+            Err(ArtifactError::OutsideDir(_))
+        ));
+
+        // Concurrent writes of a same path leave one of the complete files
+        std::thread::scope(|scope| {
+            for byte in 0..4u8 {
+                let dir = &dir;
+                scope.spawn(move || dir.write("super", "proof", &[byte; 1 << 16]).unwrap());
+            }
+        });
+        let proof = fs::read(root.join("super").join("proof")).unwrap();
+        assert_eq!(proof.len(), 1 << 16);
+        assert!(proof.iter().all(|byte| *byte == proof[0]));
+        assert_eq!(fs::read_dir(root.join("super")).unwrap().count(), 1);
+
+        // The paths of the index are portable
+        let mut store = dir.store().unwrap();
+        store
+            .track(
+                Path::new("super").join("proof"),
+                ArtifactKind::Proof {
+                    circuit: "super".to_string(),
+                },
+            )
+            .unwrap();
+        let index = fs::read_to_string(root.join(INDEX_FILE)).unwrap();
+        assert!(index.contains("\"super/proof\""));
+        let store = dir.store().unwrap();
+        assert_eq!(
+            store.artifacts()[&Path::new("super").join("proof")].kind,
+            ArtifactKind::Proof {
+                circuit: "super".to_string(),
+            }
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
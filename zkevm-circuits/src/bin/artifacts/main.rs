@@ -0,0 +1,112 @@
+//! Manage the disk space of the artifacts of a prover.
+//!
+//! Usage:
+//!
+//! - `artifacts <dir> list` lists the artifacts with their kind, size and last use
+//! - `artifacts <dir> track <path> proof <circuit>|params <k>|key <circuit>|other` tracks a file of
+//!   the directory as an artifact of the kind
+//! - `artifacts <dir> touch <path>` records a use of an artifact
+//! - `artifacts <dir> gc <max bytes>` removes the least recently used artifacts until they fit the
+//!   size
+//! - `artifacts <dir> retain [proofs=<n>] [degrees=<k>,...]` keeps the `n` most recently used
+//!   proofs of each circuit, and the params of the degrees
+use std::{env, process};
+use zkevm_circuits::artifacts::{ArtifactKind, ArtifactStore, RetentionPolicy};
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} <dir> list", program);
+    eprintln!(
+        "       {} <dir> track <path> proof <circuit>|params <k>|key <circuit>|other",
+        program
+    );
+    eprintln!("       {} <dir> touch <path>", program);
+    eprintln!("       {} <dir> gc <max bytes>", program);
+    eprintln!(
+        "       {} <dir> retain [proofs=<n>] [degrees=<k>,...]",
+        program
+    );
+    process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if args.len() < 3 {
+        usage(args[0]);
+    }
+    let mut store =
+        ArtifactStore::open(args[1]).unwrap_or_else(|err| panic!("open {}: {}", args[1], err));
+    match args[2..] {
+        ["list"] => {
+            for (path, artifact) in store.artifacts() {
+                println!(
+                    "{}\t{:?}\t{}\t{}",
+                    path.display(),
+                    artifact.kind,
+                    artifact.size,
+                    artifact.last_use_ms
+                );
+            }
+            println!("total: {} bytes", store.total_bytes());
+        }
+        ["track", path, ref kind @ ..] => {
+            let kind = match kind {
+                ["proof", circuit] => ArtifactKind::Proof {
+                    circuit: circuit.to_string(),
+                },
+                ["params", k] => ArtifactKind::Params {
+                    degree: k.parse().unwrap_or_else(|_| usage(args[0])),
+                },
+                ["key", circuit] => ArtifactKind::Key {
+                    circuit: circuit.to_string(),
+                },
+                ["other"] => ArtifactKind::Other,
+                _ => usage(args[0]),
+            };
+            store
+                .track(path, kind)
+                .unwrap_or_else(|err| panic!("track {}: {}", path, err));
+        }
+        ["touch", path] => store
+            .touch(path)
+            .unwrap_or_else(|err| panic!("touch {}: {}", path, err)),
+        ["gc", max_bytes] => {
+            let max_bytes = max_bytes.parse().unwrap_or_else(|_| usage(args[0]));
+            let removed = store
+                .gc(max_bytes)
+                .unwrap_or_else(|err| panic!("gc {}: {}", args[1], err));
+            for path in removed {
+                println!("removed {}", path.display());
+            }
+            println!("total: {} bytes", store.total_bytes());
+        }
+        ["retain", ref options @ ..] => {
+            let mut policy = RetentionPolicy::default();
+            for option in options {
+                match option.split_once('=') {
+                    Some(("proofs", n)) => {
+                        policy.proofs_per_circuit =
+                            Some(n.parse().unwrap_or_else(|_| usage(args[0])));
+                    }
+                    Some(("degrees", degrees)) => {
+                        policy.active_degrees = Some(
+                            degrees
+                                .split(',')
+                                .map(|k| k.parse().unwrap_or_else(|_| usage(args[0])))
+                                .collect(),
+                        );
+                    }
+                    _ => usage(args[0]),
+                }
+            }
+            let removed = store
+                .retain(&policy)
+                .unwrap_or_else(|err| panic!("retain {}: {}", args[1], err));
+            for path in removed {
+                println!("removed {}", path.display());
+            }
+            println!("total: {} bytes", store.total_bytes());
+        }
+        _ => usage(args[0]),
+    }
+}
@@ -0,0 +1,129 @@
+//! Explore the capacities of the circuits for the blocks of a `CapacityReport`, without
+//! generating any key.  Each command changes a capacity and prints the rows of the sub-circuits,
+//! the degree of the `SuperCircuit` and its estimated proving time.
+//!
+//! Usage: `capacity_planner <report.json>`, then the commands on the standard input:
+//!
+//! - `max_txs <n>`, `max_calldata <n>`, `max_ecrecover <n>`, `keccak_rows <n>` or `rws <n>` set a
+//!   capacity
+//! - `calibrate <k> <secs>` sets the proving time measured at degree `k`, to estimate the others
+//! - `show` prints the plan again, `help` the commands and `quit` exits
+use halo2_proofs::halo2curves::bn256::Fr;
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    process,
+};
+use zkevm_circuits::capacity_stats::{Capacities, CapacityReport, ProveTimeCalibration};
+
+const HELP: &str = "\
+max_txs <n>            maximum number of txs
+max_calldata <n>       maximum number of calldata bytes of all the txs
+max_ecrecover <n>      maximum number of ecRecover signatures
+keccak_rows <n>        rows of the Keccak circuit, 0 to size it for the blocks
+rws <n>                rows of the State circuit
+calibrate <k> <secs>   proving time measured at degree k
+show                   print the plan
+quit                   exit";
+
+fn print_plan(
+    report: &CapacityReport,
+    capacities: &Capacities,
+    calibration: Option<ProveTimeCalibration>,
+) {
+    let plan = report.plan::<Fr>(capacities);
+    println!("{:?}", capacities);
+    for (name, rows) in plan.rows.iter() {
+        let used = report.rows.get(name).map(|stats| stats.max).unwrap_or(0);
+        let overflow = if plan.overflows.contains(name) {
+            "  OVERFLOW"
+        } else {
+            ""
+        };
+        println!(
+            "  {:<12} {:>10} rows ({} used){}",
+            name, rows, used, overflow
+        );
+    }
+    match calibration {
+        Some(calibration) => println!(
+            "k = {}, estimated proving time {:.0}s",
+            plan.k,
+            calibration.estimate(plan.k)
+        ),
+        None => println!("k = {} (calibrate to estimate the proving time)", plan.k),
+    }
+}
+
+fn parse<T: std::str::FromStr>(arg: Option<&str>) -> Option<T> {
+    arg.and_then(|arg| arg.parse().ok())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: {} <report.json>", args[0]);
+        process::exit(2);
+    }
+    let file = File::open(&args[1]).unwrap_or_else(|err| panic!("open {}: {}", args[1], err));
+    let report = CapacityReport::read(BufReader::new(file))
+        .unwrap_or_else(|err| panic!("read {}: {}", args[1], err));
+    println!("{} blocks", report.blocks);
+
+    let mut capacities = Capacities::default();
+    let mut calibration = None;
+    print_plan(&report, &capacities, calibration);
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let arg = words.next();
+        let capacity = match command {
+            "max_txs" => Some(&mut capacities.max_txs),
+            "max_calldata" => Some(&mut capacities.max_calldata),
+            "max_ecrecover" => Some(&mut capacities.max_ecrecover),
+            "keccak_rows" => Some(&mut capacities.max_keccak_rows),
+            "rws" => Some(&mut capacities.max_rws),
+            _ => None,
+        };
+        match (command, capacity) {
+            (_, Some(capacity)) => match parse(arg) {
+                Some(value) => *capacity = value,
+                None => {
+                    println!("usage: {} <n>", command);
+                    continue;
+                }
+            },
+            ("calibrate", _) => match (parse(arg), parse(words.next())) {
+                (Some(k), Some(secs)) if k > 0 => {
+                    calibration = Some(ProveTimeCalibration { k, secs })
+                }
+                _ => {
+                    println!("usage: calibrate <k> <secs>");
+                    continue;
+                }
+            },
+            ("show", _) => {}
+            ("help", _) => {
+                println!("{}", HELP);
+                continue;
+            }
+            ("quit" | "exit", _) => break,
+            _ => {
+                println!("unknown command {}, try help", command);
+                continue;
+            }
+        }
+        print_plan(&report, &capacities, calibration);
+    }
+}
@@ -0,0 +1,79 @@
+//! Run the steps of a powers of tau ceremony for the KZG params of the circuits.
+//!
+//! Usage:
+//!
+//! - `ceremony init <k> <params>` writes the params of degree `k` the ceremony starts from
+//! - `ceremony contribute <params> <next_params> <transcript>` contributes a random toxic waste to
+//!   the params, writes the new params and appends the proof of the contribution to the transcript
+//! - `ceremony verify <params> <transcript>` verifies that the params are the result of the
+//!   contributions of the transcript
+use rand::rngs::OsRng;
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter},
+    process,
+};
+use zkevm_circuits::ceremony::{
+    contribute, initial_params, read_params, read_transcript, verify_transcript, write_params,
+};
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} init <k> <params>", program);
+    eprintln!(
+        "       {} contribute <params> <next_params> <transcript>",
+        program
+    );
+    eprintln!("       {} verify <params> <transcript>", program);
+    process::exit(2);
+}
+
+fn open(path: &str) -> BufReader<File> {
+    BufReader::new(File::open(path).unwrap_or_else(|err| panic!("open {}: {}", path, err)))
+}
+
+fn create(path: &str) -> BufWriter<File> {
+    BufWriter::new(File::create(path).unwrap_or_else(|err| panic!("create {}: {}", path, err)))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args[1..] {
+        ["init", k, path] => {
+            let k = k.parse().unwrap_or_else(|_| usage(args[0]));
+            write_params(&initial_params(k), &mut create(path))
+                .unwrap_or_else(|err| panic!("write {}: {}", path, err));
+        }
+        ["contribute", path, next_path, transcript_path] => {
+            let params =
+                read_params(&mut open(path)).unwrap_or_else(|err| panic!("read {}: {}", path, err));
+            let (params, contribution) = contribute(&params, OsRng);
+            write_params(&params, &mut create(next_path))
+                .unwrap_or_else(|err| panic!("write {}: {}", next_path, err));
+            let mut transcript = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(transcript_path)
+                .unwrap_or_else(|err| panic!("open {}: {}", transcript_path, err));
+            contribution
+                .write(&mut transcript)
+                .unwrap_or_else(|err| panic!("write {}: {}", transcript_path, err));
+            println!("contribution: {:?}", contribution);
+        }
+        ["verify", path, transcript_path] => {
+            let params =
+                read_params(&mut open(path)).unwrap_or_else(|err| panic!("read {}: {}", path, err));
+            let contributions = read_transcript(&mut open(transcript_path))
+                .unwrap_or_else(|err| panic!("read {}: {}", transcript_path, err));
+            match verify_transcript(&params, &contributions, OsRng) {
+                Ok(()) => println!("{} contributions verified", contributions.len()),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
+            }
+        }
+        _ => usage(args[0]),
+    }
+}
@@ -0,0 +1,87 @@
+//! Verify again all the `ProofBundle`s of an archive, for example after the fix of a bug of the
+//! verifier or the corruption of the storage of the archive.
+//!
+//! Usage: `reverify-bundles <archive dir> [<vks dir>]`
+//!
+//! Every `.json` file under the archive directory is read as a bundle.  Given a directory of the
+//! verifying keys of the deployed verifiers, in `SerdeFormat::RawBytes`, a bundle must also have
+//! one of these verifying keys, so that a bundle with a replaced verifying key is reported too.
+use ethers_core::utils::keccak256;
+use std::{
+    collections::HashSet,
+    env,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    process,
+};
+use zkevm_circuits::root_circuit::ProofBundle;
+
+/// Sorted paths of the `.json` files under `dir`.
+fn bundle_paths(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).unwrap_or_else(|err| panic!("read {}: {}", dir.display(), err)) {
+        let path = entry
+            .unwrap_or_else(|err| panic!("read {}: {}", dir.display(), err))
+            .path();
+        if path.is_dir() {
+            paths.extend(bundle_paths(&path));
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+}
+
+/// Hashes of the verifying keys in `dir`, like `ProofBundle::vk_hash`.
+fn vk_hashes(dir: &Path) -> HashSet<String> {
+    fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("read {}: {}", dir.display(), err))
+        .map(|entry| {
+            let path = entry
+                .unwrap_or_else(|err| panic!("read {}: {}", dir.display(), err))
+                .path();
+            let vk =
+                fs::read(&path).unwrap_or_else(|err| panic!("read {}: {}", path.display(), err));
+            hex::encode(keccak256(vk))
+        })
+        .collect()
+}
+
+fn reverify(path: &Path, vk_hashes: Option<&HashSet<String>>) -> Result<(), String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let bundle = ProofBundle::read(BufReader::new(file)).map_err(|err| err.to_string())?;
+    if let Some(vk_hashes) = vk_hashes {
+        let vk_hash = bundle.vk_hash();
+        if !vk_hashes.contains(&vk_hash) {
+            return Err(format!("unknown verifying key {}", vk_hash));
+        }
+    }
+    bundle.verify().map_err(|err| err.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 && args.len() != 3 {
+        eprintln!("Usage: {} <archive dir> [<vks dir>]", args[0]);
+        process::exit(2);
+    }
+
+    let vk_hashes = args.get(2).map(|dir| vk_hashes(Path::new(dir)));
+    let paths = bundle_paths(Path::new(&args[1]));
+    let mut failures = 0;
+    for path in paths.iter() {
+        match reverify(path, vk_hashes.as_ref()) {
+            Ok(()) => println!("{}: ok", path.display()),
+            Err(err) => {
+                println!("{}: {}", path.display(), err);
+                failures += 1;
+            }
+        }
+    }
+    println!("{} bundles, {} failures", paths.len(), failures);
+    if failures > 0 {
+        process::exit(1);
+    }
+}
@@ -10,13 +10,22 @@ use helpers::{bytecode_prefix_op_big_rws, print_circuit_stats_by_states};
 use itertools::Itertools;
 use mock::MOCK_ACCOUNTS;
 use std::env;
-use zkevm_circuits::evm_circuit::{
-    param::{
-        LOOKUP_CONFIG, N_COPY_COLUMNS, N_PHASE1_COLUMNS, N_PHASE2_COLUMNS, N_U16_LOOKUPS,
-        N_U8_LOOKUPS,
+use zkevm_circuits::{
+    bytecode_circuit::{BytecodeCircuit, MAX_CODE_SIZE},
+    evm_circuit::{
+        param::{
+            LOOKUP_CONFIG, N_COPY_COLUMNS, N_PHASE1_COLUMNS, N_PHASE2_COLUMNS, N_U16_LOOKUPS,
+            N_U8_LOOKUPS,
+        },
+        step::ExecutionState,
+        EvmCircuit,
+    },
+    keccak_circuit::{
+        keccak_packed_multi::get_num_keccak_f, KeccakCircuit, KeccakCircuitConfig,
+        KeccakCircuitConfigArgs,
     },
-    step::ExecutionState,
-    EvmCircuit,
+    table::KeccakTable,
+    util::{Challenges, SubCircuit, SubCircuitConfig},
 };
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -26,6 +35,8 @@ fn main() {
         "state" => state_states_stats(),
         "copy" => copy_states_stats(),
         "exec" => get_exec_steps_occupancy(),
+        "keccak" => keccak_capacity_stats(),
+        "bytecode" => bytecode_capacity_stats(),
         &_ => unreachable!("Unsupported arg"),
     }
 }
@@ -33,12 +44,7 @@ fn main() {
 /// Prints the stats of EVM circuit per execution state.
 fn evm_states_stats() {
     print_circuit_stats_by_states(
-        |state| {
-            !matches!(
-                state,
-                ExecutionState::ErrorInvalidOpcode | ExecutionState::SELFDESTRUCT
-            )
-        },
+        |state| !matches!(state, ExecutionState::ErrorInvalidOpcode),
         |opcode| match opcode {
             OpcodeId::RETURNDATACOPY => {
                 bytecode! {
@@ -67,12 +73,7 @@ fn evm_states_stats() {
 /// Prints the stats of State circuit per execution state.
 fn state_states_stats() {
     print_circuit_stats_by_states(
-        |state| {
-            !matches!(
-                state,
-                ExecutionState::ErrorInvalidOpcode | ExecutionState::SELFDESTRUCT
-            )
-        },
+        |state| !matches!(state, ExecutionState::ErrorInvalidOpcode),
         bytecode_prefix_op_big_rws,
         |block, _, step_index| {
             let step = &block.txs[0].steps()[step_index];
@@ -110,6 +111,92 @@ fn copy_states_stats() {
     );
 }
 
+/// Prints the width and the keccak_f capacity at `DEGREE` of the Keccak circuit for each number
+/// of rows per round that can be set with `KECCAK_ROWS`.
+fn keccak_capacity_stats() {
+    let degree: u32 = env::var("DEGREE")
+        .unwrap_or_else(|_| "19".to_string())
+        .parse()
+        .expect("Cannot parse DEGREE env var as u32");
+    let keccak_rows = env::var("KECCAK_ROWS");
+
+    let rows = (9..=32)
+        .map(|rows_per_round| {
+            env::set_var("KECCAK_ROWS", format!("{rows_per_round}"));
+            let mut meta = ConstraintSystem::<Fr>::default();
+            let keccak_table = KeccakTable::construct(&mut meta);
+            let challenges = Challenges::construct(&mut meta).exprs(&mut meta);
+            KeccakCircuitConfig::new(
+                &mut meta,
+                KeccakCircuitConfigArgs {
+                    keccak_table,
+                    challenges,
+                },
+            );
+            vec![
+                format!("{}", rows_per_round),
+                format!("{}", meta.num_advice_columns()),
+                format!("{}", meta.num_fixed_columns()),
+                format!("{}", KeccakCircuit::<Fr>::unusable_rows()),
+                format!("{}", KeccakCircuit::<Fr>::capacity_for_degree(degree)),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    match keccak_rows {
+        Ok(keccak_rows) => env::set_var("KECCAK_ROWS", keccak_rows),
+        Err(_) => env::remove_var("KECCAK_ROWS"),
+    }
+
+    let table = rows.table().title(vec![
+        "KECCAK_ROWS".cell().bold(true),
+        "advice columns".cell().bold(true),
+        "fixed columns".cell().bold(true),
+        "unusable rows".cell().bold(true),
+        format!("keccak_f capacity (DEGREE = {})", degree)
+            .cell()
+            .bold(true),
+    ]);
+    print_stdout(table).unwrap();
+}
+
+/// Prints the number of contracts of each code size that fit in a Bytecode circuit of `DEGREE`,
+/// and the keccak_f's that hashing their codes takes in a Keccak circuit of the same degree.
+fn bytecode_capacity_stats() {
+    let degree: u32 = env::var("DEGREE")
+        .unwrap_or_else(|_| "19".to_string())
+        .parse()
+        .expect("Cannot parse DEGREE env var as u32");
+    let keccak_capacity = KeccakCircuit::<Fr>::capacity_for_degree(degree);
+
+    let rows = [32, 1024, 4096, 8192, 16384, MAX_CODE_SIZE]
+        .into_iter()
+        .map(|code_len| {
+            let bytecodes = BytecodeCircuit::<Fr>::bytecodes_for_degree(degree, code_len);
+            vec![
+                format!("{}", code_len),
+                format!("{}", bytecodes),
+                format!("{}", bytecodes * code_len),
+                format!("{}", bytecodes * get_num_keccak_f(code_len)),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+
+    let table = rows.table().title(vec![
+        "code size".cell().bold(true),
+        "contracts".cell().bold(true),
+        "bytes".cell().bold(true),
+        format!("keccak_f (capacity = {})", keccak_capacity)
+            .cell()
+            .bold(true),
+    ]);
+    println!(
+        "Bytecode circuit capacity (DEGREE = {}): {} rows",
+        degree,
+        BytecodeCircuit::<Fr>::capacity_for_degree(degree)
+    );
+    print_stdout(table).unwrap();
+}
+
 /// This function prints to stdout a table with the top X ExecutionState
 /// cell consumers of each EVM Cell type.
 fn get_exec_steps_occupancy() {
@@ -201,6 +288,10 @@ fn get_exec_steps_occupancy() {
         keccak_table,
         LOOKUP_CONFIG[6].1,
         exp_table,
-        LOOKUP_CONFIG[7].1
+        LOOKUP_CONFIG[7].1,
+        sig_table,
+        LOOKUP_CONFIG[8].1,
+        wd_credit_table,
+        LOOKUP_CONFIG[9].1
     );
 }
@@ -0,0 +1,26 @@
+//! Verify a `RootCircuit` proof bundled by `ProofBundle::new` with its verifier params and
+//! verifying key, without any other setup.
+//!
+//! Usage: `verify-bundle <bundle.json>`
+use std::{env, fs::File, io::BufReader, process};
+use zkevm_circuits::root_circuit::ProofBundle;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: {} <bundle.json>", args[0]);
+        process::exit(2);
+    }
+
+    let path = &args[1];
+    let file = File::open(path).unwrap_or_else(|err| panic!("open {}: {}", path, err));
+    let bundle = ProofBundle::read(BufReader::new(file))
+        .unwrap_or_else(|err| panic!("read {}: {}", path, err));
+    match bundle.verify() {
+        Ok(()) => println!("proof verified"),
+        Err(err) => {
+            println!("{}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Compare two witness dumps produced by `WitnessDump::from_block`, for example by two versions of
+//! bus-mapping running on the same trace.
+//!
+//! Usage: `witness_diff <left.json> <right.json>`
+use std::{env, fs::File, io::BufReader, process};
+use zkevm_circuits::witness::WitnessDump;
+
+fn read_dump(path: &str) -> WitnessDump {
+    let file = File::open(path).unwrap_or_else(|err| panic!("open {}: {}", path, err));
+    WitnessDump::read(BufReader::new(file)).unwrap_or_else(|err| panic!("read {}: {}", path, err))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} <left.json> <right.json>", args[0]);
+        process::exit(2);
+    }
+
+    let left = read_dump(&args[1]);
+    let right = read_dump(&args[2]);
+    let diffs = left.diff(&right);
+    for diff in diffs.iter() {
+        println!("{}", diff);
+    }
+    if !diffs.is_empty() {
+        println!("{} differences found", diffs.len());
+        process::exit(1);
+    }
+}
@@ -37,6 +37,9 @@ use std::{iter, ops::Deref, vec};
 
 const PUSH_TABLE_WIDTH: usize = 2;
 
+/// Maximum size of the code of a deployed contract, in bytes (EIP-170)
+pub const MAX_CODE_SIZE: usize = 0x6000;
+
 #[derive(Debug, Clone, Default)]
 /// Row for assignment
 pub(crate) struct BytecodeCircuitRow<F: Field> {
@@ -789,6 +792,20 @@ impl<F: Field> BytecodeCircuit<F> {
             max_rows,
         }
     }
+
+    /// The number of rows, a header row per bytecode and a row per byte, that can be assigned in
+    /// a circuit of `2^k` rows
+    pub fn capacity_for_degree(k: u32) -> usize {
+        // The last row is reserved for padding, as in `assign_internal`
+        (1usize << k).saturating_sub(Self::unusable_rows() + 1)
+    }
+
+    /// The number of bytecodes of `code_len` bytes that fit in a circuit of `2^k` rows.  A bytecode
+    /// of any length up to the capacity fits: its hash is looked up in the KeccakCircuit, which
+    /// absorbs it in chunks of 136 bytes.
+    pub fn bytecodes_for_degree(k: u32, code_len: usize) -> usize {
+        Self::capacity_for_degree(k) / (code_len + 1)
+    }
 }
 
 impl<F: Field> SubCircuit<F> for BytecodeCircuit<F> {
@@ -1,4 +1,4 @@
-use super::{BytecodeCircuit, BytecodeCircuitRow};
+use super::{BytecodeCircuit, BytecodeCircuitRow, MAX_CODE_SIZE};
 use crate::util::{log2_ceil, unusable_rows, SubCircuit};
 use bus_mapping::{evm::OpcodeId, state_db::CodeDB};
 use eth_types::Field;
@@ -59,6 +59,26 @@ fn bytecode_full() {
     BytecodeCircuit::<Fr>::from_bytes(vec![vec![7u8; 2usize.pow(k) - 8]], k).verify(true);
 }
 
+/// Tests a circuit filled up to its capacity with contracts of the maximum size
+#[test]
+fn bytecode_capacity_for_degree() {
+    let k = 15;
+    let capacity = BytecodeCircuit::<Fr>::capacity_for_degree(k);
+    assert_eq!(capacity, 2usize.pow(k) - 7);
+    let num_bytecodes = BytecodeCircuit::<Fr>::bytecodes_for_degree(k, MAX_CODE_SIZE);
+    assert_eq!(num_bytecodes, 1);
+
+    let mut bytecodes = (0..num_bytecodes)
+        .map(|i| vec![i as u8; MAX_CODE_SIZE])
+        .collect::<Vec<_>>();
+    // Fill the rows left with a last bytecode
+    bytecodes.push(vec![
+        7u8;
+        capacity - num_bytecodes * (MAX_CODE_SIZE + 1) - 1
+    ]);
+    BytecodeCircuit::<Fr>::from_bytes(bytecodes, k).verify(true);
+}
+
 #[test]
 fn bytecode_last_row_with_byte() {
     let k = 9;
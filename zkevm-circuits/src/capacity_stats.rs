@@ -0,0 +1,317 @@
+//! Opt-in statistics of the capacity used by the proven blocks, to tune the capacities of the
+//! circuits with real data.
+//!
+//! When the `CAPACITY_STATS_REPORT` environment variable is set to a path, each block built by
+//! `SuperCircuit::build_from_circuit_input_builder` is added to the [`CapacityReport`] stored at
+//! this path.  The report is anonymous: it only counts the rows used by each sub-circuit and the
+//! executed opcodes, with nothing identifying the blocks, and it never leaves the machine.
+//!
+//! [`CapacityReport::plan`] sizes the circuits for the blocks of a report with other capacities,
+//! without generating any key, which the `capacity_planner` binary does interactively.
+
+use crate::{
+    bytecode_circuit::BytecodeCircuit,
+    copy_circuit::CopyCircuit,
+    evm_circuit::EvmCircuit,
+    exp_circuit::ExpCircuit,
+    keccak_circuit::KeccakCircuit,
+    pi_circuit::PiCircuit,
+    state_circuit::StateCircuit,
+    tx_circuit::TxCircuit,
+    util::{log2_ceil, SubCircuit},
+    withdrawal_circuit::WithdrawalCircuit,
+    witness::Block,
+};
+use eth_types::Field;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+/// Environment variable with the path of the report.
+pub const CAPACITY_STATS_REPORT_VAR: &str = "CAPACITY_STATS_REPORT";
+
+/// Rows used by the blocks in a sub-circuit, without padding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowStats {
+    /// Most rows used by a block
+    pub max: usize,
+    /// Rows used by all the blocks
+    pub total: u64,
+}
+
+/// Capacity statistics of a set of blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapacityReport {
+    /// Number of blocks
+    pub blocks: u64,
+    /// Rows used in each sub-circuit, by name
+    pub rows: BTreeMap<String, RowStats>,
+    /// Number of executions of each opcode
+    pub opcodes: BTreeMap<String, u64>,
+}
+
+impl CapacityReport {
+    /// Add the block to the statistics.
+    pub fn record<F: Field>(&mut self, block: &Block<F>) {
+        self.blocks += 1;
+        for (name, (rows, _)) in [
+            ("EVM", EvmCircuit::<F>::min_num_rows_block(block)),
+            ("State", StateCircuit::<F>::min_num_rows_block(block)),
+            ("Tx", TxCircuit::<F>::min_num_rows_block(block)),
+            ("Bytecode", BytecodeCircuit::<F>::min_num_rows_block(block)),
+            ("Copy", CopyCircuit::<F>::min_num_rows_block(block)),
+            ("Keccak", KeccakCircuit::<F>::min_num_rows_block(block)),
+            ("Exp", ExpCircuit::<F>::min_num_rows_block(block)),
+            ("Pi", PiCircuit::<F>::min_num_rows_block(block)),
+            (
+                "Withdrawal",
+                WithdrawalCircuit::<F>::min_num_rows_block(block),
+            ),
+        ] {
+            let stats = self.rows.entry(name.to_string()).or_default();
+            stats.max = stats.max.max(rows);
+            stats.total += rows as u64;
+        }
+        for step in block.txs.iter().flat_map(|tx| tx.steps()) {
+            if let Some(opcode) = step.opcode() {
+                *self.opcodes.entry(opcode.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Size the circuits for the blocks of the report with the `capacities`.  The circuits sized
+    /// by a capacity use the rows of the capacity, the others the most rows used by a block.
+    pub fn plan<F: Field>(&self, capacities: &Capacities) -> CapacityPlan {
+        let mut rows = self
+            .rows
+            .iter()
+            .map(|(name, stats)| (name.clone(), stats.max))
+            .collect::<BTreeMap<_, _>>();
+        rows.insert(
+            "Tx".to_string(),
+            TxCircuit::<F>::min_num_rows(
+                capacities.max_txs,
+                capacities.max_calldata,
+                capacities.max_ecrecover,
+            ),
+        );
+        rows.insert("State".to_string(), capacities.max_rws);
+        // The Keccak circuit sizes itself with a 0 capacity
+        if capacities.max_keccak_rows > 0 {
+            rows.insert("Keccak".to_string(), capacities.max_keccak_rows);
+        }
+
+        let overflows = self
+            .rows
+            .iter()
+            .filter(|(name, stats)| stats.max > rows[*name])
+            .map(|(name, _)| name.clone())
+            .collect();
+        let rows_needed = rows.values().copied().max().unwrap_or_default();
+        let k = log2_ceil(unusable_rows::<F>() + rows_needed);
+        CapacityPlan { rows, k, overflows }
+    }
+
+    /// Write the report as JSON.
+    pub fn write<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    /// Read a report from JSON.
+    pub fn read<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Add the block to the report stored at `path`, creating it if it doesn't exist.
+    pub fn record_to_file<F: Field>(path: &Path, block: &Block<F>) -> io::Result<()> {
+        let mut report = if path.exists() {
+            Self::read(fs::File::open(path)?)?
+        } else {
+            Self::default()
+        };
+        report.record(block);
+        // Write then rename, so that a crash doesn't leave a truncated report
+        let tmp = path.with_extension("tmp");
+        report.write(fs::File::create(&tmp)?)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// Most rows that any sub-circuit can't use, like in `SuperCircuit::unusable_rows`.
+fn unusable_rows<F: Field>() -> usize {
+    [
+        EvmCircuit::<F>::unusable_rows(),
+        StateCircuit::<F>::unusable_rows(),
+        TxCircuit::<F>::unusable_rows(),
+        BytecodeCircuit::<F>::unusable_rows(),
+        CopyCircuit::<F>::unusable_rows(),
+        KeccakCircuit::<F>::unusable_rows(),
+        ExpCircuit::<F>::unusable_rows(),
+        PiCircuit::<F>::unusable_rows(),
+        WithdrawalCircuit::<F>::unusable_rows(),
+    ]
+    .into_iter()
+    .max()
+    .unwrap()
+}
+
+/// Capacities of the circuits that [`CapacityReport::plan`] sizes the circuits with, named like
+/// in `FixedCParams`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capacities {
+    /// Maximum number of txs in the Tx circuit
+    pub max_txs: usize,
+    /// Maximum number of bytes from all txs calldata in the Tx circuit
+    pub max_calldata: usize,
+    /// Maximum number of signatures verified for the ecRecover calls in the Tx circuit
+    pub max_ecrecover: usize,
+    /// Rows of the Keccak circuit, or 0 to size it for the blocks
+    pub max_keccak_rows: usize,
+    /// Rows of the State circuit
+    pub max_rws: usize,
+}
+
+impl Default for Capacities {
+    /// Capacities of `FixedCParams::default`
+    fn default() -> Self {
+        Self {
+            max_txs: 1,
+            max_calldata: 256,
+            max_ecrecover: 0,
+            max_keccak_rows: 0,
+            max_rws: 1000,
+        }
+    }
+}
+
+/// Sizes of the circuits computed by [`CapacityReport::plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityPlan {
+    /// Rows of each sub-circuit, by name
+    pub rows: BTreeMap<String, usize>,
+    /// Degree of the `SuperCircuit`
+    pub k: u32,
+    /// Sub-circuits with fewer rows than used by a block of the report, which can't prove it
+    pub overflows: Vec<String>,
+}
+
+/// Proving time measured at a degree, to estimate the proving time at other degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProveTimeCalibration {
+    /// Degree of the measured proof
+    pub k: u32,
+    /// Proving time of the measured proof, in seconds
+    pub secs: f64,
+}
+
+impl ProveTimeCalibration {
+    /// Estimate the proving time at degree `k`, in seconds.  The FFTs and MSMs of the prover take
+    /// O(n log n) for n = 2^k rows, so the time doubles a bit more than once per degree.
+    pub fn estimate(&self, k: u32) -> f64 {
+        self.secs * 2f64.powi(k as i32 - self.k as i32) * k as f64 / self.k as f64
+    }
+}
+
+/// Add the block to the report at the path of [`CAPACITY_STATS_REPORT_VAR`], if it is set.
+pub fn record_from_env<F: Field>(block: &Block<F>) {
+    let Ok(path) = env::var(CAPACITY_STATS_REPORT_VAR) else {
+        return;
+    };
+    if let Err(err) = CapacityReport::record_to_file(Path::new(&path), block) {
+        log::warn!(
+            "recording the capacity statistics to {} failed: {}",
+            path,
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::bytecode;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    #[test]
+    fn capacity_report_record() {
+        let block = CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+                PUSH1(0x02)
+                PUSH1(0x03)
+                EXP
+                STOP
+            })
+            .unwrap(),
+        )
+        .build_block()
+        .unwrap();
+
+        let path = env::temp_dir().join(format!("capacity_stats_{}.json", std::process::id()));
+        CapacityReport::record_to_file(&path, &block).unwrap();
+        CapacityReport::record_to_file(&path, &block).unwrap();
+        let report = CapacityReport::read(fs::File::open(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(report.blocks, 2);
+        assert_eq!(report.opcodes["PUSH1"], 4);
+        assert_eq!(report.opcodes["EXP"], 2);
+        let (exp_rows, _) = ExpCircuit::<_>::min_num_rows_block(&block);
+        assert_eq!(
+            report.rows["Exp"],
+            RowStats {
+                max: exp_rows,
+                total: 2 * exp_rows as u64,
+            }
+        );
+    }
+
+    #[test]
+    fn capacity_report_plan() {
+        let report = CapacityReport {
+            blocks: 2,
+            rows: [
+                ("EVM", 3000),
+                ("State", 1200),
+                ("Tx", 100),
+                ("Keccak", 5000),
+            ]
+            .into_iter()
+            .map(|(name, max)| {
+                let stats = RowStats {
+                    max,
+                    total: 2 * max as u64,
+                };
+                (name.to_string(), stats)
+            })
+            .collect(),
+            opcodes: BTreeMap::new(),
+        };
+
+        let plan = report.plan::<Fr>(&Capacities::default());
+        assert_eq!(plan.rows["EVM"], 3000);
+        assert_eq!(plan.rows["State"], 1000);
+        assert_eq!(plan.rows["Keccak"], 5000);
+        assert_eq!(plan.rows["Tx"], TxCircuit::<Fr>::min_num_rows(1, 256, 0));
+        assert_eq!(plan.overflows, vec!["State".to_string()]);
+        let rows_needed = plan.rows.values().copied().max().unwrap();
+        assert_eq!(plan.k, log2_ceil(unusable_rows::<Fr>() + rows_needed));
+
+        let plan = report.plan::<Fr>(&Capacities {
+            max_keccak_rows: 4000,
+            max_rws: 2000,
+            ..Default::default()
+        });
+        assert_eq!(plan.rows["State"], 2000);
+        assert_eq!(plan.overflows, vec!["Keccak".to_string()]);
+
+        let calibration = ProveTimeCalibration { k: 20, secs: 100.0 };
+        assert_eq!(calibration.estimate(20), 100.0);
+        assert_eq!(calibration.estimate(21), 210.0);
+    }
+}
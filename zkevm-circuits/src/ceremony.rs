@@ -0,0 +1,333 @@
+//! Powers of tau ceremony to generate the KZG params of the circuits without a trusted party.
+//!
+//! The params are the powers `[s^i]G1` and `[s]G2` of a toxic waste `s` that nobody may know.
+//! The ceremony starts from the params of `s = 1`, and each contributor multiplies `s` by a random
+//! `tau` that they throw away: the final `s` is unknown as long as one contributor was honest.
+//!
+//! A [`Contribution`] is the proof of a step: the new `[s]G1` and `[tau]G2`, checked with a
+//! pairing against the previous `[s]G1`, and a proof of knowledge of `tau`: `[tau]H` for a point
+//! `H` hashed from the step, so that a contributor can't pick their `[s]G1` from the previous
+//! contributions without knowing their `tau`.  [`verify_transcript`] checks the chain of the
+//! contributions from the start of the ceremony to the final params, and that the final params
+//! are powers of the same `s`, with only the final params and the contributions at hand.
+//!
+//! The params are written with `SerdeFormat::RawBytes`, the format the provers read them in.
+
+use halo2_proofs::{
+    arithmetic::{best_multiexp, parallelize},
+    halo2curves::{
+        bn256::{pairing, Bn256, Fr, G1Affine, G2Affine, G1},
+        ff::Field,
+        group::{prime::PrimeCurveAffine, Curve, GroupEncoding},
+        CurveExt,
+    },
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
+    SerdeFormat,
+};
+use rand::RngCore;
+use std::io::{self, Read, Write};
+use thiserror::Error;
+
+/// Error verifying a ceremony.
+#[derive(Debug, Error)]
+pub enum CeremonyError {
+    /// The params aren't powers of a toxic waste.
+    #[error("malformed params: {0}")]
+    Malformed(&'static str),
+    /// A contribution doesn't extend the previous one, by index in the transcript.
+    #[error("contribution {0} doesn't extend the previous one")]
+    InvalidContribution(usize),
+    /// The proof of knowledge of `tau` of a contribution is invalid, by index in the transcript.
+    #[error("contribution {0} doesn't prove the knowledge of its tau")]
+    InvalidProofOfKnowledge(usize),
+    /// The params aren't the result of the last contribution.
+    #[error("the params are not the result of the contributions")]
+    UnmatchedParams,
+    /// Error reading or writing the params or the transcript.
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Proof of a contribution to the ceremony.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contribution {
+    /// `[s * tau]G1` of the params after the contribution, with `s` of the previous params
+    pub s_g1: G1Affine,
+    /// `[tau]G2`
+    pub tau_g2: G2Affine,
+    /// Proof of knowledge of `tau`: `[tau]H`, with `H` the [`pok_base`] of the contribution
+    pub tau_pok: G1Affine,
+}
+
+impl Contribution {
+    /// Size of a serialized contribution: its compressed points.
+    pub const BYTES: usize = 32 + 64 + 32;
+
+    /// Write the contribution, with its compressed points.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.s_g1.to_bytes().as_ref())?;
+        writer.write_all(self.tau_g2.to_bytes().as_ref())?;
+        writer.write_all(self.tau_pok.to_bytes().as_ref())
+    }
+
+    /// Read a contribution written with [`Contribution::write`].
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid point");
+        let mut s_g1 = <G1Affine as GroupEncoding>::Repr::default();
+        reader.read_exact(s_g1.as_mut())?;
+        let mut tau_g2 = <G2Affine as GroupEncoding>::Repr::default();
+        reader.read_exact(tau_g2.as_mut())?;
+        let mut tau_pok = <G1Affine as GroupEncoding>::Repr::default();
+        reader.read_exact(tau_pok.as_mut())?;
+        Ok(Self {
+            s_g1: Option::from(G1Affine::from_bytes(&s_g1)).ok_or_else(invalid)?,
+            tau_g2: Option::from(G2Affine::from_bytes(&tau_g2)).ok_or_else(invalid)?,
+            tau_pok: Option::from(G1Affine::from_bytes(&tau_pok)).ok_or_else(invalid)?,
+        })
+    }
+}
+
+/// Domain of the hash to G1 of [`pok_base`].
+const POK_DOMAIN: &str = "zkevm-circuits-ceremony";
+
+/// Point `H` the proof of knowledge of `tau` of a contribution is checked against, hashed to G1
+/// from the `[s]G1` of the previous params and the `[s * tau]G1` and `[tau]G2` of the
+/// contribution.  Nobody knows the discrete logarithm of `H`, so that `[tau]H` can only be
+/// computed knowing `tau`, and it can't be replayed in another contribution.
+pub fn pok_base(prev_s_g1: &G1Affine, s_g1: &G1Affine, tau_g2: &G2Affine) -> G1Affine {
+    let mut message = Vec::with_capacity(2 * 32 + 64);
+    message.extend_from_slice(prev_s_g1.to_bytes().as_ref());
+    message.extend_from_slice(s_g1.to_bytes().as_ref());
+    message.extend_from_slice(tau_g2.to_bytes().as_ref());
+    G1::hash_to_curve(POK_DOMAIN)(&message).to_affine()
+}
+
+/// Read the contributions of a transcript, written one after the other with
+/// [`Contribution::write`].
+pub fn read_transcript<R: Read>(reader: &mut R) -> io::Result<Vec<Contribution>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() % Contribution::BYTES != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "truncated contribution",
+        ));
+    }
+    bytes
+        .chunks_exact(Contribution::BYTES)
+        .map(|mut chunk| Contribution::read(&mut chunk))
+        .collect()
+}
+
+/// Write the params in the format they are read in by the provers.
+pub fn write_params<W: Write>(params: &ParamsKZG<Bn256>, writer: &mut W) -> io::Result<()> {
+    params.write_custom(writer, SerdeFormat::RawBytes)
+}
+
+/// Read params written with [`write_params`].
+pub fn read_params<R: Read>(reader: &mut R) -> io::Result<ParamsKZG<Bn256>> {
+    ParamsKZG::read_custom(reader, SerdeFormat::RawBytes)
+}
+
+/// Params of degree `k` the ceremony starts from, of the toxic waste `s = 1`.
+pub fn initial_params(k: u32) -> ParamsKZG<Bn256> {
+    ParamsKZG::unsafe_setup_with_s(k, Fr::ONE)
+}
+
+/// Contribute a random toxic waste `tau` to the params, and return the new params with the
+/// proof of the contribution.  `tau` is dropped when this returns.
+pub fn contribute(
+    params: &ParamsKZG<Bn256>,
+    mut rng: impl RngCore,
+) -> (ParamsKZG<Bn256>, Contribution) {
+    let tau = Fr::random(&mut rng);
+    let mut g = params.get_g().to_vec();
+    parallelize(&mut g, |g, start| {
+        let mut power = tau.pow_vartime([start as u64]);
+        for point in g.iter_mut() {
+            *point = (*point * power).to_affine();
+            power *= tau;
+        }
+    });
+    let s_g2 = (params.s_g2() * tau).to_affine();
+    let tau_g2 = (params.g2() * tau).to_affine();
+    let base = pok_base(&params.get_g()[1], &g[1], &tau_g2);
+    let contribution = Contribution {
+        s_g1: g[1],
+        tau_g2,
+        tau_pok: (base * tau).to_affine(),
+    };
+    let params = params.from_parts(params.k(), g, None, params.g2(), s_g2);
+    (params, contribution)
+}
+
+/// Check that the params are the powers of a toxic waste `s`: `[s^i]G1` for all `i`, with the
+/// lagrange basis of these powers, and `[s]G2`.  The powers are checked together, with a random
+/// linear combination.
+pub fn verify_params(
+    params: &ParamsKZG<Bn256>,
+    mut rng: impl RngCore,
+) -> Result<(), CeremonyError> {
+    let g = params.get_g();
+    if g.len() < 2 {
+        return Err(CeremonyError::Malformed("a single power"));
+    }
+    if g[0] != G1Affine::generator() || params.g2() != G2Affine::generator() {
+        return Err(CeremonyError::Malformed("not the generators"));
+    }
+    if bool::from(g[1].is_identity()) {
+        return Err(CeremonyError::Malformed("zero toxic waste"));
+    }
+    if pairing(&g[1], &params.g2()) != pairing(&g[0], &params.s_g2()) {
+        return Err(CeremonyError::Malformed("s_g2 is not [s]G2"));
+    }
+
+    // sum(r^i [s^(i+1)]G1) = s sum(r^i [s^i]G1) for a random r
+    let r = Fr::random(&mut rng);
+    let coeffs = std::iter::successors(Some(Fr::ONE), |power| Some(*power * r))
+        .take(g.len() - 1)
+        .collect::<Vec<_>>();
+    let lhs = best_multiexp(&coeffs, &g[1..]).to_affine();
+    let rhs = best_multiexp(&coeffs, &g[..g.len() - 1]).to_affine();
+    if pairing(&lhs, &params.g2()) != pairing(&rhs, &params.s_g2()) {
+        return Err(CeremonyError::Malformed("not the powers of s"));
+    }
+
+    // The lagrange basis isn't part of the proof: check that it is the one of the powers
+    let expected = params.from_parts(params.k(), g.to_vec(), None, params.g2(), params.s_g2());
+    let (mut bytes, mut expected_bytes) = (Vec::new(), Vec::new());
+    write_params(params, &mut bytes)?;
+    write_params(&expected, &mut expected_bytes)?;
+    if bytes != expected_bytes {
+        return Err(CeremonyError::Malformed(
+            "not the lagrange basis of the powers",
+        ));
+    }
+    Ok(())
+}
+
+/// Verify that `params` are the result of the `contributions` of a ceremony started from
+/// [`initial_params`], in order.
+pub fn verify_transcript(
+    params: &ParamsKZG<Bn256>,
+    contributions: &[Contribution],
+    rng: impl RngCore,
+) -> Result<(), CeremonyError> {
+    verify_params(params, rng)?;
+    let g2 = G2Affine::generator();
+    let mut s_g1 = G1Affine::generator();
+    for (index, contribution) in contributions.iter().enumerate() {
+        // e([s tau]G1, G2) = e([s]G1, [tau]G2)
+        if bool::from(contribution.tau_g2.is_identity())
+            || pairing(&contribution.s_g1, &g2) != pairing(&s_g1, &contribution.tau_g2)
+        {
+            return Err(CeremonyError::InvalidContribution(index));
+        }
+        // e([tau]H, G2) = e(H, [tau]G2)
+        let base = pok_base(&s_g1, &contribution.s_g1, &contribution.tau_g2);
+        if pairing(&contribution.tau_pok, &g2) != pairing(&base, &contribution.tau_g2) {
+            return Err(CeremonyError::InvalidProofOfKnowledge(index));
+        }
+        s_g1 = contribution.s_g1;
+    }
+    if params.get_g()[1] != s_g1 {
+        return Err(CeremonyError::UnmatchedParams);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::arithmetic::g_to_lagrange;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const K: u32 = 4;
+
+    #[test]
+    fn ceremony_contributions() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let initial = initial_params(K);
+        verify_transcript(&initial, &[], &mut rng).unwrap();
+
+        let (params, first) = contribute(&initial, &mut rng);
+        let (params, second) = contribute(&params, &mut rng);
+        verify_transcript(&params, &[first, second], &mut rng).unwrap();
+
+        // The params and the transcript round trip
+        let mut bytes = Vec::new();
+        write_params(&params, &mut bytes).unwrap();
+        let read = read_params(&mut bytes.as_slice()).unwrap();
+        let mut transcript = Vec::new();
+        first.write(&mut transcript).unwrap();
+        second.write(&mut transcript).unwrap();
+        let contributions = read_transcript(&mut transcript.as_slice()).unwrap();
+        assert_eq!(contributions, vec![first, second]);
+        verify_transcript(&read, &contributions, &mut rng).unwrap();
+
+        assert!(matches!(
+            verify_transcript(&params, &[second, first], &mut rng),
+            Err(CeremonyError::InvalidContribution(0))
+        ));
+        assert!(matches!(
+            verify_transcript(&params, &[first], &mut rng),
+            Err(CeremonyError::UnmatchedParams)
+        ));
+        // A proof of knowledge of another contribution
+        let forged = Contribution {
+            tau_pok: first.tau_pok,
+            ..second
+        };
+        assert!(matches!(
+            verify_transcript(&params, &[first, forged], &mut rng),
+            Err(CeremonyError::InvalidProofOfKnowledge(1))
+        ));
+        // Params from a known toxic waste aren't the result of the contributions
+        let setup = ParamsKZG::<Bn256>::setup(K, &mut rng);
+        assert!(matches!(
+            verify_transcript(&setup, &[first, second], &mut rng),
+            Err(CeremonyError::UnmatchedParams)
+        ));
+    }
+
+    #[test]
+    fn ceremony_malformed_params() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let (params, contribution) = contribute(&initial_params(K), &mut rng);
+
+        // A power of another toxic waste
+        let mut g = params.get_g().to_vec();
+        g[3] = (g[3] * Fr::from(2)).to_affine();
+        let tampered = params.from_parts(K, g, None, params.g2(), params.s_g2());
+        assert!(matches!(
+            verify_transcript(&tampered, &[contribution], &mut rng),
+            Err(CeremonyError::Malformed(_))
+        ));
+
+        // The lagrange basis of another toxic waste
+        let other = ParamsKZG::<Bn256>::setup(K, &mut rng);
+        let g_lagrange = g_to_lagrange(
+            other
+                .get_g()
+                .iter()
+                .map(PrimeCurveAffine::to_curve)
+                .collect::<Vec<G1>>(),
+            K,
+        );
+        let tampered = params.from_parts(
+            K,
+            params.get_g().to_vec(),
+            Some(g_lagrange),
+            params.g2(),
+            params.s_g2(),
+        );
+        assert!(matches!(
+            verify_transcript(&tampered, &[contribution], &mut rng),
+            Err(CeremonyError::Malformed(_))
+        ));
+    }
+}
@@ -1,7 +1,6 @@
 //! The Copy circuit implements constraints and lookups for read-write steps for
 //! copied bytes while execution opcodes such as CALLDATACOPY, CODECOPY, LOGS,
 //! etc.
-pub(crate) mod util;
 
 #[cfg(any(test, feature = "test-circuits"))]
 mod dev;
@@ -14,7 +14,8 @@ use halo2_proofs::{
     halo2curves::bn256::Fr,
 };
 use mock::{
-    test_ctx::helpers::account_0_code_account_1_no_code, Sha3CodeGen, TestContext, MOCK_ACCOUNTS,
+    generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+    MockCallBytecodeParams, Sha3CodeGen, TestContext, MOCK_ACCOUNTS,
 };
 
 #[test]
@@ -88,10 +89,10 @@ fn gen_calldatacopy_data() -> CircuitInputBuilder<FixedCParams> {
         .unwrap()
 }
 
-fn gen_codecopy_data() -> CircuitInputBuilder<FixedCParams> {
+fn gen_codecopy_data(code_offset: usize, length: usize) -> CircuitInputBuilder<FixedCParams> {
     let code = bytecode! {
-        PUSH32(Word::from(0x20))
-        PUSH32(Word::from(0x00))
+        PUSH32(Word::from(length))
+        PUSH32(Word::from(code_offset))
         PUSH32(Word::from(0x00))
         CODECOPY
         STOP
@@ -139,6 +140,49 @@ fn gen_extcodecopy_data() -> CircuitInputBuilder<FixedCParams> {
         .unwrap()
 }
 
+fn gen_returndatacopy_data() -> CircuitInputBuilder<FixedCParams> {
+    let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+    let code_b = bytecode! {
+        .op_mstore(0, Word::from_big_endian(&rand_bytes(32)))
+        .op_return(0, 0x20)
+        STOP
+    };
+    // The copy overwrites part of the memory written by the call
+    let instruction = bytecode! {
+        PUSH32(0x18) // size
+        PUSH32(0x08) // offset
+        PUSH32(0x10) // dest_offset
+        RETURNDATACOPY
+    };
+    let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+        address: addr_b,
+        return_data_offset: 0,
+        return_data_size: 0x20,
+        instructions_after_call: instruction,
+        ..MockCallBytecodeParams::default()
+    });
+    let test_ctx = TestContext::<3, 1>::new(
+        None,
+        |accs| {
+            accs[0].address(addr_a).code(code_a);
+            accs[1].address(addr_b).code(code_b);
+            accs[2]
+                .address(MOCK_ACCOUNTS[2])
+                .balance(Word::from(1u64 << 30));
+        },
+        |mut txs, accs| {
+            txs[0].to(accs[0].address).from(accs[2].address);
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap();
+    let block: GethData = test_ctx.into();
+    let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap()
+}
+
 fn gen_sha3_data() -> CircuitInputBuilder<FixedCParams> {
     let (code, _) = Sha3CodeGen::mem_eq_size(0x20, 0x200).gen_sha3_code();
     let test_ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
@@ -181,11 +225,21 @@ fn copy_circuit_valid_calldatacopy() {
 
 #[test]
 fn copy_circuit_valid_codecopy() {
-    let builder = gen_codecopy_data();
+    let builder = gen_codecopy_data(0x00, 0x20);
     let block = block_convert::<Fr>(&builder).unwrap();
     assert_eq!(test_copy_circuit_from_block(10, block), Ok(()));
 }
 
+#[test]
+fn copy_circuit_valid_codecopy_out_of_bound() {
+    // The code is of 0x65 bytes: the copy is partially out of bound, and then fully
+    for code_offset in [0x50, 0x100] {
+        let builder = gen_codecopy_data(code_offset, 0x40);
+        let block = block_convert::<Fr>(&builder).unwrap();
+        assert_eq!(test_copy_circuit_from_block(10, block), Ok(()));
+    }
+}
+
 #[test]
 fn copy_circuit_valid_extcodecopy() {
     let builder = gen_extcodecopy_data();
@@ -193,6 +247,13 @@ fn copy_circuit_valid_extcodecopy() {
     assert_eq!(test_copy_circuit_from_block(14, block), Ok(()));
 }
 
+#[test]
+fn copy_circuit_valid_returndatacopy() {
+    let builder = gen_returndatacopy_data();
+    let block = block_convert::<Fr>(&builder).unwrap();
+    assert_eq!(test_copy_circuit_from_block(10, block), Ok(()));
+}
+
 #[test]
 fn copy_circuit_valid_sha3() {
     let builder = gen_sha3_data();
@@ -225,7 +286,7 @@ fn copy_circuit_invalid_calldatacopy() {
 
 #[test]
 fn copy_circuit_invalid_codecopy() {
-    let mut builder = gen_codecopy_data();
+    let mut builder = gen_codecopy_data(0x00, 0x20);
 
     // modify first byte of first copy event
     builder.block.copy_events[0].bytes[0].0 =
@@ -255,6 +316,22 @@ fn copy_circuit_invalid_extcodecopy() {
     );
 }
 
+#[test]
+fn copy_circuit_invalid_returndatacopy() {
+    let mut builder = gen_returndatacopy_data();
+
+    // modify first byte of the copy event of RETURNDATACOPY, after the one of RETURN
+    let copy_event = builder.block.copy_events.last_mut().unwrap();
+    copy_event.bytes[0].0 = copy_event.bytes[0].0.wrapping_add(1);
+
+    let block = block_convert::<Fr>(&builder).unwrap();
+
+    assert_error_matches(
+        test_copy_circuit_from_block(10, block),
+        vec!["Memory lookup"],
+    );
+}
+
 #[test]
 fn copy_circuit_invalid_sha3() {
     let mut builder = gen_sha3_data();
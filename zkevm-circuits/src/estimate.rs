@@ -0,0 +1,273 @@
+//! Estimate of the proving time and memory of a circuit, without proving.
+//!
+//! The work of the halo2 prover only depends on the degree `k` of the circuit and on the shape of
+//! its constraint system, not on its witness: the MSMs of the commitments are of `2^k` points and
+//! the FFTs of `2^k` or of the extended domain.  [`CircuitShape`] counts the columns, lookups and
+//! permutation of a circuit from its configuration, and [`estimate`] combines them with a
+//! [`ProverCalibration`], the MSM and FFT times of the machine measured once, into an
+//! [`Estimate`] of the proving time and the peak memory.
+//!
+//! The estimate is a lower bound of the proving time: the evaluation of the gates on the extended
+//! domain isn't counted, and is negligible only for circuits with few gates.
+
+use halo2_proofs::{
+    arithmetic::{best_fft, best_multiexp},
+    halo2curves::{
+        bn256::{Bn256, Fr},
+        ff::Field,
+    },
+    plonk::{Circuit, ConstraintSystem},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG, EvaluationDomain},
+};
+use rand::RngCore;
+use std::{fs, hint::black_box, time::Instant};
+
+/// Size of a field element or of a polynomial coefficient, in bytes.
+const SCALAR_BYTES: u64 = 32;
+/// Size of an affine G1 point of the params, in bytes.
+const POINT_BYTES: u64 = 64;
+/// MSMs of the multiopen argument.
+const MULTIOPEN_MSMS: u64 = 2;
+
+/// Shape of the constraint system of a circuit at a degree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitShape {
+    /// Degree of the circuit
+    pub k: u32,
+    /// Degree of the extended domain, on which the quotient is computed
+    pub extended_k: u32,
+    /// Advice columns
+    pub advice_columns: usize,
+    /// Fixed columns, with the selectors before their compression
+    pub fixed_columns: usize,
+    /// Instance columns
+    pub instance_columns: usize,
+    /// Lookup arguments
+    pub lookups: usize,
+    /// Columns of the permutation argument
+    pub permutation_columns: usize,
+    /// Degree of the constraint system
+    pub degree: usize,
+    /// Rows that can be assigned, without the blinding rows
+    pub usable_rows: usize,
+}
+
+impl CircuitShape {
+    /// Shape of the circuit at degree `k`.
+    pub fn of<C: Circuit<Fr>>(k: u32, circuit: &C) -> Self {
+        let mut cs = ConstraintSystem::default();
+        C::configure_with_params(&mut cs, circuit.params());
+        let degree = cs.degree();
+        // Same as `EvaluationDomain::new`
+        let quotient_degree = degree.saturating_sub(1).max(1);
+        let mut extended_k = k;
+        while (1 << extended_k) < (1 << k) * quotient_degree {
+            extended_k += 1;
+        }
+        Self {
+            k,
+            extended_k,
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns() + cs.num_selectors(),
+            instance_columns: cs.num_instance_columns(),
+            lookups: cs.lookups().len(),
+            permutation_columns: cs.permutation().get_columns().len(),
+            degree,
+            usable_rows: (1 << k) - (cs.blinding_factors() + 1),
+        }
+    }
+
+    /// Grand products of the permutation argument, each of `degree - 2` columns.
+    pub fn permutation_products(&self) -> usize {
+        let chunk_len = self.degree.saturating_sub(2).max(1);
+        (self.permutation_columns + chunk_len - 1) / chunk_len
+    }
+
+    /// Polynomials the prover commits to: the advice columns, the permuted columns and the
+    /// product of each lookup, the permutation products, the random polynomial and the pieces of
+    /// the quotient.
+    pub fn committed_polys(&self) -> usize {
+        self.advice_columns
+            + 3 * self.lookups
+            + self.permutation_products()
+            + 1
+            + self.degree.saturating_sub(1)
+    }
+
+    /// Polynomials the prover interpolates and evaluates on the extended domain.
+    pub fn extended_polys(&self) -> usize {
+        self.advice_columns + self.instance_columns + 3 * self.lookups + self.permutation_products()
+    }
+}
+
+/// MSM and FFT times of a machine, measured at a degree, to estimate them at the other degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProverCalibration {
+    /// Degree of the measures
+    pub k: u32,
+    /// Time of an MSM of `2^k` points, in seconds
+    pub msm_secs: f64,
+    /// Time of an FFT of `2^k` scalars, in seconds
+    pub fft_secs: f64,
+}
+
+impl ProverCalibration {
+    /// Measure the MSM and FFT times of this machine at degree `k`.
+    pub fn measure(k: u32, mut rng: impl RngCore) -> Self {
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, Fr::random(&mut rng));
+        let mut scalars = (0..1 << k)
+            .map(|_| Fr::random(&mut rng))
+            .collect::<Vec<_>>();
+
+        let start = Instant::now();
+        black_box(best_multiexp(&scalars, &params.get_g()[..scalars.len()]));
+        let msm_secs = start.elapsed().as_secs_f64();
+
+        let omega = EvaluationDomain::<Fr>::new(1, k).get_omega();
+        let start = Instant::now();
+        best_fft(&mut scalars, omega, k);
+        let fft_secs = start.elapsed().as_secs_f64();
+
+        Self {
+            k,
+            msm_secs,
+            fft_secs,
+        }
+    }
+
+    /// Time of an MSM of `2^k` points, in seconds.  Pippenger's algorithm takes O(n / log n) for
+    /// n points.
+    pub fn msm_secs(&self, k: u32) -> f64 {
+        self.msm_secs * 2f64.powi(k as i32 - self.k as i32) * self.k as f64 / k as f64
+    }
+
+    /// Time of an FFT of `2^k` scalars, in seconds, which takes O(n log n) for n scalars.
+    pub fn fft_secs(&self, k: u32) -> f64 {
+        self.fft_secs * 2f64.powi(k as i32 - self.k as i32) * k as f64 / self.k as f64
+    }
+}
+
+/// Estimated proving time and peak memory of a circuit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// Shape of the circuit
+    pub shape: CircuitShape,
+    /// Time of the MSMs, in seconds
+    pub msm_secs: f64,
+    /// Time of the FFTs, in seconds
+    pub fft_secs: f64,
+    /// Memory of the params, the proving key and the polynomials of the prover, in bytes
+    pub peak_memory_bytes: u64,
+}
+
+impl Estimate {
+    /// Estimated proving time, in seconds.
+    pub fn secs(&self) -> f64 {
+        self.msm_secs + self.fft_secs
+    }
+}
+
+/// Estimate the proving time and peak memory of the circuit at degree `k` with the MSM and FFT
+/// times of the calibration.
+pub fn estimate<C: Circuit<Fr>>(k: u32, circuit: &C, calibration: &ProverCalibration) -> Estimate {
+    let shape = CircuitShape::of(k, circuit);
+    let (n, extended_n) = (1u64 << shape.k, 1u64 << shape.extended_k);
+
+    let msms = shape.committed_polys() as u64 + MULTIOPEN_MSMS;
+    let msm_secs = msms as f64 * calibration.msm_secs(shape.k);
+    // An inverse FFT and a coset FFT on the extended domain for each polynomial, and an inverse
+    // FFT of the quotient on the extended domain
+    let fft_secs = shape.extended_polys() as f64
+        * (calibration.fft_secs(shape.k) + calibration.fft_secs(shape.extended_k))
+        + calibration.fft_secs(shape.extended_k);
+
+    // A column is stored as its values, its coefficients and its values on the extended domain,
+    // the fixed and permutation columns in the proving key, with the lagrange polynomials of the
+    // first, last and active rows.
+    let column_bytes = (2 * n + extended_n) * SCALAR_BYTES;
+    let params_bytes = 2 * n * POINT_BYTES;
+    let pk_bytes = (shape.fixed_columns + shape.permutation_columns) as u64 * column_bytes
+        + 3 * extended_n * SCALAR_BYTES;
+    let prover_bytes = shape.extended_polys() as u64 * column_bytes + extended_n * SCALAR_BYTES;
+
+    Estimate {
+        shape,
+        msm_secs,
+        fft_secs,
+        peak_memory_bytes: params_bytes + pk_bytes + prover_bytes,
+    }
+}
+
+/// Memory field of a `/proc` file, like `/proc/meminfo` or `/proc/self/status`, in kB: for
+/// example `VmRSS`, the resident memory of the process, or `VmHWM`, its peak.  `None` where
+/// `/proc` isn't available, so that the measured memory can be compared with the [`Estimate`]
+/// where it can.
+pub fn proc_memory_kb(path: &str, field: &str) -> Option<u64> {
+    parse_proc_memory_kb(&fs::read_to_string(path).ok()?, field)
+}
+
+/// Parse a memory field, in kB, from the content of a `/proc` file, see [`proc_memory_kb`].
+pub fn parse_proc_memory_kb(content: &str, field: &str) -> Option<u64> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(all(test, feature = "exp-circuit"))]
+mod tests {
+    use super::*;
+    use crate::exp_circuit::ExpCircuit;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn estimate_exp_circuit() {
+        let circuit = ExpCircuit::<Fr>::default();
+        let mut cs = ConstraintSystem::default();
+        ExpCircuit::<Fr>::configure(&mut cs);
+        let shape = CircuitShape::of(10, &circuit);
+        assert_eq!(shape.advice_columns, cs.num_advice_columns());
+        assert_eq!(shape.lookups, cs.lookups().len());
+        assert!(shape.extended_k > shape.k);
+        assert_eq!(shape.usable_rows, 1024 - cs.blinding_factors() - 1);
+
+        let calibration = ProverCalibration {
+            k: 10,
+            msm_secs: 1.0,
+            fft_secs: 0.1,
+        };
+        let estimate_10 = estimate(10, &circuit, &calibration);
+        assert_eq!(
+            estimate_10.msm_secs,
+            (shape.committed_polys() as u64 + MULTIOPEN_MSMS) as f64
+        );
+        // Doubling the rows more than doubles the time of the FFTs, less than doubles the time of
+        // the MSMs, and doubles the memory
+        let estimate_11 = estimate(11, &circuit, &calibration);
+        assert!(estimate_11.fft_secs > 2.0 * estimate_10.fft_secs);
+        assert!(estimate_11.msm_secs < 2.0 * estimate_10.msm_secs);
+        assert_eq!(
+            estimate_11.peak_memory_bytes,
+            2 * estimate_10.peak_memory_bytes
+        );
+
+        let calibration = ProverCalibration::measure(8, ChaCha20Rng::seed_from_u64(2));
+        assert!(calibration.msm_secs > 0.0 && calibration.fft_secs > 0.0);
+    }
+
+    #[test]
+    fn parse_proc_memory() {
+        let status = "Name:\tprover\nVmHWM:\t  204800 kB\nVmRSS:\t  102400 kB\nThreads:\t8\n";
+        assert_eq!(parse_proc_memory_kb(status, "VmRSS"), Some(102400));
+        assert_eq!(parse_proc_memory_kb(status, "VmHWM"), Some(204800));
+        assert_eq!(parse_proc_memory_kb(status, "VmSwap"), None);
+        let meminfo = "MemTotal:       65536000 kB\nMemFree:         1024 kB\n";
+        assert_eq!(parse_proc_memory_kb(meminfo, "MemTotal"), Some(65536000));
+        assert_eq!(parse_proc_memory_kb(meminfo, "Mem"), None);
+    }
+}
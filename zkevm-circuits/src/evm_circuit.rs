@@ -1,10 +1,15 @@
 //! The EVM circuit implementation.
+//!
+//! Without the `evm-circuit` feature only the parameters, the execution states, the fixed table
+//! and the gadget utilities shared with the other circuits are built.
 
+#[cfg(feature = "evm-circuit")]
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::*,
 };
 
+#[cfg(feature = "evm-circuit")]
 mod execution;
 pub mod param;
 pub mod step;
@@ -13,27 +18,34 @@ pub(crate) mod util;
 
 #[cfg(test)]
 pub(crate) mod test;
+#[cfg(feature = "evm-circuit")]
 use self::step::HasExecutionState;
-#[cfg(feature = "test-circuits")]
+#[cfg(all(feature = "evm-circuit", feature = "test-circuits"))]
 pub use self::EvmCircuit as TestEvmCircuit;
 
 pub use crate::witness;
+#[cfg(feature = "evm-circuit")]
 use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
     table::{
-        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LookupTable, RwTable, TxTable,
-        UXTable,
+        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LookupTable, RwTable,
+        SigTable, TxTable, UXTable, WdCreditTable,
     },
     util::{Challenges, SubCircuit, SubCircuitConfig},
 };
-use bus_mapping::{circuit_input_builder::FeatureConfig, evm::OpcodeId};
+#[cfg(feature = "evm-circuit")]
+use bus_mapping::circuit_input_builder::FeatureConfig;
+use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
+#[cfg(feature = "evm-circuit")]
 use execution::ExecutionConfig;
+#[cfg(feature = "evm-circuit")]
 use itertools::Itertools;
 use strum::IntoEnumIterator;
 use table::FixedTableTag;
 use witness::Block;
 
+#[cfg(feature = "evm-circuit")]
 /// EvmCircuitConfig implements verification of execution trace of a block.
 #[derive(Clone, Debug)]
 pub struct EvmCircuitConfig<F> {
@@ -50,8 +62,11 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     exp_table: ExpTable,
+    sig_table: SigTable,
+    wd_credit_table: WdCreditTable,
 }
 
+#[cfg(feature = "evm-circuit")]
 /// Circuit configuration arguments
 pub struct EvmCircuitConfigArgs<F: Field> {
     /// Challenge
@@ -70,6 +85,10 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// ExpTable
     pub exp_table: ExpTable,
+    /// SigTable
+    pub sig_table: SigTable,
+    /// WdCreditTable
+    pub wd_credit_table: WdCreditTable,
     /// U8Table
     pub u8_table: UXTable<8>,
     /// U16Table
@@ -78,6 +97,7 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub feature_config: FeatureConfig,
 }
 
+#[cfg(feature = "evm-circuit")]
 impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
     type ConfigArgs = EvmCircuitConfigArgs<F>;
 
@@ -93,6 +113,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            sig_table,
+            wd_credit_table,
             u8_table,
             u16_table,
             feature_config,
@@ -112,6 +134,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &copy_table,
             &keccak_table,
             &exp_table,
+            &sig_table,
+            &wd_credit_table,
             feature_config,
         ));
 
@@ -127,6 +151,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         copy_table.annotate_columns(meta);
         keccak_table.annotate_columns(meta);
         exp_table.annotate_columns(meta);
+        sig_table.annotate_columns(meta);
+        wd_credit_table.annotate_columns(meta);
         u8_table.annotate_columns(meta);
         u16_table.annotate_columns(meta);
 
@@ -142,10 +168,13 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            sig_table,
+            wd_credit_table,
         }
     }
 }
 
+#[cfg(feature = "evm-circuit")]
 impl<F: Field> EvmCircuitConfig<F> {
     /// Load fixed table
     pub fn load_fixed_table(
@@ -171,6 +200,7 @@ impl<F: Field> EvmCircuitConfig<F> {
     }
 }
 
+#[cfg(feature = "evm-circuit")]
 /// Tx Circuit for verifying transaction signatures
 #[derive(Clone, Default, Debug)]
 pub struct EvmCircuit<F: Field> {
@@ -179,6 +209,7 @@ pub struct EvmCircuit<F: Field> {
     fixed_table_tags: Vec<FixedTableTag>,
 }
 
+#[cfg(feature = "evm-circuit")]
 impl<F: Field> EvmCircuit<F> {
     /// Return a new EvmCircuit
     pub fn new(block: Block<F>) -> Self {
@@ -232,6 +263,7 @@ impl<F: Field> EvmCircuit<F> {
     }
 }
 
+#[cfg(feature = "evm-circuit")]
 impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     type Config = EvmCircuitConfig<F>;
 
@@ -298,7 +330,7 @@ pub(crate) fn detect_fixed_table_tags<F: Field>(block: &Block<F>) -> Vec<FixedTa
         .collect()
 }
 
-#[cfg(any(feature = "test-util", test))]
+#[cfg(all(feature = "evm-circuit", any(feature = "test-util", test)))]
 pub(crate) mod cached {
     use super::*;
     use halo2_proofs::halo2curves::bn256::Fr;
@@ -358,6 +390,7 @@ pub(crate) mod cached {
 }
 
 // Always exported because of `EXECUTION_STATE_HEIGHT_MAP`
+#[cfg(feature = "evm-circuit")]
 impl<F: Field> Circuit<F> for EvmCircuit<F> {
     type Config = (EvmCircuitConfig<F>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
@@ -384,6 +417,8 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let keccak_table = KeccakTable::construct(meta);
         let exp_table = ExpTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
+        let wd_credit_table = WdCreditTable::construct(meta);
         let u8_table = UXTable::construct(meta);
         let u16_table = UXTable::construct(meta);
         let challenges = Challenges::construct(meta);
@@ -401,6 +436,8 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     copy_table,
                     keccak_table,
                     exp_table,
+                    sig_table,
+                    wd_credit_table,
                     u8_table,
                     u16_table,
                     feature_config: params,
@@ -445,6 +482,12 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             .keccak_table
             .dev_load(&mut layouter, &block.sha3_inputs, &challenges)?;
         config.exp_table.load(&mut layouter, block)?;
+        config
+            .sig_table
+            .dev_load(&mut layouter, &block.ecrecover_events)?;
+        config
+            .wd_credit_table
+            .dev_load(&mut layouter, block.withdrawal_rw_counters())?;
 
         config.u8_table.load(&mut layouter)?;
         config.u16_table.load(&mut layouter)?;
@@ -453,7 +496,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "evm-circuit"))]
 mod evm_circuit_stats {
     use crate::{
         evm_circuit::EvmCircuit,
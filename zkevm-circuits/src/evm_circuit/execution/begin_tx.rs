@@ -15,7 +15,7 @@ use crate::{
                 ContractCreateGadget, IsEqualWordGadget, IsZeroWordGadget, RangeCheckGadget,
             },
             not, or,
-            tx::{BeginTxHelperGadget, TxDataGadget},
+            tx::{BeginTxHelperGadget, TxAccessListGadget, TxDataGadget},
             AccountAddress, CachedRegion, Cell, StepRws,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -54,6 +54,8 @@ pub(crate) struct BeginTxGadget<F> {
     // coinbase, and may be duplicate.
     // <https://github.com/ethereum/go-ethereum/blob/604e215d1bb070dff98fb76aa965064c74e3633f/core/state/statedb.go#LL1119C9-L1119C9>
     is_coinbase_warm: Cell<F>,
+    // EIP-2930 (Access list)
+    access_list: TxAccessListGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
@@ -153,6 +155,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         ); // rwc_delta += 1
 
+        // Add the addresses and storage keys of the access list (EIP-2930).
+        // rwc_delta += access list len
+        let access_list = TxAccessListGadget::configure(cb, tx_id.expr(), &tx);
+
         // Read code_hash of callee
         let code_hash = cb.query_word_unchecked();
         let is_empty_code_hash =
@@ -272,6 +278,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write TxAccessListAccount (Caller)
                 //   - Write TxAccessListAccount (Callee)
                 //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                //   - Write TxAccessListAccount and TxAccessListAccountStorage x access list len
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
                 //   - Write CallContext Depth
@@ -288,7 +295,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
                 rw_counter: Delta(
-                    23.expr() + transfer_with_gas_fee.rw_delta() + PRECOMPILE_COUNT.expr(),
+                    23.expr()
+                        + transfer_with_gas_fee.rw_delta()
+                        + PRECOMPILE_COUNT.expr()
+                        + tx.access_list_rw_delta(),
                 ),
                 call_id: To(call_id.expr()),
                 is_root: To(true.expr()),
@@ -332,10 +342,15 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
                     //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                    //   - Write TxAccessListAccount and TxAccessListAccountStorage x access list
+                    //     len
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
                     rw_counter: Delta(
-                        9.expr() + transfer_with_gas_fee.rw_delta() + PRECOMPILE_COUNT.expr(),
+                        9.expr()
+                            + transfer_with_gas_fee.rw_delta()
+                            + PRECOMPILE_COUNT.expr()
+                            + tx.access_list_rw_delta(),
                     ),
                     call_id: To(call_id.expr()),
                     ..StepStateTransition::any()
@@ -395,6 +410,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
                     //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                    //   - Write TxAccessListAccount and TxAccessListAccountStorage x access list
+                    //     len
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
                     //   - Write CallContext Depth
@@ -411,7 +428,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write CallContext IsCreate
                     //   - Write CallContext CodeHash
                     rw_counter: Delta(
-                        22.expr() + transfer_with_gas_fee.rw_delta() + PRECOMPILE_COUNT.expr(),
+                        22.expr()
+                            + transfer_with_gas_fee.rw_delta()
+                            + PRECOMPILE_COUNT.expr()
+                            + tx.access_list_rw_delta(),
                     ),
                     call_id: To(call_id.expr()),
                     is_root: To(true.expr()),
@@ -441,6 +461,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             is_caller_callee_equal,
             coinbase,
             is_coinbase_warm,
+            access_list,
         }
     }
 
@@ -462,6 +483,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         rws.offset_add(PRECOMPILE_COUNT as usize);
 
         let is_coinbase_warm = rws.next().tx_access_list_value_pair().1;
+        self.access_list.assign(region, offset, tx, &mut rws)?;
         let mut callee_code_hash = zero;
         if !is_precompiled(&tx.to_or_contract_addr()) {
             callee_code_hash = rws.next().account_codehash_pair().1;
@@ -481,7 +503,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         };
 
         self.begin_tx.assign(region, offset, tx)?;
-        self.tx.assign(region, offset, tx)?;
+        self.tx.assign(region, offset, block, tx)?;
 
         self.tx_caller_address_is_zero.assign_u256(
             region,
@@ -559,10 +581,16 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use crate::{
+        evm_circuit::{param::MAX_ACCESS_LIST_ADDRESSES, test::rand_bytes},
+        test_util::{CircuitTestBuilder, CircuitTestError},
+    };
     use bus_mapping::evm::OpcodeId;
-    use eth_types::{self, bytecode, evm_types::GasCost, word, Address, Bytecode, Word};
-    use ethers_core::utils::get_contract_address;
+    use eth_types::{self, bytecode, evm_types::GasCost, word, Address, Bytecode, Word, H256};
+    use ethers_core::{
+        types::transaction::eip2930::{AccessList, AccessListItem},
+        utils::get_contract_address,
+    };
     use mock::{eth, gwei, MockTransaction, TestContext, MOCK_ACCOUNTS};
     use std::vec;
 
@@ -684,6 +712,130 @@ mod test {
         CircuitTestBuilder::new_from_test_ctx(ctx).run();
     }
 
+    #[test]
+    fn begin_tx_access_list() {
+        let to = MOCK_ACCOUNTS[0];
+        let from = MOCK_ACCOUNTS[1];
+
+        // Slot 1 is pre-warmed by the access list, slot 2 is cold.
+        let code = bytecode! {
+            PUSH1(1)
+            SLOAD
+            PUSH1(2)
+            SLOAD
+            STOP
+        };
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address: to,
+                storage_keys: vec![H256::from_low_u64_be(1)],
+            },
+            // Duplicate addresses and keys are charged again, but are already warm.
+            AccessListItem {
+                address: to,
+                storage_keys: vec![H256::from_low_u64_be(1)],
+            },
+            AccessListItem {
+                address: MOCK_ACCOUNTS[2],
+                storage_keys: vec![],
+            },
+        ]);
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(to).balance(eth(1)).code(code);
+                accs[1].address(from).balance(eth(1));
+            },
+            |mut txs, _| {
+                txs[0]
+                    .to(to)
+                    .from(from)
+                    .transaction_type(1)
+                    .access_list(access_list);
+            },
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn begin_tx_access_list_too_long() {
+        let to = MOCK_ACCOUNTS[0];
+        let from = MOCK_ACCOUNTS[1];
+
+        // One more address than the tx table has slots for.
+        let access_list = AccessList(
+            (0..=MAX_ACCESS_LIST_ADDRESSES)
+                .map(|i| AccessListItem {
+                    address: Address::repeat_byte(0x10 + i as u8),
+                    storage_keys: vec![],
+                })
+                .collect(),
+        );
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(to).balance(eth(1));
+                accs[1].address(from).balance(eth(1));
+            },
+            |mut txs, _| {
+                txs[0]
+                    .to(to)
+                    .from(from)
+                    .transaction_type(1)
+                    .access_list(access_list);
+            },
+            |block, _| block,
+        )
+        .unwrap();
+
+        let err = CircuitTestBuilder::new_from_test_ctx(ctx)
+            .run_with_result()
+            .unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                CircuitTestError::CannotHandleBlock(msg)
+                    if msg.starts_with("TooManyAccessListAddresses")
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn begin_tx_dynamic_fee() {
+        // The effective gas price is base_fee + max_priority_fee_per_gas, and max_fee_per_gas
+        // when it's lower.
+        for (max_fee, max_priority_fee) in [(gwei(3), gwei(1)), (gwei(2), gwei(2))] {
+            let ctx = TestContext::<2, 1>::new(
+                None,
+                |accs| {
+                    accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(1));
+                    accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(1));
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[1].address)
+                        .to(accs[0].address)
+                        .value(eth(0))
+                        .transaction_type(2)
+                        .gas_price(gwei(2))
+                        .max_fee_per_gas(max_fee)
+                        .max_priority_fee_per_gas(max_priority_fee);
+                },
+                |block, _| block.base_fee_per_gas(Some(gwei(1))),
+            )
+            .unwrap();
+
+            CircuitTestBuilder::new_from_test_ctx(ctx).run();
+        }
+    }
+
     #[test]
     fn begin_tx_gadget_rand() {
         let random_amount = Word::from_little_endian(&rand_bytes(32)) % eth(1);
@@ -395,7 +395,8 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
                 // copy table lookup to verify the copying of bytes:
                 // - from caller's memory (`call_data_length` bytes starting at `call_data_offset`)
-                // - to the current call's memory (`call_data_length` bytes starting at `0`).
+                // - to the precompile's input (`precompile_input_len` bytes, padded with zeros past
+                //   the call data).
                 let precompile_input_bytes_rlc =
                     cb.condition(call_gadget.cd_address.has_length(), |cb| {
                         let precompile_input_bytes_rlc = cb.query_cell_phase2();
@@ -405,7 +406,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                             WordLoHi::from_lo_unchecked(callee_call_id.expr()),
                             CopyDataType::RlcAcc.expr(),
                             call_gadget.cd_address.offset(),
-                            call_gadget.cd_address.offset() + precompile_input_len.expr(),
+                            call_gadget.cd_address.address(),
                             0.expr(),
                             precompile_input_len.expr(),
                             precompile_input_bytes_rlc.expr(),
@@ -413,6 +414,17 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                         );
                         precompile_input_bytes_rlc
                     });
+                // Without call data the precompile's input is all zeros.
+                cb.condition(not::expr(call_gadget.cd_address.has_length()), |cb| {
+                    cb.require_zero(
+                        "the input bytes rlc is zero without call data",
+                        precompile_input_bytes_rlc.expr(),
+                    );
+                    cb.require_zero(
+                        "no memory read without call data",
+                        precompile_input_rws.expr(),
+                    );
+                });
 
                 // copy table lookup to verify the precompile result.
                 // - from precompiled contract.
@@ -512,6 +524,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     call_gadget.rd_address.offset(),
                     call_gadget.rd_address.length(),
                     precompile_return_length.expr(),
+                    precompile_input_len.expr(),
                     precompile_input_bytes_rlc.expr(),
                     precompile_output_bytes_rlc.expr(),
                     precompile_return_bytes_rlc.expr(),
@@ -1019,15 +1032,14 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             return_rws,
         ) = if is_precheck_ok && is_precompiled(&callee_address.to_address()) {
             let precompile_call: PrecompileCalls = precompile_addr.0[19].into();
-            let input_len = if let Some(input_len) = precompile_call.input_len() {
-                min(input_len, cd_length.as_usize())
-            } else {
-                cd_length.as_usize()
-            };
+            let input_len = precompile_call.input_len().unwrap_or(cd_length.as_usize());
 
-            let input_bytes = (0..input_len)
+            // the call data bytes taken, padded with zeros to the input length
+            let mut input_bytes = (0..min(input_len, cd_length.as_usize()))
                 .map(|_| rws.next().memory_value())
                 .collect::<Vec<_>>();
+            let input_rws = input_bytes.len() as u64;
+            input_bytes.resize(input_len, 0);
             let output_bytes = (0..precompile_return_length.as_u64())
                 .map(|_| rws.next().memory_value())
                 .collect::<Vec<_>>();
@@ -1050,7 +1062,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                 .keccak_input()
                 .map(|randomness| rlc::value(return_bytes.iter().rev(), randomness));
 
-            let input_rws = input_bytes.len() as u64;
             let output_rws = output_bytes.len() as u64;
             let return_rws = (return_bytes.len() * 2) as u64;
 
@@ -240,6 +240,18 @@ mod tests {
         test_ok(0x102.into(), 0x103.into(), 0x101, true);
     }
 
+    #[test]
+    fn codecopy_gadget_out_of_bound() {
+        // The code is of 0x65 bytes: the copy is partially out of bound, and then fully
+        test_ok(0x50.into(), 0x00.into(), 0x40, false);
+        test_ok(0x100.into(), 0x00.into(), 0x40, false);
+    }
+
+    #[test]
+    fn codecopy_gadget_zero_size() {
+        test_ok(0x20.into(), 0x10.into(), 0, false);
+    }
+
     #[test]
     fn codecopy_gadget_code_offset_overflow() {
         test_ok(Word::MAX, 0x103.into(), 0x101, true);
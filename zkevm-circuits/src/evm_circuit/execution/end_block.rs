@@ -23,6 +23,7 @@ pub(crate) struct EndBlockGadget<F> {
     total_txs: Cell<F>,
     total_txs_is_max_txs: IsEqualGadget<F>,
     is_empty_block: IsZeroGadget<F>,
+    num_withdrawal_rws: Cell<F>,
     max_rws: Cell<F>,
     max_txs: Cell<F>,
 }
@@ -41,12 +42,21 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
         let is_empty_block =
             IsZeroGadget::construct(cb, cb.curr.state.rw_counter.clone().expr() - 1.expr());
 
-        let total_rws_before_padding = cb.curr.state.rw_counter.clone().expr() - 1.expr()
+        // The withdrawals are credited after the call_context lookup below, at the consecutive
+        // rw counters of the wd credit table, which are the ones of the rw table lookups of the
+        // withdrawal circuit.
+        let num_withdrawal_rws = cb.query_cell();
+        let withdrawal_rw_counter = cb.curr.state.rw_counter.clone().expr()
             + select::expr(
                 is_empty_block.expr(),
                 0.expr(),
                 1.expr(), // If the block is not empty, we will do 1 call_context lookup below
             );
+        cb.wd_credit_table_lookup(
+            withdrawal_rw_counter.clone(),
+            withdrawal_rw_counter.clone() + num_withdrawal_rws.expr(),
+        );
+        let total_rws_before_padding = withdrawal_rw_counter - 1.expr() + num_withdrawal_rws.expr();
 
         // 1. Constraint total_rws and total_txs witness values depending on the empty
         // block case.
@@ -113,6 +123,7 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
             total_txs,
             total_txs_is_max_txs,
             is_empty_block,
+            num_withdrawal_rws,
         }
     }
 
@@ -127,6 +138,15 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
     ) -> Result<(), Error> {
         self.is_empty_block
             .assign(region, offset, F::from(u64::from(step.rwc) - 1))?;
+        // The withdrawal credits are only in the last EndBlock step, but every EndBlock step
+        // does the same lookups.
+        let (rw_counter_start, rw_counter_end) = block.withdrawal_rw_counters();
+        let num_withdrawal_rws = rw_counter_end - rw_counter_start;
+        self.num_withdrawal_rws.assign(
+            region,
+            offset,
+            Value::known(F::from(num_withdrawal_rws as u64)),
+        )?;
         let max_rws = F::from(block.circuits_params.max_rws as u64);
         let max_rws_assigned = self.max_rws.assign(region, offset, Value::known(max_rws))?;
 
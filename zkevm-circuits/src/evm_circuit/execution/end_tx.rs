@@ -1,7 +1,7 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::N_BYTES_GAS,
+        param::{MAX_DESTRUCTED_ACCOUNTS, N_BYTES_GAS},
         step::ExecutionState,
         util::{
             common_gadget::{TransferToGadget, UpdateBalanceGadget},
@@ -10,10 +10,11 @@ use crate::{
                 AddWordsGadget, ConstantDivisionGadget, IsZeroWordGadget, MinMaxGadget,
                 MulWordByU64Gadget,
             },
+            not, sum,
             tx::EndTxHelperGadget,
             CachedRegion, Cell,
         },
-        witness::{Block, Call, ExecStep, Transaction},
+        witness::{Block, Call, ExecStep, Rw, Transaction},
     },
     table::{AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, TxContextFieldTag},
     util::{
@@ -23,6 +24,8 @@ use crate::{
 };
 use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field};
 use halo2_proofs::{circuit::Value, plonk::Error};
+use itertools::{izip, Itertools};
+use std::array;
 
 #[derive(Clone, Debug)]
 pub(crate) struct EndTxGadget<F> {
@@ -40,6 +43,11 @@ pub(crate) struct EndTxGadget<F> {
     coinbase_code_hash: WordLoHiCell<F>,
     coinbase_code_hash_is_zero: IsZeroWordGadget<F, WordLoHiCell<F>>,
     coinbase_reward: TransferToGadget<F>,
+    is_destructed: [Cell<F>; MAX_DESTRUCTED_ACCOUNTS],
+    destructed_address: [WordLoHiCell<F>; MAX_DESTRUCTED_ACCOUNTS],
+    destructed_nonce: [Cell<F>; MAX_DESTRUCTED_ACCOUNTS],
+    destructed_balance: [WordLoHiCell<F>; MAX_DESTRUCTED_ACCOUNTS],
+    destructed_code_hash: [WordLoHiCell<F>; MAX_DESTRUCTED_ACCOUNTS],
     is_persistent: Cell<F>,
     end_tx: EndTxHelperGadget<F>,
 }
@@ -114,12 +122,68 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             true,
         );
 
+        // Delete the accounts self destructed in the tx, one per slot in use.  Clearing the mark
+        // of a slot requires the account to be marked, so no account is deleted twice, and the
+        // State Circuit requires every mark to be cleared by the end of the tx, so none is left
+        // out.  The storage of the accounts is NOT cleared: the rw table keeps its values, so a
+        // later access to a slot of a destructed account reads the value from before the
+        // deletion.  This is known to be unsound, and the bus-mapping prefilter rejects every
+        // block with a SELFDESTRUCT until the reset is constrained.
+        let is_destructed: [Cell<F>; MAX_DESTRUCTED_ACCOUNTS] = array::from_fn(|_| cb.query_bool());
+        for (prev, cur) in is_destructed.iter().tuple_windows() {
+            cb.require_zero(
+                "the slots in use come first",
+                cur.expr() * not::expr(prev.expr()),
+            );
+        }
+        let destructed_address: [WordLoHiCell<F>; MAX_DESTRUCTED_ACCOUNTS] =
+            array::from_fn(|_| cb.query_word_unchecked());
+        let destructed_nonce: [Cell<F>; MAX_DESTRUCTED_ACCOUNTS] =
+            array::from_fn(|_| cb.query_cell());
+        let destructed_balance: [WordLoHiCell<F>; MAX_DESTRUCTED_ACCOUNTS] =
+            array::from_fn(|_| cb.query_word_unchecked());
+        let destructed_code_hash: [WordLoHiCell<F>; MAX_DESTRUCTED_ACCOUNTS] =
+            array::from_fn(|_| cb.query_word_unchecked());
+        for (is_destructed, address, nonce, balance, code_hash) in izip!(
+            &is_destructed,
+            &destructed_address,
+            &destructed_nonce,
+            &destructed_balance,
+            &destructed_code_hash
+        ) {
+            cb.condition(is_destructed.expr(), |cb| {
+                cb.account_destructed_write(
+                    tx_id.expr(),
+                    address.to_word(),
+                    0.expr(),
+                    1.expr(),
+                    None,
+                );
+                for (field_tag, value_prev) in [
+                    (
+                        AccountFieldTag::Nonce,
+                        WordLoHi::from_lo_unchecked(nonce.expr()),
+                    ),
+                    (AccountFieldTag::Balance, balance.to_word()),
+                    (AccountFieldTag::CodeHash, code_hash.to_word()),
+                ] {
+                    cb.account_write(
+                        address.to_word(),
+                        field_tag,
+                        WordLoHi::zero(),
+                        value_prev,
+                        None,
+                    );
+                }
+            });
+        }
+
         let end_tx = EndTxHelperGadget::construct(
             cb,
             tx_id.expr(),
             is_persistent.expr(),
             gas_used,
-            9.expr() + coinbase_reward.rw_delta(),
+            9.expr() + coinbase_reward.rw_delta() + 4.expr() * sum::expr(&is_destructed),
         );
 
         Self {
@@ -137,6 +201,11 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             coinbase_code_hash,
             coinbase_code_hash_is_zero,
             coinbase_reward,
+            is_destructed,
+            destructed_address,
+            destructed_nonce,
+            destructed_balance,
+            destructed_code_hash,
             is_persistent,
             end_tx,
         }
@@ -208,17 +277,13 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             .assign_u256(region, offset, coinbase_code_hash_prev)?;
         self.coinbase_code_hash_is_zero
             .assign_u256(region, offset, coinbase_code_hash_prev)?;
+        let mut rw_offset = 5;
         if !coinbase_reward.is_zero() {
-            let coinbase_balance_pair = block
-                .get_rws(
-                    step,
-                    if coinbase_code_hash_prev.is_zero() {
-                        6
-                    } else {
-                        5
-                    },
-                )
-                .account_balance_pair();
+            if coinbase_code_hash_prev.is_zero() {
+                rw_offset += 1;
+            }
+            let coinbase_balance_pair = block.get_rws(step, rw_offset).account_balance_pair();
+            rw_offset += 1;
             self.coinbase_reward.assign(
                 region,
                 offset,
@@ -226,6 +291,44 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
                 effective_tip * gas_used,
             )?;
         }
+        // The mark of each destructed account is followed by the writes of its nonce, balance
+        // and code hash.
+        let destructed = (rw_offset..step.rw_indices_len())
+            .step_by(4)
+            .take_while(|idx| matches!(block.get_rws(step, *idx), Rw::TxDestructedAccount { .. }))
+            .map(|idx| {
+                let address = block.get_rws(step, idx).address().unwrap();
+                let (_, nonce) = block.get_rws(step, idx + 1).account_nonce_pair();
+                let (_, balance) = block.get_rws(step, idx + 2).account_balance_pair();
+                let (_, code_hash) = block.get_rws(step, idx + 3).account_codehash_pair();
+                (address, nonce, balance, code_hash)
+            })
+            .collect_vec();
+        // Rejected by the bus-mapping already.
+        if destructed.len() > MAX_DESTRUCTED_ACCOUNTS {
+            return Err(Error::Synthesis);
+        }
+        for (index, (is_destructed, address, nonce, balance, code_hash)) in izip!(
+            &self.is_destructed,
+            &self.destructed_address,
+            &self.destructed_nonce,
+            &self.destructed_balance,
+            &self.destructed_code_hash
+        )
+        .enumerate()
+        {
+            let (slot_address, slot_nonce, slot_balance, slot_code_hash) =
+                destructed.get(index).copied().unwrap_or_default();
+            is_destructed.assign(
+                region,
+                offset,
+                Value::known(F::from((index < destructed.len()) as u64)),
+            )?;
+            address.assign_h160(region, offset, slot_address)?;
+            nonce.assign(region, offset, Value::known(F::from(slot_nonce.as_u64())))?;
+            balance.assign_u256(region, offset, slot_balance)?;
+            code_hash.assign_u256(region, offset, slot_code_hash)?;
+        }
         self.is_persistent.assign(
             region,
             offset,
@@ -64,10 +64,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPrecompileGadget<F> {
 
         // calculate required gas for precompile
         let precompiles_required_gas = vec![
-            // (
-            //     addr_bits.value_equals(PrecompileCalls::ECRecover),
-            //     GasCost::PRECOMPILE_ECRECOVER_BASE.expr(),
-            // ),
+            (
+                addr_bits.value_equals(PrecompileCalls::ECRecover),
+                GasCost::PRECOMPILE_ECRECOVER_BASE.expr(),
+            ),
             // addr_bits.value_equals(PrecompileCalls::Sha256),
             // addr_bits.value_equals(PrecompileCalls::Ripemd160),
             // addr_bits.value_equals(PrecompileCalls::Blake2F),
@@ -190,8 +190,9 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGPrecompileGadget<F> {
                 let n_words = (call.call_data_length + 31) / 32;
                 precompile_call.base_gas_cost() + n_words * GasCost::PRECOMPILE_IDENTITY_PER_WORD
             }
-            // PrecompileCalls::Bn128Add | PrecompileCalls::Bn128Mul | PrecompileCalls::ECRecover =>
-            // {     precompile_call.base_gas_cost()
+            PrecompileCalls::ECRecover => precompile_call.base_gas_cost(),
+            // PrecompileCalls::Bn128Add | PrecompileCalls::Bn128Mul => {
+            //     precompile_call.base_gas_cost()
             // }
             _ => unreachable!(),
         };
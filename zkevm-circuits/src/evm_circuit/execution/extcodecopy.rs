@@ -458,4 +458,36 @@ mod test {
             true,
         );
     }
+
+    #[test]
+    fn extcodecopy_zero_length() {
+        for code_offset in [0x20, 0x100] {
+            test_ok(
+                Some(Account {
+                    address: *EXTERNAL_ADDRESS,
+                    code: Bytes::from(rand_bytes_array::<64>()),
+                    ..Default::default()
+                }),
+                code_offset.into(),
+                0x10.into(),
+                0,
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn extcodecopy_code_offset_past_end() {
+        test_ok(
+            Some(Account {
+                address: *EXTERNAL_ADDRESS,
+                code: Bytes::from(rand_bytes_array::<64>()),
+                ..Default::default()
+            }),
+            0x100.into(),
+            Word::zero(),
+            0x36,
+            false,
+        );
+    }
 }
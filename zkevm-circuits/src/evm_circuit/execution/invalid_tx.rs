@@ -111,7 +111,7 @@ impl<F: Field> ExecutionGadget<F> for InvalidTxGadget<F> {
             .expect("unexpected U256 -> Scalar conversion failure");
         let balance = rws.next().account_balance_pair().0;
         self.begin_tx.assign(region, offset, tx)?;
-        self.tx.assign(region, offset, tx)?;
+        self.tx.assign(region, offset, block, tx)?;
         self.account_nonce
             .assign(region, offset, Value::known(account_nonce))?;
         self.is_nonce_match
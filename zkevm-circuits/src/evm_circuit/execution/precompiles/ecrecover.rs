@@ -0,0 +1,437 @@
+use bus_mapping::circuit_input_builder::Call;
+use eth_types::{evm_types::GasCost, word, Field, ToLittleEndian, ToScalar, Word};
+use gadgets::util::{and, not, select, Expr};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_WORD,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::{IsEqualWordGadget, IsZeroWordGadget, LtWordGadget},
+            rlc, AccountAddress, CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    util::word::{Word32, Word32Cell, WordExpr, WordLoHi},
+    witness::{Block, ExecStep, Transaction},
+};
+
+lazy_static::lazy_static! {
+    /// Order of the secp256k1 group, the upper bound of the signature r and s.
+    static ref SECP256K1_N: Word =
+        word!("0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+}
+
+/// Gadget for the ecRecover precompile.  The signature is verified by the SignVerifyChip of the
+/// Tx Circuit, and looked up here in the sig table together with its recovery id and the
+/// recovered address.
+///
+/// A message hash not lower than the secp256k1 group order, or an `r` that isn't the x coordinate
+/// of a curve point, can't be proven: the sig table holds reduced message hashes and only verified
+/// signatures.
+#[derive(Clone, Debug)]
+pub struct EcrecoverGadget<F> {
+    // The first three cells are also queried, in this order, by the `PrecompileGadget` of the
+    // calling step.
+    input_bytes_rlc: Cell<F>,
+    output_bytes_rlc: Cell<F>,
+    is_recovered: Cell<F>,
+
+    msg_hash: Word32Cell<F>,
+    sig_v: Word32Cell<F>,
+    sig_r: Word32Cell<F>,
+    sig_s: Word32Cell<F>,
+    recovered_addr: AccountAddress<F>,
+    is_recovered_addr_zero: IsZeroWordGadget<F, WordLoHi<Expression<F>>>,
+
+    is_v_27: IsEqualWordGadget<F, Word32Cell<F>, WordLoHi<Expression<F>>>,
+    is_v_28: IsEqualWordGadget<F, Word32Cell<F>, WordLoHi<Expression<F>>>,
+    is_r_zero: IsZeroWordGadget<F, Word32Cell<F>>,
+    is_r_lt_n: LtWordGadget<F>,
+    is_s_zero: IsZeroWordGadget<F, Word32Cell<F>>,
+    is_s_lt_n: LtWordGadget<F>,
+
+    is_success: Cell<F>,
+    callee_address: Cell<F>,
+    caller_id: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EcrecoverGadget<F> {
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileEcRecover;
+
+    const NAME: &'static str = "ECRECOVER";
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let input_bytes_rlc = cb.query_cell_phase2();
+        let output_bytes_rlc = cb.query_cell_phase2();
+        // Whether a public key could be recovered from the signature.  If not, the call still
+        // succeeds but returns no data.
+        let is_recovered = cb.query_bool();
+
+        let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+            [
+                CallContextFieldTag::IsSuccess,
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+
+        // The input is `msg_hash || v || r || s`, the call data being padded with zeros to 128
+        // bytes.
+        let [msg_hash, sig_v, sig_r, sig_s] = [(); 4].map(|_| cb.query_word32());
+        let input_bytes_le = [&sig_s, &sig_r, &sig_v, &msg_hash]
+            .into_iter()
+            .flat_map(|word| word.limbs.clone().map(|byte| byte.expr()))
+            .collect::<Vec<_>>();
+        cb.require_equal(
+            "input bytes rlc is the rlc of msg_hash || v || r || s",
+            input_bytes_rlc.expr(),
+            cb.keccak_rlc::<128>(input_bytes_le.try_into().unwrap()),
+        );
+
+        // Signature prechecks: v is 27 or 28, and 0 < r, s < n.
+        let is_v_27 =
+            IsEqualWordGadget::construct(cb, &sig_v, &WordLoHi::from_lo_unchecked(27.expr()));
+        let is_v_28 =
+            IsEqualWordGadget::construct(cb, &sig_v, &WordLoHi::from_lo_unchecked(28.expr()));
+        let secp256k1_n = Word32::new(SECP256K1_N.to_le_bytes().map(|byte| byte.expr())).to_word();
+        let is_r_zero = IsZeroWordGadget::construct(cb, &sig_r);
+        let is_r_lt_n = LtWordGadget::construct(cb, &sig_r.to_word(), &secp256k1_n);
+        let is_s_zero = IsZeroWordGadget::construct(cb, &sig_s);
+        let is_s_lt_n = LtWordGadget::construct(cb, &sig_s.to_word(), &secp256k1_n);
+        cb.require_equal(
+            "an address is recovered if and only if the signature passes the prechecks",
+            is_recovered.expr(),
+            and::expr([
+                is_v_27.expr() + is_v_28.expr(),
+                not::expr(is_r_zero.expr()),
+                is_r_lt_n.expr(),
+                not::expr(is_s_zero.expr()),
+                is_s_lt_n.expr(),
+            ]),
+        );
+
+        // The recovered address is returned left padded to a word.
+        let recovered_addr = cb.query_account_address();
+        let is_recovered_addr_zero = IsZeroWordGadget::construct(cb, &recovered_addr.to_word());
+        cb.condition(is_recovered.expr(), |cb| {
+            // The sig table binds the address to the public key only when it isn't zero.
+            cb.require_zero(
+                "the recovered address is not zero",
+                is_recovered_addr_zero.expr(),
+            );
+            // The recovery id is 0 for v = 27 and 1 for v = 28.
+            cb.sig_table_lookup(
+                msg_hash.to_word(),
+                is_v_28.expr(),
+                sig_r.to_word(),
+                sig_s.to_word(),
+                recovered_addr.to_word(),
+            );
+            cb.require_equal(
+                "output bytes rlc is the rlc of the recovered address",
+                output_bytes_rlc.expr(),
+                cb.keccak_rlc::<20>(recovered_addr.limbs.clone().map(|byte| byte.expr())),
+            );
+        });
+
+        let gas_cost = select::expr(
+            is_success.expr(),
+            GasCost::PRECOMPILE_ECRECOVER_BASE.expr(),
+            cb.curr.state.gas_left.expr(),
+        );
+
+        cb.precompile_info_lookup(
+            cb.execution_state().as_u64().expr(),
+            callee_address.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr(),
+        );
+
+        // As for Identity, the only failure of ecRecover is insufficient gas, which is diverted
+        // to the ErrorOogPrecompile gadget.
+        let restore_context = RestoreContextGadget::construct2(
+            cb,
+            is_success.expr(),
+            gas_cost.expr(),
+            0.expr(),
+            0x00.expr(),                               // ReturnDataOffset
+            is_recovered.expr() * N_BYTES_WORD.expr(), // ReturnDataLength
+            0.expr(),
+            0.expr(),
+        );
+
+        Self {
+            input_bytes_rlc,
+            output_bytes_rlc,
+            is_recovered,
+            msg_hash,
+            sig_v,
+            sig_r,
+            sig_s,
+            recovered_addr,
+            is_recovered_addr_zero,
+            is_v_27,
+            is_v_28,
+            is_r_zero,
+            is_r_lt_n,
+            is_s_zero,
+            is_s_lt_n,
+            is_success,
+            callee_address,
+            caller_id,
+            call_data_offset,
+            call_data_length,
+            return_data_offset,
+            return_data_length,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let aux_data = step.aux_data.as_ref().ok_or(Error::Synthesis)?;
+        let (input_bytes, output_bytes) = (&aux_data.input_bytes, &aux_data.output_bytes);
+
+        self.input_bytes_rlc.assign(
+            region,
+            offset,
+            region
+                .challenges()
+                .keccak_input()
+                .map(|r| rlc::value(input_bytes.iter().rev(), r)),
+        )?;
+        self.output_bytes_rlc.assign(
+            region,
+            offset,
+            region
+                .challenges()
+                .keccak_input()
+                .map(|r| rlc::value(output_bytes.iter().rev(), r)),
+        )?;
+        self.is_recovered.assign(
+            region,
+            offset,
+            Value::known(F::from(u64::from(!output_bytes.is_empty()))),
+        )?;
+
+        let [msg_hash, sig_v, sig_r, sig_s] =
+            [0, 32, 64, 96].map(|i| Word::from_big_endian(&input_bytes[i..i + N_BYTES_WORD]));
+        for (cell, value) in [
+            (&self.msg_hash, msg_hash),
+            (&self.sig_v, sig_v),
+            (&self.sig_r, sig_r),
+            (&self.sig_s, sig_s),
+        ] {
+            cell.assign_u256(region, offset, value)?;
+        }
+        let recovered_addr = if output_bytes.is_empty() {
+            Word::zero()
+        } else {
+            Word::from_big_endian(output_bytes)
+        };
+        self.recovered_addr
+            .assign_u256(region, offset, recovered_addr)?;
+        self.is_recovered_addr_zero
+            .assign_u256(region, offset, recovered_addr)?;
+
+        self.is_v_27
+            .assign_u256(region, offset, sig_v, Word::from(27))?;
+        self.is_v_28
+            .assign_u256(region, offset, sig_v, Word::from(28))?;
+        self.is_r_zero.assign_u256(region, offset, sig_r)?;
+        self.is_r_lt_n.assign(region, offset, sig_r, *SECP256K1_N)?;
+        self.is_s_zero.assign_u256(region, offset, sig_s)?;
+        self.is_s_lt_n.assign(region, offset, sig_s, *SECP256K1_N)?;
+
+        self.is_success.assign(
+            region,
+            offset,
+            Value::known(F::from(u64::from(call.is_success))),
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.code_address().unwrap().to_scalar().unwrap()),
+        )?;
+        self.caller_id.assign(
+            region,
+            offset,
+            Value::known(F::from(call.caller_id.try_into().unwrap())),
+        )?;
+        self.call_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_offset)),
+        )?;
+        self.call_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_length)),
+        )?;
+        self.return_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_offset)),
+        )?;
+        self.return_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_length)),
+        )?;
+        self.restore_context
+            .assign(region, offset, block, call, step, 7)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bus_mapping::{
+        evm::OpcodeId,
+        precompile::{PrecompileCallArgs, PrecompileCalls},
+    };
+    use eth_types::{bytecode, word, ToWord};
+    use itertools::Itertools;
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    lazy_static::lazy_static! {
+        static ref TEST_VECTOR: Vec<PrecompileCallArgs> = {
+            vec![
+                PrecompileCallArgs {
+                    name: "ecrecover (valid sig)",
+                    setup_code: bytecode! {
+                        // msg hash from 0x00
+                        PUSH32(word!("0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3"))
+                        PUSH1(0x00)
+                        MSTORE
+                        // signature v from 0x20
+                        PUSH1(28)
+                        PUSH1(0x20)
+                        MSTORE
+                        // signature r from 0x40
+                        PUSH32(word!("0x9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608"))
+                        PUSH1(0x40)
+                        MSTORE
+                        // signature s from 0x60
+                        PUSH32(word!("0x4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada"))
+                        PUSH1(0x60)
+                        MSTORE
+                    },
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x80.into(),
+                    ret_offset: 0x80.into(),
+                    ret_size: 0x20.into(),
+                    gas: 0xFFFF.into(),
+                    address: PrecompileCalls::ECRecover.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "ecrecover (invalid sig, no address recovered)",
+                    setup_code: bytecode! {
+                        // msg hash from 0x00
+                        PUSH32(word!("0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3"))
+                        PUSH1(0x00)
+                        MSTORE
+                        // signature v from 0x20, neither 27 nor 28
+                        PUSH1(30)
+                        PUSH1(0x20)
+                        MSTORE
+                        // signature r from 0x40
+                        PUSH32(word!("0x9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608"))
+                        PUSH1(0x40)
+                        MSTORE
+                        // signature s from 0x60
+                        PUSH32(word!("0x4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada"))
+                        PUSH1(0x60)
+                        MSTORE
+                    },
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x80.into(),
+                    ret_offset: 0x80.into(),
+                    ret_size: 0x20.into(),
+                    gas: 0xFFFF.into(),
+                    address: PrecompileCalls::ECRecover.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "ecrecover (short input)",
+                    setup_code: bytecode! {
+                        // msg hash from 0x00
+                        PUSH32(word!("0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3"))
+                        PUSH1(0x00)
+                        MSTORE
+                    },
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x20.into(),
+                    ret_offset: 0x20.into(),
+                    ret_size: 0x20.into(),
+                    gas: 0xFFFF.into(),
+                    address: PrecompileCalls::ECRecover.address().to_word(),
+                    ..Default::default()
+                },
+                PrecompileCallArgs {
+                    name: "insufficient gas (precompile call should fail)",
+                    setup_code: bytecode! {
+                        // msg hash from 0x00
+                        PUSH32(word!("0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3"))
+                        PUSH1(0x00)
+                        MSTORE
+                    },
+                    call_data_offset: 0x00.into(),
+                    call_data_length: 0x80.into(),
+                    ret_offset: 0x80.into(),
+                    ret_size: 0x20.into(),
+                    address: PrecompileCalls::ECRecover.address().to_word(),
+                    // set gas to be insufficient
+                    gas: 2999.into(),
+                    ..Default::default()
+                },
+            ]
+        };
+    }
+
+    #[test]
+    fn precompile_ecrecover_test() {
+        let call_kinds = vec![
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+
+        for (test_vector, &call_kind) in TEST_VECTOR.iter().cartesian_product(&call_kinds) {
+            let bytecode = test_vector.with_call_op(call_kind);
+
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+            )
+            .run();
+        }
+    }
+}
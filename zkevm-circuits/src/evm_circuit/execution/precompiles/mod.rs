@@ -1,2 +1,5 @@
+mod ecrecover;
+pub use ecrecover::EcrecoverGadget;
+
 mod identity;
 pub use identity::IdentityGadget;
@@ -0,0 +1,439 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::{RestoreContextGadget, TransferToGadget},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            math_gadget::{IsEqualWordGadget, IsZeroWordGadget},
+            not, select, AccountAddress, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::{
+        word::{Word32Cell, WordExpr, WordLoHi, WordLoHiCell},
+        Expr,
+    },
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToWord, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for SELFDESTRUCT, with the pre-Cancun semantics: the balance of the account is moved
+/// to the beneficiary (or burnt if the beneficiary is the account itself) and the account is
+/// marked as destructed, to be deleted at the end of the transaction, which is done in EndTx.
+/// The EIP-6780 rule that only deletes the accounts created in the same transaction isn't
+/// applied, as the tree follows Shanghai.
+#[derive(Clone, Debug)]
+pub(crate) struct SelfdestructGadget<F> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    is_static: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    callee_address: WordLoHiCell<F>,
+    beneficiary: AccountAddress<F>,
+    is_warm: Cell<F>,
+    beneficiary_code_hash: WordLoHiCell<F>,
+    beneficiary_not_exists: IsZeroWordGadget<F, WordLoHiCell<F>>,
+    is_beneficiary_callee: IsEqualWordGadget<F, AccountAddress<F>, WordLoHiCell<F>>,
+    balance: Word32Cell<F>,
+    transfer: TransferToGadget<F>,
+    is_destructed_prev: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfdestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        // We do the responsible opcode check explicitly here because we're not using
+        // the `SameContextGadget` for `SELFDESTRUCT`.
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_equal(
+            "Opcode should be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+
+        // constrain not in static call
+        let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
+        cb.require_zero("is_static is false", is_static.expr());
+
+        let mut reversion_info = cb.reversion_info_read(None);
+        let callee_address = cb.call_context_read_as_word(None, CallContextFieldTag::CalleeAddress);
+
+        // Call ends with SELFDESTRUCT must be successful
+        cb.call_context_lookup_read(None, CallContextFieldTag::IsSuccess, WordLoHi::one());
+
+        let beneficiary = cb.query_account_address();
+        cb.stack_pop(beneficiary.to_word());
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write_unchecked(
+            tx_id.expr(),
+            beneficiary.to_word(),
+            1.expr(),
+            is_warm.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // For non-existing accounts the code_hash must be 0 in the rw_table.
+        let beneficiary_code_hash = cb.query_word_unchecked();
+        cb.account_read(
+            beneficiary.to_word(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.to_word(),
+        );
+        let beneficiary_not_exists = IsZeroWordGadget::construct(cb, &beneficiary_code_hash);
+
+        // The whole balance leaves the account, and is burnt if the beneficiary is the account
+        // itself.
+        let balance = cb.query_word32();
+        cb.account_write(
+            callee_address.to_word(),
+            AccountFieldTag::Balance,
+            WordLoHi::zero(),
+            balance.to_word(),
+            Some(&mut reversion_info),
+        );
+        let is_beneficiary_callee = IsEqualWordGadget::construct(cb, &beneficiary, &callee_address);
+        let not_beneficiary_callee = not::expr(is_beneficiary_callee.expr());
+        let transfer = cb.condition(not_beneficiary_callee.expr(), |cb| {
+            TransferToGadget::construct(
+                cb,
+                beneficiary.to_word(),
+                not::expr(beneficiary_not_exists.expr()),
+                false.expr(),
+                balance.clone(),
+                Some(&mut reversion_info),
+                true,
+            )
+        });
+
+        // Mark the account to be deleted in EndTx, unless the call is reverted.
+        let is_destructed_prev = cb.query_bool();
+        cb.account_destructed_write(
+            tx_id.expr(),
+            callee_address.to_word(),
+            1.expr(),
+            is_destructed_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // The account itself always exists, so there is no account creation when the balance is
+        // burnt.
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + select::expr(
+                is_warm.expr(),
+                0.expr(),
+                GasCost::COLD_ACCOUNT_ACCESS.expr(),
+            )
+            + not_beneficiary_callee.expr()
+                * beneficiary_not_exists.expr()
+                * not::expr(transfer.value_is_zero.expr())
+                * GasCost::NEW_ACCOUNT.expr();
+
+        // Write the access list, the balance, the transfer to the beneficiary and the mark.
+        let reversible_write_counter_delta =
+            3.expr() + not_beneficiary_callee.expr() * transfer.rw_delta();
+
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(cb.rw_counter_offset()),
+                gas_left: Delta(-gas_cost.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call.  The gas cost is paid out of the gas returned to the
+        // caller.
+        let restore_context = cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                true.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                gas_cost.expr(),
+                reversible_write_counter_delta,
+            )
+        });
+
+        Self {
+            opcode,
+            tx_id,
+            is_static,
+            reversion_info,
+            callee_address,
+            beneficiary,
+            is_warm,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            is_beneficiary_callee,
+            balance,
+            transfer,
+            is_destructed_prev,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id)))?;
+        self.is_static
+            .assign(region, offset, Value::known(F::from(call.is_static as u64)))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.callee_address
+            .assign_h160(region, offset, call.address)?;
+
+        let beneficiary = block.get_rws(step, 6).stack_value().to_address();
+        self.beneficiary.assign_h160(region, offset, beneficiary)?;
+
+        let (_, is_warm) = block.get_rws(step, 7).tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+
+        let code_hash = block.get_rws(step, 8).account_codehash_pair().0;
+        self.beneficiary_code_hash
+            .assign_u256(region, offset, code_hash)?;
+        self.beneficiary_not_exists
+            .assign_u256(region, offset, code_hash)?;
+
+        let (_, balance) = block.get_rws(step, 9).account_balance_pair();
+        self.balance.assign_u256(region, offset, balance)?;
+
+        self.is_beneficiary_callee.assign_u256(
+            region,
+            offset,
+            beneficiary.to_word(),
+            call.address.to_word(),
+        )?;
+        let mut rw_offset = 10;
+        let beneficiary_balance_pair = if beneficiary == call.address || balance.is_zero() {
+            (U256::zero(), U256::zero())
+        } else {
+            // Skip the creation of the beneficiary
+            if code_hash.is_zero() {
+                rw_offset += 1;
+            }
+            let pair = block.get_rws(step, rw_offset).account_balance_pair();
+            rw_offset += 1;
+            pair
+        };
+        self.transfer
+            .assign(region, offset, beneficiary_balance_pair, balance)?;
+
+        let (_, is_destructed_prev) = block
+            .get_rws(step, rw_offset)
+            .tx_destructed_account_value_pair();
+        rw_offset += 1;
+        self.is_destructed_prev.assign(
+            region,
+            offset,
+            Value::known(F::from(is_destructed_prev as u64)),
+        )?;
+
+        if !call.is_root {
+            self.restore_context
+                .assign(region, offset, block, call, step, rw_offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{address, bytecode, Address, Bytecode, ToWord, Word};
+    use lazy_static::lazy_static;
+    use mock::{generate_mock_call_bytecode, MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS};
+
+    lazy_static! {
+        static ref BENEFICIARY: Address = address!("0xaabbccddeeffdeadbeef00000000000000000000");
+    }
+
+    fn selfdestruct_code(beneficiary: Address, is_warm: bool) -> Bytecode {
+        let mut code = Bytecode::default();
+        if is_warm {
+            code.append(&bytecode! {
+                .op_balance(beneficiary)
+                POP
+            });
+        }
+        code.append(&bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        });
+        code
+    }
+
+    fn test_root_ok(beneficiary: Address, beneficiary_balance: Option<Word>, is_warm: bool) {
+        let contract = MOCK_ACCOUNTS[0];
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(contract)
+                    .balance(Word::from(1_u64 << 20))
+                    .code(selfdestruct_code(beneficiary, is_warm));
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(1_u64 << 20));
+                if let Some(balance) = beneficiary_balance {
+                    accs[2].address(beneficiary).balance(balance);
+                } else {
+                    accs[2]
+                        .address(MOCK_ACCOUNTS[2])
+                        .balance(Word::from(1_u64 << 20));
+                }
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        let balance = 1_i128 << 20;
+        let mut test =
+            CircuitTestBuilder::new_from_test_ctx(ctx).expect_balance_change(contract, -balance);
+        if beneficiary != contract {
+            test = test.expect_balance_change(beneficiary, balance);
+        }
+        test.run();
+    }
+
+    fn test_internal_ok(beneficiary: Address, balance: Word, is_warm: bool, revert: bool) {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code A calls code B, which self destructs.
+        let mut instructions_after_call = Bytecode::default();
+        if revert {
+            instructions_after_call.append(&bytecode! {
+                PUSH1(0)
+                PUSH1(0)
+                REVERT
+            });
+        }
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            instructions_after_call,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(addr_b)
+                    .balance(balance)
+                    .code(selfdestruct_code(beneficiary, is_warm));
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1_u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn selfdestruct_gadget_twice() {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code A calls code B twice.  B keeps its code until the end of the tx, so it self
+        // destructs again, with an empty balance, and is only deleted once.
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            instructions_after_call: bytecode! {
+                POP
+                .op_call(0xFFFF, addr_b, 0, 0, 0, 0, 0)
+                POP
+            },
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(addr_b)
+                    .balance(Word::from(1000))
+                    .code(selfdestruct_code(*BENEFICIARY, false));
+                accs[1].address(addr_a).code(code_a);
+                accs[2]
+                    .address(MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1_u64 << 20));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[1].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn selfdestruct_gadget_root() {
+        // Non-existing beneficiary, which is created.
+        test_root_ok(*BENEFICIARY, None, false);
+        test_root_ok(*BENEFICIARY, None, true);
+        // Existing beneficiary
+        test_root_ok(*BENEFICIARY, Some(Word::from(900)), false);
+        // The balance is burnt.
+        test_root_ok(MOCK_ACCOUNTS[0], None, true);
+    }
+
+    #[test]
+    fn selfdestruct_gadget_internal() {
+        test_internal_ok(*BENEFICIARY, Word::from(1000), false, false);
+        test_internal_ok(*BENEFICIARY, Word::zero(), true, false);
+        test_internal_ok(MOCK_ACCOUNTS[1], Word::from(1000), true, false);
+        // The caller reverts, so the account is not deleted.
+        test_internal_ok(*BENEFICIARY, Word::from(1000), false, true);
+    }
+}
@@ -517,7 +517,9 @@ mod test {
             )
             .unwrap();
 
-            CircuitTestBuilder::new_from_test_ctx(ctx).run();
+            CircuitTestBuilder::new_from_test_ctx(ctx)
+                .expect_storage_write(MOCK_ACCOUNTS[0], key, value_prev, value)
+                .run();
         }
     }
 }
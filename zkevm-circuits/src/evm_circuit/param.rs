@@ -1,11 +1,15 @@
 //! Constants and parameters for the EVM circuit
 use super::table::Table;
+#[cfg(feature = "evm-circuit")]
 use crate::evm_circuit::{step::ExecutionState, EvmCircuit};
+#[cfg(feature = "evm-circuit")]
 use bus_mapping::circuit_input_builder::FeatureConfig;
+#[cfg(feature = "evm-circuit")]
 use halo2_proofs::{
     halo2curves::bn256::Fr,
     plonk::{Circuit, ConstraintSystem},
 };
+#[cfg(feature = "evm-circuit")]
 use std::collections::HashMap;
 
 // Step dimension
@@ -42,7 +46,9 @@ pub(crate) const EVM_LOOKUP_COLS: usize = FIXED_TABLE_LOOKUPS
     + BLOCK_TABLE_LOOKUPS
     + COPY_TABLE_LOOKUPS
     + KECCAK_TABLE_LOOKUPS
-    + EXP_TABLE_LOOKUPS;
+    + EXP_TABLE_LOOKUPS
+    + SIG_TABLE_LOOKUPS
+    + WD_CREDIT_TABLE_LOOKUPS;
 
 /// Lookups done per row.
 pub const LOOKUP_CONFIG: &[(Table, usize)] = &[
@@ -54,6 +60,8 @@ pub const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::Copy, COPY_TABLE_LOOKUPS),
     (Table::Keccak, KECCAK_TABLE_LOOKUPS),
     (Table::Exp, EXP_TABLE_LOOKUPS),
+    (Table::Sig, SIG_TABLE_LOOKUPS),
+    (Table::WdCredit, WD_CREDIT_TABLE_LOOKUPS),
 ];
 
 /// Fixed Table lookups done in EVMCircuit
@@ -80,6 +88,12 @@ pub const KECCAK_TABLE_LOOKUPS: usize = 1;
 /// Exp Table lookups done in EVMCircuit
 pub const EXP_TABLE_LOOKUPS: usize = 1;
 
+/// Sig Table lookups done in EVMCircuit
+pub const SIG_TABLE_LOOKUPS: usize = 1;
+
+/// Wd Credit Table lookups done in EVMCircuit
+pub const WD_CREDIT_TABLE_LOOKUPS: usize = 1;
+
 /// Maximum number of bytes that an integer can fit in field without wrapping
 /// around.
 pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
@@ -103,6 +117,10 @@ pub(crate) const N_BYTES_MEMORY_WORD_SIZE: usize = 4;
 
 pub(crate) const STACK_CAPACITY: usize = 1024;
 
+pub use bus_mapping::circuit_input_builder::{
+    MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS, MAX_DESTRUCTED_ACCOUNTS,
+};
+
 // Number of bytes that will be used of prorgam counter. Although the maximum
 // size of execution bytecode could be at most 128kB due to the size limit of a
 // transaction, which could be covered by 3 bytes, we still support program
@@ -139,7 +157,8 @@ pub(crate) const N_BYTES_BLOCK: usize = N_BYTES_COINBASE
 
 pub(crate) const N_BYTES_EXTRA_VALUE: usize = N_BYTES_WORD // block hash
     + N_BYTES_WORD // state root
-    + N_BYTES_WORD; // prev state root
+    + N_BYTES_WORD // prev state root
+    + N_BYTES_WORD; // prev pi digest
 
 // Number of bytes that will be used for tx values
 pub(crate) const N_BYTES_TX_NONCE: usize = N_BYTES_U64;
@@ -152,6 +171,14 @@ pub(crate) const N_BYTES_TX_VALUE: usize = N_BYTES_WORD;
 pub(crate) const N_BYTES_TX_CALLDATA_LEN: usize = N_BYTES_CALLDATASIZE;
 pub(crate) const N_BYTES_TX_CALLDATA_GASCOST: usize = N_BYTES_U64;
 pub(crate) const N_BYTES_TX_TXSIGNHASH: usize = N_BYTES_WORD;
+pub(crate) const N_BYTES_TX_ACCESS_LIST_ADDRESSES_LEN: usize = N_BYTES_U64;
+pub(crate) const N_BYTES_TX_ACCESS_LIST_STORAGE_KEYS_LEN: usize = N_BYTES_U64;
+pub(crate) const N_BYTES_TX_MAX_FEE_PER_GAS: usize = N_BYTES_WORD;
+pub(crate) const N_BYTES_TX_MAX_PRIORITY_FEE_PER_GAS: usize = N_BYTES_WORD;
+// An address slot, and a storage key slot with the address of the key, per access list entry
+pub(crate) const N_BYTES_TX_ACCESS_LIST: usize = MAX_ACCESS_LIST_ADDRESSES
+    * N_BYTES_ACCOUNT_ADDRESS
+    + MAX_ACCESS_LIST_STORAGE_KEYS * (N_BYTES_WORD + N_BYTES_ACCOUNT_ADDRESS);
 pub(crate) const N_BYTES_TX: usize = N_BYTES_TX_NONCE
     + N_BYTES_TX_GAS_LIMIT
     + N_BYTES_TX_GASPRICE
@@ -161,13 +188,19 @@ pub(crate) const N_BYTES_TX: usize = N_BYTES_TX_NONCE
     + N_BYTES_TX_VALUE
     + N_BYTES_TX_CALLDATA_LEN
     + N_BYTES_TX_CALLDATA_GASCOST
-    + N_BYTES_TX_TXSIGNHASH;
+    + N_BYTES_TX_TXSIGNHASH
+    + N_BYTES_TX_ACCESS_LIST_ADDRESSES_LEN
+    + N_BYTES_TX_ACCESS_LIST_STORAGE_KEYS_LEN
+    + N_BYTES_TX_MAX_FEE_PER_GAS
+    + N_BYTES_TX_MAX_PRIORITY_FEE_PER_GAS
+    + N_BYTES_TX_ACCESS_LIST;
 
 pub(crate) const N_BYTES_WITHDRAWAL: usize = N_BYTES_U64 //id 
     + N_BYTES_U64 // validator id
     + N_BYTES_ACCOUNT_ADDRESS // address
     + N_BYTES_U64; // amount
 
+#[cfg(feature = "evm-circuit")]
 lazy_static::lazy_static! {
     static ref INVALID_TX_CONFIG: FeatureConfig = FeatureConfig {
         invalid_tx: true,
@@ -178,12 +211,13 @@ lazy_static::lazy_static! {
     // We garentee the heights of other ExecutionStates remains unchanged in the following test
     pub(crate) static ref EXECUTION_STATE_HEIGHT_MAP : HashMap<ExecutionState, usize> = get_step_height_map(*INVALID_TX_CONFIG);
 }
+#[cfg(feature = "evm-circuit")]
 fn get_step_height_map(feature_config: FeatureConfig) -> HashMap<ExecutionState, usize> {
     let mut meta = ConstraintSystem::<Fr>::default();
     let circuit = EvmCircuit::configure_with_params(&mut meta, feature_config);
     circuit.0.execution.height_map
 }
-#[cfg(test)]
+#[cfg(all(test, feature = "evm-circuit"))]
 mod tests {
     use super::*;
 
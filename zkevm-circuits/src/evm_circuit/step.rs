@@ -7,9 +7,11 @@ use super::{
     param::MAX_STEP_HEIGHT,
     util::{evm_cm_distribute_advice, CachedRegion, Cell, CellType},
 };
+#[cfg(feature = "evm-circuit")]
+use crate::evm_circuit::param::EXECUTION_STATE_HEIGHT_MAP;
 use crate::{
     evm_circuit::{
-        param::{EXECUTION_STATE_HEIGHT_MAP, STEP_WIDTH},
+        param::STEP_WIDTH,
         witness::{Block, Call, ExecStep},
     },
     util::{
@@ -24,7 +26,7 @@ use bus_mapping::{
     evm::OpcodeId,
     precompile::PrecompileCalls,
 };
-use eth_types::{evm_unimplemented, Field, ToWord};
+use eth_types::{Field, ToWord};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Advice, Column, ConstraintSystem, Error, Expression},
@@ -253,13 +255,6 @@ impl From<&ExecStep> for ExecutionState {
                     return ExecutionState::LOG;
                 }
 
-                macro_rules! dummy {
-                    ($name:expr) => {{
-                        evm_unimplemented!("{:?} is implemented with DummyGadget", $name);
-                        $name
-                    }};
-                }
-
                 match op {
                     OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
                     OpcodeId::ADDMOD => ExecutionState::ADDMOD,
@@ -324,8 +319,7 @@ impl From<&ExecStep> for ExecutionState {
                     OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
                     OpcodeId::CREATE => ExecutionState::CREATE,
                     OpcodeId::CREATE2 => ExecutionState::CREATE2,
-                    // dummy ops
-                    OpcodeId::SELFDESTRUCT => dummy!(ExecutionState::SELFDESTRUCT),
+                    OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
                     _ => unimplemented!("unimplemented opcode {:?}", op),
                 }
             }
@@ -382,8 +376,9 @@ impl ExecutionState {
         )
     }
 
-    pub(crate) fn precompile_base_gas_cost(&self) -> u64 {
-        (match self {
+    /// Get the precompile handled by the execution state
+    pub fn precompile(&self) -> Option<PrecompileCalls> {
+        Some(match self {
             Self::PrecompileEcRecover => PrecompileCalls::ECRecover,
             Self::PrecompileSha256 => PrecompileCalls::Sha256,
             Self::PrecompileRipemd160 => PrecompileCalls::Ripemd160,
@@ -393,9 +388,14 @@ impl ExecutionState {
             Self::PrecompileBn256ScalarMul => PrecompileCalls::Bn128Mul,
             Self::PrecompileBn256Pairing => PrecompileCalls::Bn128Pairing,
             Self::PrecompileBlake2f => PrecompileCalls::Blake2F,
-            _ => return 0,
+            _ => return None,
         })
-        .base_gas_cost()
+    }
+
+    pub(crate) fn precompile_base_gas_cost(&self) -> u64 {
+        self.precompile()
+            .map(|precompile| precompile.base_gas_cost())
+            .unwrap_or_default()
     }
 
     pub(crate) fn halts_in_exception(&self) -> bool {
@@ -597,10 +597,12 @@ impl ExecutionState {
     }
 
     /// Get the state hight
+    #[cfg(feature = "evm-circuit")]
     pub fn get_step_height_option(&self) -> Option<usize> {
         EXECUTION_STATE_HEIGHT_MAP.get(self).copied()
     }
 
+    #[cfg(feature = "evm-circuit")]
     pub(crate) fn get_step_height(&self) -> usize {
         self.get_step_height_option()
             .unwrap_or_else(|| panic!("Execution state unknown: {:?}", self))
@@ -191,6 +191,10 @@ pub enum Table {
     Keccak,
     /// Lookup for exp table
     Exp,
+    /// Lookup for sig table
+    Sig,
+    /// Lookup for wd credit table
+    WdCredit,
 }
 
 #[derive(Clone, Debug)]
@@ -359,6 +363,26 @@ pub(crate) enum Lookup<F> {
         exponent_lo_hi: [Expression<F>; 2],
         exponentiation_lo_hi: [Expression<F>; 2],
     },
+    /// Lookup to sig table, which contains the verified signatures of the ecRecover calls.
+    SigTable {
+        /// Message hash, reduced modulo the secp256k1 scalar field order.
+        msg_hash: WordLoHi<Expression<F>>,
+        /// Recovery id, the parity of the y coordinate of the public key.
+        sig_v: Expression<F>,
+        /// Signature r.
+        sig_r: WordLoHi<Expression<F>>,
+        /// Signature s.
+        sig_s: WordLoHi<Expression<F>>,
+        /// Address recovered from the signature.
+        recovered_addr: WordLoHi<Expression<F>>,
+    },
+    /// Lookup to wd credit table, which contains the rw counters of the withdrawal credits.
+    WdCreditTable {
+        /// rw counter of the first write of the credits.
+        rw_counter_start: Expression<F>,
+        /// rw counter following the last write of the credits.
+        rw_counter_end: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -378,6 +402,8 @@ impl<F: Field> Lookup<F> {
             Self::CopyTable { .. } => Table::Copy,
             Self::KeccakTable { .. } => Table::Keccak,
             Self::ExpTable { .. } => Table::Exp,
+            Self::SigTable { .. } => Table::Sig,
+            Self::WdCreditTable { .. } => Table::WdCredit,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -496,6 +522,32 @@ impl<F: Field> Lookup<F> {
                 exponentiation_lo_hi[0].clone(),
                 exponentiation_lo_hi[1].clone(),
             ],
+            Self::SigTable {
+                msg_hash,
+                sig_v,
+                sig_r,
+                sig_s,
+                recovered_addr,
+            } => vec![
+                1.expr(), // q_enable
+                msg_hash.lo(),
+                msg_hash.hi(),
+                sig_v.clone(),
+                sig_r.lo(),
+                sig_r.hi(),
+                sig_s.lo(),
+                sig_s.hi(),
+                recovered_addr.lo(),
+                recovered_addr.hi(),
+            ],
+            Self::WdCreditTable {
+                rw_counter_start,
+                rw_counter_end,
+            } => vec![
+                1.expr(), // q_enable
+                rw_counter_start.clone(),
+                rw_counter_end.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
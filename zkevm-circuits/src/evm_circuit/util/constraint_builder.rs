@@ -395,6 +395,20 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         )
     }
 
+    /// Returns the names of the constraints added so far.
+    pub(crate) fn constraint_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        let Constraints {
+            step,
+            step_first,
+            step_last,
+            not_step_last,
+        } = &self.constraints;
+        [step, step_first, step_last, not_step_last]
+            .into_iter()
+            .flatten()
+            .map(|(name, _)| *name)
+    }
+
     pub(crate) fn query_expression<T>(&mut self, f: impl FnMut(&mut VirtualCells<F>) -> T) -> T {
         query_expression(self.meta, f)
     }
@@ -420,6 +434,12 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         self.rw_counter_offset.clone()
     }
 
+    /// Increase the rw_counter_offset by `delta` rws that the gadget doesn't look up, so that
+    /// the lookups after them use the right rw_counter.
+    pub(crate) fn increase_rw_counter_offset(&mut self, delta: Expression<F>) {
+        self.rw_counter_offset = self.rw_counter_offset.clone() + self.condition_expr() * delta;
+    }
+
     pub(crate) fn stack_pointer_offset(&self) -> Expression<F> {
         self.stack_pointer_offset.clone()
     }
@@ -904,6 +924,32 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             ),
         );
     }
+
+    // Destructed accounts
+    pub(crate) fn account_destructed_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: WordLoHi<Expression<F>>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TxDestructedAccount write",
+            Target::TxDestructedAccount,
+            RwValues::new(
+                tx_id,
+                account_address.compress(),
+                0.expr(),
+                WordLoHi::zero(),
+                WordLoHi::from_lo_unchecked(value),
+                WordLoHi::from_lo_unchecked(value_prev),
+                WordLoHi::zero(),
+            ),
+            reversion_info,
+        );
+    }
+
     pub(crate) fn account_storage_access_list_write(
         &mut self,
         tx_id: Expression<F>,
@@ -1449,6 +1495,42 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Sig Table
+    pub(crate) fn sig_table_lookup(
+        &mut self,
+        msg_hash: WordLoHi<Expression<F>>,
+        sig_v: Expression<F>,
+        sig_r: WordLoHi<Expression<F>>,
+        sig_s: WordLoHi<Expression<F>>,
+        recovered_addr: WordLoHi<Expression<F>>,
+    ) {
+        self.add_lookup(
+            "sig table lookup",
+            Lookup::SigTable {
+                msg_hash,
+                sig_v,
+                sig_r,
+                sig_s,
+                recovered_addr,
+            },
+        );
+    }
+
+    // Wd Credit Table
+    pub(crate) fn wd_credit_table_lookup(
+        &mut self,
+        rw_counter_start: Expression<F>,
+        rw_counter_end: Expression<F>,
+    ) {
+        self.add_lookup(
+            "wd credit table lookup",
+            Lookup::WdCreditTable {
+                rw_counter_start,
+                rw_counter_end,
+            },
+        );
+    }
+
     // Validation
 
     pub(crate) fn validate_degree(&self, degree: usize, name: &'static str) {
@@ -1496,17 +1578,20 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             sum::expr(&conditions),
         );
 
-        // TODO: constraining the same cells repeatedly requires a height-resetting mechanism
-        // on the cell manager. In this case, since only identity precompile is added
-        // this height management maneuver is temporarily left out.
+        // Every constraint queries the cells of a different gadget in the next step, so the
+        // next step's cell manager is reset before each of them to hand out the gadget's first
+        // cells.
+        let next_cell_manager = self.next.cell_manager.clone();
         for ((&next_state, condition), constraint) in next_states
             .iter()
             .zip(conditions.into_iter())
             .zip(constraints.into_iter())
         {
+            self.next.cell_manager = next_cell_manager.clone();
             // constrain the next step.
             self.constrain_next_step(next_state, Some(condition), constraint);
         }
+        self.next.cell_manager = next_cell_manager;
     }
 
     /// This function needs to be used with extra precaution. You need to make
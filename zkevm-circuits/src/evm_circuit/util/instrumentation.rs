@@ -16,6 +16,10 @@ type ColumnSize = (usize, usize, usize);
 pub struct Instrument {
     // States -> Cell Types -> (width, height, num_cells)
     states: Vec<(ExecutionState, StepSize)>,
+    // States -> Gadget name
+    gadgets: Vec<(ExecutionState, &'static str)>,
+    // States -> Gadget name -> Constraint name
+    constraints: Vec<(ExecutionState, &'static str, &'static str)>,
 }
 
 impl Instrument {
@@ -24,6 +28,7 @@ impl Instrument {
     pub(crate) fn on_gadget_built<F: Field>(
         &mut self,
         execution_state: ExecutionState,
+        name: &'static str,
         cb: &EVMConstraintBuilder<F>,
     ) {
         let sizes = cb
@@ -35,6 +40,24 @@ impl Instrument {
             .collect::<Vec<_>>();
 
         self.states.push((execution_state, sizes));
+        self.gadgets.push((execution_state, name));
+        self.constraints.extend(
+            cb.constraint_names()
+                .map(|constraint| (execution_state, name, constraint)),
+        );
+    }
+
+    /// Returns the `ExecutionState`s that have a gadget in the compiled
+    /// EVMCircuit, together with the name of the gadget.
+    pub fn gadgets(&self) -> &[(ExecutionState, &'static str)] {
+        &self.gadgets
+    }
+
+    /// Returns the constraints of each gadget in the compiled EVMCircuit, as
+    /// the `ExecutionState`, the name of the gadget, which is also the name of
+    /// its gates, and the name of the constraint.
+    pub fn constraints(&self) -> &[(ExecutionState, &'static str, &'static str)] {
+        &self.constraints
     }
 
     /// Dissasembles the instrumentation data and returns a collection of
@@ -104,6 +127,12 @@ impl Instrument {
                     CellType::Lookup(Table::Exp) => {
                         report.exp_table = data_entry;
                     }
+                    CellType::Lookup(Table::Sig) => {
+                        report.sig_table = data_entry;
+                    }
+                    CellType::Lookup(Table::WdCredit) => {
+                        report.wd_credit_table = data_entry;
+                    }
                 }
             }
             report_collection.push(report);
@@ -131,6 +160,8 @@ pub struct ExecStateReport {
     pub copy_table: StateReportRow,
     pub keccak_table: StateReportRow,
     pub exp_table: StateReportRow,
+    pub sig_table: StateReportRow,
+    pub wd_credit_table: StateReportRow,
 }
 
 impl From<ExecutionState> for ExecStateReport {
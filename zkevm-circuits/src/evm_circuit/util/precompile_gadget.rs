@@ -3,7 +3,10 @@ use eth_types::Field;
 use gadgets::util::{not, Expr};
 use halo2_proofs::plonk::Expression;
 
-use crate::evm_circuit::step::{ExecutionState, ExecutionState::ErrorOutOfGasPrecompile};
+use crate::evm_circuit::{
+    param::N_BYTES_WORD,
+    step::{ExecutionState, ExecutionState::ErrorOutOfGasPrecompile},
+};
 
 use super::{
     constraint_builder::{BoxedClosure, ConstrainBuilderCommon, EVMConstraintBuilder},
@@ -28,16 +31,19 @@ impl<F: Field> PrecompileGadget<F> {
         _rd_offset: Expression<F>,
         _rd_length: Expression<F>,
         precompile_return_length: Expression<F>,
+        // number of input bytes taken by the precompile call.
+        precompile_input_len: Expression<F>,
         // input bytes to precompile call.
-        _input_bytes_rlc: Expression<F>,
+        input_bytes_rlc: Expression<F>,
         // output result from precompile call.
-        _output_bytes_rlc: Expression<F>,
+        output_bytes_rlc: Expression<F>,
         // returned bytes back to caller.
         _return_bytes_rlc: Expression<F>,
     ) -> Self {
         let address = BinaryNumberGadget::construct(cb, callee_address.expr());
 
         let conditions = vec![
+            address.value_equals(PrecompileCalls::ECRecover),
             address.value_equals(PrecompileCalls::Identity),
             // match more precompiles
         ]
@@ -48,12 +54,50 @@ impl<F: Field> PrecompileGadget<F> {
         .collect::<Vec<_>>();
 
         let next_states = vec![
+            ExecutionState::PrecompileEcRecover,
             ExecutionState::PrecompileIdentity, // add more precompile execution states
         ];
 
+        let ecrecover_return_length = precompile_return_length.clone();
+        let ecrecover_input_len = precompile_input_len.clone();
         let constraints: Vec<BoxedClosure<F>> = vec![
+            Box::new(|cb| {
+                // ECRecover
+                // The cells are queried in the same order as the first cells of the
+                // `EcrecoverGadget`, so that they are the gadget's cells in the next step.
+                let recover_input_bytes_rlc = cb.query_cell_phase2();
+                let recover_output_bytes_rlc = cb.query_cell_phase2();
+                let is_recovered = cb.query_bool();
+                cb.require_equal(
+                    "precompile takes the call data padded to 128 bytes",
+                    ecrecover_input_len,
+                    PrecompileCalls::ECRecover.input_len().unwrap().expr(),
+                );
+                cb.require_equal(
+                    "precompile input bytes rlc",
+                    input_bytes_rlc,
+                    recover_input_bytes_rlc.expr(),
+                );
+                cb.require_equal(
+                    "precompile returns the recovered address or nothing",
+                    ecrecover_return_length,
+                    is_recovered.expr() * N_BYTES_WORD.expr(),
+                );
+                cb.condition(is_recovered.expr(), |cb| {
+                    cb.require_equal(
+                        "precompile output bytes rlc",
+                        output_bytes_rlc,
+                        recover_output_bytes_rlc.expr(),
+                    );
+                });
+            }),
             Box::new(|cb| {
                 // Identity
+                cb.require_equal(
+                    "precompile takes all the call data",
+                    precompile_input_len,
+                    cd_length.clone(),
+                );
                 cb.require_equal(
                     "input length and precompile return length are the same",
                     cd_length,
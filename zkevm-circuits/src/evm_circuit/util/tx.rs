@@ -1,28 +1,31 @@
 use crate::{
     evm_circuit::{
-        param::N_BYTES_U64,
+        param::{MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS, N_BYTES_U64},
         step::ExecutionState,
         util::{
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition, Transition::*,
             },
             math_gadget::{
-                AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, MulWordByU64Gadget,
+                AddWordsGadget, CmpWordsGadget, ConstantDivisionGadget, IsEqualGadget,
+                MulWordByU64Gadget,
             },
-            CachedRegion, Cell,
+            CachedRegion, Cell, StepRws,
         },
         witness::{Block, Transaction},
     },
-    table::{CallContextFieldTag, TxContextFieldTag, TxReceiptFieldTag},
-    util::word::{Word32Cell, WordLoHi, WordLoHiCell},
+    table::{BlockContextFieldTag, CallContextFieldTag, TxContextFieldTag, TxReceiptFieldTag},
+    util::word::{Word32Cell, WordExpr, WordLoHi, WordLoHiCell},
 };
 use bus_mapping::operation::Target;
-use eth_types::{evm_types::GasCost, Field};
-use gadgets::util::{select, Expr, Scalar};
+use eth_types::{evm_types::GasCost, Address, Field, U256};
+use gadgets::util::{not, select, sum, Expr, Scalar};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
 };
+use itertools::Itertools;
+use std::array;
 use strum::EnumCount;
 
 /// Gadget for beginning a tx
@@ -202,8 +205,16 @@ pub(crate) struct TxDataGadget<F> {
     pub(crate) gas: Cell<F>,
     pub(crate) call_data_length: Cell<F>,
     pub(crate) call_data_gas_cost: Cell<F>,
+    pub(crate) access_list_addresses_len: Cell<F>,
+    pub(crate) access_list_storage_keys_len: Cell<F>,
     pub(crate) gas_price: Word32Cell<F>,
     pub(crate) value: Word32Cell<F>,
+    pub(crate) max_fee_per_gas: Word32Cell<F>,
+
+    pub(crate) add_base_fee_by_priority_fee: AddWordsGadget<F, 2, true>,
+    pub(crate) max_fee_lt_priority_price: CmpWordsGadget<F, Word32Cell<F>, Word32Cell<F>>,
+    pub(crate) max_fee_lt_base_fee: CmpWordsGadget<F, Word32Cell<F>, Word32Cell<F>>,
+    pub(crate) max_fee_lt_max_priority_fee: CmpWordsGadget<F, Word32Cell<F>, Word32Cell<F>>,
 
     pub(crate) mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
     pub(crate) call_data_word_length: ConstantDivisionGadget<F, N_BYTES_U64>,
@@ -218,16 +229,24 @@ impl<F: Field> TxDataGadget<F> {
         tx_id: Expression<F>,
         calculate_total_cost: bool,
     ) -> Self {
-        let [nonce, gas, is_create, call_data_length, call_data_gas_cost] = [
-            TxContextFieldTag::Nonce,
-            TxContextFieldTag::Gas,
-            TxContextFieldTag::IsCreate,
-            TxContextFieldTag::CallDataLength,
-            TxContextFieldTag::CallDataGasCost,
+        let [nonce, gas, is_create, call_data_length, call_data_gas_cost, access_list_addresses_len, access_list_storage_keys_len] =
+            [
+                TxContextFieldTag::Nonce,
+                TxContextFieldTag::Gas,
+                TxContextFieldTag::IsCreate,
+                TxContextFieldTag::CallDataLength,
+                TxContextFieldTag::CallDataGasCost,
+                TxContextFieldTag::AccessListAddressesLen,
+                TxContextFieldTag::AccessListStorageKeysLen,
+            ]
+            .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
+        let [gas_price, value, max_fee_per_gas, max_priority_fee_per_gas] = [
+            TxContextFieldTag::GasPrice,
+            TxContextFieldTag::Value,
+            TxContextFieldTag::MaxFeePerGas,
+            TxContextFieldTag::MaxPriorityFeePerGas,
         ]
-        .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
-        let [gas_price, value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
-            .map(|field_tag| cb.tx_context_as_word32(tx_id.expr(), field_tag, None));
+        .map(|field_tag| cb.tx_context_as_word32(tx_id.expr(), field_tag, None));
 
         let [caller_address, callee_address] = [
             TxContextFieldTag::CallerAddress,
@@ -235,8 +254,50 @@ impl<F: Field> TxDataGadget<F> {
         ]
         .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
 
-        // TODO: Implement EIP 1559 (currently it only supports legacy
-        // transaction format)
+        // The gas price is the effective gas price of EIP-1559:
+        // min(max_fee_per_gas, base_fee + max_priority_fee_per_gas).  Both fees are the gas
+        // price for the transactions before EIP-1559, for which it's the gas price itself.
+        let base_fee = cb.query_word32();
+        cb.block_lookup(
+            BlockContextFieldTag::BaseFee.expr(),
+            None,
+            base_fee.to_word(),
+        );
+        // EIP-1559: max_fee_per_gas >= base_fee and max_priority_fee_per_gas <= max_fee_per_gas
+        let max_fee_lt_base_fee =
+            CmpWordsGadget::construct(cb, max_fee_per_gas.clone(), base_fee.clone());
+        cb.require_zero(
+            "max_fee_per_gas >= base_fee",
+            max_fee_lt_base_fee.lt.clone(),
+        );
+        let max_fee_lt_max_priority_fee = CmpWordsGadget::construct(
+            cb,
+            max_fee_per_gas.clone(),
+            max_priority_fee_per_gas.clone(),
+        );
+        cb.require_zero(
+            "max_priority_fee_per_gas <= max_fee_per_gas",
+            max_fee_lt_max_priority_fee.lt.clone(),
+        );
+
+        let priority_price = cb.query_word32();
+        let add_base_fee_by_priority_fee = AddWordsGadget::construct(
+            cb,
+            [base_fee.clone(), max_priority_fee_per_gas.clone()],
+            priority_price.clone(),
+        );
+        let max_fee_lt_priority_price =
+            CmpWordsGadget::construct(cb, max_fee_per_gas.clone(), priority_price.clone());
+        cb.require_equal_word(
+            "gas_price == min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)",
+            gas_price.to_word(),
+            WordLoHi::select(
+                max_fee_lt_priority_price.lt.clone(),
+                max_fee_per_gas.to_word(),
+                priority_price.to_word(),
+            ),
+        );
+
         // Calculate transaction gas fee
         let mul_gas_fee_by_gas = MulWordByU64Gadget::construct(cb, gas_price.clone(), gas.expr());
 
@@ -261,8 +322,15 @@ impl<F: Field> TxDataGadget<F> {
             gas,
             call_data_length,
             call_data_gas_cost,
+            access_list_addresses_len,
+            access_list_storage_keys_len,
             gas_price,
             value,
+            max_fee_per_gas,
+            add_base_fee_by_priority_fee,
+            max_fee_lt_priority_price,
+            max_fee_lt_base_fee,
+            max_fee_lt_max_priority_fee,
             mul_gas_fee_by_gas,
             call_data_word_length,
             caller_address,
@@ -286,9 +354,22 @@ impl<F: Field> TxDataGadget<F> {
             GasCost::CREATION_TX.expr(),
             GasCost::TX.expr(),
         ) + self.call_data_gas_cost.expr()
+            + self.access_list_gas_cost()
             + init_code_gas_cost.expr()
     }
 
+    /// Gas cost of the access list (EIP-2930).
+    pub(crate) fn access_list_gas_cost(&self) -> Expression<F> {
+        self.access_list_addresses_len.expr() * GasCost::ACCESS_LIST_PER_ADDRESS.expr()
+            + self.access_list_storage_keys_len.expr() * GasCost::ACCESS_LIST_PER_STORAGE_KEY.expr()
+    }
+
+    /// Number of writes to the access list needed to pre-warm the addresses and storage keys
+    /// of the access list, one per slot in use of the TxAccessListGadget.
+    pub(crate) fn access_list_rw_delta(&self) -> Expression<F> {
+        self.access_list_addresses_len.expr() + self.access_list_storage_keys_len.expr()
+    }
+
     pub(crate) fn total_cost(&self) -> Word32Cell<F> {
         self.gas_mul_gas_price_plus_value
             .clone()
@@ -301,6 +382,7 @@ impl<F: Field> TxDataGadget<F> {
         &self,
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
+        block: &Block<F>,
         tx: &Transaction,
     ) -> Result<(), Error> {
         let gas_fee = tx.gas_price * tx.gas();
@@ -321,10 +403,42 @@ impl<F: Field> TxDataGadget<F> {
             offset,
             Value::known(tx.call_data_gas_cost().scalar()),
         )?;
+        self.access_list_addresses_len.assign(
+            region,
+            offset,
+            Value::known(tx.access_list_addresses_len().scalar()),
+        )?;
+        self.access_list_storage_keys_len.assign(
+            region,
+            offset,
+            Value::known(tx.access_list_storage_keys_len().scalar()),
+        )?;
         self.call_data_word_length
             .assign(region, offset, tx.call_data.len() as u128 + 31)?;
         self.gas_price.assign_u256(region, offset, tx.gas_price)?;
         self.value.assign_u256(region, offset, tx.value)?;
+        let base_fee = block.context.base_fee;
+        let (max_fee_per_gas, max_priority_fee_per_gas) =
+            (tx.max_fee_per_gas(), tx.max_priority_fee_per_gas());
+        let priority_price = base_fee + max_priority_fee_per_gas;
+        self.max_fee_per_gas
+            .assign_u256(region, offset, max_fee_per_gas)?;
+        self.add_base_fee_by_priority_fee.assign(
+            region,
+            offset,
+            [base_fee, max_priority_fee_per_gas],
+            priority_price,
+        )?;
+        self.max_fee_lt_priority_price
+            .assign(region, offset, max_fee_per_gas, priority_price)?;
+        self.max_fee_lt_base_fee
+            .assign(region, offset, max_fee_per_gas, base_fee)?;
+        self.max_fee_lt_max_priority_fee.assign(
+            region,
+            offset,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        )?;
         self.callee_address
             .assign_h160(region, offset, tx.to_or_contract_addr())?;
         self.caller_address.assign_h160(region, offset, tx.from)?;
@@ -348,3 +462,171 @@ impl<F: Field> TxDataGadget<F> {
         Ok(())
     }
 }
+
+/// Gadget for pre-warming the access list of a tx (EIP-2930).  Each address and storage key
+/// of the access list is read from its slot in the tx table and written to the tx access list,
+/// the addresses first.
+#[derive(Clone, Debug)]
+pub(crate) struct TxAccessListGadget<F> {
+    is_address: [Cell<F>; MAX_ACCESS_LIST_ADDRESSES],
+    address: [WordLoHiCell<F>; MAX_ACCESS_LIST_ADDRESSES],
+    is_address_warm_prev: [Cell<F>; MAX_ACCESS_LIST_ADDRESSES],
+    is_storage_key: [Cell<F>; MAX_ACCESS_LIST_STORAGE_KEYS],
+    storage_key: [WordLoHiCell<F>; MAX_ACCESS_LIST_STORAGE_KEYS],
+    storage_key_address: [WordLoHiCell<F>; MAX_ACCESS_LIST_STORAGE_KEYS],
+    is_storage_key_warm_prev: [Cell<F>; MAX_ACCESS_LIST_STORAGE_KEYS],
+}
+
+impl<F: Field> TxAccessListGadget<F> {
+    pub(crate) fn configure(
+        cb: &mut EVMConstraintBuilder<F>,
+        tx_id: Expression<F>,
+        tx: &TxDataGadget<F>,
+    ) -> Self {
+        let is_address = Self::slots_in_use(cb, tx.access_list_addresses_len.expr());
+        let is_storage_key = Self::slots_in_use(cb, tx.access_list_storage_keys_len.expr());
+
+        let mut address = vec![];
+        let mut is_address_warm_prev = vec![];
+        for (index, is_address) in is_address.iter().enumerate() {
+            cb.condition(is_address.expr(), |cb| {
+                let slot_address = cb.tx_context_as_word(
+                    tx_id.expr(),
+                    TxContextFieldTag::AccessListAddress,
+                    Some(index.expr()),
+                );
+                // No extra constraint being used here.
+                // Correctness will be enforced in build_tx_access_list_account_constraints
+                let is_warm_prev = cb.query_bool();
+                cb.account_access_list_write_unchecked(
+                    tx_id.expr(),
+                    slot_address.to_word(),
+                    1.expr(),
+                    is_warm_prev.expr(),
+                    None,
+                ); // rwc_delta += 1
+                address.push(slot_address);
+                is_address_warm_prev.push(is_warm_prev);
+            });
+        }
+
+        let mut storage_key = vec![];
+        let mut storage_key_address = vec![];
+        let mut is_storage_key_warm_prev = vec![];
+        for (index, is_storage_key) in is_storage_key.iter().enumerate() {
+            cb.condition(is_storage_key.expr(), |cb| {
+                let [slot_storage_key, slot_address] = [
+                    TxContextFieldTag::AccessListStorageKey,
+                    TxContextFieldTag::AccessListStorageKeyAddress,
+                ]
+                .map(|field_tag| {
+                    cb.tx_context_as_word(tx_id.expr(), field_tag, Some(index.expr()))
+                });
+                let is_warm_prev = cb.query_bool();
+                cb.account_storage_access_list_write(
+                    tx_id.expr(),
+                    slot_address.to_word(),
+                    slot_storage_key.to_word(),
+                    WordLoHi::one(),
+                    WordLoHi::from_lo_unchecked(is_warm_prev.expr()),
+                    None,
+                ); // rwc_delta += 1
+                storage_key.push(slot_storage_key);
+                storage_key_address.push(slot_address);
+                is_storage_key_warm_prev.push(is_warm_prev);
+            });
+        }
+
+        Self {
+            is_address,
+            address: address.try_into().unwrap(),
+            is_address_warm_prev: is_address_warm_prev.try_into().unwrap(),
+            is_storage_key,
+            storage_key: storage_key.try_into().unwrap(),
+            storage_key_address: storage_key_address.try_into().unwrap(),
+            is_storage_key_warm_prev: is_storage_key_warm_prev.try_into().unwrap(),
+        }
+    }
+
+    /// Return the flags of the `N` slots that hold an entry: the first `len` ones.
+    fn slots_in_use<const N: usize>(
+        cb: &mut EVMConstraintBuilder<F>,
+        len: Expression<F>,
+    ) -> [Cell<F>; N] {
+        let is_in_use: [Cell<F>; N] = array::from_fn(|_| cb.query_bool());
+        for (prev, cur) in is_in_use.iter().tuple_windows() {
+            cb.require_zero(
+                "the slots in use come first",
+                cur.expr() * not::expr(prev.expr()),
+            );
+        }
+        cb.require_equal(
+            "the number of slots in use is the length of the access list",
+            sum::expr(&is_in_use),
+            len,
+        );
+        is_in_use
+    }
+
+    /// Assign the slots of the access list of the tx, reading the previous warm state of each
+    /// entry from its write in `rws`.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        tx: &Transaction,
+        rws: &mut StepRws,
+    ) -> Result<(), Error> {
+        let addresses = tx.access_list_addresses();
+        for (index, (is_address, (address, is_warm_prev))) in self
+            .is_address
+            .iter()
+            .zip(self.address.iter().zip(self.is_address_warm_prev.iter()))
+            .enumerate()
+        {
+            let (slot_address, warm_prev) = match addresses.get(index) {
+                Some(slot_address) => (*slot_address, rws.next().tx_access_list_value_pair().1),
+                None => (Address::zero(), false),
+            };
+            is_address.assign(
+                region,
+                offset,
+                Value::known(F::from((index < addresses.len()) as u64)),
+            )?;
+            address.assign_h160(region, offset, slot_address)?;
+            is_warm_prev.assign(region, offset, Value::known(F::from(warm_prev as u64)))?;
+        }
+
+        let storage_keys = tx.access_list_storage_keys();
+        for (index, (is_storage_key, ((storage_key, address), is_warm_prev))) in self
+            .is_storage_key
+            .iter()
+            .zip(
+                self.storage_key
+                    .iter()
+                    .zip(self.storage_key_address.iter())
+                    .zip(self.is_storage_key_warm_prev.iter()),
+            )
+            .enumerate()
+        {
+            let (slot_address, slot_storage_key, warm_prev) = match storage_keys.get(index) {
+                Some((slot_address, slot_storage_key)) => (
+                    *slot_address,
+                    *slot_storage_key,
+                    rws.next().tx_access_list_value_pair().1,
+                ),
+                None => (Address::zero(), U256::zero(), false),
+            };
+            is_storage_key.assign(
+                region,
+                offset,
+                Value::known(F::from((index < storage_keys.len()) as u64)),
+            )?;
+            storage_key.assign_u256(region, offset, slot_storage_key)?;
+            address.assign_h160(region, offset, slot_address)?;
+            is_warm_prev.assign(region, offset, Value::known(F::from(warm_prev as u64)))?;
+        }
+
+        Ok(())
+    }
+}
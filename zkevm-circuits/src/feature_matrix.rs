@@ -0,0 +1,160 @@
+//! Features of the Ethereum protocol supported by this version of the circuits.
+//!
+//! The supported opcodes and precompiles are generated from the gadgets that the EVM circuit
+//! of the [`SuperCircuit`] configures, so the matrix can't go out of sync with the circuit: an
+//! [`ExecutionState`] only counts as supported if it has a gadget that is not a
+//! `DummyGadget`, and if the bus-mapping prefilter accepts its opcodes and precompile.
+
+use crate::{evm_circuit::step::ExecutionState, super_circuit::SuperCircuit};
+use bus_mapping::{
+    circuit_input_builder::{is_opcode_supported, is_precompile_supported, FeatureConfig},
+    precompile::PrecompileCalls,
+};
+use eth_types::evm_types::OpcodeId;
+use halo2_proofs::{
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem},
+};
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+/// Name of the gadget used for execution states that are not yet constrained.
+const DUMMY_GADGET_NAME: &str = "DUMMY";
+
+/// Hard forks whose rules the circuits follow.
+pub const SUPPORTED_FORKS: &[&str] = &["Shanghai"];
+
+pub use bus_mapping::circuit_input_builder::SUPPORTED_TX_TYPES;
+
+/// Matrix of the features supported by this version of the circuits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureMatrix {
+    /// Version of the zkevm-circuits crate
+    pub version: &'static str,
+    /// Supported hard forks
+    pub forks: Vec<&'static str>,
+    /// Supported transaction types
+    pub tx_types: Vec<u64>,
+    /// Supported opcodes
+    pub opcodes: Vec<OpcodeId>,
+    /// Supported precompiles
+    pub precompiles: Vec<PrecompileCalls>,
+    /// Execution states without a complete gadget, or rejected by the prefilter
+    pub unsupported_states: Vec<ExecutionState>,
+}
+
+impl FeatureMatrix {
+    /// Build the feature matrix by configuring the [`SuperCircuit`] with the given
+    /// [`FeatureConfig`].
+    pub fn new(feature_config: FeatureConfig) -> Self {
+        let circuit = SuperCircuit::<Fr> {
+            feature_config,
+            mock_randomness: Fr::from(0x100),
+            ..Default::default()
+        };
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = SuperCircuit::<Fr>::configure_with_params(&mut meta, circuit.params());
+        let gadgets = config.evm_circuit.execution.instrument().gadgets();
+
+        let opcode_states = ExecutionState::STOP.as_u64()..=ExecutionState::SELFDESTRUCT.as_u64();
+        let is_accepted = |state: &ExecutionState| {
+            let opcodes_accepted = !opcode_states.contains(&state.as_u64())
+                || state
+                    .responsible_opcodes()
+                    .iter()
+                    .all(|op| is_opcode_supported(op.opcode()));
+            opcodes_accepted && state.precompile().map_or(true, is_precompile_supported)
+        };
+        let supported_states = ExecutionState::iter()
+            .filter(|state| {
+                gadgets
+                    .iter()
+                    .any(|(s, name)| s == state && *name != DUMMY_GADGET_NAME)
+                    && is_accepted(state)
+            })
+            .collect_vec();
+        let unsupported_states = ExecutionState::iter()
+            .filter(|state| !supported_states.contains(state))
+            .collect_vec();
+
+        let opcodes = supported_states
+            .iter()
+            .filter(|state| opcode_states.contains(&state.as_u64()))
+            .flat_map(|state| state.responsible_opcodes())
+            .map(|op| op.opcode())
+            .unique()
+            .sorted_by_key(|opcode| opcode.as_u8())
+            .collect();
+        let precompiles = supported_states
+            .iter()
+            .filter_map(|state| state.precompile())
+            .collect();
+
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            forks: SUPPORTED_FORKS.to_vec(),
+            tx_types: SUPPORTED_TX_TYPES.to_vec(),
+            opcodes,
+            precompiles,
+            unsupported_states,
+        }
+    }
+
+    /// Return true if the opcode is supported.
+    pub fn supports_opcode(&self, opcode: OpcodeId) -> bool {
+        self.opcodes.contains(&opcode)
+    }
+
+    /// Return true if the precompile is supported.
+    pub fn supports_precompile(&self, precompile: PrecompileCalls) -> bool {
+        self.precompiles.contains(&precompile)
+    }
+
+    /// Return true if the transaction type is supported.
+    pub fn supports_tx_type(&self, tx_type: u64) -> bool {
+        self.tx_types.contains(&tx_type)
+    }
+
+    /// Return true if the hard fork is supported.
+    pub fn supports_fork(&self, fork: &str) -> bool {
+        self.forks.iter().any(|f| f.eq_ignore_ascii_case(fork))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_matrix() {
+        let matrix = FeatureMatrix::new(FeatureConfig::default());
+
+        assert!(matrix.supports_opcode(OpcodeId::ADD));
+        assert!(matrix.supports_opcode(OpcodeId::PUSH0));
+        assert!(matrix.supports_opcode(OpcodeId::CREATE2));
+        // EndTx doesn't clear the storage of destructed accounts.
+        assert!(!matrix.supports_opcode(OpcodeId::SELFDESTRUCT));
+        assert!(matrix
+            .unsupported_states
+            .contains(&ExecutionState::SELFDESTRUCT));
+        assert!(matrix
+            .unsupported_states
+            .contains(&ExecutionState::ErrorDepth));
+
+        assert!(matrix
+            .unsupported_states
+            .contains(&ExecutionState::PrecompileSha256));
+        assert!(matrix
+            .unsupported_states
+            .contains(&ExecutionState::PrecompileBn256Pairing));
+        assert_eq!(
+            matrix.precompiles,
+            vec![PrecompileCalls::ECRecover, PrecompileCalls::Identity]
+        );
+        assert!(matrix.supports_tx_type(0));
+        assert!(matrix.supports_tx_type(1));
+        assert!(matrix.supports_tx_type(2));
+        assert!(!matrix.supports_tx_type(3));
+        assert!(matrix.supports_fork("shanghai"));
+    }
+}
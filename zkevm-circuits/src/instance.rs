@@ -7,7 +7,12 @@ use std::{iter, ops::Deref};
 use eth_types::{geth_types::Transaction, Address, ToBigEndian, Word, H256};
 use itertools::Itertools;
 
-use crate::{util::word::WordLoHi, witness::Block};
+use crate::{
+    evm_circuit::param::{MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS},
+    table::TxFieldTag,
+    util::word::WordLoHi,
+    witness::Block,
+};
 
 pub(super) const ZERO_BYTE_GAS_COST: u64 = 4;
 pub(super) const NONZERO_BYTE_GAS_COST: u64 = 16;
@@ -58,6 +63,64 @@ pub struct TxValues {
     pub call_data_gas_cost: u64,
     /// tx_sign_hash
     pub tx_sign_hash: [u8; 32],
+    /// access_list_addresses_len
+    pub access_list_addresses_len: u64,
+    /// access_list_storage_keys_len
+    pub access_list_storage_keys_len: u64,
+    /// max_fee_per_gas
+    pub max_fee_per_gas: Word,
+    /// max_priority_fee_per_gas
+    pub max_priority_fee_per_gas: Word,
+    /// access_list_addresses
+    pub access_list_addresses: Vec<Address>,
+    /// access_list_storage_keys, with their address
+    pub access_list_storage_keys: Vec<(Address, Word)>,
+}
+
+impl TxValues {
+    /// Returns the tag, index and big-endian value bytes of the access list slots of the tx, in
+    /// the order of their rows in the tx table.  The slots after the last entry are zero.
+    pub fn access_list_slots(&self) -> Vec<(TxFieldTag, u64, Vec<u8>)> {
+        assert!(self.access_list_addresses.len() <= MAX_ACCESS_LIST_ADDRESSES);
+        assert!(self.access_list_storage_keys.len() <= MAX_ACCESS_LIST_STORAGE_KEYS);
+        let storage_keys = self
+            .access_list_storage_keys
+            .iter()
+            .copied()
+            .pad_using(MAX_ACCESS_LIST_STORAGE_KEYS, |_| Default::default())
+            .collect_vec();
+
+        iter::empty()
+            .chain(
+                self.access_list_addresses
+                    .iter()
+                    .copied()
+                    .pad_using(MAX_ACCESS_LIST_ADDRESSES, |_| Address::zero())
+                    .enumerate()
+                    .map(|(i, address)| {
+                        (
+                            TxFieldTag::AccessListAddress,
+                            i as u64,
+                            address.as_fixed_bytes().to_vec(),
+                        )
+                    }),
+            )
+            .chain(storage_keys.iter().enumerate().map(|(i, (_, key))| {
+                (
+                    TxFieldTag::AccessListStorageKey,
+                    i as u64,
+                    key.to_be_bytes().to_vec(),
+                )
+            }))
+            .chain(storage_keys.iter().enumerate().map(|(i, (address, _))| {
+                (
+                    TxFieldTag::AccessListStorageKeyAddress,
+                    i as u64,
+                    address.as_fixed_bytes().to_vec(),
+                )
+            }))
+            .collect()
+    }
 }
 
 /// Extra values (not contained in block or tx tables)
@@ -69,6 +132,107 @@ pub struct ExtraValues {
     pub state_root: H256,
     /// prev_state_root
     pub prev_state_root: H256,
+    /// prev_pi_digest
+    pub prev_pi_digest: H256,
+}
+
+/// Public inputs of the PiCircuit, which the verifier checks against the L1 data.  Each value
+/// is a 32 bytes hash exposed as its lo and hi 128 bits limbs, in the order of the fields.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct PublicInputs {
+    /// Keccak digest of all the public data bytes
+    pub rpi_digest: H256,
+    /// Hash of the parent block, the latest of the history hashes
+    pub parent_hash: H256,
+    /// State root after the block
+    pub state_root: H256,
+    /// withdrawals_root
+    pub withdrawals_root: H256,
+    /// Keccak digest of the tx table and calldata bytes
+    pub tx_digest: H256,
+    /// State root before the block
+    pub prev_state_root: H256,
+    /// Hash of the block
+    pub block_hash: H256,
+    /// Keccak digest of all the public data bytes of the previous block, zero when the proofs
+    /// are not chained
+    pub prev_pi_digest: H256,
+}
+
+impl PublicInputs {
+    /// Offset of the keccak digest limbs in the instance column
+    pub const RPI_DIGEST_OFFSET: usize = 0;
+    /// Offset of the parent hash limbs in the instance column
+    pub const PARENT_HASH_OFFSET: usize = 2;
+    /// Offset of the state root limbs in the instance column
+    pub const STATE_ROOT_OFFSET: usize = 4;
+    /// Offset of the previous state root limbs in the instance column
+    pub const PREV_STATE_ROOT_OFFSET: usize = 10;
+    /// Offset of the block hash limbs in the instance column
+    pub const BLOCK_HASH_OFFSET: usize = 12;
+    /// Offset of the previous block keccak digest limbs in the instance column
+    pub const PREV_PI_DIGEST_OFFSET: usize = 14;
+
+    /// Number of values in the instance column
+    pub const NUM_INSTANCE: usize = 16;
+
+    fn hashes(&self) -> [H256; Self::NUM_INSTANCE / 2] {
+        [
+            self.rpi_digest,
+            self.parent_hash,
+            self.state_root,
+            self.withdrawals_root,
+            self.tx_digest,
+            self.prev_state_root,
+            self.block_hash,
+            self.prev_pi_digest,
+        ]
+    }
+
+    /// Returns the instance column values, as the lo and hi limbs of each hash
+    pub fn instance<F: Field>(&self) -> Vec<F> {
+        self.hashes()
+            .iter()
+            .flat_map(|hash| {
+                let word = WordLoHi::<F>::from(Word::from_big_endian(hash.as_bytes()));
+                [word.lo(), word.hi()]
+            })
+            .collect()
+    }
+
+    /// Decodes the public inputs from the values of the instance column, which is the first
+    /// instance column of the SuperCircuit.  Returns `None` if the values are not the lo and hi
+    /// limbs of the hashes of [`PublicInputs::instance`].
+    pub fn from_instance<F: Field>(instance: &[F]) -> Option<Self> {
+        if instance.len() != Self::NUM_INSTANCE {
+            return None;
+        }
+        let hashes = instance
+            .chunks(2)
+            .map(|limbs| {
+                let mut bytes = [0u8; 32];
+                for (limb, half) in limbs.iter().zip(bytes.chunks_mut(16)) {
+                    let repr = limb.to_repr();
+                    if repr[16..].iter().any(|byte| *byte != 0) {
+                        return None;
+                    }
+                    half.copy_from_slice(&repr[..16]);
+                }
+                bytes.reverse();
+                Some(H256::from(bytes))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            rpi_digest: hashes[0],
+            parent_hash: hashes[1],
+            state_root: hashes[2],
+            withdrawals_root: hashes[3],
+            tx_digest: hashes[4],
+            prev_state_root: hashes[5],
+            block_hash: hashes[6],
+            prev_pi_digest: hashes[7],
+        })
+    }
 }
 
 /// PublicData contains all the values that the PiCircuit receives as input
@@ -93,6 +257,9 @@ pub struct PublicData {
     pub block_hash: Option<H256>,
     /// withdrawals_root
     pub withdrawals_root: H256,
+    /// Digest of the public inputs of the previous block, to chain the proofs of consecutive
+    /// blocks.  Zero when the proofs are not chained.
+    pub prev_pi_digest: H256,
 }
 
 impl Default for PublicData {
@@ -107,6 +274,7 @@ impl Default for PublicData {
             block_constants: BlockConstants::default(),
             block_hash: None,
             withdrawals_root: H256::zero(),
+            prev_pi_digest: H256::zero(),
         }
     }
 }
@@ -163,6 +331,12 @@ impl PublicData {
                     }
                 }),
                 tx_sign_hash: msg_hash_le,
+                access_list_addresses_len: tx.access_list_addresses_len(),
+                access_list_storage_keys_len: tx.access_list_storage_keys_len(),
+                max_fee_per_gas: tx.max_fee_per_gas(),
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas(),
+                access_list_addresses: tx.access_list_addresses(),
+                access_list_storage_keys: tx.access_list_storage_keys(),
             });
         }
         tx_vals
@@ -174,6 +348,7 @@ impl PublicData {
             block_hash: self.block_hash.unwrap_or_else(H256::zero),
             state_root: self.state_root,
             prev_state_root: self.prev_state_root,
+            prev_pi_digest: self.prev_pi_digest,
         }
     }
 
@@ -208,30 +383,63 @@ impl PublicData {
         let result = result
             .chain(extra_vals.block_hash.to_fixed_bytes()) // block hash
             .chain(extra_vals.state_root.to_fixed_bytes()) // block state root
-            .chain(extra_vals.prev_state_root.to_fixed_bytes()); // previous block state root
+            .chain(extra_vals.prev_state_root.to_fixed_bytes()) // previous block state root
+            .chain(extra_vals.prev_pi_digest.to_fixed_bytes()); // previous block pi digest
+
+        // Assign Tx table and calldata
+        let result = result.chain(self.get_tx_bytes(max_txs, max_calldata));
+
+        // serialize withdrawals
+        let wd_bytes_fn = |wd: Withdrawal| {
+            iter::empty()
+                .chain(wd.id.to_be_bytes()) // id
+                .chain(wd.validator_id.to_be_bytes()) // validator_id
+                .chain(wd.address.as_fixed_bytes().to_vec()) // address
+                .chain(wd.amount.to_be_bytes()) // amount
+        };
+        let wd_defaults = Withdrawal::default();
+        let all_wd_bytes = iter::empty()
+            .chain(self.withdrawals.clone())
+            .chain((0..(max_withdrawals - self.withdrawals.len())).map(|_| wd_defaults))
+            .flat_map(wd_bytes_fn);
 
-        // Assign Tx table
+        result.chain(all_wd_bytes).collect_vec()
+    }
+
+    /// get the serialized bytes of the tx table and its calldata, which are the part of the public
+    /// data bytes that the transactions commitment is computed from
+    pub fn get_tx_bytes(&self, max_txs: usize, max_calldata: usize) -> Vec<u8> {
         let tx_field_byte_fn = |tx_id: u64, index: u64, value_bytes: &[u8]| {
             iter::empty()
                 .chain(tx_id.to_be_bytes()) // tx_id
                 .chain(index.to_be_bytes()) // index
                 .chain(value_bytes.to_vec()) // value
         };
-        let tx_bytes_fn = |tx_id: u64, index: u64, tx: &TxValues| {
+        let tx_bytes_fn = |tx_id: u64, tx: &TxValues| {
             vec![
-                tx.nonce.to_be_bytes().to_vec(),                     // nonce
-                tx.gas_limit.to_be_bytes().to_vec(),                 // gas_limit
-                tx.gas_price.to_be_bytes().to_vec(),                 // gas price
-                tx.from_addr.as_fixed_bytes().to_vec(),              // from_addr
-                tx.to_addr.as_fixed_bytes().to_vec(),                // to_addr
-                tx.is_create.to_be_bytes().to_vec(),                 // is_create
-                tx.value.to_be_bytes().to_vec(),                     // value
-                tx.call_data_len.to_be_bytes().to_vec(),             // call_data_len
-                tx.call_data_gas_cost.to_be_bytes().to_vec(),        // call_data_gas_cost
-                tx.tx_sign_hash.iter().rev().copied().collect_vec(), // tx sign hash
+                tx.nonce.to_be_bytes().to_vec(),                        // nonce
+                tx.gas_limit.to_be_bytes().to_vec(),                    // gas_limit
+                tx.gas_price.to_be_bytes().to_vec(),                    // gas price
+                tx.from_addr.as_fixed_bytes().to_vec(),                 // from_addr
+                tx.to_addr.as_fixed_bytes().to_vec(),                   // to_addr
+                tx.is_create.to_be_bytes().to_vec(),                    // is_create
+                tx.value.to_be_bytes().to_vec(),                        // value
+                tx.call_data_len.to_be_bytes().to_vec(),                // call_data_len
+                tx.call_data_gas_cost.to_be_bytes().to_vec(),           // call_data_gas_cost
+                tx.tx_sign_hash.iter().rev().copied().collect_vec(),    // tx sign hash
+                tx.access_list_addresses_len.to_be_bytes().to_vec(),    // access list addresses
+                tx.access_list_storage_keys_len.to_be_bytes().to_vec(), // access list storage keys
+                tx.max_fee_per_gas.to_be_bytes().to_vec(),              // max fee per gas
+                tx.max_priority_fee_per_gas.to_be_bytes().to_vec(),     // max priority fee per gas
             ]
-            .iter()
-            .flat_map(move |value_bytes| tx_field_byte_fn(tx_id, index, value_bytes))
+            .into_iter()
+            .map(|value_bytes| (0, value_bytes))
+            .chain(
+                tx.access_list_slots()
+                    .into_iter()
+                    .map(|(_, index, value_bytes)| (index, value_bytes)), // access list slots
+            )
+            .flat_map(move |(index, value_bytes)| tx_field_byte_fn(tx_id, index, &value_bytes))
             .collect_vec()
         };
 
@@ -245,11 +453,11 @@ impl PublicData {
             .enumerate()
             .flat_map(|(i, tx)| {
                 let i: u64 = i.try_into().unwrap();
-                tx_bytes_fn(i + 1, 0, tx)
+                tx_bytes_fn(i + 1, tx)
             });
 
         // first tx empty row happened here
-        let result = result
+        let result = iter::empty()
             .chain(tx_field_byte_fn(0, 0, &[0u8; 1])) // empty row
             .chain(all_tx_bytes);
 
@@ -264,23 +472,20 @@ impl PublicData {
         let calldata_chain = iter::empty()
             .chain(all_calldata)
             .chain((0..max_calldata - calldata_count).map(|_| 0u8));
-        let result = result.chain(calldata_chain);
-
-        // serialize withdrawals
-        let wd_bytes_fn = |wd: Withdrawal| {
-            iter::empty()
-                .chain(wd.id.to_be_bytes()) // id
-                .chain(wd.validator_id.to_be_bytes()) // validator_id
-                .chain(wd.address.as_fixed_bytes().to_vec()) // address
-                .chain(wd.amount.to_be_bytes()) // amount
-        };
-        let wd_defaults = Withdrawal::default();
-        let all_wd_bytes = iter::empty()
-            .chain(self.withdrawals.clone())
-            .chain((0..(max_withdrawals - self.withdrawals.len())).map(|_| wd_defaults))
-            .flat_map(wd_bytes_fn);
+        result.chain(calldata_chain).collect_vec()
+    }
 
-        result.chain(all_wd_bytes).collect_vec()
+    /// Keccak digest of the public data bytes.  The digest of a block is the `prev_pi_digest`
+    /// of the next block when chaining proofs.
+    pub fn get_rpi_digest(
+        &self,
+        max_txs: usize,
+        max_withdrawals: usize,
+        max_calldata: usize,
+    ) -> H256 {
+        let mut keccak = Keccak::default();
+        keccak.update(&self.get_pi_bytes(max_txs, max_withdrawals, max_calldata));
+        H256::from_slice(&keccak.digest())
     }
 
     /// generate public data from validator perspective
@@ -290,10 +495,39 @@ impl PublicData {
         max_withdrawals: usize,
         max_calldata: usize,
     ) -> WordLoHi<F> {
+        let digest = self.get_rpi_digest(max_txs, max_withdrawals, max_calldata);
+        WordLoHi::from(Word::from_big_endian(digest.as_bytes()))
+    }
+
+    /// Keccak digest of the tx table and calldata bytes, which commits to the transactions of
+    /// the block.
+    pub fn get_tx_digest(&self, max_txs: usize, max_calldata: usize) -> H256 {
         let mut keccak = Keccak::default();
-        keccak.update(&self.get_pi_bytes(max_txs, max_withdrawals, max_calldata));
-        let digest = keccak.digest();
-        WordLoHi::from(Word::from_big_endian(&digest))
+        keccak.update(&self.get_tx_bytes(max_txs, max_calldata));
+        H256::from_slice(&keccak.digest())
+    }
+
+    /// Values exposed as public inputs by the PiCircuit
+    pub fn get_public_inputs(
+        &self,
+        max_txs: usize,
+        max_withdrawals: usize,
+        max_calldata: usize,
+    ) -> PublicInputs {
+        let block_values = self.get_block_table_values();
+        PublicInputs {
+            rpi_digest: self.get_rpi_digest(max_txs, max_withdrawals, max_calldata),
+            parent_hash: *block_values
+                .history_hashes
+                .last()
+                .expect("history hashes are padded to 256"),
+            state_root: self.state_root,
+            withdrawals_root: self.withdrawals_root,
+            tx_digest: self.get_tx_digest(max_txs, max_calldata),
+            prev_state_root: self.prev_state_root,
+            block_hash: self.block_hash.unwrap_or_else(H256::zero),
+            prev_pi_digest: self.prev_pi_digest,
+        }
     }
 }
 
@@ -316,5 +550,6 @@ pub fn public_data_convert<F: Field>(block: &Block<F>) -> PublicData {
             base_fee: block.context.base_fee,
         },
         withdrawals_root: block.withdrawals_root(),
+        prev_pi_digest: block.prev_pi_digest,
     }
 }
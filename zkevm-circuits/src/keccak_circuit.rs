@@ -90,8 +90,8 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
         }: Self::ConfigArgs,
     ) -> Self {
         assert!(
-            get_num_rows_per_round() > NUM_BYTES_PER_WORD,
-            "KeccakCircuit requires KECCAK_ROWS>=9"
+            (NUM_BYTES_PER_WORD + 1..=MAX_KECCAK_ROWS).contains(&get_num_rows_per_round()),
+            "KeccakCircuit requires 9<=KECCAK_ROWS<=32"
         );
         let q_enable = meta.fixed_column();
         let q_first = meta.fixed_column();
@@ -878,16 +878,18 @@ impl<F: Field> SubCircuitConfig<F> for KeccakCircuitConfig<F> {
 }
 
 impl<F: Field> KeccakCircuitConfig<F> {
-    pub(crate) fn assign(
+    /// Assign the rows generated by `witness`, which is called each time the region is assigned,
+    /// so that the rows are assigned as they are generated.
+    pub(crate) fn assign<I: Iterator<Item = KeccakRow<F>>>(
         &self,
         layouter: &mut impl Layouter<F>,
-        witness: &[KeccakRow<F>],
+        witness: impl Fn() -> I,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "assign keccak rows",
             |mut region| {
-                for (offset, keccak_row) in witness.iter().enumerate() {
-                    self.set_row(&mut region, offset, keccak_row)?;
+                for (offset, keccak_row) in witness().enumerate() {
+                    self.set_row(&mut region, offset, &keccak_row)?;
                 }
                 self.keccak_table.annotate_columns_in_region(&mut region);
                 self.annotate_circuit(&mut region);
@@ -1036,8 +1038,7 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         config.load_aux_tables(layouter)?;
-        let witness = self.generate_witness(*challenges);
-        config.assign(layouter, witness.as_slice())
+        config.assign(layouter, || self.generate_witness(*challenges))
     }
 }
 
@@ -1061,8 +1062,18 @@ impl<F: Field> KeccakCircuit<F> {
         }
     }
 
+    /// The number of keccak_f's that can be done in a circuit of `2^k` rows
+    pub fn capacity_for_degree(k: u32) -> usize {
+        let num_rows = (1usize << k).saturating_sub(keccak_unusable_rows());
+        // Subtract two for unusable rows, as in `capacity`
+        (num_rows / ((NUM_ROUNDS + 1) * get_num_rows_per_round())).saturating_sub(2)
+    }
+
     /// Sets the witness using the data to be hashed
-    pub(crate) fn generate_witness(&self, challenges: Challenges<Value<F>>) -> Vec<KeccakRow<F>> {
+    pub(crate) fn generate_witness(
+        &self,
+        challenges: Challenges<Value<F>>,
+    ) -> impl Iterator<Item = KeccakRow<F>> + '_ {
         multi_keccak(self.inputs.as_slice(), challenges, self.capacity())
             .expect("Too many inputs for given capacity")
     }
@@ -12,7 +12,16 @@ use halo2_proofs::{
 use log::debug;
 use std::{env::var, vec};
 
-pub(crate) fn get_num_rows_per_round() -> usize {
+/// Number of keccak_f permutations to hash an input of `len` bytes: the input is absorbed in
+/// chunks of RATE bytes, with at least one byte of padding.
+pub fn get_num_keccak_f(len: usize) -> usize {
+    len / RATE + 1
+}
+
+/// Number of rows used by each round of the keccak_f permutation, set with the `KECCAK_ROWS` env
+/// var.  The cells of a round are packed in fewer columns when they are spread over more rows, so
+/// that the width of the KeccakCircuit can be traded for its degree.
+pub fn get_num_rows_per_round() -> usize {
     var("KECCAK_ROWS")
         .unwrap_or_else(|_| format!("{DEFAULT_KECCAK_ROWS}"))
         .parse()
@@ -20,7 +29,7 @@ pub(crate) fn get_num_rows_per_round() -> usize {
 }
 
 pub(crate) fn keccak_unusable_rows() -> usize {
-    const UNUSABLE_ROWS_BY_KECCAK_ROWS: [usize; 24] = [
+    const UNUSABLE_ROWS_BY_KECCAK_ROWS: [usize; MAX_KECCAK_ROWS - NUM_BYTES_PER_WORD] = [
         53, 67, 63, 59, 45, 79, 77, 75, 73, 71, 69, 67, 65, 63, 61, 59, 57, 71, 89, 107, 107, 107,
         107, 107,
     ];
@@ -868,53 +877,64 @@ pub(crate) fn keccak<F: Field>(
     }
 }
 
+/// Rows of the keccaks of `bytes`, padded to `capacity` keccak_f's when there is one.  The rows
+/// are generated one input at a time while they are consumed, so that only the rows of a single
+/// input are in memory.
 pub(crate) fn multi_keccak<F: Field>(
     bytes: &[Vec<u8>],
     challenges: Challenges<Value<F>>,
     capacity: Option<usize>,
-) -> Result<Vec<KeccakRow<F>>, Error> {
-    let mut rows: Vec<KeccakRow<F>> = Vec::new();
-    // Dummy first row so that the initial data is absorbed
-    // The initial data doesn't really matter, `is_final` just needs to be disabled.
-    for idx in 0..get_num_rows_per_round() {
-        rows.push(KeccakRow {
-            q_enable: idx == 0,
-            q_round: false,
-            q_absorb: idx == 0,
-            q_round_last: false,
-            q_padding: false,
-            q_padding_last: false,
-            round_cst: F::ZERO,
-            is_final: false,
-            length: 0usize,
-            data_rlc: Value::known(F::ZERO),
-            hash: WordLoHi::default().into_value(),
-            cell_values: Vec::new(),
-        });
-    }
-    // Actual keccaks
-    for bytes in bytes {
-        keccak(&mut rows, bytes, challenges);
-    }
-    if let Some(capacity) = capacity {
-        let padding_rows = {
-            let mut rows = Vec::new();
-            keccak(&mut rows, &[], challenges);
-            rows
-        };
-        // Pad with no data hashes to the expected capacity
-        while rows.len() < (1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round() {
-            rows.extend(padding_rows.clone());
-        }
+) -> Result<impl Iterator<Item = KeccakRow<F>> + '_, Error> {
+    let num_keccak_f: usize = bytes
+        .iter()
+        .map(|bytes| get_num_keccak_f(bytes.len()))
+        .sum();
+    let num_padding_keccak_f = match capacity {
         // Check that we are not over capacity
-        if rows.len() > (1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round() {
+        Some(capacity) if num_keccak_f > capacity => {
             log::error!(
                 "Keccack inputs exceed capacity.  needed_rows = {}, available_rows = {}",
-                rows.len(),
+                (1 + num_keccak_f * (NUM_ROUNDS + 1)) * get_num_rows_per_round(),
                 (1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round()
             );
             return Err(Error::BoundsFailure);
         }
-    }
-    Ok(rows)
+        Some(capacity) => capacity - num_keccak_f,
+        None => 0,
+    };
+
+    // Dummy first row so that the initial data is absorbed
+    // The initial data doesn't really matter, `is_final` just needs to be disabled.
+    let dummy_rows = (0..get_num_rows_per_round()).map(|idx| KeccakRow {
+        q_enable: idx == 0,
+        q_round: false,
+        q_absorb: idx == 0,
+        q_round_last: false,
+        q_padding: false,
+        q_padding_last: false,
+        round_cst: F::ZERO,
+        is_final: false,
+        length: 0usize,
+        data_rlc: Value::known(F::ZERO),
+        hash: WordLoHi::default().into_value(),
+        cell_values: Vec::new(),
+    });
+    // Actual keccaks
+    let keccak_rows = bytes.iter().flat_map(move |bytes| {
+        let mut rows = Vec::new();
+        keccak(&mut rows, bytes, challenges);
+        rows
+    });
+    // Pad with no data hashes to the expected capacity
+    let padding_rows = {
+        let mut rows = Vec::new();
+        if num_padding_keccak_f > 0 {
+            keccak(&mut rows, &[], challenges);
+        }
+        rows
+    };
+    let padding_rows = std::iter::repeat(padding_rows)
+        .take(num_padding_keccak_f)
+        .flatten();
+    Ok(dummy_rows.chain(keccak_rows).chain(padding_rows))
 }
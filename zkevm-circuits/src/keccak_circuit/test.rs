@@ -12,6 +12,8 @@ use halo2_proofs::{
 };
 use itertools::izip;
 use log::error;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use std::iter::zip;
 
 use super::util::{target_part_sizes, target_part_sizes_rot, WordParts};
@@ -21,7 +23,7 @@ use super::util::{target_part_sizes, target_part_sizes_rot, WordParts};
 #[ignore]
 #[test]
 fn serial_keccak_circuit_unusable_rows() {
-    for keccak_rows in NUM_BYTES_PER_WORD + 1..=32 {
+    for keccak_rows in NUM_BYTES_PER_WORD + 1..=MAX_KECCAK_ROWS {
         std::env::set_var("KECCAK_ROWS", format!("{keccak_rows}"));
         assert_eq!(
             KeccakCircuit::<Fr>::unusable_rows(),
@@ -127,6 +129,129 @@ fn packed_multi_keccak_simple() {
     verify::<Fr>(k, inputs, digests, true);
 }
 
+#[test]
+fn packed_multi_keccak_capacity_for_degree() {
+    let k = 12;
+    let capacity = KeccakCircuit::<Fr>::capacity_for_degree(k);
+    assert!(capacity > 0);
+
+    // Each input of 135 bytes takes a single keccak_f
+    let circuit = KeccakCircuit::<Fr>::new(
+        2usize.pow(k) - KeccakCircuit::<Fr>::unusable_rows(),
+        vec![(0u8..135).collect::<Vec<_>>(); capacity],
+    );
+    assert_eq!(circuit.capacity(), Some(capacity));
+    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+/// Byte-aligned Keccak-256 known-answer tests of `ShortMsgKAT_256.txt` from the Keccak team, as
+/// (message, digest).  Keccak-256 is the original Keccak padding used by Ethereum, not the
+/// SHA3-256 of FIPS 202, so the NIST SHA-3 vectors don't apply.
+const KECCAK_256_SHORT_MSG_KAT: &[(&str, &str)] = &[
+    ("", EMPTY_DIGEST),
+    (
+        "cc",
+        "eead6dbfc7340a56caedc044696a168870549a6a7f6f56961e84a54bd9970b8a",
+    ),
+    (
+        "41fb",
+        "a8eaceda4d47b3281a795ad9e1ea2122b407baf9aabcb9e18b5717b7873537d2",
+    ),
+    (
+        "1f877c",
+        "627d7bc1491b2ab127282827b8de2d276b13d7d70fb4c5957fdf20655bc7ac30",
+    ),
+    (
+        "c1ecfdfc",
+        "b149e766d7612eaf7d55f74e1a4fdd63709a8115b14f61fcd22aa4abc8b8e122",
+    ),
+    (
+        "21f134ac57",
+        "67f05544dbe97d5d6417c1b1ea9bc0e3a99a541381d1cd9b08a9765687eb5bb4",
+    ),
+    (
+        "c6f50bb74e29",
+        "923062c4e6f057597220d182dbb10e81cd25f60b54005b2a75dd33d6dac518d0",
+    ),
+    (
+        "119713cc83eeef",
+        "feb8405dcd315d48c6cbf7a3504996de8e25cc22566efec67433712eda99894f",
+    ),
+    (
+        "4a4f202484512526",
+        "e620d8f2982b24fedaaa3baa9b46c3f9ce204ee356666553ecb35e15c3ff9bf9",
+    ),
+    (
+        "1f66ab4185ed9b6375",
+        "9e03f7c9a3d055eca1d786ed6fb624d93f1cf0ac27f9c2b6c05e509fac9e7fca",
+    ),
+    (
+        "eed7422227613b6f53c9",
+        "caad8e1ed546630748a12f5351b518a9a431cda6ba56cbfc3ccbdd8aae5092f7",
+    ),
+    (
+        "eaeed5cdffd89dece455f1",
+        "d61708bdb3211a9aab28d4df01dfa4b29ed40285844d841042257e97488617b0",
+    ),
+    (
+        "5be43c90f22902e4fe8ed2d3",
+        "0f53be55990780b3fad9870f04f7d8153c3ae605c057c85abb5d71765043aaa8",
+    ),
+    (
+        "a746273228122f381c3b46e4f1",
+        "32215ae88204a782b62d1810d945de49948de458600f5e1e3896ceca2ed3292b",
+    ),
+    (
+        "3c5871cd619c69a63b540eb5a625",
+        "9510da68e58ebb8d2ab9de8485bb408e358299a9c011ae8544b0d0faf9d4a4ea",
+    ),
+    (
+        "fa22874bcc068879e8ef11a69f0722",
+        "f20b3bcf743aa6fa084038520791c364cb6d3d1dd75841f8d7021cd98322bd8f",
+    ),
+    (
+        "52a608ab21ccdd8a4457a57ede782176",
+        "0e32defa2071f0b5ac0e6a108b842ed0f1d3249712f58ee0ddf956fe332a5f95",
+    ),
+];
+
+#[test]
+fn packed_multi_keccak_short_msg_kat() {
+    let (inputs, digests) = KECCAK_256_SHORT_MSG_KAT
+        .iter()
+        .map(|(msg, digest)| (hex::decode(msg).unwrap(), digest.to_string()))
+        .unzip();
+    verify::<Fr>(14, inputs, digests, true);
+}
+
+/// Random messages of the given lengths, with their digests computed natively.
+fn random_messages(
+    rng: &mut impl RngCore,
+    lens: impl IntoIterator<Item = usize>,
+) -> (Vec<Vec<u8>>, Vec<String>) {
+    lens.into_iter()
+        .map(|len| {
+            let mut msg = vec![0; len];
+            rng.fill_bytes(&mut msg);
+            let digest = hex::encode(ethers_core::utils::keccak256(&msg));
+            (msg, digest)
+        })
+        .unzip()
+}
+
+#[test]
+fn packed_multi_keccak_random_long_messages() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    // Messages of many blocks, and around the boundaries of the blocks
+    let lens = (0..6)
+        .map(|_| rng.gen_range(0..=1024))
+        .chain([2 * RATE - 1, 2 * RATE, 2 * RATE + 1, 4 * RATE])
+        .collect::<Vec<_>>();
+    let (inputs, digests) = random_messages(&mut rng, lens);
+    verify::<Fr>(16, inputs, digests, true);
+}
+
 fn assigned_non_zero<F: Field>(cv: &CellValue<F>) -> bool {
     match *cv {
         CellValue::Assigned(v) => !v.is_zero_vartime(),
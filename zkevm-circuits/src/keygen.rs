@@ -0,0 +1,162 @@
+//! Proving keys regenerated from the params and the verifying key.
+//!
+//! A proving key is derived deterministically from the params, the circuit and its verifying key:
+//! only the params and the verifying key, both small, need to be distributed, and each prover
+//! derives the proving key locally with [`regenerate_pk`] instead of receiving it.
+//!
+//! The verifying key is checked against the one of the circuit for the params, so that the
+//! regenerated proving key creates proofs that verify with the distributed verifying key.
+//! [`check_artifacts`] does the same check for keys stored on disk, to detect the keys that are
+//! stale after a change of the circuit before proving with them.
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{self, keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+    SerdeFormat,
+};
+use std::io::{self, Read};
+use thiserror::Error;
+
+/// Error regenerating a proving key.
+#[derive(Debug, Error)]
+pub enum KeygenError {
+    /// The verifying key can't be decoded.
+    #[error("invalid verifying key: {0}")]
+    Io(#[from] io::Error),
+    /// The key generation failed.
+    #[error("key generation failed: {0}")]
+    Plonk(#[from] plonk::Error),
+    /// The verifying key isn't the one of the circuit for the params.
+    #[error("the verifying key is not the one of the circuit for the params")]
+    VkMismatch,
+    /// The proving key isn't the one of the circuit for the params.
+    #[error("the proving key is not the one of the circuit for the params")]
+    PkMismatch,
+}
+
+/// Read a verifying key of the circuit in `SerdeFormat::RawBytes`, and regenerate its proving key
+/// for the params.  Returns an error if the verifying key isn't the one of the circuit for the
+/// params.
+pub fn regenerate_pk<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    vk: &mut impl Read,
+    circuit: &C,
+) -> Result<ProvingKey<G1Affine>, KeygenError> {
+    let vk = VerifyingKey::<G1Affine>::read::<_, C>(vk, SerdeFormat::RawBytes, circuit.params())?;
+    if vk.transcript_repr() != keygen_vk(params, circuit)?.transcript_repr() {
+        return Err(KeygenError::VkMismatch);
+    }
+    Ok(keygen_pk(params, vk, circuit)?)
+}
+
+/// Check that a verifying key, and optionally a proving key, of the circuit stored in
+/// `SerdeFormat::RawBytes` are the ones of the current code of the circuit for the params.
+/// Returns an error if a key can't be decoded or isn't the one of the circuit.
+pub fn check_artifacts<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+    vk: &mut impl Read,
+    pk: Option<&mut impl Read>,
+) -> Result<(), KeygenError> {
+    // The transcript representation of a key is a digest of its constraint system and of the
+    // commitments to its fixed columns and permutation.
+    let expected = keygen_vk(params, circuit)?.transcript_repr();
+    let vk = VerifyingKey::<G1Affine>::read::<_, C>(vk, SerdeFormat::RawBytes, circuit.params())?;
+    if vk.transcript_repr() != expected {
+        return Err(KeygenError::VkMismatch);
+    }
+    if let Some(pk) = pk {
+        let pk = ProvingKey::<G1Affine>::read::<_, C>(pk, SerdeFormat::RawBytes, circuit.params())?;
+        if pk.get_vk().transcript_repr() != expected {
+            return Err(KeygenError::PkMismatch);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "exp-circuit"))]
+mod tests {
+    use super::*;
+    use crate::exp_circuit::ExpCircuit;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const K: u32 = 10;
+
+    #[test]
+    fn regenerate_pk_from_vk() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let params = ParamsKZG::<Bn256>::setup(K, &mut rng);
+        let circuit = ExpCircuit::<Fr>::new(vec![], 50);
+        let pk = keygen_pk(&params, keygen_vk(&params, &circuit).unwrap(), &circuit).unwrap();
+        let mut vk = Vec::new();
+        pk.get_vk().write(&mut vk, SerdeFormat::RawBytes).unwrap();
+
+        // The regenerated key is the generated one
+        let regenerated = regenerate_pk(&params, &mut vk.as_slice(), &circuit).unwrap();
+        let (mut pk_bytes, mut regenerated_bytes) = (Vec::new(), Vec::new());
+        pk.write(&mut pk_bytes, SerdeFormat::RawBytes).unwrap();
+        regenerated
+            .write(&mut regenerated_bytes, SerdeFormat::RawBytes)
+            .unwrap();
+        assert_eq!(pk_bytes, regenerated_bytes);
+
+        // The verifying key is not the one of other params
+        let other_params = ParamsKZG::<Bn256>::setup(K, &mut rng);
+        assert!(matches!(
+            regenerate_pk(&other_params, &mut vk.as_slice(), &circuit),
+            Err(KeygenError::VkMismatch)
+        ));
+        assert!(matches!(
+            regenerate_pk(&params, &mut &vk[..vk.len() / 2], &circuit),
+            Err(KeygenError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn check_stale_artifacts() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let params = ParamsKZG::<Bn256>::setup(K, &mut rng);
+        let circuit = ExpCircuit::<Fr>::new(vec![], 50);
+        let write_keys = |params: &ParamsKZG<Bn256>| {
+            let pk = keygen_pk(params, keygen_vk(params, &circuit).unwrap(), &circuit).unwrap();
+            let (mut vk_bytes, mut pk_bytes) = (Vec::new(), Vec::new());
+            pk.get_vk()
+                .write(&mut vk_bytes, SerdeFormat::RawBytes)
+                .unwrap();
+            pk.write(&mut pk_bytes, SerdeFormat::RawBytes).unwrap();
+            (vk_bytes, pk_bytes)
+        };
+
+        let (vk, pk) = write_keys(&params);
+        check_artifacts(
+            &params,
+            &circuit,
+            &mut vk.as_slice(),
+            Some(&mut pk.as_slice()),
+        )
+        .unwrap();
+
+        // The keys generated with other params are stale
+        let (other_vk, other_pk) = write_keys(&ParamsKZG::<Bn256>::setup(K, &mut rng));
+        assert!(matches!(
+            check_artifacts(
+                &params,
+                &circuit,
+                &mut other_vk.as_slice(),
+                None::<&mut &[u8]>
+            ),
+            Err(KeygenError::VkMismatch)
+        ));
+        assert!(matches!(
+            check_artifacts(
+                &params,
+                &circuit,
+                &mut vk.as_slice(),
+                Some(&mut other_pk.as_slice())
+            ),
+            Err(KeygenError::PkMismatch)
+        ));
+    }
+}
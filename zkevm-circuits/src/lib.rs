@@ -22,19 +22,38 @@ compile_error!("bus-mapping: notwasm feature must be disabled when target arch i
 #[cfg(all(not(target_arch = "wasm32"), not(feature = "notwasm")))]
 compile_error!("bus-mapping: notwasm feature must be enabled when target arch is not wasm");
 
+pub mod artifacts;
+#[cfg(feature = "bytecode-circuit")]
 pub mod bytecode_circuit;
+#[cfg(feature = "super-circuit")]
+pub mod capacity_stats;
+pub mod ceremony;
 #[allow(dead_code, reason = "under active development")]
 pub mod circuit_tools;
+#[cfg(feature = "copy-circuit")]
 pub mod copy_circuit;
+pub mod estimate;
 pub mod evm_circuit;
+#[cfg(feature = "exp-circuit")]
 pub mod exp_circuit;
+#[cfg(feature = "super-circuit")]
+pub mod feature_matrix;
+#[cfg(feature = "keccak-circuit")]
 pub mod keccak_circuit;
+pub mod keygen;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod local_prover;
+#[cfg(feature = "mpt-circuit")]
 #[allow(dead_code, reason = "under active development")]
 pub mod mpt_circuit;
+#[cfg(feature = "pi-circuit")]
 pub mod pi_circuit;
-#[cfg(not(target_arch = "wasm32"))]
+pub mod registry;
+#[cfg(all(feature = "root-circuit", not(target_arch = "wasm32")))]
 pub mod root_circuit;
+#[cfg(feature = "state-circuit")]
 pub mod state_circuit;
+#[cfg(feature = "super-circuit")]
 pub mod super_circuit;
 pub mod table;
 
@@ -43,8 +62,12 @@ pub mod table;
 pub mod test_util;
 
 pub mod instance;
+#[cfg(feature = "tx-circuit")]
 pub mod tx_circuit;
 pub mod util;
+pub mod verifier;
+#[cfg(feature = "withdrawal-circuit")]
+pub mod withdrawal_circuit;
 pub mod witness;
 
 pub use gadgets::impl_expr;
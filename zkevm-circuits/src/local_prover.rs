@@ -0,0 +1,519 @@
+//! Concurrent proving of several circuits on one machine.
+//!
+//! The halo2 prover parallelizes its MSMs and FFTs over the global rayon thread pool, so that a
+//! single proof takes all the cores of the machine, and concurrent proofs compete for them.  For
+//! circuits too small to use a large machine, [`prove_many_local`] runs several proofs at once,
+//! each in its own thread pool of a [`Partition`] of the threads, sized so that the peak memory
+//! of the concurrent proofs fits in the memory of the machine.
+//!
+//! [`prove_instances`] instead proves several instances of a same circuit, like the chunks of a
+//! block, in a single proof with the public inputs of each instance.  The instances share the
+//! challenges and the multiopen argument of the proof, and [`verify_instances`] checks them all
+//! with a single pairing.
+//!
+//! [`prove`] proves a circuit with any of the commitment schemes of halo2 listed by
+//! [`ProvingScheme`], and [`verify`] checks the proof.
+
+pub use crate::verifier::{verify, verify_instances};
+
+use crate::verifier::instance_refs;
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        ff::{FromUniformBytes, WithSmallOrderMulGroup},
+    },
+    plonk::{create_proof, Circuit, Error, ProvingKey},
+    poly::{
+        commitment::{CommitmentScheme, Prover},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverSHPLONK,
+        },
+    },
+    transcript::{EncodedChallenge, TranscriptWriterBuffer},
+};
+use rand::RngCore;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+/// Polynomial commitment scheme and multiopen argument of a proof, to be proven with the
+/// matching [`CommitmentScheme`] and [`Prover`] by [`prove`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingScheme {
+    /// KZG commitments over BN256 opened with SHPLONK.  Requires a trusted setup, and it's the
+    /// only scheme whose proofs can be aggregated by the Root Circuit.
+    Kzg,
+    /// KZG commitments over BN256 opened with GWC, which opens the commitments at each point
+    /// separately.  Uses the same keys as [`ProvingScheme::Kzg`], and can be cheaper to verify for
+    /// circuits queried at few rotations, but the proofs can't be aggregated.
+    KzgGwc,
+    /// IPA commitments over the BN256 G1 curve, not over the Pasta curves of the original halo2:
+    /// the circuits are written over the BN256 scalar field.  Doesn't require a trusted setup,
+    /// but the verification is linear in the circuit size and the proofs can't be aggregated.
+    IpaBn256,
+}
+
+/// Partition of the threads of a machine between concurrent proofs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partition {
+    /// Proofs run concurrently
+    pub jobs: usize,
+    /// Threads of the pool of each proof
+    pub threads_per_job: usize,
+}
+
+impl Partition {
+    /// Partition `threads` between as many concurrent proofs of `job_memory_bytes` as fit in
+    /// `memory_bytes`, at most `max_jobs` and one per thread.  The peak memory of a proof can be
+    /// estimated with [`estimate`](crate::estimate::estimate).
+    pub fn new(threads: usize, memory_bytes: u64, job_memory_bytes: u64, max_jobs: usize) -> Self {
+        let fit = (memory_bytes / job_memory_bytes.max(1)) as usize;
+        let jobs = fit.min(max_jobs).min(threads).max(1);
+        Self {
+            jobs,
+            threads_per_job: (threads / jobs).max(1),
+        }
+    }
+
+    /// Partition of the threads of this machine, as counted by rayon.
+    pub fn of_machine(memory_bytes: u64, job_memory_bytes: u64, max_jobs: usize) -> Self {
+        Self::new(
+            rayon::current_num_threads(),
+            memory_bytes,
+            job_memory_bytes,
+            max_jobs,
+        )
+    }
+
+    fn thread_pools(&self) -> Result<Vec<ThreadPool>, ThreadPoolBuildError> {
+        (0..self.jobs)
+            .map(|job| {
+                ThreadPoolBuilder::new()
+                    .num_threads(self.threads_per_job)
+                    .thread_name(move |i| format!("prover-{}-{}", job, i))
+                    .build()
+            })
+            .collect()
+    }
+}
+
+/// Run `prove` on each of the jobs, `partition.jobs` at a time, each in a thread pool of
+/// `partition.threads_per_job` threads which the parallel work of the prover runs on.  Returns
+/// the results in the order of the jobs, or an error if the thread pools can't be built.
+pub fn prove_many_local<J, R, P>(
+    jobs: Vec<J>,
+    partition: &Partition,
+    prove: P,
+) -> Result<Vec<R>, ThreadPoolBuildError>
+where
+    J: Send,
+    R: Send,
+    P: Fn(J) -> R + Sync,
+{
+    let pools = partition.thread_pools()?;
+    let num_jobs = jobs.len();
+    let jobs = jobs
+        .into_iter()
+        .map(|job| Mutex::new(Some(job)))
+        .collect::<Vec<_>>();
+    let results = Mutex::new((0..num_jobs).map(|_| None).collect::<Vec<Option<R>>>());
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for pool in pools.iter() {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= num_jobs {
+                    break;
+                }
+                let job = jobs[index].lock().unwrap().take().unwrap();
+                let result = pool.install(|| prove(job));
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    Ok(results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every job is proven"))
+        .collect())
+}
+
+/// Prove the instances of a circuit in a single SHPLONK proof with the transcript `T`, each with
+/// its public inputs, one vector per instance column.  All the circuits must have the
+/// configuration of the proving key.
+pub fn prove_instances<T, E, C>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: &[C],
+    instances: &[Vec<Vec<Fr>>],
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error>
+where
+    T: TranscriptWriterBuffer<Vec<u8>, G1Affine, E>,
+    E: EncodedChallenge<G1Affine>,
+    C: Circuit<Fr>,
+{
+    if circuits.is_empty() || circuits.len() != instances.len() {
+        return Err(Error::InvalidInstances);
+    }
+    let instance_refs = instance_refs(instances);
+    let instance_refs = instance_refs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = T::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        circuits,
+        &instance_refs,
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Prove a circuit with the commitment scheme `Scheme`, its multiopen prover `P` and the
+/// transcript `T`, with its public inputs, one vector per instance column.  The proof is checked
+/// by [`verify`] with the matching verifier.
+pub fn prove<'params, Scheme, P, T, E, C>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuit: C,
+    instance: &[Vec<Scheme::Scalar>],
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error>
+where
+    Scheme: CommitmentScheme,
+    Scheme::Scalar: WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    P: Prover<'params, Scheme>,
+    T: TranscriptWriterBuffer<Vec<u8>, Scheme::Curve, E>,
+    E: EncodedChallenge<Scheme::Curve>,
+    C: Circuit<Scheme::Scalar>,
+{
+    let instance = instance.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = T::init(vec![]);
+    create_proof::<Scheme, P, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&instance],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+#[cfg(all(test, feature = "exp-circuit"))]
+mod tests {
+    use super::*;
+    use crate::{
+        exp_circuit::ExpCircuit,
+        verifier::{encode_instance, verify_bytes, VerifyError},
+    };
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{keygen_pk, keygen_vk, verify_proof, Advice, Column, ConstraintSystem, Instance},
+        poly::{
+            commitment::ParamsProver,
+            ipa::{
+                commitment::{IPACommitmentScheme, ParamsIPA},
+                multiopen::{ProverIPA, VerifierIPA},
+                strategy::SingleStrategy as IPASingleStrategy,
+            },
+            kzg::{
+                multiopen::{ProverGWC, VerifierGWC, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+            VerificationStrategy,
+        },
+        transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer},
+        SerdeFormat,
+    };
+    use rand::{rngs::OsRng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    /// Circuit exposing a value as its public input.
+    #[derive(Clone, Default)]
+    struct PublicValueCircuit(Fr);
+
+    impl Circuit<Fr> for PublicValueCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            (advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let cell = layouter.assign_region(
+                || "value",
+                |mut region| region.assign_advice(|| "value", advice, 0, || Value::known(self.0)),
+            )?;
+            layouter.constrain_instance(cell.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn partition_threads_and_memory() {
+        // Limited by the memory
+        assert_eq!(
+            Partition::new(64, 100, 30, 8),
+            Partition {
+                jobs: 3,
+                threads_per_job: 21
+            }
+        );
+        // Limited by the jobs
+        assert_eq!(
+            Partition::new(64, 100, 10, 4),
+            Partition {
+                jobs: 4,
+                threads_per_job: 16
+            }
+        );
+        // A proof that doesn't fit runs alone
+        assert_eq!(
+            Partition::new(8, 10, 30, 4),
+            Partition {
+                jobs: 1,
+                threads_per_job: 8
+            }
+        );
+    }
+
+    #[test]
+    fn prove_many_exp_circuits() {
+        let k = 10;
+        let params = ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::seed_from_u64(2));
+        let circuit = ExpCircuit::<Fr>::new(vec![], 50);
+        let pk = keygen_pk(&params, keygen_vk(&params, &circuit).unwrap(), &circuit).unwrap();
+
+        let partition = Partition {
+            jobs: 2,
+            threads_per_job: 1,
+        };
+        let proofs = prove_many_local(vec![circuit; 3], &partition, |circuit| {
+            // Each proof runs in a pool of a single thread
+            assert_eq!(rayon::current_num_threads(), 1);
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit],
+                &[&[]],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        })
+        .unwrap();
+
+        assert_eq!(proofs.len(), 3);
+        for proof in proofs {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.as_slice());
+            verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+                &params,
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&[]],
+                &mut transcript,
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn prove_instances_in_one_proof() {
+        let k = 4;
+        let params = ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::seed_from_u64(2));
+        let circuit = PublicValueCircuit::default();
+        let pk = keygen_pk(&params, keygen_vk(&params, &circuit).unwrap(), &circuit).unwrap();
+
+        let circuits = (1..=3)
+            .map(|value| PublicValueCircuit(Fr::from(value)))
+            .collect::<Vec<_>>();
+        let instances = (1..=3)
+            .map(|value| vec![vec![Fr::from(value)]])
+            .collect::<Vec<_>>();
+        let proof = prove_instances::<Blake2bWrite<_, _, Challenge255<_>>, _, _>(
+            &params, &pk, &circuits, &instances, OsRng,
+        )
+        .unwrap();
+        let verify = |instances: &[Vec<Vec<Fr>>]| {
+            verify_instances::<Blake2bRead<_, _, Challenge255<_>>, _>(
+                &params,
+                pk.get_vk(),
+                instances,
+                &proof,
+            )
+        };
+        verify(&instances).unwrap();
+
+        // The public inputs are bound to their instance
+        let mut swapped = instances.clone();
+        swapped.swap(0, 1);
+        assert!(verify(&swapped).is_err());
+        assert!(verify(&instances[..2]).is_err());
+        assert!(matches!(
+            prove_instances::<Blake2bWrite<_, _, Challenge255<_>>, _, _>(
+                &params,
+                &pk,
+                &circuits,
+                &instances[..2],
+                OsRng,
+            ),
+            Err(Error::InvalidInstances)
+        ));
+    }
+
+    #[test]
+    fn prove_with_each_scheme() {
+        let k = 4;
+        let circuit = PublicValueCircuit(Fr::from(7));
+        let instance = vec![vec![Fr::from(7)]];
+        let wrong_instance = vec![vec![Fr::from(8)]];
+
+        let params = ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::seed_from_u64(2));
+        let pk = keygen_pk(&params, keygen_vk(&params, &circuit).unwrap(), &circuit).unwrap();
+        let proof = prove::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<'_, Bn256>,
+            Blake2bWrite<_, _, Challenge255<_>>,
+            _,
+            _,
+        >(&params, &pk, circuit.clone(), &instance, OsRng)
+        .unwrap();
+        let verify_kzg = |instance: &[Vec<Fr>]| {
+            verify::<
+                KZGCommitmentScheme<Bn256>,
+                VerifierSHPLONK<'_, Bn256>,
+                Blake2bRead<_, _, Challenge255<_>>,
+                _,
+                _,
+            >(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                instance,
+                &proof,
+            )
+        };
+        verify_kzg(&instance).unwrap();
+        assert!(verify_kzg(&wrong_instance).is_err());
+
+        let proof = prove::<
+            KZGCommitmentScheme<Bn256>,
+            ProverGWC<'_, Bn256>,
+            Blake2bWrite<_, _, Challenge255<_>>,
+            _,
+            _,
+        >(&params, &pk, circuit.clone(), &instance, OsRng)
+        .unwrap();
+        let verify_gwc = |instance: &[Vec<Fr>]| {
+            verify::<
+                KZGCommitmentScheme<Bn256>,
+                VerifierGWC<'_, Bn256>,
+                Blake2bRead<_, _, Challenge255<_>>,
+                _,
+                _,
+            >(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                instance,
+                &proof,
+            )
+        };
+        verify_gwc(&instance).unwrap();
+        assert!(verify_gwc(&wrong_instance).is_err());
+
+        let params = ParamsIPA::<G1Affine>::new(k);
+        let pk = keygen_pk(&params, keygen_vk(&params, &circuit).unwrap(), &circuit).unwrap();
+        let proof = prove::<
+            IPACommitmentScheme<G1Affine>,
+            ProverIPA<'_, G1Affine>,
+            Blake2bWrite<_, _, Challenge255<_>>,
+            _,
+            _,
+        >(&params, &pk, circuit, &instance, OsRng)
+        .unwrap();
+        let verify_ipa = |instance: &[Vec<Fr>]| {
+            verify::<
+                IPACommitmentScheme<G1Affine>,
+                VerifierIPA<'_, G1Affine>,
+                Blake2bRead<_, _, Challenge255<_>>,
+                _,
+                _,
+            >(
+                &params,
+                pk.get_vk(),
+                IPASingleStrategy::new(&params),
+                instance,
+                &proof,
+            )
+        };
+        verify_ipa(&instance).unwrap();
+        assert!(verify_ipa(&wrong_instance).is_err());
+    }
+
+    #[test]
+    fn verify_bytes_of_instance() {
+        let k = 4;
+        let params = ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::seed_from_u64(2));
+        let circuit = PublicValueCircuit(Fr::from(7));
+        let pk = keygen_pk(&params, keygen_vk(&params, &circuit).unwrap(), &circuit).unwrap();
+        let mut vk_bytes = vec![];
+        pk.get_vk()
+            .write(&mut vk_bytes, SerdeFormat::RawBytes)
+            .unwrap();
+        let mut params_bytes = vec![];
+        params
+            .write_custom(&mut params_bytes, SerdeFormat::RawBytes)
+            .unwrap();
+
+        let proof = prove_instances::<Blake2bWrite<_, _, Challenge255<_>>, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[vec![vec![Fr::from(7)]]],
+            OsRng,
+        )
+        .unwrap();
+        let verify = |instance: &[u8]| {
+            verify_bytes::<PublicValueCircuit, Blake2bRead<_, _, Challenge255<_>>, _>(
+                &vk_bytes,
+                &params_bytes,
+                &proof,
+                &[instance],
+                (),
+            )
+        };
+        verify(&encode_instance(&[Fr::from(7)])).unwrap();
+        assert!(matches!(
+            verify(&encode_instance(&[Fr::from(8)])),
+            Err(VerifyError::Proof(_))
+        ));
+        assert!(matches!(verify(&[0; 31]), Err(VerifyError::Instance(0))));
+    }
+}
@@ -11,7 +11,7 @@ use std::{cmp::min, iter, marker::PhantomData};
 pub use PiCircuit as TestPiCircuit;
 
 use bus_mapping::circuit_input_builder::Withdrawal;
-use eth_types::{self, Field, ToLittleEndian};
+use eth_types::{self, Field, ToLittleEndian, Word};
 use halo2_proofs::plonk::{Expression, Instance, SecondPhase};
 use itertools::Itertools;
 use param::*;
@@ -19,8 +19,9 @@ use param::*;
 use crate::{
     evm_circuit::{
         param::{
-            N_BYTES_BLOCK, N_BYTES_EXTRA_VALUE, N_BYTES_HALF_WORD, N_BYTES_TX, N_BYTES_U64,
-            N_BYTES_WITHDRAWAL, N_BYTES_WORD,
+            MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS, N_BYTES_BLOCK,
+            N_BYTES_EXTRA_VALUE, N_BYTES_HALF_WORD, N_BYTES_TX, N_BYTES_U64, N_BYTES_WITHDRAWAL,
+            N_BYTES_WORD,
         },
         util::{
             constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
@@ -28,11 +29,11 @@ use crate::{
         },
     },
     instance::{
-        public_data_convert, BlockValues, ExtraValues, PublicData, TxValues, NONZERO_BYTE_GAS_COST,
-        ZERO_BYTE_GAS_COST,
+        public_data_convert, BlockValues, ExtraValues, PublicData, PublicInputs, TxValues,
+        NONZERO_BYTE_GAS_COST, ZERO_BYTE_GAS_COST,
     },
     table::{BlockTable, KeccakTable, LookupTable, TxFieldTag, TxTable, WdTable},
-    tx_circuit::TX_LEN,
+    tx_circuit::{AccessListConfig, TX_FIELDS_LEN, TX_LEN},
     util::{word::WordLoHi, Challenges, SubCircuit, SubCircuitConfig},
     witness,
 };
@@ -68,6 +69,13 @@ pub struct PiCircuitConfig<F: Field> {
     q_calldata_start: Selector,
     // q_rpi_keccak_lookup: enable keccak lookup
     q_rpi_keccak_lookup: Selector,
+    // q_tx_bytes: 1 on the rows of the tx table and calldata raw public input bytes, others are 0
+    q_tx_bytes: Selector,
+    // q_tx_bytes_last: 1 on the last row of the tx table and calldata raw public input bytes,
+    // others are 0
+    q_tx_bytes_last: Selector,
+    // q_tx_keccak_lookup: enable keccak lookup of the tx table and calldata bytes
+    q_tx_keccak_lookup: Selector,
     // q_rpi_value_start: assure rpi_bytes sync with rpi_value_lc when cross boundary.
     // because we layout rpi bytes vertically, which is concatenate from multiple original values.
     // The value can be one byte or multiple bytes. The order of values is pre-defined and
@@ -85,6 +93,8 @@ pub struct PiCircuitConfig<F: Field> {
     fixed_u16: Column<Fixed>,
     calldata_gas_cost: Column<Advice>,
     is_final: Column<Advice>,
+    // access_list: constrains the access list slots against their lengths
+    access_list: AccessListConfig<F>,
 
     // rpi_bytes: raw public input bytes laid vertically
     rpi_bytes: Column<Advice>,
@@ -98,10 +108,15 @@ pub struct PiCircuitConfig<F: Field> {
     rpi_digest_bytes: Column<Advice>,
     // rpi_digest_bytes_limbs: hi, lo limbs of digest
     rpi_digest_bytes_limbs: Column<Advice>,
+    // tx_bytes_keccak_rlc: rpi_bytes rlc by keccak challenge of the tx table and calldata bytes.
+    // This is for the tx digest Keccak lookup input rlc
+    tx_bytes_keccak_rlc: Column<Advice>,
+    // tx_digest_limbs: hi, lo limbs of the tx digest
+    tx_digest_limbs: Column<Advice>,
 
     q_rpi_byte_enable: Selector,
 
-    pi_instance: Column<Instance>, // keccak_digest_hi, keccak_digest_lo
+    pi_instance: Column<Instance>, // PublicInputs lo, hi limbs
 
     _marker: PhantomData<F>,
     // External tables
@@ -152,6 +167,9 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         let q_tx_calldata = meta.complex_selector();
         let q_calldata_start = meta.complex_selector();
         let q_rpi_keccak_lookup = meta.complex_selector();
+        let q_tx_bytes = meta.complex_selector();
+        let q_tx_bytes_last = meta.complex_selector();
+        let q_tx_keccak_lookup = meta.complex_selector();
         // Tx Table
         let tx_id = tx_table.tx_id;
         let tx_value = tx_table.value;
@@ -179,6 +197,8 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         let rpi_value_lc = meta.advice_column();
         let rpi_digest_bytes = meta.advice_column();
         let rpi_digest_bytes_limbs = meta.advice_column();
+        let tx_bytes_keccak_rlc = meta.advice_column_in(SecondPhase);
+        let tx_digest_limbs = meta.advice_column();
 
         let pi_instance = meta.instance_column();
 
@@ -204,6 +224,7 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         meta.enable_equality(rpi_bytes_keccak_rlc);
 
         meta.enable_equality(rpi_digest_bytes_limbs);
+        meta.enable_equality(tx_digest_limbs);
 
         meta.enable_equality(pi_instance);
 
@@ -318,6 +339,76 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             },
         );
 
+        // gate 6 and gate 7 are compensation branch
+        // 6: tx_bytes_keccak_rlc[last] = rpi_bytes[last]
+        meta.create_gate("tx_bytes_keccak_rlc[last] = rpi_bytes[last]", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "tx_bytes_keccak_rlc[last] = rpi_bytes[last]",
+                meta.query_advice(tx_bytes_keccak_rlc, Rotation::cur()),
+                meta.query_advice(rpi_bytes, Rotation::cur()),
+            );
+
+            cb.gate(meta.query_selector(q_tx_bytes_last) * meta.query_selector(q_tx_bytes))
+        });
+
+        // 7: tx_bytes_keccak_rlc[i] = keccak_rand * tx_bytes_keccak_rlc[i+1] + rpi_bytes[i]
+        meta.create_gate(
+            "tx_bytes_keccak_rlc[i] = keccak_rand * tx_bytes_keccak_rlc[i+1] + rpi_bytes[i]",
+            |meta| {
+                let mut cb = BaseConstraintBuilder::default();
+
+                let tx_bytes_keccakrlc_cur =
+                    meta.query_advice(tx_bytes_keccak_rlc, Rotation::cur());
+                let tx_bytes_keccakrlc_next =
+                    meta.query_advice(tx_bytes_keccak_rlc, Rotation::next());
+                let rpi_bytes_cur = meta.query_advice(rpi_bytes, Rotation::cur());
+
+                let keccak_rand = challenges.keccak_input();
+                cb.require_equal(
+                    "tx_bytes_keccak_rlc[i] = keccak_rand * tx_bytes_keccak_rlc[i+1] + rpi_bytes[i]",
+                    tx_bytes_keccakrlc_cur,
+                    tx_bytes_keccakrlc_next * keccak_rand + rpi_bytes_cur,
+                );
+
+                cb.gate(
+                    not::expr(meta.query_selector(q_tx_bytes_last))
+                        * meta.query_selector(q_tx_bytes),
+                )
+            },
+        );
+
+        // 8. lookup tx_bytes_keccak_rlc against tx_digest_limbs
+        meta.lookup_any(
+            "lookup tx_bytes_keccak_rlc against tx_digest_limbs",
+            |meta| {
+                let tx_len = PiCircuitConfig::<F>::circuit_len_tx(max_txs, max_calldata).expr();
+                let is_enabled = meta.query_advice(keccak_table.is_enabled, Rotation::cur());
+                let input_rlc = meta.query_advice(keccak_table.input_rlc, Rotation::cur());
+                let input_len = meta.query_advice(keccak_table.input_len, Rotation::cur());
+                let output_lo = meta.query_advice(keccak_table.output.lo(), Rotation::cur());
+                let output_hi = meta.query_advice(keccak_table.output.hi(), Rotation::cur());
+
+                let q_tx_keccak_lookup = meta.query_selector(q_tx_keccak_lookup);
+                let tx_bytes_keccakrlc_cur =
+                    meta.query_advice(tx_bytes_keccak_rlc, Rotation::cur());
+                let tx_digest_lo = meta.query_advice(tx_digest_limbs, Rotation::cur());
+                let tx_digest_hi = meta.query_advice(tx_digest_limbs, Rotation::next());
+
+                vec![
+                    (q_tx_keccak_lookup.expr() * 1.expr(), is_enabled),
+                    (
+                        q_tx_keccak_lookup.expr() * tx_bytes_keccakrlc_cur,
+                        input_rlc,
+                    ),
+                    (q_tx_keccak_lookup.expr() * tx_len, input_len),
+                    (q_tx_keccak_lookup.expr() * tx_digest_lo, output_lo),
+                    (q_tx_keccak_lookup * tx_digest_hi, output_hi),
+                ]
+            },
+        );
+
         let tx_id_is_zero_config = IsZeroChip::configure(
             meta,
             |meta| meta.query_selector(q_tx_calldata),
@@ -496,6 +587,8 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             ]
         });
 
+        let access_list = AccessListConfig::configure(meta, &tx_table);
+
         Self {
             max_txs,
             max_withdrawals,
@@ -506,6 +599,9 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             q_tx_calldata,
             q_calldata_start,
             q_rpi_keccak_lookup,
+            q_tx_bytes,
+            q_tx_bytes_last,
+            q_tx_keccak_lookup,
             q_rpi_value_start,
             q_tx_table,
             q_digest_value_start,
@@ -518,11 +614,14 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             fixed_u16,
             calldata_gas_cost,
             is_final,
+            access_list,
             rpi_bytes,
             rpi_bytes_keccak_rlc,
             rpi_value_lc,
             rpi_digest_bytes,
             rpi_digest_bytes_limbs,
+            tx_bytes_keccak_rlc,
+            tx_digest_limbs,
             q_rpi_byte_enable,
             pi_instance,
             _marker: PhantomData,
@@ -543,11 +642,17 @@ impl<F: Field> PiCircuitConfig<F> {
         N_BYTES_ONE
             + N_BYTES_BLOCK
             + N_BYTES_EXTRA_VALUE
-            + Self::circuit_len_tx_id(txs)
+            + Self::circuit_len_tx(txs, calldata)
+            + Self::circuit_len_withdrawal(wds)
+    }
+
+    /// Return the number of rows for the tx table and calldata
+    #[inline]
+    fn circuit_len_tx(txs: usize, calldata: usize) -> usize {
+        Self::circuit_len_tx_id(txs)
             + Self::circuit_len_tx_index(txs)
             + Self::circuit_len_tx_values(txs)
             + calldata
-            + Self::circuit_len_withdrawal(wds)
     }
 
     #[inline]
@@ -674,7 +779,7 @@ impl<F: Field> PiCircuitConfig<F> {
     }
 
     /// Assigns a tx_table row and stores the values in a vec for the
-    /// raw_public_inputs column
+    /// raw_public_inputs column.  Returns the assigned cells of the value.
     #[allow(clippy::too_many_arguments)]
     fn assign_tx_row(
         &self,
@@ -689,7 +794,7 @@ impl<F: Field> PiCircuitConfig<F> {
         current_rpi_offset: &mut usize,
         rpi_bytes: &mut [u8],
         zero_cell: AssignedCell<F, F>,
-    ) -> Result<(), Error> {
+    ) -> Result<WordLoHi<AssignedCell<F, F>>, Error> {
         // tx_id_inv = (tag - CallDataLength)^(-1)
         let tx_id_inv = if tag != TxFieldTag::CallDataLength {
             let x = F::from(tag as u64) - F::from(TxFieldTag::CallDataLength as u64);
@@ -784,7 +889,7 @@ impl<F: Field> PiCircuitConfig<F> {
             || tx_value_inv.lo(),
         )?;
 
-        Ok(())
+        Ok(tx_value_assignedcell)
     }
 
     /// Assigns one calldata row
@@ -1107,7 +1212,8 @@ impl<F: Field> PiCircuitConfig<F> {
 
     /// Assigns the values for block table in the block_table column
     /// and rpi_bytes columns. Copy constraints will be enable
-    /// to assure block_table value cell equal with respective rpi_byte_rlc cell
+    /// to assure block_table value cell equal with respective rpi_byte_rlc cell.
+    /// Returns the withdrawals root and parent hash cells
     #[allow(clippy::too_many_arguments)]
     fn assign_block_table(
         &self,
@@ -1119,7 +1225,7 @@ impl<F: Field> PiCircuitConfig<F> {
         current_rpi_offset: &mut usize,
         rpi_bytes: &mut [u8],
         zero_cell: AssignedCell<F, F>,
-    ) -> Result<(), Error> {
+    ) -> Result<(WordLoHi<AssignedCell<F, F>>, WordLoHi<AssignedCell<F, F>>), Error> {
         let mut block_copy_cells = vec![];
 
         // coinbase
@@ -1293,6 +1399,7 @@ impl<F: Field> PiCircuitConfig<F> {
             challenges,
             zero_cell.clone(),
         )?;
+        let withdrawals_root = word.clone();
         block_copy_cells.push((block_value, word));
         *block_table_offset += 1;
 
@@ -1320,6 +1427,11 @@ impl<F: Field> PiCircuitConfig<F> {
             block_copy_cells.push((block_value, word));
             *block_table_offset += 1;
         }
+        // the parent hash is the latest of the history hashes
+        let (_, parent_hash) = block_copy_cells
+            .last()
+            .expect("history hashes are padded to 256")
+            .clone();
 
         block_copy_cells.iter().try_for_each(|(left, right)| {
             region.constrain_equal(left.lo().cell(), right.lo().cell())?;
@@ -1327,14 +1439,16 @@ impl<F: Field> PiCircuitConfig<F> {
             Ok::<(), Error>(())
         })?;
 
-        Ok(())
+        Ok((withdrawals_root, parent_hash))
     }
 
     /// Assigns the extra fields (not in block or tx tables):
     ///   - block hash
     ///   - state root
     ///   - previous block state root
-    /// to the rpi_byte column
+    ///   - previous block pi digest
+    /// to the rpi_byte column. Returns the block hash, state root,
+    /// previous block state root and previous block pi digest cells
     #[allow(clippy::too_many_arguments)]
     fn assign_extra_fields(
         &self,
@@ -1345,9 +1459,9 @@ impl<F: Field> PiCircuitConfig<F> {
         current_rpi_offset: &mut usize,
         rpi_bytes: &mut [u8],
         zero_cell: AssignedCell<F, F>,
-    ) -> Result<(), Error> {
+    ) -> Result<[WordLoHi<AssignedCell<F, F>>; 4], Error> {
         // block hash
-        self.assign_raw_bytes(
+        let (_, block_hash) = self.assign_raw_bytes(
             region,
             &extra
                 .block_hash
@@ -1364,7 +1478,7 @@ impl<F: Field> PiCircuitConfig<F> {
         )?;
 
         // block state root
-        self.assign_raw_bytes(
+        let (_, state_root) = self.assign_raw_bytes(
             region,
             &extra
                 .state_root
@@ -1381,7 +1495,7 @@ impl<F: Field> PiCircuitConfig<F> {
         )?;
 
         // previous block state root
-        self.assign_raw_bytes(
+        let (_, prev_state_root) = self.assign_raw_bytes(
             region,
             &extra
                 .prev_state_root
@@ -1394,10 +1508,74 @@ impl<F: Field> PiCircuitConfig<F> {
             rpi_bytes,
             current_rpi_offset,
             challenges,
+            zero_cell.clone(),
+        )?;
+
+        // previous block pi digest
+        let (_, prev_pi_digest) = self.assign_raw_bytes(
+            region,
+            &extra
+                .prev_pi_digest
+                .to_fixed_bytes()
+                .iter()
+                .copied()
+                .rev()
+                .collect_vec(),
+            rpi_bytes_keccak_rlc,
+            rpi_bytes,
+            current_rpi_offset,
+            challenges,
             zero_cell,
         )?;
 
-        Ok(())
+        Ok([block_hash, state_root, prev_state_root, prev_pi_digest])
+    }
+
+    /// Assigns the keccak rlc of the tx table and calldata bytes, which are laid from
+    /// `circuit_len_withdrawal` to `circuit_len_withdrawal + circuit_len_tx - 1` in the rpi_bytes
+    /// column, and the tx digest word
+    fn assign_tx_digest(
+        &self,
+        region: &mut Region<'_, F>,
+        rpi_bytes: &[u8],
+        challenges: &Challenges<Value<F>>,
+        tx_digest_word: WordLoHi<F>,
+    ) -> Result<WordLoHi<AssignedCell<F, F>>, Error> {
+        let keccak_rand = challenges.keccak_input();
+        let tx_offset = Self::circuit_len_withdrawal(self.max_withdrawals);
+        let tx_len = Self::circuit_len_tx(self.max_txs, self.max_calldata);
+
+        let mut tx_bytes_keccak_rlc = Value::known(F::ZERO);
+        for offset in (tx_offset..tx_offset + tx_len).rev() {
+            tx_bytes_keccak_rlc = tx_bytes_keccak_rlc
+                .zip(keccak_rand)
+                .map(|(acc, rand)| acc * rand + F::from(rpi_bytes[offset] as u64));
+            self.q_tx_bytes.enable(region, offset)?;
+            region.assign_advice(
+                || "tx_bytes_keccak_rlc",
+                self.tx_bytes_keccak_rlc,
+                offset,
+                || tx_bytes_keccak_rlc,
+            )?;
+        }
+        self.q_tx_bytes_last
+            .enable(region, tx_offset + tx_len - 1)?;
+        // keccak lookup occur on the last tx byte
+        self.q_tx_keccak_lookup.enable(region, tx_offset)?;
+
+        let lo_assigned_cell = region.assign_advice(
+            || "tx_digest_limbs_lo",
+            self.tx_digest_limbs,
+            tx_offset,
+            || tx_digest_word.into_value().lo(),
+        )?;
+        let hi_assigned_cell = region.assign_advice(
+            || "tx_digest_limbs_hi",
+            self.tx_digest_limbs,
+            tx_offset + 1,
+            || tx_digest_word.into_value().hi(),
+        )?;
+        Ok(WordLoHi::new([lo_assigned_cell, hi_assigned_cell]))
     }
 
     /// Assign digest word
@@ -1485,13 +1663,13 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
 
     /// Compute the public inputs for this circuit.
     fn instance(&self) -> Vec<Vec<F>> {
-        let rpi_digest_byte_field = self.public_data.get_rpi_digest_word(
+        let public_inputs = self.public_data.get_public_inputs(
             self.max_txs,
             self.max_withdrawals,
             self.max_calldata,
         );
 
-        vec![vec![rpi_digest_byte_field.lo(), rpi_digest_byte_field.hi()]]
+        vec![public_inputs.instance()]
     }
 
     /// Make the assignments to the PiCircuit
@@ -1516,7 +1694,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 Ok(())
             },
         )?;
-        let digest_word_assigned = layouter.assign_region(
+        let public_inputs_assigned = layouter.assign_region(
             || "region 0",
             |mut region| {
                 // Annotate columns
@@ -1532,6 +1710,8 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 region.name_column(|| "q_digest_value_start", config.q_digest_value_start);
                 region.name_column(|| "rpi_digest_bytes", config.rpi_digest_bytes);
                 region.name_column(|| "rpi_digest_bytes_lc", config.rpi_digest_bytes_limbs);
+                region.name_column(|| "tx_bytes_keccak_rlc", config.tx_bytes_keccak_rlc);
+                region.name_column(|| "tx_digest_limbs", config.tx_digest_limbs);
                 region.name_column(|| "tx_id_inv", config.tx_id_inv);
                 region.name_column(|| "tx_value_lo_inv", config.tx_value_lo_inv);
                 region.name_column(|| "tx_id_diff_inv", config.tx_id_diff_inv);
@@ -1581,7 +1761,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     zero_cell.clone(),
                 )?;
                 block_table_offset += 1;
-                config.assign_block_table(
+                let (withdrawals_root, parent_hash) = config.assign_block_table(
                     &mut region,
                     &mut block_table_offset,
                     block_values,
@@ -1598,15 +1778,16 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
 
                 // Assign extra fields
                 let extra_vals = self.public_data.get_extra_values();
-                config.assign_extra_fields(
-                    &mut region,
-                    extra_vals,
-                    &mut rpi_bytes_keccak_rlc,
-                    challenges,
-                    &mut current_rpi_offset,
-                    &mut rpi_bytes,
-                    zero_cell,
-                )?;
+                let [block_hash, state_root, prev_state_root, prev_pi_digest] = config
+                    .assign_extra_fields(
+                        &mut region,
+                        extra_vals,
+                        &mut rpi_bytes_keccak_rlc,
+                        challenges,
+                        &mut current_rpi_offset,
+                        &mut rpi_bytes,
+                        zero_cell,
+                    )?;
                 assert_eq!(
                     start_offset - current_rpi_offset,
                     N_BYTES_ONE + N_BYTES_BLOCK + N_BYTES_EXTRA_VALUE
@@ -1645,7 +1826,8 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     .chain((0..(config.max_txs - txs.len())).map(|_| &tx_default))
                     .enumerate()
                     .try_for_each(|(i, tx)| -> Result<(), Error> {
-                        for (tag, value_bytes) in &[
+                        let mut access_list_len_cells = vec![];
+                        for (tag, index, value_bytes) in [
                             (TxFieldTag::Nonce, tx.nonce.to_le_bytes().to_vec()),
                             (TxFieldTag::Gas, tx.gas_limit.to_le_bytes().to_vec()),
                             (TxFieldTag::GasPrice, tx.gas_price.to_le_bytes().to_vec()),
@@ -1679,16 +1861,39 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                             ),
                             // TODO witness tx.tx_sign_hash
                             (TxFieldTag::TxSignHash, tx.tx_sign_hash.to_vec()),
-                        ] {
+                            (
+                                TxFieldTag::AccessListAddressesLen,
+                                tx.access_list_addresses_len.to_le_bytes().to_vec(),
+                            ),
+                            (
+                                TxFieldTag::AccessListStorageKeysLen,
+                                tx.access_list_storage_keys_len.to_le_bytes().to_vec(),
+                            ),
+                            (
+                                TxFieldTag::MaxFeePerGas,
+                                tx.max_fee_per_gas.to_le_bytes().to_vec(),
+                            ),
+                            (
+                                TxFieldTag::MaxPriorityFeePerGas,
+                                tx.max_priority_fee_per_gas.to_le_bytes().to_vec(),
+                            ),
+                        ]
+                        .into_iter()
+                        .map(|(tag, value_bytes)| (tag, 0, value_bytes))
+                        .chain(tx.access_list_slots().into_iter().map(
+                            |(tag, index, value_bytes)| {
+                                (tag, index, value_bytes.into_iter().rev().collect_vec())
+                            },
+                        )) {
                             let i: u64 = i.try_into().unwrap();
                             // assign tx field
-                            config.assign_tx_row(
+                            let value_cell = config.assign_tx_row(
                                 &mut region,
                                 tx_table_offset,
                                 i + 1,
-                                *tag,
-                                0,
-                                value_bytes,
+                                tag,
+                                index,
+                                &value_bytes,
                                 &mut rpi_bytes_keccak_rlc,
                                 challenges,
                                 &mut current_rpi_offset,
@@ -1696,7 +1901,39 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                                 zero_cell.clone(),
                             )?;
                             tx_table_offset += 1;
+                            if matches!(
+                                tag,
+                                TxFieldTag::AccessListAddressesLen
+                                    | TxFieldTag::AccessListStorageKeysLen
+                            ) {
+                                access_list_len_cells.push(value_cell.lo());
+                            }
                         }
+
+                        // Constrain the access list slots against their lengths
+                        let [addresses_len, storage_keys_len]: [_; 2] =
+                            access_list_len_cells.try_into().unwrap();
+                        config.access_list.assign(
+                            &mut region,
+                            tx_table_offset - (TX_LEN - TX_FIELDS_LEN),
+                            &[
+                                (
+                                    MAX_ACCESS_LIST_ADDRESSES,
+                                    tx.access_list_addresses.len() as u64,
+                                    addresses_len,
+                                ),
+                                (
+                                    MAX_ACCESS_LIST_STORAGE_KEYS,
+                                    tx.access_list_storage_keys.len() as u64,
+                                    storage_keys_len.clone(),
+                                ),
+                                (
+                                    MAX_ACCESS_LIST_STORAGE_KEYS,
+                                    tx.access_list_storage_keys.len() as u64,
+                                    storage_keys_len,
+                                ),
+                            ],
+                        )?;
                         Ok(())
                     })?;
                 assert_eq!(
@@ -1835,13 +2072,34 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 // keccak lookup occur on offset 0
                 config.q_rpi_keccak_lookup.enable(&mut region, 0)?;
 
-                Ok(digest_word_assigned)
+                // assign tx digest
+                let tx_digest_word = WordLoHi::from(Word::from_big_endian(
+                    self.public_data
+                        .get_tx_digest(config.max_txs, config.max_calldata)
+                        .as_bytes(),
+                ));
+                let tx_digest_assigned =
+                    config.assign_tx_digest(&mut region, &rpi_bytes, challenges, tx_digest_word)?;
+
+                // in the order of PublicInputs
+                Ok([
+                    digest_word_assigned,
+                    parent_hash,
+                    state_root,
+                    withdrawals_root,
+                    tx_digest_assigned,
+                    prev_state_root,
+                    block_hash,
+                    prev_pi_digest,
+                ])
             },
         )?;
 
         // Constrain raw_public_input cells to public inputs
-        layouter.constrain_instance(digest_word_assigned.lo().cell(), config.pi_instance, 0)?;
-        layouter.constrain_instance(digest_word_assigned.hi().cell(), config.pi_instance, 1)?;
+        for (i, word) in public_inputs_assigned.iter().enumerate() {
+            layouter.constrain_instance(word.lo().cell(), config.pi_instance, 2 * i)?;
+            layouter.constrain_instance(word.hi().cell(), config.pi_instance, 2 * i + 1)?;
+        }
 
         Ok(())
     }
@@ -80,9 +80,12 @@ impl<F: Field> Circuit<F> for PiCircuit<F> {
             config.max_withdrawals,
             config.max_calldata,
         );
+        let tx_bytes = self
+            .public_data
+            .get_tx_bytes(config.max_txs, config.max_calldata);
         config
             .keccak_table
-            .dev_load(&mut layouter, vec![&rpi_bytes], &challenges)?;
+            .dev_load(&mut layouter, vec![&rpi_bytes, &tx_bytes], &challenges)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
@@ -86,6 +86,94 @@ fn test_simple_pi() {
     );
 }
 
+#[test]
+fn test_chained_pi() {
+    let max_txs = 2;
+    let max_withdrawals = 2;
+    let max_calldata = 8;
+
+    let mut prev_public_data = PublicData::default();
+    prev_public_data.chain_id = *MOCK_CHAIN_ID;
+    let prev_pi_digest = prev_public_data.get_rpi_digest(max_txs, max_withdrawals, max_calldata);
+
+    let mut public_data = PublicData::default();
+    public_data.prev_pi_digest = prev_pi_digest;
+    assert_ne!(
+        public_data.get_rpi_digest(max_txs, max_withdrawals, max_calldata),
+        prev_pi_digest
+    );
+
+    // The digest of the previous block is exposed as a public input
+    let public_inputs = public_data.get_public_inputs(max_txs, max_withdrawals, max_calldata);
+    assert_eq!(public_inputs.prev_pi_digest, prev_pi_digest);
+    let circuit = PiCircuit::<Fr>::new(max_txs, max_withdrawals, max_calldata, public_data);
+    let instance = circuit.instance();
+    assert_eq!(
+        PublicInputs::from_instance(&instance[0]).map(|inputs| inputs.prev_pi_digest),
+        Some(prev_pi_digest)
+    );
+
+    let k = 17;
+    let prover = MockProver::run(k, &circuit, instance.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // the instance must match the previous pi digest of the public data
+    let mut wrong_instance = instance;
+    wrong_instance[0][PublicInputs::PREV_PI_DIGEST_OFFSET] += Fr::from(1);
+    let prover = MockProver::run(k, &circuit, wrong_instance).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_pi_instance() {
+    let max_txs = 2;
+    let max_withdrawals = 2;
+    let max_calldata = 200;
+
+    let mut public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        history_hashes: vec![Word::from(0xaau64), Word::from(0xbbu64)],
+        state_root: H256::repeat_byte(0x11),
+        withdrawals_root: H256::repeat_byte(0x22),
+        prev_state_root: H256::repeat_byte(0x33),
+        block_hash: Some(H256::repeat_byte(0x44)),
+        ..Default::default()
+    };
+    public_data
+        .transactions
+        .push(CORRECT_MOCK_TXS[0].clone().into());
+
+    let public_inputs = public_data.get_public_inputs(max_txs, max_withdrawals, max_calldata);
+    assert_eq!(public_inputs.parent_hash, H256::from_low_u64_be(0xbb));
+    assert_eq!(public_inputs.state_root, public_data.state_root);
+    assert_eq!(public_inputs.withdrawals_root, public_data.withdrawals_root);
+    assert_eq!(public_inputs.prev_state_root, public_data.prev_state_root);
+    assert_eq!(public_inputs.block_hash, H256::repeat_byte(0x44));
+    assert_ne!(
+        public_inputs.tx_digest,
+        PublicData::default().get_tx_digest(max_txs, max_calldata)
+    );
+
+    let circuit = PiCircuit::<Fr>::new(max_txs, max_withdrawals, max_calldata, public_data);
+    let instance = circuit.instance();
+    assert_eq!(instance, vec![public_inputs.instance::<Fr>()]);
+    assert_eq!(
+        PublicInputs::from_instance(&instance[0]),
+        Some(public_inputs.clone())
+    );
+    assert_eq!(PublicInputs::from_instance(&instance[0][1..]), None);
+
+    let k = 17;
+    let prover = MockProver::run(k, &circuit, instance.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // the instance must match the state root of the public data
+    let mut wrong_instance = instance;
+    wrong_instance[0][PublicInputs::STATE_ROOT_OFFSET] += Fr::from(1);
+    let prover = MockProver::run(k, &circuit, wrong_instance).unwrap();
+    assert!(prover.verify().is_err());
+}
+
 #[test]
 fn test_1tx_1maxtx() {
     const MAX_TXS: usize = 1;
@@ -0,0 +1,394 @@
+//! Registry of the circuits by name.
+//!
+//! Tools that drive several circuits can look them up by name and operate on them through
+//! [`CircuitOps`] instead of being compiled against each concrete circuit type.  All the circuits
+//! of this crate are registered by default; downstream crates can add their own with
+//! [`register_circuit!`](crate::register_circuit).
+//!
+//! Each circuit is behind a cargo feature, like the `SuperCircuit` with `super-circuit`, and is
+//! only registered when its feature is enabled.  Use [`require`] to get an error telling which
+//! feature to enable when such a circuit is looked up.
+//!
+//! The circuits implement [`Circuit`] for the tests and with the `test-circuits` feature, which
+//! the MockProver and the real prover operations of [`CircuitOps`] need.  The real proofs are
+//! SHPLONK proofs over KZG with the Blake2b transcript.
+
+use crate::{util::SubCircuit, witness::Block};
+use halo2_proofs::halo2curves::bn256::Fr;
+#[cfg(any(test, feature = "test-circuits"))]
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::{Bn256, G1Affine},
+    plonk::{self, create_proof, keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey},
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::ProverSHPLONK,
+    },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+};
+use lazy_static::lazy_static;
+#[cfg(any(test, feature = "test-circuits"))]
+use rand::RngCore;
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::{Arc, RwLock},
+};
+use thiserror::Error;
+
+/// Circuits of this crate that are only built with a cargo feature, with the name of the feature.
+const FEATURE_GATED_CIRCUITS: &[(&str, &str)] = &[
+    ("EVM", "evm-circuit"),
+    ("State", "state-circuit"),
+    ("Tx", "tx-circuit"),
+    ("Bytecode", "bytecode-circuit"),
+    ("Copy", "copy-circuit"),
+    ("Keccak", "keccak-circuit"),
+    ("Exp", "exp-circuit"),
+    ("Pi", "pi-circuit"),
+    ("Withdrawal", "withdrawal-circuit"),
+    ("Super", "super-circuit"),
+];
+
+/// Error looking up a circuit in the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RegistryError {
+    /// No circuit is registered with the name.
+    #[error("unknown circuit {0}")]
+    Unknown(String),
+    /// The circuit exists but was left out of this build.
+    #[error("circuit {name} is disabled, build with the `{feature}` feature to enable it")]
+    Disabled {
+        /// Name of the circuit
+        name: String,
+        /// Cargo feature that enables the circuit
+        feature: &'static str,
+    },
+}
+
+/// Error proving a circuit of the registry.
+#[cfg(any(test, feature = "test-circuits"))]
+#[derive(Debug, Error)]
+pub enum ProveError {
+    /// The circuit can't be synthesized or proven, or the proof doesn't verify.
+    #[error("proof error: {0}")]
+    Plonk(#[from] plonk::Error),
+    /// The MockProver found constraints or lookups that the witness doesn't satisfy.
+    #[error("{} constraints or lookups are not satisfied", .0.len())]
+    Unsatisfied(Vec<VerifyFailure>),
+}
+
+/// Operations on a circuit that don't depend on its concrete type.
+pub trait CircuitOps: Send + Sync {
+    /// Name of the circuit
+    fn name(&self) -> &'static str;
+
+    /// Return the minimum number of rows required to prove the block.
+    /// Row numbers without/with padding are both returned.
+    fn min_num_rows_block(&self, block: &Block<Fr>) -> (usize, usize);
+
+    /// Return the instance columns of the circuit for the block.
+    fn instance(&self, block: &Block<Fr>) -> Vec<Vec<Fr>>;
+
+    /// Run the MockProver on the circuit for the block.
+    #[cfg(any(test, feature = "test-circuits"))]
+    fn mock_prove(&self, block: &Block<Fr>, k: u32) -> Result<(), ProveError>;
+
+    /// Generate the proving key of the circuit, with the configuration of the circuit for the
+    /// block.
+    #[cfg(any(test, feature = "test-circuits"))]
+    fn keygen(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        block: &Block<Fr>,
+    ) -> Result<ProvingKey<G1Affine>, ProveError>;
+
+    /// Prove the circuit for the block.
+    #[cfg(any(test, feature = "test-circuits"))]
+    fn prove(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        block: &Block<Fr>,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u8>, ProveError>;
+
+    /// Verify a proof of the circuit for the block, against the public inputs of the block.
+    #[cfg(any(test, feature = "test-circuits"))]
+    fn verify(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        block: &Block<Fr>,
+        proof: &[u8],
+    ) -> Result<(), ProveError>;
+}
+
+/// [`CircuitOps`] of a [`SubCircuit`].
+pub struct SubCircuitOps<C> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl<C> SubCircuitOps<C> {
+    /// Return the operations of the circuit `C` registered under `name`.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+}
+
+macro_rules! impl_sub_circuit_ops {
+    ($($bound:tt)*) => {
+        impl<C: $($bound)*> CircuitOps for SubCircuitOps<C> {
+            fn name(&self) -> &'static str {
+                self.name
+            }
+
+            fn min_num_rows_block(&self, block: &Block<Fr>) -> (usize, usize) {
+                C::min_num_rows_block(block)
+            }
+
+            fn instance(&self, block: &Block<Fr>) -> Vec<Vec<Fr>> {
+                C::new_from_block(block).instance()
+            }
+
+            #[cfg(any(test, feature = "test-circuits"))]
+            fn mock_prove(&self, block: &Block<Fr>, k: u32) -> Result<(), ProveError> {
+                let circuit = C::new_from_block(block);
+                let instance = circuit.instance();
+                let prover = MockProver::<Fr>::run(k, &circuit, instance)?;
+                prover.verify().map_err(ProveError::Unsatisfied)
+            }
+
+            #[cfg(any(test, feature = "test-circuits"))]
+            fn keygen(
+                &self,
+                params: &ParamsKZG<Bn256>,
+                block: &Block<Fr>,
+            ) -> Result<ProvingKey<G1Affine>, ProveError> {
+                let circuit = C::new_from_block(block);
+                let vk = keygen_vk(params, &circuit)?;
+                Ok(keygen_pk(params, vk, &circuit)?)
+            }
+
+            #[cfg(any(test, feature = "test-circuits"))]
+            fn prove(
+                &self,
+                params: &ParamsKZG<Bn256>,
+                pk: &ProvingKey<G1Affine>,
+                block: &Block<Fr>,
+                rng: &mut dyn RngCore,
+            ) -> Result<Vec<u8>, ProveError> {
+                let circuit = C::new_from_block(block);
+                let instance = circuit.instance();
+                let instance = instance.iter().map(Vec::as_slice).collect::<Vec<_>>();
+                let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+                create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+                    params,
+                    pk,
+                    &[circuit],
+                    &[&instance],
+                    rng,
+                    &mut transcript,
+                )?;
+                Ok(transcript.finalize())
+            }
+
+            #[cfg(any(test, feature = "test-circuits"))]
+            fn verify(
+                &self,
+                params: &ParamsKZG<Bn256>,
+                vk: &VerifyingKey<G1Affine>,
+                block: &Block<Fr>,
+                proof: &[u8],
+            ) -> Result<(), ProveError> {
+                let instance = self.instance(block);
+                Ok(crate::verifier::verify_instances::<
+                    Blake2bRead<_, G1Affine, Challenge255<_>>,
+                    _,
+                >(params, vk, &[instance], proof)?)
+            }
+        }
+    };
+}
+
+#[cfg(any(test, feature = "test-circuits"))]
+impl_sub_circuit_ops!(SubCircuit<Fr> + Circuit<Fr>);
+#[cfg(not(any(test, feature = "test-circuits")))]
+impl_sub_circuit_ops!(SubCircuit<Fr>);
+
+lazy_static! {
+    static ref REGISTRY: RwLock<BTreeMap<&'static str, Arc<dyn CircuitOps>>> = {
+        #[allow(unused_mut)]
+        let mut circuits: Vec<Arc<dyn CircuitOps>> = vec![];
+        #[cfg(feature = "evm-circuit")]
+        circuits.push(Arc::new(
+            SubCircuitOps::<crate::evm_circuit::EvmCircuit<Fr>>::new("EVM"),
+        ));
+        #[cfg(feature = "state-circuit")]
+        circuits.push(Arc::new(SubCircuitOps::<
+            crate::state_circuit::StateCircuit<Fr>,
+        >::new("State")));
+        #[cfg(feature = "tx-circuit")]
+        circuits.push(Arc::new(
+            SubCircuitOps::<crate::tx_circuit::TxCircuit<Fr>>::new("Tx"),
+        ));
+        #[cfg(feature = "bytecode-circuit")]
+        circuits.push(Arc::new(SubCircuitOps::<
+            crate::bytecode_circuit::BytecodeCircuit<Fr>,
+        >::new("Bytecode")));
+        #[cfg(feature = "copy-circuit")]
+        circuits.push(Arc::new(SubCircuitOps::<
+            crate::copy_circuit::CopyCircuit<Fr>,
+        >::new("Copy")));
+        #[cfg(feature = "keccak-circuit")]
+        circuits.push(Arc::new(SubCircuitOps::<
+            crate::keccak_circuit::KeccakCircuit<Fr>,
+        >::new("Keccak")));
+        #[cfg(feature = "exp-circuit")]
+        circuits.push(Arc::new(
+            SubCircuitOps::<crate::exp_circuit::ExpCircuit<Fr>>::new("Exp"),
+        ));
+        #[cfg(feature = "pi-circuit")]
+        circuits.push(Arc::new(
+            SubCircuitOps::<crate::pi_circuit::PiCircuit<Fr>>::new("Pi"),
+        ));
+        #[cfg(feature = "withdrawal-circuit")]
+        circuits.push(Arc::new(SubCircuitOps::<
+            crate::withdrawal_circuit::WithdrawalCircuit<Fr>,
+        >::new("Withdrawal")));
+        #[cfg(feature = "super-circuit")]
+        circuits.push(Arc::new(SubCircuitOps::<
+            crate::super_circuit::SuperCircuit<Fr>,
+        >::new("Super")));
+        RwLock::new(circuits.into_iter().map(|ops| (ops.name(), ops)).collect())
+    };
+}
+
+/// Register a circuit, replacing any circuit registered with the same name.
+pub fn register(ops: Arc<dyn CircuitOps>) {
+    REGISTRY.write().unwrap().insert(ops.name(), ops);
+}
+
+/// Get the circuit registered with `name`.
+pub fn get(name: &str) -> Option<Arc<dyn CircuitOps>> {
+    REGISTRY.read().unwrap().get(name).cloned()
+}
+
+/// Get the circuit registered with `name`, or an error telling whether it doesn't exist or its
+/// feature is disabled in this build.
+pub fn require(name: &str) -> Result<Arc<dyn CircuitOps>, RegistryError> {
+    get(name).ok_or_else(|| {
+        match FEATURE_GATED_CIRCUITS
+            .iter()
+            .find(|(gated, _)| *gated == name)
+        {
+            Some((_, feature)) => RegistryError::Disabled {
+                name: name.to_string(),
+                feature,
+            },
+            None => RegistryError::Unknown(name.to_string()),
+        }
+    })
+}
+
+/// Return the names of all the registered circuits, sorted.
+pub fn names() -> Vec<&'static str> {
+    REGISTRY.read().unwrap().keys().copied().collect()
+}
+
+/// Register a [`SubCircuit`] under a name.
+///
+/// ```ignore
+/// register_circuit!("MyCircuit", MyCircuit<Fr>);
+/// ```
+#[macro_export]
+macro_rules! register_circuit {
+    ($name:expr, $circuit:ty) => {
+        $crate::registry::register(std::sync::Arc::new($crate::registry::SubCircuitOps::<
+            $circuit,
+        >::new($name)))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "evm-circuit", feature = "exp-circuit"))]
+    #[test]
+    fn registry_lookup() {
+        use crate::{exp_circuit::ExpCircuit, test_util::CircuitTestBuilder, util::log2_ceil};
+        use eth_types::bytecode;
+        use mock::TestContext;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        assert!(names().contains(&"EVM"));
+        assert!(get("Unknown").is_none());
+
+        register_circuit!("Exp2", ExpCircuit<Fr>);
+        let ops = get("Exp2").unwrap();
+        assert_eq!(ops.name(), "Exp2");
+
+        let block = CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+                PUSH1(0x02)
+                PUSH1(0x03)
+                EXP
+                STOP
+            })
+            .unwrap(),
+        )
+        .build_block()
+        .unwrap();
+        let (_, rows) = ops.min_num_rows_block(&block);
+        assert_eq!(rows, get("Exp").unwrap().min_num_rows_block(&block).1);
+        let k = log2_ceil(rows + ExpCircuit::<Fr>::unusable_rows());
+        assert!(ops.mock_prove(&block, k).is_ok());
+        // The MockProver errors instead of panicking when the rows don't fit
+        assert!(matches!(
+            ops.mock_prove(&block, 1),
+            Err(ProveError::Plonk(_))
+        ));
+
+        let params = ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::seed_from_u64(2));
+        let pk = ops.keygen(&params, &block).unwrap();
+        let proof = ops
+            .prove(&params, &pk, &block, &mut ChaCha20Rng::seed_from_u64(3))
+            .unwrap();
+        ops.verify(&params, pk.get_vk(), &block, &proof).unwrap();
+        assert!(ops
+            .verify(&params, pk.get_vk(), &block, &proof[1..])
+            .is_err());
+    }
+
+    #[test]
+    fn registry_require() {
+        assert_eq!(
+            require("Unknown").err(),
+            Some(RegistryError::Unknown("Unknown".to_string()))
+        );
+
+        for (name, feature) in FEATURE_GATED_CIRCUITS {
+            match require(name) {
+                Ok(ops) => assert_eq!(ops.name(), *name),
+                Err(err) => assert_eq!(
+                    err,
+                    RegistryError::Disabled {
+                        name: name.to_string(),
+                        feature: *feature,
+                    }
+                ),
+            }
+        }
+        // The SuperCircuit enables all the circuits
+        #[cfg(feature = "super-circuit")]
+        assert!(FEATURE_GATED_CIRCUITS
+            .iter()
+            .all(|(name, _)| require(name).is_ok()));
+    }
+}
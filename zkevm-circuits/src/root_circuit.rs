@@ -22,10 +22,18 @@ use snark_verifier::{
 use std::{iter, marker::PhantomData, rc::Rc};
 
 mod aggregation;
+mod batch;
+mod bundle;
+mod compression;
+mod evm;
+mod export;
+mod proof_cache;
+#[cfg(feature = "submitter")]
+mod submitter;
 
 #[cfg(any(test, feature = "test-circuits"))]
 mod dev;
-#[cfg(test)]
+#[cfg(all(test, feature = "super-circuit"))]
 mod test;
 #[cfg(feature = "test-circuits")]
 pub use self::RootCircuit as TestRootCircuit;
@@ -33,9 +41,19 @@ pub use self::RootCircuit as TestRootCircuit;
 #[cfg(any(feature = "test-circuits", test))]
 pub use dev::TestAggregationCircuit;
 
+pub use batch::BatchCircuit;
+pub use bundle::{BundleError, ProofBundle};
+pub use compression::{compress_proof, CompressionCircuit};
+pub use evm::{evm_verify, gen_evm_verifier};
+pub use export::{vk_digest, VerifierData};
+pub use proof_cache::{CachedProof, ProofCache, ProofCacheError};
+#[cfg(feature = "submitter")]
+pub use submitter::{GasStrategy, SubmitError, Submitter};
+
 pub use aggregation::{
-    aggregate, AggregationConfig, EccChip, Gwc, Halo2Loader, KzgDk, KzgSvk, PlonkSuccinctVerifier,
-    PlonkVerifier, PoseidonTranscript, Shplonk, Snark, SnarkWitness, BITS, LIMBS,
+    aggregate, vk_digest, AggregationConfig, EccChip, Gwc, Halo2Loader, KzgDk, KzgSvk,
+    PlonkSuccinctVerifier, PlonkVerifier, PoseidonTranscript, Shplonk, Snark, SnarkWitness, BITS,
+    LIMBS,
 };
 pub use snark_verifier::{
     loader::native::NativeLoader,
@@ -50,6 +68,7 @@ where
 {
     svk: KzgSvk<M>,
     snark: SnarkWitness<'a, M::G1Affine>,
+    accepted_vks: Option<Vec<M::Fr>>,
     instance: Vec<M::Fr>,
     _marker: PhantomData<As>,
 }
@@ -80,7 +99,52 @@ where
         super_circuit_instances: Value<&'a Vec<Vec<M::Fr>>>,
         super_circuit_proof: Value<&'a [u8]>,
     ) -> Result<Self, snark_verifier::Error> {
-        let num_instances = super_circuit_protocol.num_instance.iter().sum::<usize>() + 4 * LIMBS;
+        Self::new_inner(
+            params,
+            super_circuit_protocol,
+            None,
+            super_circuit_instances,
+            super_circuit_proof,
+        )
+    }
+
+    /// Create a `RootCircuit` that accepts the `SuperCircuit` proofs of any protocol with the
+    /// same constraint system as `super_circuit_protocol` and a [`vk_digest`] in `accepted_vks`,
+    /// so that the `SuperCircuit` can be upgraded without changing the `RootCircuit`.  The
+    /// accepted digests are exposed as instances, after the `SuperCircuit`'s ones.  Returns an
+    /// error if given proof is invalid or if the digest of its protocol isn't accepted.
+    pub fn new_with_accepted_vks(
+        params: &ParamsKZG<M>,
+        super_circuit_protocol: &'a PlonkProtocol<M::G1Affine>,
+        accepted_vks: Vec<M::Fr>,
+        super_circuit_instances: Value<&'a Vec<Vec<M::Fr>>>,
+        super_circuit_proof: Value<&'a [u8]>,
+    ) -> Result<Self, snark_verifier::Error> {
+        if !accepted_vks.contains(&vk_digest(super_circuit_protocol)?) {
+            return Err(snark_verifier::Error::AssertionFailure(
+                "verifying key of the SuperCircuit isn't accepted".to_string(),
+            ));
+        }
+        Self::new_inner(
+            params,
+            super_circuit_protocol,
+            Some(accepted_vks),
+            super_circuit_instances,
+            super_circuit_proof,
+        )
+    }
+
+    fn new_inner(
+        params: &ParamsKZG<M>,
+        super_circuit_protocol: &'a PlonkProtocol<M::G1Affine>,
+        accepted_vks: Option<Vec<M::Fr>>,
+        super_circuit_instances: Value<&'a Vec<Vec<M::Fr>>>,
+        super_circuit_proof: Value<&'a [u8]>,
+    ) -> Result<Self, snark_verifier::Error> {
+        let accepted_vks_len = accepted_vks.as_ref().map_or(0, Vec::len);
+        let num_instances = super_circuit_protocol.num_instance.iter().sum::<usize>()
+            + accepted_vks_len
+            + 4 * LIMBS;
         let instance = {
             let mut instance = Ok(vec![M::Fr::ZERO; num_instances]);
             super_circuit_instances
@@ -96,6 +160,8 @@ where
                         iter::empty()
                             // Propagate `SuperCircuit`'s instance
                             .chain(super_circuit_instances.iter().flatten().cloned())
+                            // Output accepted verifying key digests
+                            .chain(accepted_vks.iter().flatten().cloned())
                             // Output aggregated accumulator limbs
                             .chain(accumulator_limbs)
                             .collect_vec()
@@ -112,6 +178,7 @@ where
                 super_circuit_instances,
                 super_circuit_proof,
             ),
+            accepted_vks,
             instance,
             _marker: PhantomData,
         })
@@ -120,13 +187,13 @@ where
     /// Returns accumulator indices in instance columns, which will be in
     /// the last `4 * LIMBS` rows of instance column in `MainGate`.
     pub fn accumulator_indices(&self) -> Vec<(usize, usize)> {
-        let offset = self.snark.protocol().num_instance.iter().sum::<usize>();
+        let offset = self.instance.len() - 4 * LIMBS;
         (offset..).map(|idx| (0, idx)).take(4 * LIMBS).collect()
     }
 
     /// Returns number of instance
     pub fn num_instance(&self) -> Vec<usize> {
-        vec![self.snark.protocol().num_instance.iter().sum::<usize>() + 4 * LIMBS]
+        vec![self.instance.len()]
     }
 
     /// Returns instance
@@ -160,6 +227,7 @@ where
         Self {
             svk: self.svk,
             snark: self.snark.without_witnesses(),
+            accepted_vks: self.accepted_vks.clone(),
             instance: vec![M::Fr::ZERO; self.instance.len()],
             _marker: PhantomData,
         }
@@ -175,8 +243,13 @@ where
         mut layouter: impl Layouter<M::Fr>,
     ) -> Result<(), Error> {
         config.load_table(&mut layouter)?;
-        let (instance, accumulator_limbs) =
-            config.aggregate::<M, As>(&mut layouter, &self.svk, [self.snark])?;
+        let (instance, accepted_vks, accumulator_limbs) = config
+            .aggregate_with_accepted_vks::<M, As>(
+                &mut layouter,
+                &self.svk,
+                [self.snark],
+                self.accepted_vks.as_deref(),
+            )?;
 
         // Constrain equality to instance values
         let main_gate = config.main_gate();
@@ -184,6 +257,7 @@ where
             .into_iter()
             .flatten()
             .flatten()
+            .chain(accepted_vks)
             .chain(accumulator_limbs)
             .enumerate()
         {
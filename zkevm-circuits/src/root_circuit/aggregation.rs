@@ -24,7 +24,10 @@ use snark_verifier::{
         PolynomialCommitmentScheme,
     },
     system::halo2::transcript,
-    util::arithmetic::{fe_to_limbs, FromUniformBytes, MultiMillerLoop, PrimeField},
+    util::{
+        arithmetic::{fe_to_limbs, FromUniformBytes, MultiMillerLoop, PrimeField},
+        transcript::Transcript,
+    },
     verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
 };
 use std::{io, iter, rc::Rc};
@@ -42,8 +45,10 @@ pub type Shplonk<M> = KzgAs<M, Bdfg21>;
 pub type KzgSvk<M> = KzgSuccinctVerifyingKey<<M as Engine>::G1Affine>;
 /// KZG deciding key
 pub type KzgDk<M> = KzgDecidingKey<M>;
-/// Plonk succinct verifier with `KzgAs`
-pub type PlonkSuccinctVerifier<As> = verifier::plonk::PlonkSuccinctVerifier<As>;
+/// Plonk succinct verifier with `KzgAs` and `LimbsEncoding<LIMBS, BITS>`, which folds the
+/// accumulators in the instances of a snark at the `accumulator_indices` of its protocol.
+pub type PlonkSuccinctVerifier<As> =
+    verifier::plonk::PlonkSuccinctVerifier<As, LimbsEncoding<LIMBS, BITS>>;
 /// Plonk verifier with `KzgAs` and `LimbsEncoding<LIMBS, BITS>`.
 pub type PlonkVerifier<As> = verifier::plonk::PlonkVerifier<As, LimbsEncoding<LIMBS, BITS>>;
 
@@ -64,7 +69,7 @@ pub type PoseidonTranscript<C, S> =
 /// Snark contains the minimal information for verification
 #[derive(Clone, Copy)]
 pub struct Snark<'a, C: CurveAffine> {
-    protocol: &'a PlonkProtocol<C>,
+    pub(crate) protocol: &'a PlonkProtocol<C>,
     pub(crate) instances: &'a Vec<Vec<C::Scalar>>,
     proof: &'a [u8],
 }
@@ -219,6 +224,50 @@ impl AggregationConfig {
         ),
         Error,
     >
+    where
+        M: MultiMillerLoop,
+        M::Fr: Field,
+        M::G1Affine: CurveAffine<ScalarExt = M::Fr>,
+        for<'b> As: PolynomialCommitmentScheme<
+                M::G1Affine,
+                Rc<Halo2Loader<'b, M::G1Affine>>,
+                VerifyingKey = KzgSvk<M>,
+                Output = KzgAccumulator<M::G1Affine, Rc<Halo2Loader<'b, M::G1Affine>>>,
+            > + AccumulationScheme<
+                M::G1Affine,
+                Rc<Halo2Loader<'b, M::G1Affine>>,
+                Accumulator = KzgAccumulator<M::G1Affine, Rc<Halo2Loader<'b, M::G1Affine>>>,
+                VerifyingKey = KzgAsVerifyingKey,
+            >,
+    {
+        self.aggregate_with_accepted_vks::<M, As>(layouter, svk, snarks, None)
+            .map(|(instances, _, accumulator_limbs)| (instances, accumulator_limbs))
+    }
+
+    /// Aggregate snarks into a single accumulator and decompose it into
+    /// `4 * LIMBS` limbs.
+    ///
+    /// With `accepted_vks`, the verifying keys of the snarks are witnessed instead of being fixed
+    /// in the circuit, and the [`vk_digest`] of each of them is constrained to be one of the
+    /// accepted digests.  The circuit then verifies the proofs of any protocol with the same
+    /// constraint system and an accepted digest, for example during the upgrade of the fixed
+    /// columns of a circuit.
+    /// Returns assigned instances of snarks, accepted digests and aggregated accumulator limbs.
+    #[allow(clippy::type_complexity)]
+    pub fn aggregate_with_accepted_vks<'a, M, As>(
+        &self,
+        layouter: &mut impl Layouter<M::Fr>,
+        svk: &KzgSvk<M>,
+        snarks: impl IntoIterator<Item = SnarkWitness<'a, M::G1Affine>>,
+        accepted_vks: Option<&[M::Fr]>,
+    ) -> Result<
+        (
+            Vec<Vec<Vec<AssignedCell<M::Fr, M::Fr>>>>,
+            Vec<AssignedCell<M::Fr, M::Fr>>,
+            Vec<AssignedCell<M::Fr, M::Fr>>,
+        ),
+        Error,
+    >
     where
         M: MultiMillerLoop,
         M::Fr: Field,
@@ -237,6 +286,9 @@ impl AggregationConfig {
     {
         type PoseidonTranscript<'a, C, S> =
             transcript::halo2::PoseidonTranscript<C, Rc<Halo2Loader<'a, C>>, S, T, RATE, R_F, R_P>;
+        if accepted_vks.is_some_and(|accepted_vks| accepted_vks.is_empty()) {
+            return Err(Error::Synthesis);
+        }
         let snarks = snarks.into_iter().collect_vec();
         layouter.assign_region(
             || "Aggregate snarks",
@@ -254,10 +306,37 @@ impl AggregationConfig {
 
                 // Verify the cheap part and get accumulator (left-hand and right-hand side of
                 // pairing) of individual proof.
+                let mut digests = Vec::new();
                 let (instances, accumulators) = snarks
                     .iter()
                     .map(|snark| {
-                        let protocol = snark.protocol.loaded(&loader);
+                        let mut protocol = snark.protocol.loaded(&loader);
+                        if accepted_vks.is_some() {
+                            protocol.preprocessed = snark
+                                .protocol
+                                .preprocessed
+                                .iter()
+                                .map(|preprocessed| {
+                                    loader.assign_ec_point(Value::known(*preprocessed))
+                                })
+                                .collect();
+                            protocol.transcript_initial_state = snark
+                                .protocol
+                                .transcript_initial_state
+                                .as_ref()
+                                .map(|state| loader.assign_scalar(Value::known(*state)));
+
+                            // Same hash as `vk_digest`
+                            let mut hasher =
+                                PoseidonTranscript::new(&loader, Value::known(&[] as &[u8]));
+                            for preprocessed in protocol.preprocessed.iter() {
+                                hasher.common_ec_point(preprocessed).unwrap();
+                            }
+                            if let Some(state) = protocol.transcript_initial_state.as_ref() {
+                                hasher.common_scalar(state).unwrap();
+                            }
+                            digests.push(hasher.squeeze_challenge());
+                        }
                         let instances = snark.loaded_instances(&loader);
                         let mut transcript = PoseidonTranscript::new(&loader, snark.proof());
                         let proof = PlonkSuccinctVerifier::<As>::read_proof(
@@ -292,6 +371,33 @@ impl AggregationConfig {
                     <As as AccumulationScheme<_, _>>::verify(&as_vk, &accumulators, &proof).unwrap()
                 };
 
+                // Constrain each digest to be one of the accepted ones: the product of its
+                // differences with them is zero.
+                let digests = digests
+                    .iter()
+                    .map(|digest| digest.assigned().to_owned())
+                    .collect_vec();
+                let accepted_vks = match accepted_vks {
+                    None => Vec::new(),
+                    Some(accepted_vks) => {
+                        let main_gate = self.main_gate::<M::Fr>();
+                        let mut ctx = loader.ctx_mut();
+                        let accepted_vks = accepted_vks
+                            .iter()
+                            .map(|vk| main_gate.assign_value(&mut ctx, Value::known(*vk)))
+                            .collect::<Result<Vec<_>, Error>>()?;
+                        for digest in digests.iter() {
+                            let mut product = main_gate.sub(&mut ctx, digest, &accepted_vks[0])?;
+                            for vk in accepted_vks[1..].iter() {
+                                let diff = main_gate.sub(&mut ctx, digest, vk)?;
+                                product = main_gate.mul(&mut ctx, &product, &diff)?;
+                            }
+                            main_gate.assert_zero(&mut ctx, &product)?;
+                        }
+                        accepted_vks
+                    }
+                };
+
                 let instances = instances
                     .iter()
                     .map(|instances| {
@@ -318,12 +424,30 @@ impl AggregationConfig {
                     .flatten()
                     .collect();
 
-                Ok((instances, accumulator_limbs))
+                Ok((instances, accepted_vks, accumulator_limbs))
             },
         )
     }
 }
 
+/// Digest of the verifying key of a protocol: the Poseidon hash of its preprocessed commitments
+/// and transcript initial state.  It's the value constrained by
+/// [`AggregationConfig::aggregate_with_accepted_vks`].
+pub fn vk_digest<C>(protocol: &PlonkProtocol<C>) -> Result<C::Scalar, snark_verifier::Error>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+{
+    let mut hasher = PoseidonTranscript::<C, Vec<u8>>::new(Vec::new());
+    for preprocessed in protocol.preprocessed.iter() {
+        hasher.common_ec_point(preprocessed)?;
+    }
+    if let Some(state) = protocol.transcript_initial_state.as_ref() {
+        hasher.common_scalar(state)?;
+    }
+    Ok(hasher.squeeze_challenge())
+}
+
 /// Aggregate snarks into a single accumulator and decompose it into
 /// `4 * LIMBS` limbs.
 /// Returns `None` if any given snarks is invalid.
@@ -414,7 +538,7 @@ where
 #[cfg(test)]
 pub mod test {
     use crate::root_circuit::{
-        aggregation::Gwc, PoseidonTranscript, Snark, TestAggregationCircuit,
+        aggregation::Gwc, vk_digest, PoseidonTranscript, RootCircuit, Snark, TestAggregationCircuit,
     };
     use eth_types::Field;
     use halo2_proofs::{
@@ -447,9 +571,9 @@ pub mod test {
 
     #[derive(Clone)]
     pub struct SnarkOwned<C: CurveAffine> {
-        protocol: PlonkProtocol<C>,
-        instances: Vec<Vec<C::Scalar>>,
-        proof: Vec<u8>,
+        pub(crate) protocol: PlonkProtocol<C>,
+        pub(crate) instances: Vec<Vec<C::Scalar>>,
+        pub(crate) proof: Vec<u8>,
     }
 
     impl<C: CurveAffine> SnarkOwned<C> {
@@ -670,4 +794,47 @@ pub mod test {
             ])
         );
     }
+
+    #[test]
+    fn test_root_circuit_accepted_vks() {
+        let params = ParamsKZG::<Bn256>::setup(8, OsRng);
+
+        let snark = rand_standard_plonk_snarks(&params, 1).pop().unwrap();
+        let digest = vk_digest(&snark.protocol).unwrap();
+        // Accept the digest of another version of the circuit too
+        let accepted_vks = vec![Fr::ONE, digest];
+        let root_circuit = RootCircuit::<Bn256, Gwc<_>>::new_with_accepted_vks(
+            &params,
+            &snark.protocol,
+            accepted_vks.clone(),
+            Value::known(&snark.instances),
+            Value::known(&snark.proof),
+        )
+        .unwrap();
+        let mut instances = root_circuit.instance();
+        assert_eq!(instances[0][1..3], accepted_vks);
+        assert_eq!(
+            MockProver::run(21, &root_circuit, instances.clone())
+                .unwrap()
+                .verify(),
+            Ok(())
+        );
+
+        // Change the accepted digests
+        instances[0][2] += Fr::ONE;
+        assert!(MockProver::run(21, &root_circuit, instances)
+            .unwrap()
+            .verify()
+            .is_err());
+
+        // The digest of the snark isn't accepted
+        assert!(RootCircuit::<Bn256, Gwc<_>>::new_with_accepted_vks(
+            &params,
+            &snark.protocol,
+            vec![Fr::ONE],
+            Value::known(&snark.instances),
+            Value::known(&snark.proof),
+        )
+        .is_err());
+    }
 }
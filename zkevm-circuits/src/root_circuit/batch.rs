@@ -0,0 +1,246 @@
+use super::{
+    aggregate, vk_digest, AggregationConfig, Halo2Loader, KzgSvk, Snark, SnarkWitness, LIMBS,
+};
+use crate::instance::PublicInputs;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::{ff::Field as Halo2Field, serde::SerdeObject, CurveAffine, CurveExt},
+    plonk::{Circuit, ConstraintSystem, Error},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use itertools::Itertools;
+use maingate::MainGateInstructions;
+use snark_verifier::{
+    loader::native::NativeLoader,
+    pcs::{
+        kzg::*, AccumulationDecider, AccumulationScheme, AccumulationSchemeProver,
+        PolynomialCommitmentScheme,
+    },
+    util::arithmetic::MultiMillerLoop,
+};
+use std::{iter, marker::PhantomData, rc::Rc};
+
+/// Instance column of the `SuperCircuit` with the public inputs of the `PiCircuit`.  The
+/// `KeccakCircuit` has no instance column, so it's the first one.
+const PI_INSTANCE_COLUMN: usize = 0;
+
+/// BatchCircuit for aggregating the `SuperCircuit` proofs of a batch of consecutive blocks into a
+/// single proof.  Each block of the batch must start from the state root of the previous block
+/// and its parent hash must be the hash of the previous block, see
+/// [`block_convert_batch`](crate::witness::block_convert_batch).
+#[derive(Clone)]
+pub struct BatchCircuit<'a, M: MultiMillerLoop, As>
+where
+    M::G1Affine: CurveAffine,
+{
+    svk: KzgSvk<M>,
+    snarks: Vec<SnarkWitness<'a, M::G1Affine>>,
+    accepted_vks: Option<Vec<M::Fr>>,
+    instances: Vec<M::Fr>,
+    _marker: PhantomData<As>,
+}
+
+impl<'a, M, As> BatchCircuit<'a, M, As>
+where
+    M: MultiMillerLoop,
+    M::Fr: Field,
+    M::G1: CurveExt<AffineExt = M::G1Affine, ScalarExt = M::Fr>,
+    M::G1Affine: SerdeObject + CurveAffine<ScalarExt = M::Fr, CurveExt = M::G1>,
+    M::G2Affine: SerdeObject + CurveAffine,
+    for<'b> As: PolynomialCommitmentScheme<
+            M::G1Affine,
+            NativeLoader,
+            VerifyingKey = KzgSvk<M>,
+            Output = KzgAccumulator<M::G1Affine, NativeLoader>,
+        > + AccumulationSchemeProver<
+            M::G1Affine,
+            Accumulator = KzgAccumulator<M::G1Affine, NativeLoader>,
+            ProvingKey = KzgAsProvingKey<M::G1Affine>,
+        > + AccumulationDecider<M::G1Affine, NativeLoader, DecidingKey = KzgDecidingKey<M>>,
+{
+    /// Create a `BatchCircuit` with accumulator computed given the `SuperCircuit` proofs of the
+    /// blocks of the batch, in the order of the blocks.  Returns an error if any given proof is
+    /// invalid.
+    pub fn new(
+        params: &ParamsKZG<M>,
+        snarks: impl IntoIterator<Item = Snark<'a, M::G1Affine>>,
+    ) -> Result<Self, snark_verifier::Error> {
+        Self::new_inner(params, snarks, None)
+    }
+
+    /// Create a `BatchCircuit` that accepts the `SuperCircuit` proofs of any protocol with the
+    /// same constraint system and a [`vk_digest`] in `accepted_vks`, so that the blocks of a
+    /// batch can be proven by different versions of the `SuperCircuit` during an upgrade.  The
+    /// accepted digests are exposed as instances, after the `SuperCircuit`s' ones.  Returns an
+    /// error if any given proof is invalid or if the digest of its protocol isn't accepted.
+    pub fn new_with_accepted_vks(
+        params: &ParamsKZG<M>,
+        snarks: impl IntoIterator<Item = Snark<'a, M::G1Affine>>,
+        accepted_vks: Vec<M::Fr>,
+    ) -> Result<Self, snark_verifier::Error> {
+        let snarks = snarks.into_iter().collect_vec();
+        for snark in snarks.iter() {
+            if !accepted_vks.contains(&vk_digest(snark.protocol)?) {
+                return Err(snark_verifier::Error::AssertionFailure(
+                    "verifying key of a SuperCircuit isn't accepted".to_string(),
+                ));
+            }
+        }
+        Self::new_inner(params, snarks, Some(accepted_vks))
+    }
+
+    fn new_inner(
+        params: &ParamsKZG<M>,
+        snarks: impl IntoIterator<Item = Snark<'a, M::G1Affine>>,
+        accepted_vks: Option<Vec<M::Fr>>,
+    ) -> Result<Self, snark_verifier::Error> {
+        let snarks = snarks.into_iter().collect_vec();
+
+        let accumulator_limbs = aggregate::<M, As>(params, snarks.clone())?;
+        let instances = iter::empty()
+            // Propagate `SuperCircuit`s' instances
+            .chain(
+                snarks
+                    .iter()
+                    .flat_map(|snark| snark.instances.clone())
+                    .flatten(),
+            )
+            // Output accepted verifying key digests
+            .chain(accepted_vks.iter().flatten().cloned())
+            // Output aggregated accumulator limbs
+            .chain(accumulator_limbs)
+            .collect_vec();
+
+        Ok(Self {
+            svk: KzgSvk::<M>::new(params.get_g()[0]),
+            snarks: snarks.into_iter().map_into().collect(),
+            accepted_vks,
+            instances,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns accumulator indices in instance columns, which will be in
+    /// the last `4 * LIMBS` rows of instance column in `MainGate`.
+    pub fn accumulator_indices(&self) -> Vec<(usize, usize)> {
+        (self.instances.len() - 4 * LIMBS..)
+            .map(|idx| (0, idx))
+            .take(4 * LIMBS)
+            .collect()
+    }
+
+    /// Returns number of instance
+    pub fn num_instance(&self) -> Vec<usize> {
+        vec![self.instances.len()]
+    }
+
+    /// Returns instance
+    pub fn instance(&self) -> Vec<Vec<M::Fr>> {
+        vec![self.instances.clone()]
+    }
+}
+
+impl<'a, M: MultiMillerLoop, As> Circuit<M::Fr> for BatchCircuit<'a, M, As>
+where
+    M::Fr: Field,
+    M::G1Affine: CurveAffine<ScalarExt = M::Fr>,
+    for<'b> As: PolynomialCommitmentScheme<
+            M::G1Affine,
+            Rc<Halo2Loader<'b, M::G1Affine>>,
+            VerifyingKey = KzgSvk<M>,
+            Output = KzgAccumulator<M::G1Affine, Rc<Halo2Loader<'b, M::G1Affine>>>,
+        > + AccumulationScheme<
+            M::G1Affine,
+            Rc<Halo2Loader<'b, M::G1Affine>>,
+            Accumulator = KzgAccumulator<M::G1Affine, Rc<Halo2Loader<'b, M::G1Affine>>>,
+            VerifyingKey = KzgAsVerifyingKey,
+        >,
+{
+    type Config = AggregationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            snarks: self
+                .snarks
+                .iter()
+                .map(SnarkWitness::without_witnesses)
+                .collect(),
+            accepted_vks: self.accepted_vks.clone(),
+            instances: vec![M::Fr::ZERO; self.instances.len()],
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<M::Fr>) -> Self::Config {
+        AggregationConfig::configure::<M::G1Affine>(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<M::Fr>,
+    ) -> Result<(), Error> {
+        config.load_table(&mut layouter)?;
+        let (instances, accepted_vks, accumulator_limbs) = config
+            .aggregate_with_accepted_vks::<M, As>(
+                &mut layouter,
+                &self.svk,
+                self.snarks.clone(),
+                self.accepted_vks.as_deref(),
+            )?;
+
+        // Chain each block to the previous one: the previous state root, the parent hash and the
+        // previous pi digest of the block are the state root, the hash and the pi digest of the
+        // previous block.
+        layouter.assign_region(
+            || "Chain blocks",
+            |mut region| {
+                for (prev, next) in instances.iter().tuple_windows() {
+                    let (prev, next) = (&prev[PI_INSTANCE_COLUMN], &next[PI_INSTANCE_COLUMN]);
+                    for (prev_offset, next_offset) in [
+                        (
+                            PublicInputs::STATE_ROOT_OFFSET,
+                            PublicInputs::PREV_STATE_ROOT_OFFSET,
+                        ),
+                        (
+                            PublicInputs::BLOCK_HASH_OFFSET,
+                            PublicInputs::PARENT_HASH_OFFSET,
+                        ),
+                        (
+                            PublicInputs::RPI_DIGEST_OFFSET,
+                            PublicInputs::PREV_PI_DIGEST_OFFSET,
+                        ),
+                    ] {
+                        // lo and hi limbs
+                        for i in 0..2 {
+                            region.constrain_equal(
+                                prev[prev_offset + i].cell(),
+                                next[next_offset + i].cell(),
+                            )?;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        // Constrain equality to instance values
+        let main_gate = config.main_gate();
+        for (row, limb) in instances
+            .into_iter()
+            .flatten()
+            .flatten()
+            .chain(accepted_vks)
+            .chain(accumulator_limbs)
+            .enumerate()
+        {
+            main_gate.expose_public(layouter.namespace(|| ""), limb, row)?;
+        }
+
+        Ok(())
+    }
+}
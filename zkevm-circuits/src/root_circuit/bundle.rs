@@ -0,0 +1,251 @@
+//! Self-contained bundle of a `RootCircuit` proof.
+//!
+//! A [`ProofBundle`] carries the verifier params, the verifying key, the instances and the proof
+//! of a `RootCircuit` proof created with SHPLONK and the [`EvmTranscript`], so that it can be
+//! verified by a machine without any prior setup:
+//!
+//! ```text
+//! cargo run --bin verify-bundle -- <bundle.json>
+//! ```
+
+use super::{vk_digest, KzgDk, KzgSvk, RootCircuit, Shplonk, BITS, LIMBS};
+use ethers_core::utils::keccak256;
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fq, Fr, G1Affine},
+        ff::PrimeField,
+        CurveAffine,
+    },
+    plonk::{self, verify_proof, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+    },
+    SerdeFormat,
+};
+use serde::{Deserialize, Serialize};
+use snark_verifier::{
+    loader::{evm, native::NativeLoader},
+    pcs::{kzg::KzgAccumulator, AccumulationDecider},
+    system::halo2::transcript::evm::EvmTranscript,
+    util::arithmetic::fe_from_limbs,
+};
+use std::io::{self, Read, Write};
+use thiserror::Error;
+
+/// `RootCircuit` of the proofs of a [`ProofBundle`].
+type BundleCircuit = RootCircuit<'static, Bn256, Shplonk<Bn256>>;
+
+/// Error verifying a [`ProofBundle`].
+#[derive(Debug, Error)]
+pub enum BundleError {
+    /// The params or the verifying key can't be decoded.
+    #[error("invalid verifying key or params: {0}")]
+    Io(#[from] io::Error),
+    /// An instance isn't a canonical scalar.
+    #[error("invalid instance {0}")]
+    Instance(String),
+    /// The proof doesn't verify for the instances.
+    #[error("invalid proof: {0}")]
+    Proof(#[from] plonk::Error),
+    /// The proof verifies but the accumulator of the aggregated proof doesn't.
+    #[error("invalid accumulator: {0:?}")]
+    Accumulator(snark_verifier::Error),
+    /// The digest of the bundle isn't the one of its verifying key.
+    #[error("verifying key digest {vk} differs from the digest {bundle} of the bundle")]
+    VkDigest {
+        /// Digest of the bundle
+        bundle: String,
+        /// Digest of the verifying key of the bundle
+        vk: String,
+    },
+}
+
+pub(super) fn scalar_to_hex(scalar: &Fr) -> String {
+    let mut bytes = scalar.to_repr();
+    bytes.reverse();
+    hex::encode(bytes)
+}
+
+pub(super) fn scalar_from_hex(s: &str) -> Option<Fr> {
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    hex::decode_to_slice(s, &mut repr).ok()?;
+    repr.reverse();
+    Fr::from_repr(repr).into()
+}
+
+/// Verifier params, verifying key, instances and proof of a `RootCircuit` proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofBundle {
+    /// Verifier params, in `SerdeFormat::RawBytes`
+    #[serde(with = "hex")]
+    pub params: Vec<u8>,
+    /// Verifying key of the `RootCircuit`, in `SerdeFormat::RawBytes`
+    #[serde(with = "hex")]
+    pub vk: Vec<u8>,
+    /// Instances of each instance column, as big-endian hex scalars
+    pub instances: Vec<Vec<String>>,
+    /// Proof
+    #[serde(with = "hex")]
+    pub proof: Vec<u8>,
+    /// Digest of the verifying key, hex encoded, to check it against the digest of the deployed
+    /// verifier.  Empty in the bundles created before the digest.
+    #[serde(default)]
+    pub vk_digest: String,
+}
+
+impl ProofBundle {
+    /// Bundle a `RootCircuit` proof with the params and the verifying key it was created with.
+    pub fn new(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        instances: &[Vec<Fr>],
+        proof: Vec<u8>,
+    ) -> io::Result<Self> {
+        let mut params_bytes = Vec::new();
+        params
+            .verifier_params()
+            .write_custom(&mut params_bytes, SerdeFormat::RawBytes)?;
+        Ok(Self {
+            params: params_bytes,
+            vk: vk.to_bytes(SerdeFormat::RawBytes),
+            instances: instances
+                .iter()
+                .map(|column| column.iter().map(scalar_to_hex).collect())
+                .collect(),
+            proof,
+            vk_digest: hex::encode(vk_digest(vk)),
+        })
+    }
+
+    /// Write the bundle as JSON.
+    pub fn write<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Read a bundle from JSON.
+    pub fn read<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Decode the instances.
+    pub fn instances(&self) -> Result<Vec<Vec<Fr>>, BundleError> {
+        self.instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|s| scalar_from_hex(s).ok_or_else(|| BundleError::Instance(s.clone())))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Keccak hash of the verifying key, hex encoded, to check it against the hashes of the
+    /// verifying keys of the deployed verifiers.
+    pub fn vk_hash(&self) -> String {
+        hex::encode(keccak256(&self.vk))
+    }
+
+    /// Calldata of the call to the EVM verifier of the proof.
+    pub fn calldata(&self) -> Result<Vec<u8>, BundleError> {
+        Ok(evm::encode_calldata(&self.instances()?, &self.proof))
+    }
+
+    /// Verify the digest of the verifying key, the proof, and the pairing of the accumulator of
+    /// the aggregated proof in the last `4 * LIMBS` instances.
+    pub fn verify(&self) -> Result<(), BundleError> {
+        let params =
+            ParamsKZG::<Bn256>::read_custom(&mut self.params.as_slice(), SerdeFormat::RawBytes)?;
+        let vk = VerifyingKey::<G1Affine>::read::<_, BundleCircuit>(
+            &mut self.vk.as_slice(),
+            SerdeFormat::RawBytes,
+            (),
+        )?;
+        let digest = hex::encode(vk_digest(&vk));
+        if !self.vk_digest.is_empty() && self.vk_digest != digest {
+            return Err(BundleError::VkDigest {
+                bundle: self.vk_digest.clone(),
+                vk: digest,
+            });
+        }
+        let instances = self.instances()?;
+
+        let mut transcript =
+            EvmTranscript::<G1Affine, NativeLoader, _, _>::new(self.proof.as_slice());
+        // The single strategy checks the pairing, and fails with `ConstraintSystemFailure`
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategy::new(&params),
+            &[&instances.iter().map(Vec::as_slice).collect::<Vec<_>>()],
+            &mut transcript,
+        )?;
+
+        let limbs = instances
+            .first()
+            .filter(|instance| instance.len() >= 4 * LIMBS)
+            .map(|instance| &instance[instance.len() - 4 * LIMBS..])
+            .ok_or(BundleError::Accumulator(
+                snark_verifier::Error::InvalidInstances,
+            ))?;
+        let [lhs, rhs] = [&limbs[..2 * LIMBS], &limbs[2 * LIMBS..]].map(|limbs| {
+            let [x, y] = [&limbs[..LIMBS], &limbs[LIMBS..]]
+                .map(|limbs| fe_from_limbs::<Fr, Fq, LIMBS, BITS>(limbs.try_into().unwrap()));
+            Option::<G1Affine>::from(G1Affine::from_xy(x, y))
+        });
+        let (Some(lhs), Some(rhs)) = (lhs, rhs) else {
+            return Err(BundleError::Accumulator(
+                snark_verifier::Error::InvalidInstances,
+            ));
+        };
+
+        let svk = KzgSvk::<Bn256>::new(params.get_g()[0]);
+        let dk = KzgDk::new(svk, params.g2(), params.s_g2());
+        <Shplonk<Bn256> as AccumulationDecider<G1Affine, NativeLoader>>::decide(
+            &dk,
+            KzgAccumulator::new(lhs, rhs),
+        )
+        .map_err(BundleError::Accumulator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proof_bundle_roundtrip() {
+        let bundle = ProofBundle {
+            params: vec![1, 2, 3],
+            vk: vec![4, 5],
+            instances: vec![vec![
+                scalar_to_hex(&Fr::from(0x1234)),
+                scalar_to_hex(&-Fr::from(1)),
+            ]],
+            proof: vec![6],
+            vk_digest: String::new(),
+        };
+        let mut buf = Vec::new();
+        bundle.write(&mut buf).unwrap();
+        let bundle = ProofBundle::read(buf.as_slice()).unwrap();
+        assert_eq!(
+            bundle.instances().unwrap(),
+            vec![vec![Fr::from(0x1234), -Fr::from(1)]]
+        );
+
+        // the modulus isn't a canonical scalar
+        let modulus = Fr::MODULUS.trim_start_matches("0x").to_string();
+        let bundle = ProofBundle {
+            instances: vec![vec![modulus.clone()]],
+            ..bundle
+        };
+        assert!(matches!(bundle.instances(), Err(BundleError::Instance(s)) if s == modulus));
+        // the params are garbage
+        assert!(matches!(bundle.verify(), Err(BundleError::Io(_))));
+    }
+}
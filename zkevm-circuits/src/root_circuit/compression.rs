@@ -0,0 +1,325 @@
+//! Compression of the proofs of the aggregation circuits for their verification on the EVM.
+//!
+//! A `RootCircuit` or `BatchCircuit` proof created with the
+//! [`PoseidonTranscript`](super::PoseidonTranscript) is cheap to verify in a circuit but not on
+//! the EVM.  The [`CompressionCircuit`] verifies such a proof, folds the accumulator the proof
+//! carries in its instances into its own, and is proven by [`compress_proof`] with SHPLONK and
+//! the [`EvmTranscript`], so that its proof is the one verified by the verifier of
+//! [`gen_evm_verifier`](super::gen_evm_verifier), with the `accumulator_indices` of the
+//! `CompressionCircuit`.
+
+use super::{aggregate, AggregationConfig, Halo2Loader, KzgSvk, Snark, SnarkWitness, LIMBS};
+use eth_types::Field;
+use halo2_proofs::{
+    arithmetic::Field as Halo2Field,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::{
+        bn256::{Bn256, G1Affine},
+        serde::SerdeObject,
+        CurveAffine, CurveExt,
+    },
+    plonk::{create_proof, Circuit, ConstraintSystem, Error, ProvingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverSHPLONK,
+        },
+    },
+};
+use itertools::Itertools;
+use maingate::MainGateInstructions;
+use rand::RngCore;
+use snark_verifier::{
+    loader::native::NativeLoader,
+    pcs::{
+        kzg::{KzgAccumulator, KzgAsProvingKey, KzgAsVerifyingKey, KzgDecidingKey},
+        AccumulationDecider, AccumulationScheme, AccumulationSchemeProver,
+        PolynomialCommitmentScheme,
+    },
+    system::halo2::transcript::evm::EvmTranscript,
+    util::arithmetic::MultiMillerLoop,
+    verifier::plonk::PlonkProtocol,
+};
+use std::{iter, marker::PhantomData, rc::Rc};
+
+/// CompressionCircuit for wrapping the proof of an aggregation circuit into a proof to verify on
+/// the EVM.  The instances of the aggregation circuit are propagated, except its accumulator
+/// limbs which are replaced by the ones of the folded accumulator.
+#[derive(Clone)]
+pub struct CompressionCircuit<'a, M: MultiMillerLoop, As>
+where
+    M::G1Affine: CurveAffine,
+{
+    svk: KzgSvk<M>,
+    snark: SnarkWitness<'a, M::G1Affine>,
+    instance: Vec<M::Fr>,
+    _marker: PhantomData<As>,
+}
+
+impl<'a, M, As> CompressionCircuit<'a, M, As>
+where
+    M: MultiMillerLoop,
+    M::Fr: Field,
+    M::G1: CurveExt<AffineExt = M::G1Affine, ScalarExt = M::Fr>,
+    M::G1Affine: SerdeObject + CurveAffine<ScalarExt = M::Fr, CurveExt = M::G1>,
+    M::G2Affine: SerdeObject + CurveAffine,
+    As: PolynomialCommitmentScheme<
+            M::G1Affine,
+            NativeLoader,
+            VerifyingKey = KzgSvk<M>,
+            Output = KzgAccumulator<M::G1Affine, NativeLoader>,
+        > + AccumulationSchemeProver<
+            M::G1Affine,
+            Accumulator = KzgAccumulator<M::G1Affine, NativeLoader>,
+            ProvingKey = KzgAsProvingKey<M::G1Affine>,
+        > + AccumulationDecider<M::G1Affine, NativeLoader, DecidingKey = KzgDecidingKey<M>>,
+{
+    /// Create a `CompressionCircuit` with accumulator computed given the proof of an aggregation
+    /// circuit and its instance.  The protocol must be compiled with the `accumulator_indices` of
+    /// the aggregation circuit.  Returns an error if the protocol has no accumulator or if the
+    /// given proof is invalid.
+    pub fn new(
+        params: &ParamsKZG<M>,
+        protocol: &'a PlonkProtocol<M::G1Affine>,
+        instances: Value<&'a Vec<Vec<M::Fr>>>,
+        proof: Value<&'a [u8]>,
+    ) -> Result<Self, snark_verifier::Error> {
+        if protocol.accumulator_indices.is_empty() {
+            return Err(snark_verifier::Error::AssertionFailure(
+                "protocol of the aggregation circuit has no accumulator indices".to_string(),
+            ));
+        }
+        let num_instances = protocol.num_instance.iter().sum::<usize>()
+            - protocol.accumulator_indices.iter().flatten().count()
+            + 4 * LIMBS;
+        let instance = {
+            let mut instance = Ok(vec![M::Fr::ZERO; num_instances]);
+            instances.zip(proof).map(|(instances, proof)| {
+                let snark = Snark::new(protocol, instances, proof);
+                instance = aggregate::<M, As>(params, [snark]).map(|accumulator_limbs| {
+                    iter::empty()
+                        // Propagate the instances of the aggregation circuit, without its
+                        // accumulator
+                        .chain(propagated(protocol, instances).cloned())
+                        // Output folded accumulator limbs
+                        .chain(accumulator_limbs)
+                        .collect_vec()
+                });
+            });
+            instance?
+        };
+        debug_assert_eq!(instance.len(), num_instances);
+
+        Ok(Self {
+            svk: KzgSvk::<M>::new(params.get_g()[0]),
+            snark: SnarkWitness::new(protocol, instances, proof),
+            instance,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns accumulator indices in instance columns, which will be in
+    /// the last `4 * LIMBS` rows of instance column in `MainGate`.
+    pub fn accumulator_indices(&self) -> Vec<(usize, usize)> {
+        let offset = self.instance.len() - 4 * LIMBS;
+        (offset..).map(|idx| (0, idx)).take(4 * LIMBS).collect()
+    }
+
+    /// Returns number of instance
+    pub fn num_instance(&self) -> Vec<usize> {
+        vec![self.instance.len()]
+    }
+
+    /// Returns instance
+    pub fn instance(&self) -> Vec<Vec<M::Fr>> {
+        vec![self.instance.clone()]
+    }
+}
+
+/// Instances of the aggregation circuit not in its accumulator, in the order of the columns.
+fn propagated<'b, T, C: CurveAffine>(
+    protocol: &'b PlonkProtocol<C>,
+    instances: &'b [Vec<T>],
+) -> impl Iterator<Item = &'b T> + 'b {
+    instances.iter().enumerate().flat_map(move |(i, column)| {
+        column.iter().enumerate().filter_map(move |(j, instance)| {
+            (!protocol
+                .accumulator_indices
+                .iter()
+                .flatten()
+                .contains(&(i, j)))
+            .then_some(instance)
+        })
+    })
+}
+
+impl<'a, M, As> Circuit<M::Fr> for CompressionCircuit<'a, M, As>
+where
+    M: MultiMillerLoop,
+    M::Fr: Field,
+    M::G1Affine: CurveAffine<ScalarExt = M::Fr>,
+    for<'b> As: PolynomialCommitmentScheme<
+            M::G1Affine,
+            Rc<Halo2Loader<'b, M::G1Affine>>,
+            VerifyingKey = KzgSvk<M>,
+            Output = KzgAccumulator<M::G1Affine, Rc<Halo2Loader<'b, M::G1Affine>>>,
+        > + AccumulationScheme<
+            M::G1Affine,
+            Rc<Halo2Loader<'b, M::G1Affine>>,
+            Accumulator = KzgAccumulator<M::G1Affine, Rc<Halo2Loader<'b, M::G1Affine>>>,
+            VerifyingKey = KzgAsVerifyingKey,
+        >,
+{
+    type Config = AggregationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            snark: self.snark.without_witnesses(),
+            instance: vec![M::Fr::ZERO; self.instance.len()],
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<M::Fr>) -> Self::Config {
+        AggregationConfig::configure::<M::G1Affine>(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<M::Fr>,
+    ) -> Result<(), Error> {
+        config.load_table(&mut layouter)?;
+        let (instances, accumulator_limbs) =
+            config.aggregate::<M, As>(&mut layouter, &self.svk, [self.snark])?;
+
+        // Constrain equality to instance values
+        let main_gate = config.main_gate();
+        for (row, limb) in propagated(self.snark.protocol(), &instances[0])
+            .cloned()
+            .chain(accumulator_limbs)
+            .enumerate()
+        {
+            main_gate.expose_public(layouter.namespace(|| ""), limb, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Create the proof of a `CompressionCircuit` with SHPLONK and the [`EvmTranscript`].
+pub fn compress_proof(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: CompressionCircuit<'_, Bn256, super::Shplonk<Bn256>>,
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error> {
+    let instance = circuit.instance();
+    let mut transcript = EvmTranscript::<_, NativeLoader, _, _>::new(Vec::new());
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&instance.iter().map(Vec::as_slice).collect_vec()],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::root_circuit::{
+        aggregation::test::{rand_standard_plonk_snarks, SnarkOwned},
+        compile, Config, Gwc, PoseidonTranscript, RootCircuit,
+    };
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{keygen_pk, keygen_vk},
+        poly::kzg::multiopen::ProverGWC,
+    };
+    use rand::rngs::OsRng;
+
+    #[ignore = "Due to high memory requirement"]
+    #[test]
+    fn test_compression_circuit() {
+        let params = ParamsKZG::<Bn256>::setup(21, OsRng);
+
+        // Prove a RootCircuit of a snark with the PoseidonTranscript
+        let snark: SnarkOwned<G1Affine> = rand_standard_plonk_snarks(&params, 1).pop().unwrap();
+        let root_circuit = RootCircuit::<Bn256, Gwc<_>>::new(
+            &params,
+            &snark.protocol,
+            Value::known(&snark.instances),
+            Value::known(&snark.proof),
+        )
+        .unwrap();
+        let root_instance = root_circuit.instance();
+        let root_pk = keygen_pk(
+            &params,
+            keygen_vk(&params, &root_circuit).unwrap(),
+            &root_circuit,
+        )
+        .unwrap();
+        let root_protocol = compile(
+            &params,
+            root_pk.get_vk(),
+            Config::kzg()
+                .with_num_instance(root_circuit.num_instance())
+                .with_accumulator_indices(Some(root_circuit.accumulator_indices())),
+        );
+        let root_proof = {
+            let mut transcript = PoseidonTranscript::new(Vec::new());
+            create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
+                &params,
+                &root_pk,
+                &[root_circuit],
+                &[&root_instance.iter().map(Vec::as_slice).collect_vec()],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        };
+
+        let compression_circuit = CompressionCircuit::<Bn256, Gwc<_>>::new(
+            &params,
+            &root_protocol,
+            Value::known(&root_instance),
+            Value::known(&root_proof),
+        )
+        .unwrap();
+        let instance = compression_circuit.instance();
+        // The instance of the snark is propagated, and the accumulator replaced
+        assert_eq!(instance[0].len(), root_instance[0].len());
+        assert_eq!(
+            instance[0][..snark.instances[0].len()],
+            snark.instances[0][..]
+        );
+        assert_ne!(
+            instance[0][snark.instances[0].len()..],
+            root_instance[0][snark.instances[0].len()..]
+        );
+        assert_eq!(
+            MockProver::<Fr>::run(21, &compression_circuit, instance)
+                .unwrap()
+                .verify(),
+            Ok(())
+        );
+
+        // A protocol without accumulator can't be compressed
+        assert!(CompressionCircuit::<Bn256, Gwc<_>>::new(
+            &params,
+            &snark.protocol,
+            Value::known(&snark.instances),
+            Value::known(&snark.proof),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! EVM verifier of the `RootCircuit` proofs.
+//!
+//! The verifier is generated as Solidity and compiled with `solc`, which must be in the `PATH`.
+
+use super::{compile, Config, EvmTranscript, PlonkVerifier, Shplonk};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::VerifyingKey,
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use snark_verifier::{
+    loader::evm::{self, EvmLoader},
+    pcs::kzg::KzgDecidingKey,
+    verifier::SnarkVerifier,
+};
+use std::rc::Rc;
+
+/// Generate the deployment code of the EVM verifier of the proofs of a `RootCircuit`, created
+/// with SHPLONK and the [`EvmTranscript`].
+pub fn gen_evm_verifier(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    accumulator_indices: Vec<(usize, usize)>,
+) -> Vec<u8> {
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg()
+            .with_num_instance(num_instance.clone())
+            .with_accumulator_indices(Some(accumulator_indices)),
+    );
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+
+    let instances = transcript.load_instances(num_instance);
+    let proof =
+        PlonkVerifier::<Shplonk<Bn256>>::read_proof(&dk, &protocol, &instances, &mut transcript)
+            .unwrap();
+    PlonkVerifier::<Shplonk<Bn256>>::verify(&dk, &protocol, &instances, &proof).unwrap();
+
+    evm::compile_solidity(&loader.solidity_code())
+}
+
+/// Deploy the EVM verifier and call it with the instances and the proof.  Returns the gas cost
+/// of the call, or an error if the verifier reverts.
+pub fn evm_verify(
+    deployment_code: Vec<u8>,
+    instances: Vec<Vec<Fr>>,
+    proof: Vec<u8>,
+) -> Result<u64, String> {
+    let calldata = evm::encode_calldata(&instances, &proof);
+    evm::deploy_and_call(deployment_code, calldata)
+}
@@ -0,0 +1,231 @@
+//! Export of the verifying key and the verifier params in JSON.
+//!
+//! A [`VerifierData`] contains everything a KZG verifier of a circuit needs besides the proof
+//! and the instances, in the layout of snark-verifier: the preprocessed commitments of the
+//! `PlonkProtocol` (the fixed commitments followed by the permutation commitments), the
+//! transcript initial state, and the `g`, `g2` and `s_g2` points of the KZG deciding key.
+//! Points are hex encoded in their compressed form and scalars are big-endian hex, so that
+//! external tooling can build a verifier without linking this crate.
+//!
+//! The [`vk_digest`] of the verifying key is the keccak hash of the words an EVM verifier stores
+//! for it, so that it can be compared with the digest computed from the contract of a deployed
+//! verifier, and a proof created with another verifying key is detected before its submission.
+
+use ethers_core::utils::keccak256;
+use halo2_proofs::{
+    halo2curves::{
+        ff::PrimeField, group::GroupEncoding, serde::SerdeObject, CurveAffine, CurveExt,
+    },
+    plonk::VerifyingKey,
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
+};
+use serde::{Deserialize, Serialize};
+use snark_verifier::util::arithmetic::MultiMillerLoop;
+use std::io::{Read, Write};
+
+/// Verifying key and verifier params of a circuit for a KZG verifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierData {
+    /// Degree of the circuit
+    pub k: u32,
+    /// Number of instances of each instance column
+    pub num_instance: Vec<usize>,
+    /// Initial state of the transcript, as a scalar
+    pub transcript_initial_state: String,
+    /// Commitments of the fixed columns
+    pub fixed_commitments: Vec<String>,
+    /// Commitments of the permutation
+    pub permutation_commitments: Vec<String>,
+    /// Generator of G1 of the params
+    pub g: String,
+    /// Generator of G2 of the params
+    pub g2: String,
+    /// Generator of G2 multiplied by the toxic waste `s` of the params
+    pub s_g2: String,
+    /// Digest of the verifying key, see [`vk_digest`]
+    pub vk_digest: String,
+}
+
+fn point_to_hex<C: GroupEncoding>(point: &C) -> String {
+    hex::encode(point.to_bytes())
+}
+
+fn scalar_to_hex<F: PrimeField>(scalar: F) -> String {
+    let mut bytes = scalar.to_repr().as_ref().to_vec();
+    bytes.reverse();
+    hex::encode(bytes)
+}
+
+/// Big-endian 32 bytes word of a little-endian repr, like the ones of the bn256 fields.
+fn to_word<F: PrimeField>(value: F) -> [u8; 32] {
+    let mut word = [0; 32];
+    let repr = value.to_repr();
+    for (byte, repr_byte) in word.iter_mut().rev().zip(repr.as_ref()) {
+        *byte = *repr_byte;
+    }
+    word
+}
+
+/// Words of the coordinates of a point, `(0, 0)` for the identity like in the EVM.
+fn point_words<C: CurveAffine>(point: &C) -> [[u8; 32]; 2] {
+    Option::from(point.coordinates())
+        .map(|coordinates| [to_word(*coordinates.x()), to_word(*coordinates.y())])
+        .unwrap_or_default()
+}
+
+/// Digest of the verifying key: the keccak hash of the 32 bytes words of its domain `k` and
+/// `omega`, the number of fixed commitments and their `x` and `y`, then the number of
+/// permutation commitments and their `x` and `y`.  This is the `abi.encodePacked` of these
+/// words in a verifier contract.
+pub fn vk_digest<C: CurveAffine>(vk: &VerifyingKey<C>) -> [u8; 32] {
+    let domain = vk.get_domain();
+    let count = |n: usize| to_word(C::ScalarExt::from(n as u64));
+    let mut words = vec![
+        count(domain.k() as usize),
+        to_word(domain.get_omega()),
+        count(vk.fixed_commitments().len()),
+    ];
+    words.extend(vk.fixed_commitments().iter().flat_map(point_words));
+    let permutation = vk.permutation().commitments();
+    words.push(count(permutation.len()));
+    words.extend(permutation.iter().flat_map(point_words));
+    keccak256(words.concat())
+}
+
+impl VerifierData {
+    /// Export the verifying key of a circuit with `num_instance` instances in each instance
+    /// column, and the params it was generated with.
+    pub fn new<M>(
+        params: &ParamsKZG<M>,
+        vk: &VerifyingKey<M::G1Affine>,
+        num_instance: Vec<usize>,
+    ) -> Self
+    where
+        M: MultiMillerLoop,
+        M::G1: CurveExt<AffineExt = M::G1Affine, ScalarExt = M::Fr>,
+        M::G1Affine: SerdeObject + CurveAffine<ScalarExt = M::Fr, CurveExt = M::G1>,
+        M::G2Affine: SerdeObject + CurveAffine,
+    {
+        Self {
+            k: params.k(),
+            num_instance,
+            transcript_initial_state: scalar_to_hex(vk.transcript_repr()),
+            fixed_commitments: vk.fixed_commitments().iter().map(point_to_hex).collect(),
+            permutation_commitments: vk
+                .permutation()
+                .commitments()
+                .iter()
+                .map(point_to_hex)
+                .collect(),
+            g: point_to_hex(&params.get_g()[0]),
+            g2: point_to_hex(&params.g2()),
+            s_g2: point_to_hex(&params.s_g2()),
+            vk_digest: hex::encode(vk_digest(vk)),
+        }
+    }
+
+    /// Write the verifier data as JSON.
+    pub fn write<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    /// Read verifier data from JSON.
+    pub fn read<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::{Bn256, Fr},
+        plonk::{keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
+        poly::Rotation,
+    };
+    use rand::rngs::OsRng;
+
+    struct TestCircuit(Value<Fr>);
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = (Column<Fixed>, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self(Value::unknown())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let (q, a, i) = (
+                meta.fixed_column(),
+                meta.advice_column(),
+                meta.instance_column(),
+            );
+            meta.enable_equality(a);
+            meta.enable_equality(i);
+            meta.create_gate("a * q == a", |meta| {
+                let q = meta.query_fixed(q, Rotation::cur());
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![a.clone() * q - a]
+            });
+            (q, a, i)
+        }
+
+        fn synthesize(
+            &self,
+            (q, a, i): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let cell = layouter.assign_region(
+                || "a",
+                |mut region| {
+                    region.assign_fixed(|| "q", q, 0, || Value::known(Fr::from(1)))?;
+                    region.assign_advice(|| "a", a, 0, || self.0)
+                },
+            )?;
+            layouter.constrain_instance(cell.cell(), i, 0)
+        }
+    }
+
+    #[test]
+    fn verifier_data_roundtrip() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let vk = keygen_vk(&params, &TestCircuit(Value::unknown())).unwrap();
+        let data = VerifierData::new(&params, &vk, vec![1]);
+
+        assert_eq!(data.k, 4);
+        assert_eq!(data.fixed_commitments.len(), 1);
+        assert_eq!(data.permutation_commitments.len(), 2);
+        // compressed bn256 G1 and G2 points
+        assert_eq!(data.g.len(), 64);
+        assert_eq!(data.g2.len(), 128);
+
+        let mut buf = Vec::new();
+        data.write(&mut buf).unwrap();
+        assert_eq!(VerifierData::read(buf.as_slice()).unwrap(), data);
+    }
+
+    #[test]
+    fn verifying_key_digest() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let vk = keygen_vk(&params, &TestCircuit(Value::unknown())).unwrap();
+        let digest = vk_digest(&vk);
+        assert_eq!(
+            VerifierData::new(&params, &vk, vec![1]).vk_digest,
+            hex::encode(digest)
+        );
+
+        // The digest only depends on the verifying key
+        let vk_again = keygen_vk(&params, &TestCircuit(Value::known(Fr::from(1)))).unwrap();
+        assert_eq!(vk_digest(&vk_again), digest);
+        // The domain is part of the verifying key
+        let params = ParamsKZG::<Bn256>::setup(5, OsRng);
+        let vk = keygen_vk(&params, &TestCircuit(Value::unknown())).unwrap();
+        assert_ne!(vk_digest(&vk), digest);
+    }
+}
@@ -0,0 +1,239 @@
+//! Disk-backed cache of the `SuperCircuit` proofs of blocks.
+//!
+//! A [`ProofCache`] stores the instances and the proof of a block, keyed by the hash of the block
+//! and the [`vk_digest`] of the `SuperCircuit` protocol it was proven with, which changes with
+//! any change of the circuit.  Proving a block through [`ProofCache::get_or_prove`] skips the
+//! trace fetching, the witness generation and the proof of a block that was already proven with
+//! the same circuit, for example when a batch is proven again after the failure of one of its
+//! blocks, and the cached proofs of the other blocks are aggregated as they are:
+//!
+//! ```ignore
+//! let cache = ProofCache::new("proof-cache")?;
+//! let proofs = blocks
+//!     .iter()
+//!     .map(|hash| cache.get_or_prove(*hash, &protocol, || prove_block(*hash)))
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! let circuit = BatchCircuit::new(&params, proofs.iter().map(|p| p.as_snark(&protocol)))?;
+//! ```
+
+use super::{
+    bundle::{scalar_from_hex, scalar_to_hex},
+    vk_digest, Snark,
+};
+use eth_types::H256;
+use halo2_proofs::halo2curves::bn256::{Fr, G1Affine};
+use serde::{Deserialize, Serialize};
+use snark_verifier::verifier::plonk::PlonkProtocol;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Error of a [`ProofCache`].
+#[derive(Debug, Error)]
+pub enum ProofCacheError {
+    /// Error reading or writing the cache.
+    #[error("proof cache io error: {0}")]
+    Io(#[from] io::Error),
+    /// A cached proof can't be decoded.
+    #[error("invalid cached proof: {0}")]
+    Serde(#[from] serde_json::Error),
+    /// A cached instance isn't a canonical scalar.
+    #[error("invalid cached instance {0}")]
+    Instance(String),
+    /// The digest of the protocol can't be computed.
+    #[error("invalid protocol: {0:?}")]
+    Protocol(snark_verifier::Error),
+}
+
+/// Instances and proof of a `SuperCircuit` proof of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedProof {
+    /// Instances of each instance column
+    pub instances: Vec<Vec<Fr>>,
+    /// Proof
+    pub proof: Vec<u8>,
+}
+
+impl CachedProof {
+    /// `Snark` of the proof, to aggregate it with the `protocol` it was proven with.
+    pub fn as_snark<'a>(&'a self, protocol: &'a PlonkProtocol<G1Affine>) -> Snark<'a, G1Affine> {
+        Snark::new(protocol, &self.instances, &self.proof)
+    }
+}
+
+/// Serialized [`CachedProof`].
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Instances of each instance column, as big-endian hex scalars
+    instances: Vec<Vec<String>>,
+    #[serde(with = "hex")]
+    proof: Vec<u8>,
+}
+
+/// Cache of the `SuperCircuit` proofs of blocks, stored as one JSON file per block and protocol.
+///
+/// The cached proofs are not verified when read: a corrupted proof makes the aggregation fail,
+/// and can be dropped with [`ProofCache::remove`].
+#[derive(Debug, Clone)]
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+impl ProofCache {
+    /// Create a cache stored in `dir`, which is created if it doesn't exist.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Return the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(
+        &self,
+        block_hash: H256,
+        protocol: &PlonkProtocol<G1Affine>,
+    ) -> Result<PathBuf, ProofCacheError> {
+        let digest = vk_digest(protocol).map_err(ProofCacheError::Protocol)?;
+        Ok(self
+            .dir
+            .join(format!("{:x}-{}.json", block_hash, scalar_to_hex(&digest))))
+    }
+
+    /// Return the cached proof of the block for the protocol, if any.
+    pub fn get(
+        &self,
+        block_hash: H256,
+        protocol: &PlonkProtocol<G1Affine>,
+    ) -> Result<Option<CachedProof>, ProofCacheError> {
+        let bytes = match fs::read(self.path(block_hash, protocol)?) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let entry: Entry = serde_json::from_slice(&bytes)?;
+        let instances = entry
+            .instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|s| scalar_from_hex(s).ok_or_else(|| ProofCacheError::Instance(s.clone())))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Some(CachedProof {
+            instances,
+            proof: entry.proof,
+        }))
+    }
+
+    /// Store the proof of the block for the protocol.
+    pub fn insert(
+        &self,
+        block_hash: H256,
+        protocol: &PlonkProtocol<G1Affine>,
+        proof: &CachedProof,
+    ) -> Result<(), ProofCacheError> {
+        let path = self.path(block_hash, protocol)?;
+        let entry = Entry {
+            instances: proof
+                .instances
+                .iter()
+                .map(|column| column.iter().map(scalar_to_hex).collect())
+                .collect(),
+            proof: proof.proof.clone(),
+        };
+        // Write to a temporary file first so that an interrupted run doesn't leave a truncated
+        // proof in the cache.
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(&entry)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Remove the cached proof of the block for the protocol, if any.
+    pub fn remove(
+        &self,
+        block_hash: H256,
+        protocol: &PlonkProtocol<G1Affine>,
+    ) -> Result<(), ProofCacheError> {
+        match fs::remove_file(self.path(block_hash, protocol)?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Return the cached proof of the block for the protocol, or call `prove` and cache its
+    /// proof.  `prove` is only called on a cache miss, so it should fetch the trace and build the
+    /// witness itself.
+    pub fn get_or_prove<E: From<ProofCacheError>>(
+        &self,
+        block_hash: H256,
+        protocol: &PlonkProtocol<G1Affine>,
+        prove: impl FnOnce() -> Result<CachedProof, E>,
+    ) -> Result<CachedProof, E> {
+        if let Some(proof) = self.get(block_hash, protocol)? {
+            return Ok(proof);
+        }
+        let proof = prove()?;
+        self.insert(block_hash, protocol, &proof)?;
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::aggregation::test::rand_standard_plonk_snarks, *};
+    use halo2_proofs::{halo2curves::bn256::Bn256, poly::kzg::commitment::ParamsKZG};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn proof_cache_get_or_prove() {
+        let params = ParamsKZG::<Bn256>::setup(8, OsRng);
+        let snark = rand_standard_plonk_snarks(&params, 1).pop().unwrap();
+        let protocol = snark.as_snark().protocol;
+
+        let dir = std::env::temp_dir().join(format!("proof_cache_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ProofCache::new(&dir).unwrap();
+        let proof = CachedProof {
+            instances: vec![vec![Fr::from(0x1234), -Fr::from(1)]],
+            proof: vec![1, 2, 3],
+        };
+        let block = H256::repeat_byte(1);
+        assert_eq!(cache.get(block, protocol).unwrap(), None);
+
+        let mut proven = 0;
+        let mut prove = || {
+            proven += 1;
+            Ok::<_, ProofCacheError>(proof.clone())
+        };
+        assert_eq!(
+            cache.get_or_prove(block, protocol, &mut prove).unwrap(),
+            proof
+        );
+        assert_eq!(
+            cache.get_or_prove(block, protocol, &mut prove).unwrap(),
+            proof
+        );
+        // Another block is proven again
+        cache
+            .get_or_prove(H256::repeat_byte(2), protocol, &mut prove)
+            .unwrap();
+        assert_eq!(proven, 2);
+
+        // The cache survives the instance
+        let cache = ProofCache::new(&dir).unwrap();
+        assert_eq!(cache.get(block, protocol).unwrap(), Some(proof));
+        cache.remove(block, protocol).unwrap();
+        assert_eq!(cache.get(block, protocol).unwrap(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+//! Submission of [`ProofBundle`]s to the EVM verifier contract of the `RootCircuit`.
+//!
+//! The [`Submitter`] sends the proofs from a single account.  It tracks the nonce of the account
+//! itself, so that the proofs can be submitted one after the other without waiting for the node
+//! to see the previous ones, and replaces a transaction that isn't included in time by one with
+//! a higher gas price, following its [`GasStrategy`].
+
+use super::{BundleError, ProofBundle};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Address, BlockNumber, TransactionReceipt,
+    TransactionRequest, TxHash, U256, U64,
+};
+use ethers_providers::{JsonRpcClient, Middleware, Provider, ProviderError};
+use ethers_signers::{LocalWallet, Signer, WalletError};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Error submitting a proof.
+#[derive(Debug, Error)]
+pub enum SubmitError {
+    /// The bundle has invalid instances.
+    #[error("invalid bundle: {0}")]
+    Bundle(#[from] BundleError),
+    /// The node returned an error.
+    #[error("provider error: {0}")]
+    Provider(#[from] ProviderError),
+    /// The transaction can't be signed.
+    #[error("signer error: {0}")]
+    Signer(#[from] WalletError),
+    /// The verifier rejected the proof.
+    #[error("proof rejected by the verifier in tx {0:?}")]
+    Reverted(TxHash),
+    /// The escalated gas price would be above the maximum of the strategy.
+    #[error("gas price {0} is above the maximum")]
+    GasPriceLimit(U256),
+    /// No transaction was included after all the attempts.
+    #[error("proof not included after {0} attempts")]
+    NotIncluded(usize),
+}
+
+/// Gas price and replacement policy of the transactions of a [`Submitter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasStrategy {
+    /// Gas limit of the transactions, estimated when `None`
+    pub gas_limit: Option<U256>,
+    /// Percentage of the gas price of a replaced transaction paid by its replacement.  Nodes
+    /// don't accept replacements for less than 110%.
+    pub escalation_percent: u64,
+    /// Maximum gas price of a transaction
+    pub max_gas_price: U256,
+    /// Time to wait for the inclusion of a transaction before replacing it
+    pub timeout: Duration,
+    /// Maximum number of transactions sent for a proof, including the replacements
+    pub max_attempts: usize,
+}
+
+impl Default for GasStrategy {
+    fn default() -> Self {
+        Self {
+            gas_limit: None,
+            escalation_percent: 125,
+            max_gas_price: U256::from(500_000_000_000u64),
+            timeout: Duration::from_secs(60),
+            max_attempts: 5,
+        }
+    }
+}
+
+impl GasStrategy {
+    /// Return the gas price of the replacement of a transaction with `gas_price`.
+    pub fn escalate(&self, gas_price: U256) -> Result<U256, SubmitError> {
+        // Round up, so that a low gas price always increases
+        let escalated = (gas_price * self.escalation_percent + 99) / 100;
+        if escalated > self.max_gas_price {
+            return Err(SubmitError::GasPriceLimit(escalated));
+        }
+        Ok(escalated)
+    }
+}
+
+/// Submitter of proofs to the EVM verifier contract.
+pub struct Submitter<P: JsonRpcClient> {
+    provider: Provider<P>,
+    wallet: LocalWallet,
+    verifier: Address,
+    strategy: GasStrategy,
+    confirmations: u64,
+    poll_interval: Duration,
+    nonce: Option<U256>,
+}
+
+impl<P: JsonRpcClient> Submitter<P> {
+    /// Create a submitter of proofs to the `verifier` contract, from the account of the
+    /// `wallet`.  A proof is confirmed once its transaction has `confirmations` blocks, counting
+    /// the block that includes it.
+    pub async fn new(
+        provider: Provider<P>,
+        wallet: LocalWallet,
+        verifier: Address,
+        strategy: GasStrategy,
+        confirmations: u64,
+    ) -> Result<Self, SubmitError> {
+        let chain_id = provider.get_chainid().await?.as_u64();
+        Ok(Self {
+            provider,
+            wallet: wallet.with_chain_id(chain_id),
+            verifier,
+            strategy,
+            confirmations: confirmations.max(1),
+            poll_interval: Duration::from_secs(1),
+            nonce: None,
+        })
+    }
+
+    /// Set the interval between two polls of the node for the receipts.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    async fn next_nonce(&mut self) -> Result<U256, SubmitError> {
+        match self.nonce {
+            Some(nonce) => Ok(nonce),
+            None => {
+                let nonce = self
+                    .provider
+                    .get_transaction_count(self.wallet.address(), Some(BlockNumber::Pending.into()))
+                    .await?;
+                self.nonce = Some(nonce);
+                Ok(nonce)
+            }
+        }
+    }
+
+    /// Return the receipt of the first of the transactions that has been confirmed.
+    async fn confirmed_receipt(
+        &self,
+        hashes: &[TxHash],
+    ) -> Result<Option<TransactionReceipt>, SubmitError> {
+        for hash in hashes {
+            let Some(receipt) = self.provider.get_transaction_receipt(*hash).await? else {
+                continue;
+            };
+            let Some(block_number) = receipt.block_number else {
+                continue;
+            };
+            let head = self.provider.get_block_number().await?;
+            if head + 1 >= block_number + self.confirmations {
+                return Ok(Some(receipt));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Submit the proof of the bundle and wait for its confirmation.  Returns the receipt of the
+    /// confirmed transaction, or an error if the verifier rejected the proof.
+    pub async fn submit(
+        &mut self,
+        bundle: &ProofBundle,
+    ) -> Result<TransactionReceipt, SubmitError> {
+        let nonce = self.next_nonce().await?;
+        let mut tx: TypedTransaction = TransactionRequest::new()
+            .from(self.wallet.address())
+            .to(self.verifier)
+            .data(bundle.calldata()?)
+            .nonce(nonce)
+            .chain_id(self.wallet.chain_id())
+            .into();
+        let gas = match self.strategy.gas_limit {
+            Some(gas) => gas,
+            None => self.provider.estimate_gas(&tx, None).await?,
+        };
+        tx.set_gas(gas);
+        let mut gas_price = self.provider.get_gas_price().await?;
+
+        // All the transactions sent for the proof, which replace each other
+        let mut hashes = Vec::new();
+        for attempt in 0..self.strategy.max_attempts {
+            if attempt > 0 {
+                gas_price = self.strategy.escalate(gas_price).map_err(|err| {
+                    // A transaction may still be included later
+                    self.nonce = None;
+                    err
+                })?;
+            }
+            tx.set_gas_price(gas_price);
+            let signature = self.wallet.sign_transaction_sync(&tx)?;
+            match self
+                .provider
+                .send_raw_transaction(tx.rlp_signed(&signature))
+                .await
+            {
+                Ok(pending) => hashes.push(pending.tx_hash()),
+                // A previous transaction may have been included meanwhile, which makes the nonce
+                // of its replacement too low.
+                Err(err) if !hashes.is_empty() => {
+                    log::warn!(
+                        "replacement of proof tx with nonce {} failed: {}",
+                        nonce,
+                        err
+                    )
+                }
+                Err(err) => return Err(err.into()),
+            }
+
+            let deadline = Instant::now() + self.strategy.timeout;
+            while Instant::now() < deadline {
+                if let Some(receipt) = self.confirmed_receipt(&hashes).await? {
+                    self.nonce = Some(nonce + 1);
+                    if receipt.status != Some(U64::one()) {
+                        return Err(SubmitError::Reverted(receipt.transaction_hash));
+                    }
+                    return Ok(receipt);
+                }
+                tokio::time::sleep(self.poll_interval).await;
+            }
+            log::info!(
+                "proof tx with nonce {} not confirmed after {:?}, attempt {}",
+                nonce,
+                self.strategy.timeout,
+                attempt + 1
+            );
+        }
+        self.nonce = None;
+        Err(SubmitError::NotIncluded(self.strategy.max_attempts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_strategy_escalate() {
+        let strategy = GasStrategy {
+            max_gas_price: U256::from(200),
+            ..Default::default()
+        };
+        assert_eq!(strategy.escalate(U256::from(100)).unwrap(), U256::from(125));
+        // a low gas price still increases
+        assert_eq!(strategy.escalate(U256::from(1)).unwrap(), U256::from(2));
+        assert!(matches!(
+            strategy.escalate(U256::from(161)),
+            Err(SubmitError::GasPriceLimit(price)) if price == U256::from(202)
+        ));
+    }
+}
@@ -1,12 +1,15 @@
 use crate::{
-    root_circuit::{compile, Config, Gwc, PoseidonTranscript, RootCircuit},
+    root_circuit::{compile, BatchCircuit, Config, Gwc, PoseidonTranscript, RootCircuit, Snark},
     super_circuit::{test::block_1tx, SuperCircuit},
+    util::SubCircuit,
+    witness::block_convert_batch,
 };
 use bus_mapping::circuit_input_builder::FixedCParams;
+use eth_types::{Word, H256};
 use halo2_proofs::{
     circuit::Value,
     dev::MockProver,
-    halo2curves::bn256::Bn256,
+    halo2curves::bn256::{Bn256, Fr},
     plonk::{create_proof, keygen_pk, keygen_vk},
     poly::kzg::{
         commitment::{KZGCommitmentScheme, ParamsKZG},
@@ -26,6 +29,7 @@ fn test_root_circuit() {
             max_txs: 1,
             max_withdrawals: 5,
             max_calldata: 32,
+            max_ecrecover: 0,
             max_rws: 256,
             max_copy_rows: 256,
             max_exp_steps: 256,
@@ -77,3 +81,92 @@ fn test_root_circuit() {
         Ok(())
     );
 }
+
+#[ignore = "Due to high memory requirement"]
+#[test]
+fn test_batch_circuit() {
+    const TEST_MOCK_RANDOMNESS: u64 = 0x100;
+    let circuits_params = FixedCParams {
+        max_txs: 1,
+        max_withdrawals: 5,
+        max_calldata: 32,
+        max_ecrecover: 0,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+    };
+    let build = || {
+        SuperCircuit::<Fr>::build(block_1tx(), circuits_params, TEST_MOCK_RANDOMNESS.into())
+            .unwrap()
+    };
+    let (k, _, _, mut builder_a) = build();
+    let (_, _, _, mut builder_b) = build();
+
+    // Chain the second block to the first one
+    builder_a.block.eth_block.hash = Some(H256::repeat_byte(0xaa));
+    builder_a.block.eth_block.state_root = H256::repeat_byte(0x11);
+    builder_b.block.history_hashes = vec![Word::from_big_endian(&[0xaa; 32])];
+    builder_b.block.prev_state_root = Word::from_big_endian(&[0x11; 32]);
+
+    let (circuits, instances): (Vec<_>, Vec<_>) = block_convert_batch(&[builder_a, builder_b])
+        .unwrap()
+        .into_iter()
+        .map(|mut block| {
+            block.randomness = TEST_MOCK_RANDOMNESS.into();
+            let circuit = SuperCircuit::new_from_block(&block);
+            let instance = circuit.instance();
+            (circuit, instance)
+        })
+        .unzip();
+
+    let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+    let pk = keygen_pk(
+        &params,
+        keygen_vk(&params, &circuits[0]).unwrap(),
+        &circuits[0],
+    )
+    .unwrap();
+    let protocol = compile(
+        &params,
+        pk.get_vk(),
+        Config::kzg()
+            .with_num_instance(instances[0].iter().map(|instance| instance.len()).collect()),
+    );
+
+    // Create proofs
+    let proofs = circuits
+        .into_iter()
+        .zip(instances.iter())
+        .map(|(circuit, instance)| {
+            let mut transcript = PoseidonTranscript::new(Vec::new());
+            create_proof::<KZGCommitmentScheme<_>, ProverGWC<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit],
+                &[&instance.iter().map(Vec::as_slice).collect_vec()],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        })
+        .collect_vec();
+
+    let batch_circuit = BatchCircuit::<Bn256, Gwc<_>>::new(
+        &params,
+        instances
+            .iter()
+            .zip(proofs.iter())
+            .map(|(instance, proof)| Snark::new(&protocol, instance, proof)),
+    )
+    .unwrap();
+    assert_eq!(
+        MockProver::run(26, &batch_circuit, batch_circuit.instance())
+            .unwrap()
+            .verify(),
+        Ok(())
+    );
+}
@@ -8,6 +8,8 @@ mod param;
 #[cfg(any(test, feature = "test-circuits"))]
 mod dev;
 #[cfg(test)]
+pub(crate) mod mutation;
+#[cfg(test)]
 mod test;
 use bus_mapping::operation::Target;
 #[cfg(feature = "test-circuits")]
@@ -61,6 +63,9 @@ pub struct StateCircuitConfig<F> {
     // new value are zero. Will do lookup for MPTProofType::StorageDoesNotExist if
     // non-existing, otherwise do lookup for MPTProofType::StorageChanged.
     is_non_exist: BatchedIsZeroConfig,
+    // For Rw::Account CodeHash, identify a destructed account if the new value is zero and the
+    // committed value is not.  Will do lookup for MPTProofType::AccountDestructed in that case.
+    value_is_zero: BatchedIsZeroConfig,
     // Intermediary witness used to reduce mpt lookup expression degree
     mpt_proof_type: Column<Advice>,
     state_root: WordLoHi<Column<Advice>>,
@@ -133,6 +138,17 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
                 ]
             },
         );
+        let value_is_zero = BatchedIsZeroChip::configure(
+            meta,
+            (FirstPhase, FirstPhase),
+            |meta| meta.query_fixed(selector, Rotation::cur()),
+            |meta| {
+                [
+                    meta.query_advice(rw_table.value.lo(), Rotation::cur()),
+                    meta.query_advice(rw_table.value.hi(), Rotation::cur()),
+                ]
+            },
+        );
         let mpt_proof_type = meta.advice_column_in(SecondPhase);
         let state_root = WordLoHi::new([meta.advice_column(), meta.advice_column()]);
 
@@ -161,6 +177,7 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
             sort_keys,
             initial_value,
             is_non_exist,
+            value_is_zero,
             mpt_proof_type,
             state_root,
             lexicographic_ordering,
@@ -214,15 +231,17 @@ impl<F: Field> StateCircuitConfig<F> {
     ) -> Result<(), Error> {
         let tag_chip = BinaryNumberChip::construct(self.sort_keys.tag);
 
-        let (rows, padding_length) = RwMap::table_assignments_prepad(rows, n_rows);
-        let rows_len = rows.len();
+        let (rows, padding_length) = RwMap::table_assignments_prepad_iter(rows, n_rows);
+        let mut rows = rows.enumerate().peekable();
+        let mut prev_row: Option<Rw> = None;
 
         let mut state_root = updates.old_root();
 
         // annotate columns
         self.annotate_circuit_in_region(region);
 
-        for (offset, row) in rows.iter().enumerate() {
+        while let Some((offset, row)) = rows.next() {
+            let row = &row;
             if offset >= padding_length {
                 log::trace!("state circuit assign offset:{} row:{:#?}", offset, row);
             }
@@ -254,8 +273,7 @@ impl<F: Field> StateCircuitConfig<F> {
                     .assign(region, offset, storage_key)?;
             }
 
-            if offset > 0 {
-                let prev_row = &rows[offset - 1];
+            if let Some(prev_row) = &prev_row {
                 let index = self
                     .lexicographic_ordering
                     .assign(region, offset, row, prev_row)?;
@@ -320,6 +338,11 @@ impl<F: Field> StateCircuitConfig<F> {
                     value.hi(),
                 ]),
             )?;
+            BatchedIsZeroChip::construct(self.value_is_zero.clone()).assign(
+                region,
+                offset,
+                Value::known([value.lo(), value.hi()]),
+            )?;
 
             let mpt_proof_type = match row {
                 Rw::AccountStorage { .. } => {
@@ -335,6 +358,10 @@ impl<F: Field> StateCircuitConfig<F> {
                         && matches!(field_tag, AccountFieldTag::CodeHash)
                     {
                         MPTProofType::AccountDoesNotExist as u64
+                    } else if value.is_zero_vartime()
+                        && matches!(field_tag, AccountFieldTag::CodeHash)
+                    {
+                        MPTProofType::AccountDestructed as u64
                     } else {
                         *field_tag as u64
                     }
@@ -361,7 +388,7 @@ impl<F: Field> StateCircuitConfig<F> {
                 )?;
             }
 
-            if offset == rows_len - 1 {
+            if rows.peek().is_none() {
                 // The last row is always a last access, so we need to handle the case where the
                 // state root changes because of an mpt lookup on the last row.
                 if let Some(update) = updates.get(row) {
@@ -378,6 +405,7 @@ impl<F: Field> StateCircuitConfig<F> {
                     offset,
                 )?;
             }
+            prev_row = Some(*row);
         }
 
         Ok(())
@@ -388,6 +416,8 @@ impl<F: Field> StateCircuitConfig<F> {
         self.mpt_table.annotate_columns_in_region(region);
         self.is_non_exist
             .annotate_columns_in_region(region, "STATE");
+        self.value_is_zero
+            .annotate_columns_in_region(region, "STATE_value");
         self.lexicographic_ordering
             .annotate_columns_in_region(region, "STATE");
         self.sort_keys.annotate_columns_in_region(region, "STATE");
@@ -439,7 +469,7 @@ pub struct StateCircuit<F> {
 impl<F: Field> StateCircuit<F> {
     /// make a new state circuit from an RwMap
     pub fn new(rw_map: RwMap, n_rows: usize) -> Self {
-        let rows = rw_map.table_assignments();
+        let rows = rw_map.into_table_assignments();
         let updates = MptUpdates::mock_from(&rows);
         Self {
             rows,
@@ -608,6 +638,7 @@ fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>)
         initial_value: meta_query_word(meta, c.initial_value, Rotation::cur()),
         initial_value_prev: meta_query_word(meta, c.initial_value, Rotation::prev()),
         is_non_exist: meta.query_advice(c.is_non_exist.is_zero, Rotation::cur()),
+        value_is_zero: meta.query_advice(c.value_is_zero.is_zero, Rotation::cur()),
         mpt_proof_type: meta.query_advice(c.mpt_proof_type, Rotation::cur()),
         lookups: LookupsQueries::new(meta, c.lookups),
         first_different_limb: [0, 1, 2, 3]
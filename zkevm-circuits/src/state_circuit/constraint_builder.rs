@@ -56,6 +56,7 @@ pub struct Queries<F: Field> {
     pub initial_value: WordLoHi<Expression<F>>,
     pub initial_value_prev: WordLoHi<Expression<F>>,
     pub is_non_exist: Expression<F>,
+    pub value_is_zero: Expression<F>,
     pub mpt_proof_type: Expression<F>,
     pub lookups: LookupsQueries<F>,
     pub first_different_limb: [Expression<F>; 4],
@@ -146,6 +147,9 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(Target::TxLog), |cb| {
             cb.build_tx_log_constraints(q)
         });
+        self.condition(q.tag_matches(Target::TxDestructedAccount), |cb| {
+            cb.build_tx_destructed_account_constraints(q)
+        });
     }
 
     fn build_general_constraints(&mut self, q: &Queries<F>) {
@@ -372,6 +376,42 @@ impl<F: Field> ConstraintBuilder<F> {
         });
     }
 
+    fn build_tx_destructed_account_constraints(&mut self, q: &Queries<F>) {
+        self.require_zero("field_tag is 0 for TxDestructedAccount", q.field_tag());
+        self.require_word_zero(
+            "storage_key is 0 for TxDestructedAccount",
+            q.rw_table.storage_key.clone(),
+        );
+        self.require_word_boolean("TxDestructedAccount value is boolean", q.value());
+        self.require_word_zero(
+            "initial TxDestructedAccount value is false",
+            q.initial_value(),
+        );
+
+        self.require_word_equal(
+            "state_root is unchanged for TxDestructedAccount",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_word_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+
+        // The EndTx step clears the mark of each account it deletes, so an account marked by a
+        // SELFDESTRUCT that isn't reverted can't be left out of the deletions.
+        self.condition(q.last_access(), |cb| {
+            cb.require_word_zero(
+                "TxDestructedAccount value is false at the last access",
+                q.value(),
+            );
+        });
+    }
+
     fn build_tx_access_list_account_storage_constraints(&mut self, q: &Queries<F>) {
         self.require_zero(
             "field_tag is 0 for TxAccessListAccountStorage",
@@ -438,28 +478,33 @@ impl<F: Field> ConstraintBuilder<F> {
         );
 
         // We use code_hash = 0 as non-existing account state.  code_hash: 0->0
-        // transition requires a non-existing proof.
+        // transition requires a non-existing proof, and code_hash: X->0 with X != 0 requires
+        // a destructed account proof.
+        // generate_lagrange_base_polynomial() degree = 3
+        let is_code_hash = generate_lagrange_base_polynomial(
+            q.field_tag(),
+            AccountFieldTag::CodeHash as usize,
+            [
+                AccountFieldTag::Nonce,
+                AccountFieldTag::Balance,
+                AccountFieldTag::CodeHash,
+            ]
+            .iter()
+            .map(|t| *t as usize),
+        );
         // is_non_exist degree = 4
         //   q.is_non_exist() degree = 1
-        //   generate_lagrange_base_polynomial() degree = 3
-        let is_non_exist = q.is_non_exist()
-            * generate_lagrange_base_polynomial(
-                q.field_tag(),
-                AccountFieldTag::CodeHash as usize,
-                [
-                    AccountFieldTag::Nonce,
-                    AccountFieldTag::Balance,
-                    AccountFieldTag::CodeHash,
-                ]
-                .iter()
-                .map(|t| *t as usize),
-            );
+        let is_non_exist = q.is_non_exist() * is_code_hash.expr();
+        // is_destructed degree = 4
+        //   q.value_is_zero() - q.is_non_exist() degree = 1
+        let is_destructed = (q.value_is_zero() - q.is_non_exist()) * is_code_hash;
         self.require_equal(
-            "mpt_proof_type is field_tag or AccountDoesNotExist",
+            "mpt_proof_type is field_tag, AccountDoesNotExist or AccountDestructed",
             q.mpt_proof_type(),
-            // degree = max(4, 4 + 1) = 5
+            // degree = max(4, 4, 4 + 1) = 5
             is_non_exist.expr() * MPTProofType::AccountDoesNotExist.expr()
-                + (1.expr() - is_non_exist) * q.field_tag(),
+                + is_destructed.expr() * MPTProofType::AccountDestructed.expr()
+                + (1.expr() - is_non_exist - is_destructed) * q.field_tag(),
         );
 
         // last_access degree = 1
@@ -623,6 +668,10 @@ impl<F: Field> Queries<F> {
         self.is_non_exist.clone()
     }
 
+    fn value_is_zero(&self) -> Expression<F> {
+        self.value_is_zero.clone()
+    }
+
     fn mpt_proof_type(&self) -> Expression<F> {
         self.mpt_proof_type.clone()
     }
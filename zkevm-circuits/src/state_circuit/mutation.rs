@@ -0,0 +1,138 @@
+//! Mutations of valid rw tables that the state circuit must reject.
+//!
+//! Each [`RwMutation`] turns the rows of a valid rw table into rows that break one of the
+//! properties of the table: its lexicographic ordering, or the consistency of the reads with the
+//! previous accesses.  [`assert_mutations_rejected`] applies all the mutations that make sense for
+//! the rows and reports every mutation that the `MockProver` still accepts, since it shows a
+//! missing constraint.
+
+use super::{dev::AdviceColumn, StateCircuit};
+use crate::witness::{MptUpdates, Rw};
+use eth_types::{Address, ToScalar, Word};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+};
+use std::collections::HashMap;
+
+/// Rows of the rw table used for the mutation runs.  The tables have 2^16 rows because of the
+/// u16 lookup table.
+const N_ROWS: usize = 1 << 16;
+
+/// Mutation of the rows of a valid rw table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RwMutation {
+    /// Swap the rows at `index` and `index + 1`, which have different sort keys
+    SwapRows(usize),
+    /// Insert a copy of the row at `index` after it
+    DuplicateRow(usize),
+    /// Add 1 to the low limb of the value of the read at `index`
+    TamperReadValue(usize),
+}
+
+/// Key of the lexicographic ordering of the rows, like in `RwMap::table_assignments`.
+fn sort_key(row: &Rw) -> (u64, usize, Address, u64, Word, usize) {
+    (
+        row.tag() as u64,
+        row.id().unwrap_or_default(),
+        row.address().unwrap_or_default(),
+        row.field_tag().unwrap_or_default(),
+        row.storage_key().unwrap_or_default(),
+        row.rw_counter(),
+    )
+}
+
+impl RwMutation {
+    /// All the mutations of `rows` that must be rejected.
+    pub(crate) fn all(rows: &[Rw]) -> Vec<Self> {
+        let swaps = rows
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| sort_key(&pair[0]) != sort_key(&pair[1]))
+            .map(|(index, _)| Self::SwapRows(index));
+        let duplicates = (0..rows.len()).map(Self::DuplicateRow);
+        let reads = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| !row.is_write())
+            .map(|(index, _)| Self::TamperReadValue(index));
+        swaps.chain(duplicates).chain(reads).collect()
+    }
+
+    /// Apply the mutation to `rows`, returning the mutated rows and the overrides of the advice
+    /// columns.
+    fn apply(&self, rows: &[Rw]) -> (Vec<Rw>, HashMap<(AdviceColumn, isize), Fr>) {
+        let mut rows = rows.to_vec();
+        let mut overrides = HashMap::new();
+        match *self {
+            Self::SwapRows(index) => rows.swap(index, index + 1),
+            Self::DuplicateRow(index) => rows.insert(index + 1, rows[index]),
+            Self::TamperReadValue(index) => {
+                let value_lo: Fr = (rows[index].value_assignment() & Word::from(u128::MAX))
+                    .to_scalar()
+                    .expect("the low limb fits into a scalar");
+                overrides.insert(
+                    (AdviceColumn::ValueLo, index as isize),
+                    value_lo + Fr::from(1),
+                );
+            }
+        }
+        (rows, overrides)
+    }
+}
+
+/// Verify the rows, with the overrides, in the state circuit.
+fn verify(
+    rows: Vec<Rw>,
+    overrides: HashMap<(AdviceColumn, isize), Fr>,
+) -> Result<(), Vec<VerifyFailure>> {
+    let n_active_rows = rows.len();
+    let updates = MptUpdates::mock_from(&rows);
+    let circuit = StateCircuit::<Fr> {
+        rows,
+        updates,
+        overrides,
+        n_rows: N_ROWS,
+        _marker: std::marker::PhantomData,
+    };
+    let instance = circuit.instance();
+    MockProver::<Fr>::run(17, &circuit, instance)
+        .unwrap()
+        .verify_at_rows(
+            N_ROWS - n_active_rows..N_ROWS,
+            N_ROWS - n_active_rows..N_ROWS,
+        )
+}
+
+/// Check that the state circuit accepts `rows` and rejects all their [`RwMutation::all`].  The
+/// `Start` rows are dropped, since the circuit pads the table with its own.
+///
+/// # Panics
+///
+/// If `rows` aren't valid, or with the list of the mutations that are still accepted.
+pub(crate) fn assert_mutations_rejected(rows: &[Rw]) {
+    let rows = rows
+        .iter()
+        .filter(|row| !matches!(row, Rw::Start { .. }))
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        verify(rows.clone(), HashMap::new()),
+        Ok(()),
+        "the rows to mutate aren't valid"
+    );
+
+    let accepted = RwMutation::all(&rows)
+        .into_iter()
+        .filter(|mutation| {
+            let (rows, overrides) = mutation.apply(&rows);
+            verify(rows, overrides).is_ok()
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        accepted.is_empty(),
+        "the state circuit accepts the mutations {:?} of the rows {:?}",
+        accepted,
+        rows
+    );
+}
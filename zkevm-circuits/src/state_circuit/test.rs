@@ -1,6 +1,7 @@
 pub use super::{dev::*, *};
 use crate::{
     table::{AccountFieldTag, CallContextFieldTag, TxLogFieldTag, TxReceiptFieldTag},
+    test_util::CircuitTestBuilder,
     util::{unusable_rows, SubCircuit},
     witness::{MptUpdates, Rw, RwMap},
 };
@@ -8,7 +9,7 @@ use bus_mapping::operation::{
     MemoryOp, Operation, OperationContainer, RWCounter, StackOp, StorageOp, RW,
 };
 use eth_types::{
-    address,
+    address, bytecode,
     evm_types::{MemoryAddress, StackAddress},
     Address, ToAddress, Word, U256,
 };
@@ -20,6 +21,7 @@ use halo2_proofs::{
     plonk::{keygen_vk, Circuit, ConstraintSystem},
     poly::kzg::commitment::ParamsKZG,
 };
+use mock::TestContext;
 use rand::SeedableRng;
 use std::collections::{BTreeSet, HashMap};
 use strum::IntoEnumIterator;
@@ -851,6 +853,23 @@ fn bad_initial_tx_access_list_account_value() {
     );
 }
 
+#[test]
+fn tx_destructed_account_not_cleared() {
+    let rows = vec![Rw::TxDestructedAccount {
+        rw_counter: 1,
+        is_write: true,
+        tx_id: 1,
+        account_address: address!("0x0000000000000000000000000000000004356002"),
+        is_destructed: true,
+        is_destructed_prev: false,
+    }];
+
+    assert_error_matches(
+        verify(rows),
+        "TxDestructedAccount value is false at the last access",
+    );
+}
+
 #[test]
 fn bad_initial_tx_refund_value() {
     let rows = vec![Rw::TxRefund {
@@ -990,6 +1009,80 @@ fn bad_initial_tx_receipt_value() {
     );
 }
 
+#[test]
+fn mutations_rejected() {
+    let rows = vec![
+        Rw::Memory {
+            rw_counter: 1,
+            is_write: true,
+            call_id: 1,
+            memory_address: 10,
+            byte: 12,
+        },
+        Rw::Memory {
+            rw_counter: 2,
+            is_write: false,
+            call_id: 1,
+            memory_address: 10,
+            byte: 12,
+        },
+        Rw::Stack {
+            rw_counter: 3,
+            is_write: true,
+            call_id: 1,
+            stack_pointer: 1023,
+            value: U256::from(5),
+        },
+        Rw::Stack {
+            rw_counter: 4,
+            is_write: false,
+            call_id: 1,
+            stack_pointer: 1023,
+            value: U256::from(5),
+        },
+        Rw::CallContext {
+            rw_counter: 5,
+            is_write: false,
+            call_id: 1,
+            field_tag: CallContextFieldTag::IsSuccess,
+            value: U256::zero(),
+        },
+    ];
+
+    super::mutation::assert_mutations_rejected(&rows);
+}
+
+// This runs the MockProver for every mutation of the rw tables of the blocks, which takes a few
+// minutes.
+#[ignore]
+#[test]
+fn mutations_rejected_corpus() {
+    let codes = [
+        bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+            MSTORE
+            PUSH1(0)
+            MLOAD
+            STOP
+        },
+        bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+            PUSH1(0)
+            SLOAD
+            STOP
+        },
+    ];
+    for code in codes {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .assert_state_mutations_rejected();
+    }
+}
+
 fn prover(rows: Vec<Rw>, overrides: HashMap<(AdviceColumn, isize), Fr>) -> MockProver<Fr> {
     let updates = MptUpdates::mock_from(&rows);
     let circuit = StateCircuit::<Fr> {
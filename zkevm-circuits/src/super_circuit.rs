@@ -16,6 +16,7 @@
 //! - [ ] Keccak Circuit
 //! - [ ] MPT Circuit
 //! - [x] PublicInputs Circuit
+//! - [x] Withdrawal Circuit
 //!
 //! And the following shared tables, with the circuits that use them:
 //!
@@ -28,6 +29,10 @@
 //!   - [ ] State Circuit
 //!   - [ ] EVM Circuit
 //!   - [ ] Copy Circuit
+//!   - [ ] Withdrawal Circuit
+//! - [x] Withdrawal Table
+//!   - [x] PublicInputs Circuit
+//!   - [x] Withdrawal Circuit
 //! - [x] Tx Table
 //!   - [x] Tx Circuit
 //!   - [x] EVM Circuit
@@ -55,6 +60,7 @@ pub(crate) mod test;
 
 use crate::{
     bytecode_circuit::{BytecodeCircuit, BytecodeCircuitConfig, BytecodeCircuitConfigArgs},
+    capacity_stats,
     copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
     evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs},
     exp_circuit::{ExpCircuit, ExpCircuitConfig},
@@ -62,11 +68,12 @@ use crate::{
     pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
-        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, RwTable, TxTable,
-        UXTable, WdTable,
+        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, RwTable, SigTable,
+        TxTable, UXTable, WdCreditTable, WdTable,
     },
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
     util::{log2_ceil, Challenges, SubCircuit, SubCircuitConfig},
+    withdrawal_circuit::{WithdrawalCircuit, WithdrawalCircuitConfig, WithdrawalCircuitConfigArgs},
     witness::{block_convert, Block, MptUpdates},
 };
 use bus_mapping::{
@@ -79,7 +86,7 @@ use halo2_proofs::{
     plonk::{Circuit, ConstraintSystem, Error, Expression},
 };
 
-use std::array;
+use std::{array, fmt};
 
 /// Configuration of the Super Circuit
 #[derive(Clone)]
@@ -89,7 +96,7 @@ pub struct SuperCircuitConfig<F: Field> {
     u8_table: UXTable<8>,
     u10_table: UXTable<10>,
     u16_table: UXTable<16>,
-    evm_circuit: EvmCircuitConfig<F>,
+    pub(crate) evm_circuit: EvmCircuitConfig<F>,
     state_circuit: StateCircuitConfig<F>,
     tx_circuit: TxCircuitConfig<F>,
     bytecode_circuit: BytecodeCircuitConfig<F>,
@@ -97,6 +104,7 @@ pub struct SuperCircuitConfig<F: Field> {
     keccak_circuit: KeccakCircuitConfig<F>,
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
+    withdrawal_circuit: WithdrawalCircuitConfig<F>,
 }
 
 impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
@@ -115,6 +123,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
     ) -> Self {
         let tx_table = TxTable::construct(meta);
         let wd_table = WdTable::construct(meta);
+        let wd_credit_table = WdCreditTable::construct(meta);
         let rw_table = RwTable::construct(meta);
         let mpt_table = MptTable::construct(meta);
         let bytecode_table = BytecodeTable::construct(meta);
@@ -123,6 +132,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let exp_table = ExpTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
         let u8_table = UXTable::construct(meta);
         let u10_table = UXTable::construct(meta);
         let u16_table = UXTable::construct(meta);
@@ -153,7 +163,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 max_calldata,
                 block_table: block_table.clone(),
                 tx_table: tx_table.clone(),
-                wd_table,
+                wd_table: wd_table.clone(),
                 keccak_table: keccak_table.clone(),
                 challenges: challenges.clone(),
             },
@@ -163,6 +173,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             TxCircuitConfigArgs {
                 tx_table: tx_table.clone(),
                 keccak_table: keccak_table.clone(),
+                sig_table: sig_table.clone(),
                 challenges: challenges.clone(),
             },
         );
@@ -197,6 +208,14 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             },
         );
         let exp_circuit = ExpCircuitConfig::new(meta, exp_table);
+        let withdrawal_circuit = WithdrawalCircuitConfig::new(
+            meta,
+            WithdrawalCircuitConfigArgs {
+                wd_table,
+                wd_credit_table: wd_credit_table.clone(),
+                rw_table,
+            },
+        );
         let evm_circuit = EvmCircuitConfig::new(
             meta,
             EvmCircuitConfigArgs {
@@ -208,6 +227,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 copy_table,
                 keccak_table,
                 exp_table,
+                sig_table,
+                wd_credit_table,
                 u8_table,
                 u16_table,
                 feature_config,
@@ -228,6 +249,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             keccak_circuit,
             pi_circuit,
             exp_circuit,
+            withdrawal_circuit,
         }
     }
 }
@@ -251,6 +273,8 @@ pub struct SuperCircuit<F: Field> {
     pub exp_circuit: ExpCircuit<F>,
     /// Keccak Circuit
     pub keccak_circuit: KeccakCircuit<F>,
+    /// Withdrawal Circuit
+    pub withdrawal_circuit: WithdrawalCircuit<F>,
     /// Circuits Parameters
     pub circuits_params: FixedCParams,
     /// Feature Config
@@ -263,8 +287,9 @@ impl<F: Field> SuperCircuit<F> {
     /// Return the number of rows required to verify a given block
     pub fn get_num_rows_required(block: &Block<F>) -> usize {
         let num_rows_evm_circuit = EvmCircuit::<F>::get_num_rows_required(block);
-        let num_rows_tx_circuit =
-            TxCircuitConfig::<F>::get_num_rows_required(block.circuits_params.max_txs);
+        let num_rows_tx_circuit = TxCircuitConfig::<F>::get_num_rows_required(
+            block.circuits_params.max_txs + block.circuits_params.max_ecrecover,
+        );
         num_rows_evm_circuit.max(num_rows_tx_circuit)
     }
 }
@@ -285,6 +310,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             CopyCircuit::<F>::unusable_rows(),
             ExpCircuit::<F>::unusable_rows(),
             KeccakCircuit::<F>::unusable_rows(),
+            WithdrawalCircuit::<F>::unusable_rows(),
         ])
         .unwrap()
     }
@@ -298,6 +324,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         let copy_circuit = CopyCircuit::new_from_block_no_external(block);
         let exp_circuit = ExpCircuit::new_from_block(block);
         let keccak_circuit = KeccakCircuit::new_from_block(block);
+        let withdrawal_circuit = WithdrawalCircuit::new_from_block(block);
 
         SuperCircuit::<_> {
             evm_circuit,
@@ -308,6 +335,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             copy_circuit,
             exp_circuit,
             keccak_circuit,
+            withdrawal_circuit,
             circuits_params: block.circuits_params,
             feature_config: block.feature_config,
             mock_randomness: block.randomness,
@@ -325,24 +353,18 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         instance.extend_from_slice(&self.state_circuit.instance());
         instance.extend_from_slice(&self.exp_circuit.instance());
         instance.extend_from_slice(&self.evm_circuit.instance());
+        instance.extend_from_slice(&self.withdrawal_circuit.instance());
 
         instance
     }
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &Block<F>) -> (usize, usize) {
-        let evm = EvmCircuit::min_num_rows_block(block);
-        let state = StateCircuit::min_num_rows_block(block);
-        let bytecode = BytecodeCircuit::min_num_rows_block(block);
-        let copy = CopyCircuit::min_num_rows_block(block);
-        let keccak = KeccakCircuit::min_num_rows_block(block);
-        let tx = TxCircuit::min_num_rows_block(block);
-        let exp = ExpCircuit::min_num_rows_block(block);
-        let pi = PiCircuit::min_num_rows_block(block);
-
-        let rows: Vec<(usize, usize)> = vec![evm, state, bytecode, copy, keccak, tx, exp, pi];
         let (rows_without_padding, rows_with_padding): (Vec<usize>, Vec<usize>) =
-            rows.into_iter().unzip();
+            Self::min_num_rows_sub_circuits(block)
+                .into_iter()
+                .map(|(_, rows)| rows)
+                .unzip();
         (
             itertools::max(rows_without_padding).unwrap(),
             itertools::max(rows_with_padding).unwrap(),
@@ -372,6 +394,8 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             .synthesize_sub(&config.evm_circuit, challenges, layouter)?;
         self.pi_circuit
             .synthesize_sub(&config.pi_circuit, challenges, layouter)?;
+        self.withdrawal_circuit
+            .synthesize_sub(&config.withdrawal_circuit, challenges, layouter)?;
         Ok(())
     }
 }
@@ -439,7 +463,101 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
     }
 }
 
+/// Sizes of a SuperCircuit selected from the witness of a block, see
+/// [`SuperCircuit::build_auto_sized`].
+#[derive(Debug, Clone)]
+pub struct SuperCircuitSizing {
+    /// Capacity parameters of the sub-circuits, the minimal ones for the block
+    pub circuits_params: FixedCParams,
+    /// Minimum number of rows required by each sub-circuit, without and with the padding to its
+    /// capacity
+    pub rows: Vec<(&'static str, (usize, usize))>,
+    /// Minimal degree of the circuit that fits all the sub-circuits
+    pub min_k: u32,
+    /// Degree of the circuit, which is `min_k` unless overridden
+    pub k: u32,
+}
+
+impl fmt::Display for SuperCircuitSizing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "k = {} (minimal k = {})", self.k, self.min_k)?;
+        writeln!(f, "{:#?}", self.circuits_params)?;
+        writeln!(f, "{:<12} {:>12} {:>12}", "circuit", "rows", "padded rows")?;
+        for (name, (rows, padded_rows)) in &self.rows {
+            writeln!(f, "{:<12} {:>12} {:>12}", name, rows, padded_rows)?;
+        }
+        Ok(())
+    }
+}
+
 impl<F: Field> SuperCircuit<F> {
+    /// Return the minimum number of rows required by each sub-circuit to prove the block, without
+    /// and with the padding to its capacity
+    pub fn min_num_rows_sub_circuits(block: &Block<F>) -> Vec<(&'static str, (usize, usize))> {
+        vec![
+            ("evm", EvmCircuit::min_num_rows_block(block)),
+            ("state", StateCircuit::min_num_rows_block(block)),
+            ("bytecode", BytecodeCircuit::min_num_rows_block(block)),
+            ("copy", CopyCircuit::min_num_rows_block(block)),
+            ("keccak", KeccakCircuit::min_num_rows_block(block)),
+            ("tx", TxCircuit::min_num_rows_block(block)),
+            ("exp", ExpCircuit::min_num_rows_block(block)),
+            ("pi", PiCircuit::min_num_rows_block(block)),
+            ("withdrawal", WithdrawalCircuit::min_num_rows_block(block)),
+        ]
+    }
+
+    /// From the witness data, generate a SuperCircuit instance whose sub-circuits capacities are
+    /// the minimal ones for the block, computed from its number of txs, calldata bytes, rw
+    /// operations, copied bytes, exponentiation steps and bytecode bytes.
+    ///
+    /// The degree of the circuit is the minimal one that fits all the sub-circuits, unless it is
+    /// overridden by `k`, which fails if it is below the minimal one.  Also, return with it the
+    /// chosen sizes and the Public Inputs needed.
+    #[allow(clippy::type_complexity)]
+    pub fn build_auto_sized(
+        geth_data: GethData,
+        k: Option<u32>,
+        mock_randomness: F,
+    ) -> Result<
+        (
+            SuperCircuitSizing,
+            Self,
+            Vec<Vec<F>>,
+            CircuitInputBuilder<FixedCParams>,
+        ),
+        bus_mapping::Error,
+    > {
+        let builder = BlockData::new_from_geth_data(geth_data.clone())
+            .new_circuit_input_builder()
+            .handle_block(&geth_data.eth_block, &geth_data.geth_traces)?;
+        let mut block = block_convert(&builder)?;
+        block.randomness = mock_randomness;
+
+        let (_, rows_needed) = Self::min_num_rows_block(&block);
+        let min_k = log2_ceil(Self::unusable_rows() + rows_needed);
+        let k = match k {
+            Some(k) if k < min_k => {
+                return Err(bus_mapping::Error::InternalError(
+                    "degree is below the minimal degree of the block",
+                ))
+            }
+            Some(k) => k,
+            None => min_k,
+        };
+        log::debug!("super circuit uses k = {} (minimal k = {})", k, min_k);
+        let sizing = SuperCircuitSizing {
+            circuits_params: builder.circuits_params,
+            rows: Self::min_num_rows_sub_circuits(&block),
+            min_k,
+            k,
+        };
+
+        let circuit = SuperCircuit::new_from_block(&block);
+        let instance = circuit.instance();
+        Ok((sizing, circuit, instance, builder))
+    }
+
     /// From the witness data, generate a SuperCircuit instance with all of the
     /// sub-circuits filled with their corresponding witnesses.
     ///
@@ -474,6 +592,7 @@ impl<F: Field> SuperCircuit<F> {
     ) -> Result<(u32, Self, Vec<Vec<F>>), bus_mapping::Error> {
         let mut block = block_convert(builder).unwrap();
         block.randomness = mock_randomness;
+        capacity_stats::record_from_env(&block);
 
         let (_, rows_needed) = Self::min_num_rows_block(&block);
         let k = log2_ceil(Self::unusable_rows() + rows_needed);
@@ -134,6 +134,7 @@ fn serial_test_super_circuit_1tx_1max_tx() {
         max_txs: 1,
         max_withdrawals: 5,
         max_calldata: 32,
+        max_ecrecover: 0,
         max_rws: 256,
         max_copy_rows: 256,
         max_exp_steps: 256,
@@ -151,6 +152,7 @@ fn serial_test_super_circuit_1tx_2max_tx() {
         max_txs: 2,
         max_withdrawals: 5,
         max_calldata: 32,
+        max_ecrecover: 0,
         max_rws: 256,
         max_copy_rows: 256,
         max_exp_steps: 256,
@@ -168,6 +170,7 @@ fn serial_test_super_circuit_2tx_2max_tx() {
         max_txs: 2,
         max_withdrawals: 5,
         max_calldata: 32,
+        max_ecrecover: 0,
         max_rws: 256,
         max_copy_rows: 256,
         max_exp_steps: 256,
@@ -177,3 +180,42 @@ fn serial_test_super_circuit_2tx_2max_tx() {
     };
     test_super_circuit(block, circuits_params, Fr::from(TEST_MOCK_RANDOMNESS));
 }
+
+#[test]
+fn super_circuit_auto_sized() {
+    let mock_randomness = Fr::from(TEST_MOCK_RANDOMNESS);
+    let (sizing, _, _, builder) =
+        SuperCircuit::<Fr>::build_auto_sized(block_1tx(), None, mock_randomness).unwrap();
+    assert_eq!(sizing.k, sizing.min_k);
+    assert_eq!(sizing.circuits_params.max_txs, 1);
+    assert_eq!(
+        sizing.circuits_params.max_calldata,
+        builder.block.txs()[0].call_data.len()
+    );
+    assert_eq!(sizing.rows.len(), 9);
+
+    let (sizing, _, _, _) =
+        SuperCircuit::<Fr>::build_auto_sized(block_1tx(), Some(sizing.min_k + 1), mock_randomness)
+            .unwrap();
+    assert_eq!(sizing.k, sizing.min_k + 1);
+    assert!(SuperCircuit::<Fr>::build_auto_sized(
+        block_1tx(),
+        Some(sizing.min_k - 1),
+        mock_randomness
+    )
+    .is_err());
+}
+
+#[ignore]
+#[test]
+fn serial_test_super_circuit_1tx_auto_sized() {
+    let (sizing, circuit, instance, _) =
+        SuperCircuit::<Fr>::build_auto_sized(block_1tx(), None, Fr::from(TEST_MOCK_RANDOMNESS))
+            .unwrap();
+    let prover = MockProver::run(sizing.k, &circuit, instance).unwrap();
+    let res = prover.verify();
+    if let Err(err) = res {
+        error!("Verification failures: {:#?}", err);
+        panic!("Failed verification");
+    }
+}
@@ -1,7 +1,6 @@
 //! Table definitions used cross-circuits
 
 use crate::{
-    copy_circuit::util::number_or_hash_to_word,
     evm_circuit::util::rlc,
     impl_expr,
     util::{build_tx_log_address, keccak, word::WordLoHi, Challenges},
@@ -37,10 +36,14 @@ pub(crate) mod keccak_table;
 pub mod mpt_table;
 /// rw table
 pub(crate) mod rw_table;
+/// sig table
+pub(crate) mod sig_table;
 /// tx table
 pub(crate) mod tx_table;
 /// ux table
 pub(crate) mod ux_table;
+/// withdrawal credit table
+pub(crate) mod wd_credit_table;
 /// withdrawal table
 pub(crate) mod wd_table;
 
@@ -53,9 +56,11 @@ pub(crate) use ux_table::UXTable;
 
 pub use mpt_table::{MPTProofType, MptTable};
 pub(crate) use rw_table::RwTable;
+pub(crate) use sig_table::SigTable;
 pub(crate) use tx_table::{
     TxContextFieldTag, TxFieldTag, TxLogFieldTag, TxReceiptFieldTag, TxTable,
 };
+pub(crate) use wd_credit_table::WdCreditTable;
 pub(crate) use wd_table::WdTable;
 
 /// Trait used to define lookup tables
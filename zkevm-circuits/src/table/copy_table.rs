@@ -1,8 +1,17 @@
 use super::*;
+use bus_mapping::circuit_input_builder::NumberOrHash;
 
 type CopyTableRow<F> = [(Value<F>, &'static str); 9];
 type CopyCircuitRow<F> = [(Value<F>, &'static str); 5];
 
+/// Encode the type `NumberOrHash` into a field element
+pub fn number_or_hash_to_word<F: Field>(v: &NumberOrHash) -> WordLoHi<Value<F>> {
+    match v {
+        NumberOrHash::Number(n) => WordLoHi::from(*n as u64).into_value(),
+        NumberOrHash::Hash(h) => WordLoHi::from(*h).into_value(),
+    }
+}
+
 /// Copy Table, used to verify copies of byte chunks between Memory, Bytecode,
 /// TxLogs and TxCallData.
 #[derive(Clone, Copy, Debug)]
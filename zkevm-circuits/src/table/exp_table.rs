@@ -1,12 +1,14 @@
 use super::*;
 
-use crate::{
-    exp_circuit::param::{OFFSET_INCREMENT, ROWS_PER_STEP},
-    table::LookupTable,
-    witness::Block,
-};
+use crate::{table::LookupTable, witness::Block};
 use bus_mapping::circuit_input_builder::ExpEvent;
 
+/// The number of rows assigned for each step in an exponentiation trace.
+pub(crate) const OFFSET_INCREMENT: usize = 7usize;
+/// The number of rows required for the exponentiation table within the circuit
+/// for each step.
+pub(crate) const ROWS_PER_STEP: usize = 4usize;
+
 /// Lookup table within the Exponentiation circuit.
 #[derive(Clone, Copy, Debug)]
 pub struct ExpTable {
@@ -129,8 +129,8 @@ impl RwTable {
         rws: &[Rw],
         n_rows: usize,
     ) -> Result<(), Error> {
-        let (rows, _) = RwMap::table_assignments_prepad(rws, n_rows);
-        for (offset, row) in rows.iter().enumerate() {
+        let (rows, _) = RwMap::table_assignments_prepad_iter(rws, n_rows);
+        for (offset, row) in rows.enumerate() {
             self.assign(region, offset, &row.table_assignment())?;
         }
         Ok(())
@@ -0,0 +1,152 @@
+use eth_types::{
+    keccak256,
+    sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
+    Address,
+};
+use halo2_proofs::halo2curves::group::ff::PrimeField;
+
+use super::*;
+
+/// Table of the secp256k1 signatures verified by the ecRecover calls of a block, with the address
+/// recovered from each of them.  In the SuperCircuit its rows are copied from the signature
+/// verifications of the Tx Circuit.
+///
+/// The Tx Circuit only checks the recovered address against the public key when the address isn't
+/// zero, so a row with a zero address doesn't prove anything about it: lookups must require a
+/// non-zero address.
+#[derive(Clone, Debug)]
+pub struct SigTable {
+    /// True when the row holds a verified signature
+    pub q_enable: Column<Fixed>,
+    /// Message hash, reduced modulo the secp256k1 scalar field order
+    pub msg_hash: WordLoHi<Column<Advice>>,
+    /// Recovery id: the parity of the y coordinate of the public key
+    pub sig_v: Column<Advice>,
+    /// Signature r
+    pub sig_r: WordLoHi<Column<Advice>>,
+    /// Signature s
+    pub sig_s: WordLoHi<Column<Advice>>,
+    /// Address of the public key that verifies the signature
+    pub recovered_addr: WordLoHi<Column<Advice>>,
+}
+
+impl SigTable {
+    /// Construct a new SigTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            msg_hash: WordLoHi::new([meta.advice_column(), meta.advice_column()]),
+            sig_v: meta.advice_column(),
+            sig_r: WordLoHi::new([meta.advice_column(), meta.advice_column()]),
+            sig_s: WordLoHi::new([meta.advice_column(), meta.advice_column()]),
+            recovered_addr: WordLoHi::new([meta.advice_column(), meta.advice_column()]),
+        }
+    }
+
+    /// Generate the sig table row of a verified signature, in the advice columns order.
+    pub fn assignments<F: Field>(sign_data: &SignData) -> [Value<F>; 9] {
+        let pk_be = pk_bytes_swap_endianness(&pk_bytes_le(&sign_data.pk));
+        let recovered_addr = Address::from_slice(&keccak256(pk_be)[12..]);
+        let [msg_hash, sig_r, sig_s] = [
+            sign_data.msg_hash,
+            sign_data.signature.0,
+            sign_data.signature.1,
+        ]
+        .map(|fq| WordLoHi::from(U256::from_little_endian(&fq.to_repr())));
+        let recovered_addr = WordLoHi::from(recovered_addr);
+        let sig_v = F::from((sign_data.pk.y.to_repr()[0] & 1) as u64);
+
+        [msg_hash.lo(), msg_hash.hi(), sig_v]
+            .into_iter()
+            .chain(
+                [sig_r, sig_s, recovered_addr]
+                    .into_iter()
+                    .flat_map(|word| [word.lo(), word.hi()]),
+            )
+            .map(Value::known)
+            .collect_vec()
+            .try_into()
+            .unwrap()
+    }
+
+    /// Provide this function for the case that we want to consume a sig table without running
+    /// the Tx Circuit
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        sign_datas: &[SignData],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sig table",
+            |mut region| {
+                let advice_columns = <SigTable as LookupTable<F>>::advice_columns(self);
+                for (offset, sign_data) in sign_datas.iter().enumerate() {
+                    region.assign_fixed(
+                        || format!("sig table q_enable {}", offset),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::ONE),
+                    )?;
+                    for (&column, value) in
+                        advice_columns.iter().zip_eq(Self::assignments(sign_data))
+                    {
+                        region.assign_advice(
+                            || format!("sig table row {}", offset),
+                            column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: Field> LookupTable<F> for SigTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.msg_hash.lo().into(),
+            self.msg_hash.hi().into(),
+            self.sig_v.into(),
+            self.sig_r.lo().into(),
+            self.sig_r.hi().into(),
+            self.sig_s.lo().into(),
+            self.sig_s.hi().into(),
+            self.recovered_addr.lo().into(),
+            self.recovered_addr.hi().into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("msg_hash_lo"),
+            String::from("msg_hash_hi"),
+            String::from("sig_v"),
+            String::from("sig_r_lo"),
+            String::from("sig_r_hi"),
+            String::from("sig_s_lo"),
+            String::from("sig_s_hi"),
+            String::from("recovered_addr_lo"),
+            String::from("recovered_addr_hi"),
+        ]
+    }
+
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        vec![
+            meta.query_fixed(self.q_enable, Rotation::cur()),
+            meta.query_advice(self.msg_hash.lo(), Rotation::cur()),
+            meta.query_advice(self.msg_hash.hi(), Rotation::cur()),
+            meta.query_advice(self.sig_v, Rotation::cur()),
+            meta.query_advice(self.sig_r.lo(), Rotation::cur()),
+            meta.query_advice(self.sig_r.hi(), Rotation::cur()),
+            meta.query_advice(self.sig_s.lo(), Rotation::cur()),
+            meta.query_advice(self.sig_s.hi(), Rotation::cur()),
+            meta.query_advice(self.recovered_addr.lo(), Rotation::cur()),
+            meta.query_advice(self.recovered_addr.hi(), Rotation::cur()),
+        ]
+    }
+}
@@ -1,4 +1,8 @@
+use eth_types::{geth_types, Address, ToWord, Word};
+use std::iter;
+
 use super::*;
+use crate::evm_circuit::param::{MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS};
 
 /// Tag used to identify each field in the transaction in a row of the
 /// transaction table.
@@ -27,6 +31,20 @@ pub enum TxFieldTag {
     /// TxSignHash: Hash of the transaction without the signature, used for
     /// signing.
     TxSignHash,
+    /// Number of addresses in the access list (EIP-2930)
+    AccessListAddressesLen,
+    /// Number of storage keys in the access list (EIP-2930)
+    AccessListStorageKeysLen,
+    /// MaxFeePerGas (EIP-1559), the gas price for the transactions before EIP-1559
+    MaxFeePerGas,
+    /// MaxPriorityFeePerGas (EIP-1559), the gas price for the transactions before EIP-1559
+    MaxPriorityFeePerGas,
+    /// Address of the access list (EIP-2930), at the index of its slot
+    AccessListAddress,
+    /// Storage key of the access list (EIP-2930), at the index of its slot
+    AccessListStorageKey,
+    /// Address of the storage key of the access list at the same index
+    AccessListStorageKeyAddress,
     /// CallData
     CallData,
 }
@@ -83,6 +101,126 @@ impl TxTable {
         }
     }
 
+    /// Given a transaction, get the values of the access list slots of its fields, as (tag, index,
+    /// value) in the order of their rows.  The slots after the last entry of the access list are
+    /// zero.  An access list that doesn't fit in the slots, which the bus-mapping rejects, is an
+    /// error.
+    pub fn access_list_slots(
+        tx: &geth_types::Transaction,
+    ) -> Result<Vec<(TxFieldTag, usize, Word)>, Error> {
+        let addresses = tx.access_list_addresses();
+        let storage_keys = tx.access_list_storage_keys();
+        if addresses.len() > MAX_ACCESS_LIST_ADDRESSES
+            || storage_keys.len() > MAX_ACCESS_LIST_STORAGE_KEYS
+        {
+            return Err(Error::Synthesis);
+        }
+        let storage_keys = storage_keys
+            .into_iter()
+            .pad_using(MAX_ACCESS_LIST_STORAGE_KEYS, |_| Default::default())
+            .collect_vec();
+
+        Ok(iter::empty()
+            .chain(
+                addresses
+                    .into_iter()
+                    .pad_using(MAX_ACCESS_LIST_ADDRESSES, |_| Address::zero())
+                    .enumerate()
+                    .map(|(i, address)| (TxFieldTag::AccessListAddress, i, address.to_word())),
+            )
+            .chain(
+                storage_keys
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, key))| (TxFieldTag::AccessListStorageKey, i, *key)),
+            )
+            .chain(storage_keys.iter().enumerate().map(|(i, (address, _))| {
+                (
+                    TxFieldTag::AccessListStorageKeyAddress,
+                    i,
+                    address.to_word(),
+                )
+            }))
+            .collect())
+    }
+
+    /// Given a transaction, get the assignments of its fields and of its calldata to the tx
+    /// table.  The rows of the calldata of all the transactions follow the rows of their fields.
+    #[allow(clippy::type_complexity)]
+    pub fn assignments<F: Field>(
+        tx: &Transaction,
+    ) -> Result<(Vec<[Value<F>; 5]>, Vec<[Value<F>; 5]>), Error> {
+        let tx_id = Value::known(F::from(tx.id));
+        let tx_data = vec![
+            (TxContextFieldTag::Nonce, WordLoHi::from(tx.nonce.as_u64())),
+            (TxContextFieldTag::Gas, WordLoHi::from(tx.gas())),
+            (TxContextFieldTag::GasPrice, WordLoHi::from(tx.gas_price)),
+            (TxContextFieldTag::CallerAddress, WordLoHi::from(tx.from)),
+            (
+                TxContextFieldTag::CalleeAddress,
+                WordLoHi::from(tx.to_or_contract_addr()),
+            ),
+            (TxContextFieldTag::IsCreate, WordLoHi::from(tx.is_create())),
+            (TxContextFieldTag::Value, WordLoHi::from(tx.value)),
+            (
+                TxContextFieldTag::CallDataLength,
+                WordLoHi::from(tx.call_data.len() as u64),
+            ),
+            (
+                TxContextFieldTag::CallDataGasCost,
+                WordLoHi::from(tx.call_data_gas_cost()),
+            ),
+            (
+                TxContextFieldTag::AccessListAddressesLen,
+                WordLoHi::from(tx.access_list_addresses_len()),
+            ),
+            (
+                TxContextFieldTag::AccessListStorageKeysLen,
+                WordLoHi::from(tx.access_list_storage_keys_len()),
+            ),
+            (
+                TxContextFieldTag::MaxFeePerGas,
+                WordLoHi::from(tx.max_fee_per_gas()),
+            ),
+            (
+                TxContextFieldTag::MaxPriorityFeePerGas,
+                WordLoHi::from(tx.max_priority_fee_per_gas()),
+            ),
+        ]
+        .into_iter()
+        .map(|(tag, word)| (tag, 0, word))
+        .chain(
+            Self::access_list_slots(tx)?
+                .into_iter()
+                .map(|(tag, index, value)| (tag, index, WordLoHi::from(value))),
+        )
+        .map(|(tag, index, word)| {
+            [
+                tx_id,
+                Value::known(F::from(tag as u64)),
+                Value::known(F::from(index as u64)),
+                Value::known(word.lo()),
+                Value::known(word.hi()),
+            ]
+        })
+        .collect_vec();
+        let tx_calldata = tx
+            .call_data
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| {
+                [
+                    tx_id,
+                    Value::known(F::from(TxContextFieldTag::CallData as u64)),
+                    Value::known(F::from(idx as u64)),
+                    Value::known(F::from(*byte as u64)),
+                    Value::known(F::ZERO),
+                ]
+            })
+            .collect_vec();
+        Ok((tx_data, tx_calldata))
+    }
+
     /// Assign the `TxTable` from a list of block `Transaction`s, following the
     /// same layout that the Tx Circuit uses.
     pub fn load<F: Field>(
@@ -92,19 +230,10 @@ impl TxTable {
         max_txs: usize,
         max_calldata: usize,
     ) -> Result<(), Error> {
-        assert!(
-            txs.len() <= max_txs,
-            "txs.len() <= max_txs: txs.len()={}, max_txs={}",
-            txs.len(),
-            max_txs
-        );
-        let sum_txs_calldata = txs.iter().map(|tx| tx.call_data.len()).sum();
-        assert!(
-            sum_txs_calldata <= max_calldata,
-            "sum_txs_calldata <= max_calldata: sum_txs_calldata={}, max_calldata={}",
-            sum_txs_calldata,
-            max_calldata,
-        );
+        let sum_txs_calldata: usize = txs.iter().map(|tx| tx.call_data.len()).sum();
+        if txs.len() > max_txs || sum_txs_calldata > max_calldata {
+            return Err(Error::Synthesis);
+        }
 
         fn assign_row<F: Field>(
             region: &mut Region<'_, F>,
@@ -157,52 +286,7 @@ impl TxTable {
                     .map(|i| Transaction::padding_tx(i + 1))
                     .collect();
                 for tx in txs.iter().chain(padding_txs.iter()) {
-                    let tx_id = Value::known(F::from(tx.id));
-                    let tx_data = vec![
-                        (TxContextFieldTag::Nonce, WordLoHi::from(tx.nonce.as_u64())),
-                        (TxContextFieldTag::Gas, WordLoHi::from(tx.gas())),
-                        (TxContextFieldTag::GasPrice, WordLoHi::from(tx.gas_price)),
-                        (TxContextFieldTag::CallerAddress, WordLoHi::from(tx.from)),
-                        (
-                            TxContextFieldTag::CalleeAddress,
-                            WordLoHi::from(tx.to_or_contract_addr()),
-                        ),
-                        (TxContextFieldTag::IsCreate, WordLoHi::from(tx.is_create())),
-                        (TxContextFieldTag::Value, WordLoHi::from(tx.value)),
-                        (
-                            TxContextFieldTag::CallDataLength,
-                            WordLoHi::from(tx.call_data.len() as u64),
-                        ),
-                        (
-                            TxContextFieldTag::CallDataGasCost,
-                            WordLoHi::from(tx.call_data_gas_cost()),
-                        ),
-                    ]
-                    .iter()
-                    .map(|&(tag, word)| {
-                        [
-                            tx_id,
-                            Value::known(F::from(tag as u64)),
-                            Value::known(F::ZERO),
-                            Value::known(word.lo()),
-                            Value::known(word.hi()),
-                        ]
-                    })
-                    .collect_vec();
-                    let tx_calldata = tx
-                        .call_data
-                        .iter()
-                        .enumerate()
-                        .map(|(idx, byte)| {
-                            [
-                                tx_id,
-                                Value::known(F::from(TxContextFieldTag::CallData as u64)),
-                                Value::known(F::from(idx as u64)),
-                                Value::known(F::from(*byte as u64)),
-                                Value::known(F::ZERO),
-                            ]
-                        })
-                        .collect_vec();
+                    let (tx_data, tx_calldata) = Self::assignments::<F>(tx)?;
                     for row in tx_data {
                         assign_row(&mut region, offset, &advice_columns, &self.tag, &row, "")?;
                         offset += 1;
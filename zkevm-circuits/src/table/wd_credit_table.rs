@@ -0,0 +1,83 @@
+use super::*;
+
+/// Table of the rw counters of the withdrawal credits of a block: the counter of the first
+/// credit, and the one following the last credit.  It has a single row, which in the
+/// SuperCircuit is copied from the Withdrawal Circuit.
+#[derive(Clone, Debug)]
+pub struct WdCreditTable {
+    /// True on the row of the table
+    pub q_enable: Column<Fixed>,
+    /// rw counter of the first write of the credits
+    pub rw_counter_start: Column<Advice>,
+    /// rw counter following the last write of the credits
+    pub rw_counter_end: Column<Advice>,
+}
+
+impl WdCreditTable {
+    /// Construct a new WdCreditTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: meta.fixed_column(),
+            rw_counter_start: meta.advice_column(),
+            rw_counter_end: meta.advice_column(),
+        }
+    }
+
+    /// Generate the row of the table, in the advice columns order.
+    pub fn assignments<F: Field>((start, end): (usize, usize)) -> [Value<F>; 2] {
+        [start, end].map(|rw_counter| Value::known(F::from(rw_counter as u64)))
+    }
+
+    /// Provide this function for the case that we want to consume a wd credit table without
+    /// running the Withdrawal Circuit
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rw_counters: (usize, usize),
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "wd credit table",
+            |mut region| {
+                region.assign_fixed(
+                    || "wd credit table q_enable",
+                    self.q_enable,
+                    0,
+                    || Value::known(F::ONE),
+                )?;
+                for (&column, value) in <WdCreditTable as LookupTable<F>>::advice_columns(self)
+                    .iter()
+                    .zip_eq(Self::assignments(rw_counters))
+                {
+                    region.assign_advice(|| "wd credit table row", column, 0, || value)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: Field> LookupTable<F> for WdCreditTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.rw_counter_start.into(),
+            self.rw_counter_end.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("rw_counter_start"),
+            String::from("rw_counter_end"),
+        ]
+    }
+
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        vec![
+            meta.query_fixed(self.q_enable, Rotation::cur()),
+            meta.query_advice(self.rw_counter_start, Rotation::cur()),
+            meta.query_advice(self.rw_counter_end, Rotation::cur()),
+        ]
+    }
+}
@@ -52,7 +52,7 @@ impl WdTable {
                     || format!("wd table {} row {}", msg, offset),
                     *column,
                     offset,
-                    || row[if index > 0 { index + 1 } else { index }],
+                    || row[index],
                 )?;
             }
             Ok(())
@@ -69,10 +69,9 @@ impl WdTable {
                     self.amount,
                 ];
 
-                // Assign withdrawal data
-                let padding_withdrawals: Vec<_> = (withdrawals.len()..max_withdrawals)
-                    .map(|i| Withdrawal::padding_withdrawal(i + 1))
-                    .collect();
+                // Assign withdrawal data, padded with empty withdrawals like in the PiCircuit
+                let padding_withdrawals =
+                    vec![Withdrawal::default(); max_withdrawals - withdrawals.len()];
                 for (offset, wd) in withdrawals
                     .iter()
                     .chain(padding_withdrawals.iter())
@@ -1,27 +1,40 @@
 //! Testing utilities
 
+#[cfg(feature = "evm-circuit")]
+use crate::evm_circuit::{cached::EvmCircuitCached, EvmCircuit};
+#[cfg(feature = "state-circuit")]
+use crate::state_circuit::StateCircuit;
 use crate::{
-    evm_circuit::{cached::EvmCircuitCached, EvmCircuit},
-    state_circuit::StateCircuit,
-    util::SubCircuit,
-    witness::{Block, Rw},
+    table::AccountFieldTag,
+    witness::{Block, Rw, RwMap},
 };
 use bus_mapping::{
     circuit_input_builder::{FeatureConfig, FixedCParams},
     mock::BlockData,
+    operation::Target,
 };
-use eth_types::geth_types::GethData;
-use itertools::all;
+use eth_types::{geth_types::GethData, Address, Word};
+use itertools::{all, Itertools};
+#[cfg(feature = "state-circuit")]
 use std::cmp;
 use thiserror::Error;
 
-use crate::util::log2_ceil;
+#[cfg(feature = "state-circuit")]
+use crate::util::{log2_ceil, SubCircuit};
 use halo2_proofs::{
     dev::{MockProver, VerifyFailure},
     halo2curves::bn256::Fr,
 };
 use mock::TestContext;
 
+pub mod constraint_coverage;
+#[cfg(all(
+    feature = "evm-circuit",
+    feature = "state-circuit",
+    feature = "copy-circuit"
+))]
+pub mod soundness;
+
 #[cfg(test)]
 #[ctor::ctor]
 fn init_env_logger() {
@@ -29,11 +42,13 @@ fn init_env_logger() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("error")).init();
 }
 
+#[cfg(feature = "state-circuit")]
 const NUM_BLINDING_ROWS: usize = 64;
 
 #[allow(clippy::type_complexity)]
 /// Struct used to easily generate tests for EVM &| State circuits being able to
-/// customize all of the steps involved in the testing itself.
+/// customize all of the steps involved in the testing itself.  The circuits
+/// left out of the build by their cargo feature are skipped.
 ///
 /// By default, the tests run through `prover.assert_satisfied_par()` but the
 /// builder pattern provides functions that allow to pass different functions
@@ -43,6 +58,10 @@ const NUM_BLINDING_ROWS: usize = 64;
 /// block produced from the [`TestContext`] and apply them before starting to
 /// compute the proof.
 ///
+/// Tests can also state the expected outcome of the block in terms of the rw
+/// table, like [`CircuitTestBuilder::expect_storage_write`], which is checked
+/// before the circuits are run.
+///
 /// ## Example:
 /// ```rust, no_run
 /// use eth_types::geth_types::Account;
@@ -87,6 +106,7 @@ pub struct CircuitTestBuilder<const NACC: usize, const NTX: usize> {
     feature_config: Option<FeatureConfig>,
     block: Option<Block<Fr>>,
     block_modifiers: Vec<Box<dyn Fn(&mut Block<Fr>)>>,
+    rw_expectations: Vec<RwExpectation>,
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -98,6 +118,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             feature_config: None,
             block: None,
             block_modifiers: vec![],
+            rw_expectations: vec![],
         }
     }
 
@@ -154,6 +175,32 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         self.block_modifiers.push(modifier);
         self
     }
+
+    /// Expect the rw table of the block to write `value` over `value_prev` to
+    /// the storage slot `key` of `address`.
+    pub fn expect_storage_write(
+        mut self,
+        address: Address,
+        key: Word,
+        value_prev: Word,
+        value: Word,
+    ) -> Self {
+        self.rw_expectations.push(RwExpectation::StorageWrite {
+            address,
+            key,
+            value_prev,
+            value,
+        });
+        self
+    }
+
+    /// Expect the balance of `address` to change by `delta` wei over the block,
+    /// according to the first and last balance accesses in the rw table.
+    pub fn expect_balance_change(mut self, address: Address, delta: i128) -> Self {
+        self.rw_expectations
+            .push(RwExpectation::BalanceChange { address, delta });
+        self
+    }
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -183,6 +230,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         Ok(block)
     }
 
+    #[cfg(feature = "evm-circuit")]
     fn run_evm_circuit_test(&self, block: Block<Fr>) -> Result<(), CircuitTestError> {
         let k = block.get_test_degree();
 
@@ -215,6 +263,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
     }
     // TODO: use randomness as one of the circuit public input, since randomness in
     // state circuit and evm circuit must be same
+    #[cfg(feature = "state-circuit")]
     fn run_state_circuit_test(&self, block: Block<Fr>) -> Result<(), CircuitTestError> {
         let rows_needed = StateCircuit::<Fr>::min_num_rows_block(&block).1;
         let k = cmp::max(log2_ceil(rows_needed + NUM_BLINDING_ROWS), 18);
@@ -236,7 +285,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         let rows = max_rws - non_start_rows_len..max_rws;
         prover.verify_at_rows(rows.clone(), rows).map_err(|err| {
             CircuitTestError::VerificationFailed {
-                circuit: Circuit::EVM,
+                circuit: Circuit::State,
                 reasons: err,
             }
         })
@@ -244,17 +293,120 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
     /// Triggers the `CircuitTestBuilder` to convert the [`TestContext`] if any,
     /// into a [`Block`] and apply the default or provided block_modifiers or
     /// circuit checks to the provers generated for the State and EVM circuits.
+    ///
+    /// The failures of the verification are recorded for the
+    /// [`constraint_coverage`] when it's enabled.
     pub fn run_with_result(self) -> Result<(), CircuitTestError> {
         let block = self.build_block()?;
+        for expectation in &self.rw_expectations {
+            expectation.check(&block.rws)?;
+        }
 
-        self.run_evm_circuit_test(block.clone())?;
-        self.run_state_circuit_test(block)
+        let result = Ok(());
+        #[cfg(feature = "evm-circuit")]
+        let result = result.and_then(|_| self.run_evm_circuit_test(block.clone()));
+        #[cfg(feature = "state-circuit")]
+        let result = result.and_then(|_| self.run_state_circuit_test(block));
+        if let Err(CircuitTestError::VerificationFailed { circuit, reasons }) = &result {
+            constraint_coverage::record_failures(circuit, reasons);
+        }
+        result
     }
 
     /// Convenient method to run in test cases that error handling is not required.
     pub fn run(self) {
         self.run_with_result().unwrap()
     }
+
+    /// Build the block and check that the State circuit rejects every
+    /// [`RwMutation`](crate::state_circuit::mutation::RwMutation) of its rw
+    /// table.  This runs the MockProver once per mutation, so it's slow.
+    #[cfg(all(test, feature = "state-circuit"))]
+    pub(crate) fn assert_state_mutations_rejected(&self) {
+        let block = self.build_block().unwrap();
+        crate::state_circuit::mutation::assert_mutations_rejected(&block.rws.table_assignments());
+    }
+}
+
+/// Expected content of the rw table, checked against the witness block before
+/// the circuits are run.
+#[derive(Debug, Clone)]
+enum RwExpectation {
+    StorageWrite {
+        address: Address,
+        key: Word,
+        value_prev: Word,
+        value: Word,
+    },
+    BalanceChange {
+        address: Address,
+        delta: i128,
+    },
+}
+
+impl RwExpectation {
+    fn check(&self, rws: &RwMap) -> Result<(), CircuitTestError> {
+        let rows = |target: Target| rws.0.get(&target).into_iter().flatten();
+        match *self {
+            Self::StorageWrite {
+                address,
+                key,
+                value_prev,
+                value,
+            } => {
+                let writes = rows(Target::Storage)
+                    .filter_map(|rw| match *rw {
+                        Rw::AccountStorage {
+                            is_write: true,
+                            account_address,
+                            storage_key,
+                            value: new,
+                            value_prev: old,
+                            ..
+                        } if account_address == address && storage_key == key => Some((old, new)),
+                        _ => None,
+                    })
+                    .collect_vec();
+                if !writes.contains(&(value_prev, value)) {
+                    return Err(CircuitTestError::RwExpectationFailed(format!(
+                        "storage {address:?} {key:?}: no write {value_prev:?} -> {value:?} \
+                         in {writes:?}"
+                    )));
+                }
+            }
+            Self::BalanceChange { address, delta } => {
+                let balances = rows(Target::Account)
+                    .filter_map(|rw| match *rw {
+                        Rw::Account {
+                            rw_counter,
+                            account_address,
+                            field_tag: AccountFieldTag::Balance,
+                            value: new,
+                            value_prev: old,
+                            ..
+                        } if account_address == address => Some((rw_counter, old, new)),
+                        _ => None,
+                    })
+                    .sorted_by_key(|(rw_counter, _, _)| *rw_counter)
+                    .collect_vec();
+                let (before, after) = match (balances.first(), balances.last()) {
+                    (Some((_, before, _)), Some((_, _, after))) => (*before, *after),
+                    _ => (Word::zero(), Word::zero()),
+                };
+                let expected = if delta >= 0 {
+                    before.checked_add(Word::from(delta.unsigned_abs()))
+                } else {
+                    before.checked_sub(Word::from(delta.unsigned_abs()))
+                };
+                if expected != Some(after) {
+                    return Err(CircuitTestError::RwExpectationFailed(format!(
+                        "balance {address:?}: {before:?} -> {after:?} is not a change of {delta}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -278,6 +430,9 @@ pub enum CircuitTestError {
     /// Something worng in the block_convert
     #[error("CannotConvertBlock({0})")]
     CannotConvertBlock(String),
+    /// The rw table of the block doesn't have the expected content
+    #[error("RwExpectationFailed({0})")]
+    RwExpectationFailed(String),
     /// Problem constructing MockProver
     #[error("SynthesisFailure({circuit:?}, reason: {reason:?})")]
     SynthesisFailure {
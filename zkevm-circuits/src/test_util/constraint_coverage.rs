@@ -0,0 +1,249 @@
+//! Coverage of the constraints by the negative tests.
+//!
+//! When the `CONSTRAINT_COVERAGE` environment variable is set to a directory, every
+//! [`CircuitTestBuilder`](super::CircuitTestBuilder) test that fails verification records the
+//! constraints and lookups that rejected its witness in that directory.  A [`CoverageReport`]
+//! over these records tells which constraints never reject a bad witness on their own:
+//!
+//! ```text
+//! CONSTRAINT_COVERAGE=/tmp/coverage cargo test -p zkevm-circuits
+//! CONSTRAINT_COVERAGE=/tmp/coverage cargo test -p zkevm-circuits evm_constraint_coverage \
+//!     -- --ignored --nocapture
+//! ```
+//!
+//! Disabling a constraint doesn't change the witness, so the verification of a test with the
+//! constraint disabled fails with the same failures minus the ones of the constraint.  Instead
+//! of re-running the tests once per disabled constraint, a constraint is found responsible for
+//! rejecting a witness when it is the only one that fails for it.
+
+use super::Circuit;
+use halo2_proofs::dev::VerifyFailure;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    thread,
+};
+
+/// Environment variable with the directory where the failures are recorded.
+pub const CONSTRAINT_COVERAGE_ENV: &str = "CONSTRAINT_COVERAGE";
+
+const RECORDS_FILE: &str = "failures.jsonl";
+
+/// Constraints and lookups that rejected the witness of a test.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureRecord {
+    /// Name of the test
+    pub test: String,
+    /// Circuit that failed verification
+    pub circuit: String,
+    /// Description of the failed constraints and lookups, as displayed by the MockProver
+    pub failures: BTreeSet<String>,
+}
+
+impl FailureRecord {
+    fn new(circuit: &Circuit, reasons: &[VerifyFailure]) -> Self {
+        let failures = reasons
+            .iter()
+            .filter_map(|reason| match reason {
+                // fields of halo2_proofs::dev::metadata::Constraint aren't public, so we have to
+                // use its format string.
+                VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+                    Some(format!("{}", constraint))
+                }
+                VerifyFailure::Lookup { name, .. } => Some(format!("Lookup '{}'", name)),
+                _ => None,
+            })
+            .collect();
+        Self {
+            test: thread::current().name().unwrap_or_default().to_string(),
+            circuit: format!("{:?}", circuit),
+            failures,
+        }
+    }
+}
+
+/// Record the failures of a test if the `CONSTRAINT_COVERAGE` environment variable is set.
+pub(crate) fn record_failures(circuit: &Circuit, reasons: &[VerifyFailure]) {
+    if let Ok(dir) = env::var(CONSTRAINT_COVERAGE_ENV) {
+        let record = FailureRecord::new(circuit, reasons);
+        if let Err(err) = append_record(Path::new(&dir), &record) {
+            log::error!(
+                "failed to record the constraint coverage in {}: {}",
+                dir,
+                err
+            );
+        }
+    }
+}
+
+fn append_record(dir: &Path, record: &FailureRecord) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    // A single write per record, so that the tests running in parallel don't interleave lines.
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(RECORDS_FILE))?
+        .write_all(line.as_bytes())
+}
+
+/// Load the failures recorded in `dir`.
+pub fn load_records(dir: &Path) -> io::Result<Vec<FailureRecord>> {
+    let file = fs::File::open(dir.join(RECORDS_FILE))?;
+    BufReader::new(file)
+        .lines()
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+/// Constraint coverage of a set of failed tests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Constraints that are the only failure of some tests, with the names of these tests
+    pub responsible: BTreeMap<String, BTreeSet<String>>,
+    /// Constraints that fail in some tests, but always together with other constraints.  They
+    /// are candidates for redundant constraints.
+    pub redundant: BTreeSet<String>,
+    /// Expected constraints that never fail.  They are candidates for dead constraints, or
+    /// constraints missing negative tests.
+    pub uncovered: BTreeSet<String>,
+}
+
+impl CoverageReport {
+    /// Build the report of the `records` of the `circuit`.  The `expected` constraints are given
+    /// by a predicate that tells whether a failure belongs to them and, as the failures only
+    /// cover the constraints that fail, are needed to find the ones that never fail.
+    pub fn new<'a>(
+        circuit: Circuit,
+        records: &[FailureRecord],
+        expected: impl IntoIterator<Item = (String, Box<dyn Fn(&str) -> bool + 'a>)>,
+    ) -> Self {
+        let circuit = format!("{:?}", circuit);
+        let records = records
+            .iter()
+            .filter(|record| record.circuit == circuit && !record.failures.is_empty())
+            .collect_vec();
+
+        let mut report = Self::default();
+        let mut failed = BTreeSet::new();
+        for record in &records {
+            if let Ok(failure) = record.failures.iter().exactly_one() {
+                report
+                    .responsible
+                    .entry(failure.clone())
+                    .or_default()
+                    .insert(record.test.clone());
+            }
+            failed.extend(record.failures.iter().cloned());
+        }
+        report.redundant = failed
+            .iter()
+            .filter(|failure| !report.responsible.contains_key(*failure))
+            .cloned()
+            .collect();
+        report.uncovered = expected
+            .into_iter()
+            .filter(|(_, matches)| !failed.iter().any(|failure| matches(failure)))
+            .map(|(name, _)| name)
+            .collect();
+        report
+    }
+}
+
+#[cfg(all(test, feature = "evm-circuit"))]
+mod tests {
+    use super::*;
+    use crate::evm_circuit::EvmCircuit;
+    use bus_mapping::circuit_input_builder::FeatureConfig;
+    use halo2_proofs::{
+        halo2curves::bn256::Fr,
+        plonk::{Circuit as _, ConstraintSystem},
+    };
+
+    fn record(test: &str, failures: &[&str]) -> FailureRecord {
+        FailureRecord {
+            test: test.to_string(),
+            circuit: format!("{:?}", Circuit::EVM),
+            failures: failures.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn constraint_coverage_report() {
+        let records = [
+            record("a", &["Constraint 0 ('x') in gate 1 ('ADD')"]),
+            record(
+                "b",
+                &["Constraint 0 ('x') in gate 1 ('ADD')", "Lookup 'rw'"],
+            ),
+            record("c", &["Lookup 'rw'", "Lookup 'fixed'"]),
+        ];
+        let expected = ["x", "y"].map(|name| {
+            let pattern = format!("('{}') in gate", name);
+            let matches: Box<dyn Fn(&str) -> bool> =
+                Box::new(move |failure| failure.contains(&pattern));
+            (name.to_string(), matches)
+        });
+        let report = CoverageReport::new(Circuit::EVM, &records, expected);
+
+        assert_eq!(
+            report.responsible,
+            BTreeMap::from([(
+                "Constraint 0 ('x') in gate 1 ('ADD')".to_string(),
+                BTreeSet::from(["a".to_string()])
+            )])
+        );
+        assert_eq!(
+            report.redundant,
+            BTreeSet::from(["Lookup 'fixed'".to_string(), "Lookup 'rw'".to_string()])
+        );
+        assert_eq!(report.uncovered, BTreeSet::from(["y".to_string()]));
+    }
+
+    /// Print the coverage of the EVM circuit constraints by the failures recorded in the
+    /// `CONSTRAINT_COVERAGE` directory.
+    #[ignore]
+    #[test]
+    fn evm_constraint_coverage() {
+        let dir = env::var(CONSTRAINT_COVERAGE_ENV).expect("CONSTRAINT_COVERAGE is not set");
+        let records = load_records(Path::new(&dir)).unwrap();
+
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let (config, _) =
+            EvmCircuit::<Fr>::configure_with_params(&mut meta, FeatureConfig::default());
+        let expected = config
+            .execution
+            .instrument()
+            .constraints()
+            .iter()
+            .unique_by(|(_, gadget, constraint)| (*gadget, *constraint))
+            .map(|(_, gadget, constraint)| {
+                let name = format!("('{}') in gate", constraint);
+                let gate = format!("('{}')", gadget);
+                let matches: Box<dyn Fn(&str) -> bool> =
+                    Box::new(move |failure| failure.contains(&name) && failure.ends_with(&gate));
+                (format!("{}: {}", gadget, constraint), matches)
+            })
+            .collect_vec();
+        let report = CoverageReport::new(Circuit::EVM, &records, expected);
+
+        println!("Responsible for rejecting a witness:");
+        for (failure, tests) in &report.responsible {
+            println!("  {} ({} tests)", failure, tests.len());
+        }
+        println!("Redundant candidates:");
+        for failure in &report.redundant {
+            println!("  {}", failure);
+        }
+        println!("Uncovered:");
+        for constraint in &report.uncovered {
+            println!("  {}", constraint);
+        }
+    }
+}
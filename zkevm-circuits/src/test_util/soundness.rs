@@ -0,0 +1,306 @@
+//! Malicious witness testing of the sub-circuits.
+//!
+//! The [`SoundnessHarness`] takes a valid witness [`Block`] and applies each [`Mutation`] of its
+//! library to a copy of it.  Every mutation forges a part of the witness that the circuits are
+//! supposed to constrain, so the sub-circuit it targets must reject the mutated block.  A
+//! mutation that still verifies is a missing constraint, reported as a critical [`Finding`]:
+//!
+//! ```ignore
+//! let block = CircuitTestBuilder::new_from_test_ctx(ctx).build_block()?;
+//! SoundnessHarness::new(block).run()?.assert_sound();
+//! ```
+
+use crate::{
+    copy_circuit::CopyCircuit,
+    evm_circuit::EvmCircuit,
+    state_circuit::StateCircuit,
+    util::{log2_ceil, SubCircuit},
+    witness::{Block, Rw},
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, operation::Target};
+use eth_types::{evm_types::OpcodeId, U256};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use std::{
+    cmp,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Sub-circuit checked against a mutated witness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundnessCircuit {
+    /// EVM circuit
+    Evm,
+    /// State circuit
+    State,
+    /// Copy circuit
+    Copy,
+}
+
+impl SoundnessCircuit {
+    /// Return whether the circuit accepts the witness of the block.  A witness that can't even
+    /// be assigned, because the synthesis fails or panics, is rejected.
+    pub fn accepts(&self, block: &Block<Fr>) -> bool {
+        let block = block.clone();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| match self {
+            Self::Evm => {
+                let k = block.get_test_degree();
+                let circuit = EvmCircuit::get_test_circuit_from_block(block);
+                MockProver::<Fr>::run(k, &circuit, vec![]).map(|prover| prover.verify())
+            }
+            Self::State => {
+                let k = cmp::max(log2_ceil(block.circuits_params.max_rws + 64), 18);
+                let circuit = StateCircuit::<Fr>::new(block.rws, block.circuits_params.max_rws);
+                let instance = circuit.instance();
+                MockProver::<Fr>::run(k, &circuit, instance).map(|prover| prover.verify())
+            }
+            Self::Copy => {
+                let k = block.get_test_degree();
+                let circuit = CopyCircuit::<Fr>::new_from_block(&block);
+                MockProver::<Fr>::run(k, &circuit, vec![]).map(|prover| prover.verify())
+            }
+        }));
+        matches!(result, Ok(Ok(Ok(()))))
+    }
+}
+
+/// Forgery of a part of a witness, which the targeted sub-circuit must reject.
+#[derive(Debug, Clone, Copy)]
+pub struct Mutation {
+    /// Name of the mutation
+    pub name: &'static str,
+    /// Sub-circuit that must reject the mutated witness
+    pub circuit: SoundnessCircuit,
+    /// Mutate the block, returning `false` if the block has nothing to mutate
+    pub apply: fn(&mut Block<Fr>) -> bool,
+}
+
+/// Charge one more unit of gas to the first executed opcode.
+fn bad_opcode_gas(block: &mut Block<Fr>) -> bool {
+    for tx in block.txs.iter_mut() {
+        let steps = tx.steps_mut();
+        let Some(index) = (1..steps.len())
+            .find(|&index| steps[index - 1].opcode().is_some() && steps[index].gas_left > 0)
+        else {
+            continue;
+        };
+        steps[index].gas_left -= 1;
+        return true;
+    }
+    false
+}
+
+/// Replace the digest pushed to the stack by the first `SHA3`.
+fn wrong_keccak_digest(block: &mut Block<Fr>) -> bool {
+    let pushes = block
+        .txs
+        .iter()
+        .flat_map(|tx| tx.steps())
+        .filter(|step| step.opcode() == Some(OpcodeId::SHA3))
+        .flat_map(|step| step.bus_mapping_instance.iter())
+        .filter(|op| op.0 == Target::Stack && block.rws[**op].is_write())
+        .copied()
+        .collect::<Vec<_>>();
+    let Some(push) = pushes.first() else {
+        return false;
+    };
+    if let Some(Rw::Stack { value, .. }) = block
+        .rws
+        .0
+        .get_mut(&Target::Stack)
+        .and_then(|rows| rows.get_mut(push.1))
+    {
+        *value ^= U256::one();
+        return true;
+    }
+    false
+}
+
+/// Flip a bit of the first byte copied from memory.
+fn forged_copy_event(block: &mut Block<Fr>) -> bool {
+    let Some(event) = block.copy_events.iter_mut().find(|event| {
+        event.src_type == CopyDataType::Memory
+            && event.src_addr < event.src_addr_end
+            && !event.bytes.is_empty()
+    }) else {
+        return false;
+    };
+    event.bytes[0].0 ^= 1;
+    true
+}
+
+/// Default library of mutations of the [`SoundnessHarness`].
+pub fn default_mutations() -> Vec<Mutation> {
+    vec![
+        Mutation {
+            name: "bad opcode gas",
+            circuit: SoundnessCircuit::Evm,
+            apply: bad_opcode_gas,
+        },
+        Mutation {
+            name: "wrong keccak digest",
+            circuit: SoundnessCircuit::Evm,
+            apply: wrong_keccak_digest,
+        },
+        Mutation {
+            name: "forged copy event",
+            circuit: SoundnessCircuit::Copy,
+            apply: forged_copy_event,
+        },
+    ]
+}
+
+/// Mutation that still verifies: a missing constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    /// Name of the mutation
+    pub mutation: &'static str,
+    /// Sub-circuit that accepted the mutated witness
+    pub circuit: SoundnessCircuit,
+}
+
+/// Outcome of the mutations of a [`SoundnessHarness`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SoundnessReport {
+    /// Mutations rejected by their sub-circuit
+    pub rejected: Vec<&'static str>,
+    /// Mutations with nothing to mutate in the block
+    pub skipped: Vec<&'static str>,
+    /// Mutations accepted by their sub-circuit
+    pub findings: Vec<Finding>,
+}
+
+impl SoundnessReport {
+    /// Panic with the findings, if any.
+    pub fn assert_sound(&self) {
+        assert!(
+            self.findings.is_empty(),
+            "CRITICAL: the circuits accept the malicious witnesses {:?}",
+            self.findings
+        );
+    }
+}
+
+/// Runner of the mutations of a valid witness through the sub-circuits.
+pub struct SoundnessHarness {
+    block: Block<Fr>,
+    mutations: Vec<Mutation>,
+}
+
+impl SoundnessHarness {
+    /// Create a harness of the valid `block` with the [`default_mutations`].
+    pub fn new(block: Block<Fr>) -> Self {
+        Self {
+            block,
+            mutations: default_mutations(),
+        }
+    }
+
+    /// Add a mutation to the library.
+    pub fn mutation(mut self, mutation: Mutation) -> Self {
+        self.mutations.push(mutation);
+        self
+    }
+
+    /// Apply every mutation to a copy of the block and check it with its sub-circuit.  Returns
+    /// an error with the first sub-circuit that rejects the unmutated block, since the findings
+    /// would be meaningless.
+    pub fn run(&self) -> Result<SoundnessReport, SoundnessCircuit> {
+        let mut circuits = Vec::new();
+        for mutation in self.mutations.iter() {
+            if !circuits.contains(&mutation.circuit) {
+                circuits.push(mutation.circuit);
+            }
+        }
+        if let Some(circuit) = circuits
+            .into_iter()
+            .find(|circuit| !circuit.accepts(&self.block))
+        {
+            return Err(circuit);
+        }
+
+        let mut report = SoundnessReport::default();
+        for mutation in self.mutations.iter() {
+            let mut block = self.block.clone();
+            if !(mutation.apply)(&mut block) {
+                report.skipped.push(mutation.name);
+            } else if mutation.circuit.accepts(&block) {
+                log::error!(
+                    "CRITICAL: the {:?} circuit accepts the mutation '{}'",
+                    mutation.circuit,
+                    mutation.name
+                );
+                report.findings.push(Finding {
+                    mutation: mutation.name,
+                    circuit: mutation.circuit,
+                });
+            } else {
+                report.rejected.push(mutation.name);
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, Word};
+    use mock::TestContext;
+
+    #[test]
+    fn soundness_harness_default_mutations() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SHA3
+            STOP
+        };
+        let block = CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .build_block()
+        .unwrap();
+
+        let report = SoundnessHarness::new(block.clone())
+            // The state circuit doesn't constrain the value of a write
+            .mutation(Mutation {
+                name: "any stack write",
+                circuit: SoundnessCircuit::State,
+                apply: wrong_keccak_digest,
+            })
+            .run()
+            .unwrap();
+        assert_eq!(
+            report.rejected,
+            vec!["bad opcode gas", "wrong keccak digest", "forged copy event"]
+        );
+        assert_eq!(
+            report.findings,
+            vec![Finding {
+                mutation: "any stack write",
+                circuit: SoundnessCircuit::State,
+            }]
+        );
+
+        // Nothing to mutate without a copy
+        let code = bytecode! {
+            PUSH1(0x00)
+            STOP
+        };
+        let block = CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .build_block()
+        .unwrap();
+        let report = SoundnessHarness::new(block).run().unwrap();
+        report.assert_sound();
+        assert_eq!(
+            report.skipped,
+            vec!["wrong keccak digest", "forged copy event"]
+        );
+    }
+}
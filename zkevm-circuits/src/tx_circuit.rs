@@ -14,26 +14,158 @@ mod test;
 pub use dev::TxCircuit as TestTxCircuit;
 
 use crate::{
-    table::{KeccakTable, TxFieldTag, TxTable},
+    evm_circuit::{
+        param::{MAX_ACCESS_LIST_ADDRESSES, MAX_ACCESS_LIST_STORAGE_KEYS},
+        util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    },
+    table::{KeccakTable, LookupTable, SigTable, TxFieldTag, TxTable},
     util::{word::WordLoHi, Challenges, SubCircuit, SubCircuitConfig},
     witness,
 };
 use eth_types::{geth_types::Transaction, sign_types::SignData, Field};
+use gadgets::{
+    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
+    util::{not, Expr},
+};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
 };
 use itertools::Itertools;
 use log::error;
 use sign_verify::{AssignedSignatureVerify, SignVerifyChip, SignVerifyConfig};
 use std::{marker::PhantomData, ops::Deref};
 
-/// Number of static fields per tx: [nonce, gas, gas_price,
+/// Number of fields per tx before the access list slots: [nonce, gas, gas_price,
 /// caller_address, callee_address, is_create, value, call_data_length,
-/// call_data_gas_cost, tx_sign_hash].
+/// call_data_gas_cost, tx_sign_hash, access_list_addresses_len,
+/// access_list_storage_keys_len, max_fee_per_gas, max_priority_fee_per_gas].
+pub(crate) const TX_FIELDS_LEN: usize = 14;
+
+/// Number of static rows per tx: the fields, then the access list slots
+/// [access_list_address; MAX_ACCESS_LIST_ADDRESSES],
+/// [access_list_storage_key; MAX_ACCESS_LIST_STORAGE_KEYS] and
+/// [access_list_storage_key_address; MAX_ACCESS_LIST_STORAGE_KEYS].
 /// Note that call data bytes are laid out in the TxTable after all the static
 /// fields arranged by txs.
-pub(crate) const TX_LEN: usize = 10;
+pub(crate) const TX_LEN: usize =
+    TX_FIELDS_LEN + MAX_ACCESS_LIST_ADDRESSES + 2 * MAX_ACCESS_LIST_STORAGE_KEYS;
+
+/// Gates on the access list slots of the txs in the tx table, shared by the Tx Circuit and the
+/// PI Circuit.  Each group of slots of a tx (addresses, storage keys and addresses of the
+/// storage keys) counts down the entries left from the length field of the tx, so that the
+/// length fits in the slots and the slots after the last entry are zero.
+#[derive(Clone, Debug)]
+pub(crate) struct AccessListConfig<F> {
+    // q_slot: 1 on the rows of the access list slots, others are 0
+    q_slot: Selector,
+    // q_slot_last: 1 on the last slot of each group, others are 0
+    q_slot_last: Selector,
+    // remaining: number of entries in the slots of the group from the current one
+    remaining: Column<Advice>,
+    remaining_is_zero: IsZeroConfig<F>,
+}
+
+impl<F: Field> AccessListConfig<F> {
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, tx_table: &TxTable) -> Self {
+        let q_slot = meta.selector();
+        let q_slot_last = meta.selector();
+        let remaining = meta.advice_column();
+        let remaining_inv = meta.advice_column();
+        meta.enable_equality(remaining);
+
+        let remaining_is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_slot),
+            |meta| meta.query_advice(remaining, Rotation::cur()),
+            remaining_inv,
+        );
+
+        meta.create_gate("access list slots", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_slot_last = meta.query_selector(q_slot_last);
+            let remaining_cur = meta.query_advice(remaining, Rotation::cur());
+            let remaining_next = meta.query_advice(remaining, Rotation::next());
+            let is_entry = not::expr(remaining_is_zero.expr());
+
+            cb.condition(remaining_is_zero.expr(), |cb| {
+                cb.require_zero(
+                    "slot after the last entry is zero",
+                    meta.query_advice(tx_table.value.lo(), Rotation::cur()),
+                );
+                cb.require_zero(
+                    "slot after the last entry is zero",
+                    meta.query_advice(tx_table.value.hi(), Rotation::cur()),
+                );
+            });
+            cb.condition(not::expr(is_slot_last.expr()), |cb| {
+                cb.require_equal(
+                    "remaining[i+1] = remaining[i] - is_entry[i]",
+                    remaining_next,
+                    remaining_cur.expr() - is_entry.expr(),
+                );
+            });
+            cb.condition(is_slot_last, |cb| {
+                cb.require_zero(
+                    "no entry left after the last slot",
+                    remaining_cur - is_entry,
+                );
+            });
+
+            cb.gate(meta.query_selector(q_slot))
+        });
+
+        Self {
+            q_slot,
+            q_slot_last,
+            remaining,
+            remaining_is_zero,
+        }
+    }
+
+    /// Assign the countdowns of the groups of access list slots of a tx, laid out one after the
+    /// other from `offset`.  Each group is given as its number of slots, the length of the access
+    /// list entries in it, and the assigned cell of the length field.
+    pub(crate) fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        groups: &[(usize, u64, AssignedCell<F, F>)],
+    ) -> Result<(), Error> {
+        let remaining_is_zero = IsZeroChip::construct(self.remaining_is_zero.clone());
+        let mut offset = offset;
+        for (num_slots, len, len_cell) in groups {
+            for i in 0..*num_slots {
+                let remaining = Value::known(F::from(len.saturating_sub(i as u64)));
+                let remaining_cell = region.assign_advice(
+                    || "access list remaining",
+                    self.remaining,
+                    offset,
+                    || remaining,
+                )?;
+                remaining_is_zero.assign(region, offset, remaining)?;
+                self.q_slot.enable(region, offset)?;
+                if i == 0 {
+                    region.constrain_equal(remaining_cell.cell(), len_cell.cell())?;
+                }
+                if i == num_slots - 1 {
+                    self.q_slot_last.enable(region, offset)?;
+                }
+                offset += 1;
+            }
+        }
+        // The last slot queries the next row
+        region.assign_advice(
+            || "access list remaining",
+            self.remaining,
+            offset,
+            || Value::known(F::ZERO),
+        )?;
+        Ok(())
+    }
+}
 
 /// Config for TxCircuit
 #[derive(Clone, Debug)]
@@ -43,6 +175,8 @@ pub struct TxCircuitConfig<F: Field> {
     index: Column<Advice>,
     value: WordLoHi<Column<Advice>>,
     sign_verify: SignVerifyConfig,
+    sig_table: SigTable,
+    access_list: AccessListConfig<F>,
     _marker: PhantomData<F>,
 }
 
@@ -52,6 +186,8 @@ pub struct TxCircuitConfigArgs<F: Field> {
     pub tx_table: TxTable,
     /// KeccakTable
     pub keccak_table: KeccakTable,
+    /// SigTable
+    pub sig_table: SigTable,
     /// Challenges
     pub challenges: Challenges<Expression<F>>,
 }
@@ -65,6 +201,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         Self::ConfigArgs {
             tx_table,
             keccak_table,
+            sig_table,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -74,8 +211,12 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let value = tx_table.value;
         meta.enable_equality(value.lo());
         meta.enable_equality(value.hi());
+        for column in <SigTable as LookupTable<F>>::advice_columns(&sig_table) {
+            meta.enable_equality(column);
+        }
 
         let sign_verify = SignVerifyConfig::new(meta, keccak_table, challenges);
+        let access_list = AccessListConfig::configure(meta, &tx_table);
 
         Self {
             tx_id,
@@ -83,6 +224,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             index,
             value,
             sign_verify,
+            sig_table,
+            access_list,
             _marker: PhantomData,
         }
     }
@@ -142,12 +285,16 @@ pub struct TxCircuit<F: Field> {
     pub max_txs: usize,
     /// Max number of supported calldata bytes
     pub max_calldata: usize,
+    /// Max number of supported signatures of ecRecover calls
+    pub max_ecrecover: usize,
     /// SignVerify chip
     pub sign_verify: SignVerifyChip<F>,
     /// List of Transactions
     pub txs: Vec<Transaction>,
     /// Chain ID
     pub chain_id: u64,
+    /// Signatures of the ecRecover calls, copied to the sig table
+    pub ecrecover_sign_datas: Vec<SignData>,
 }
 
 impl<F: Field> TxCircuit<F> {
@@ -156,24 +303,38 @@ impl<F: Field> TxCircuit<F> {
         TxCircuit::<F> {
             max_txs,
             max_calldata,
+            max_ecrecover: 0,
             sign_verify: SignVerifyChip::new(max_txs),
             txs,
             chain_id,
+            ecrecover_sign_datas: Vec::new(),
         }
     }
 
+    /// Return the TxCircuit also verifying the signatures of the ecRecover calls, after the
+    /// signatures of the transactions.
+    pub fn with_ecrecover(mut self, max_ecrecover: usize, sign_datas: Vec<SignData>) -> Self {
+        self.max_ecrecover = max_ecrecover;
+        self.sign_verify = SignVerifyChip::new(self.max_txs + max_ecrecover);
+        self.ecrecover_sign_datas = sign_datas;
+        self
+    }
+
     /// Return the minimum number of rows required to prove an input of a
     /// particular size.
-    pub fn min_num_rows(txs_len: usize, call_data_len: usize) -> usize {
+    pub fn min_num_rows(txs_len: usize, call_data_len: usize, ecrecover_len: usize) -> usize {
         let tx_table_len = txs_len * TX_LEN + call_data_len;
-        std::cmp::max(tx_table_len, SignVerifyChip::<F>::min_num_rows(txs_len))
+        std::cmp::max(
+            tx_table_len,
+            SignVerifyChip::<F>::min_num_rows(txs_len + ecrecover_len),
+        )
     }
 
     fn assign_tx_table(
         &self,
         config: &TxCircuitConfig<F>,
         layouter: &mut impl Layouter<F>,
-        assigned_sig_verifs: Vec<AssignedSignatureVerify<F>>,
+        assigned_sig_verifs: &[AssignedSignatureVerify<F>],
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "tx table",
@@ -191,14 +352,17 @@ impl<F: Field> TxCircuit<F> {
                 offset += 1;
                 // Assign all Tx fields except for call data
                 let tx_default = Transaction::default();
-                for (i, assigned_sig_verif) in assigned_sig_verifs.iter().enumerate() {
+                for (i, assigned_sig_verif) in
+                    assigned_sig_verifs.iter().take(self.max_txs).enumerate()
+                {
                     let tx = if i < self.txs.len() {
                         &self.txs[i]
                     } else {
                         &tx_default
                     };
 
-                    for (tag, value) in [
+                    let mut access_list_len_cells = vec![];
+                    for (tag, index, value) in [
                         (
                             TxFieldTag::Nonce,
                             WordLoHi::from(tx.nonce.as_u64()).into_value(),
@@ -233,9 +397,30 @@ impl<F: Field> TxCircuit<F> {
                             TxFieldTag::TxSignHash,
                             assigned_sig_verif.msg_hash.map(|x| x.value().copied()),
                         ),
-                    ] {
+                        (
+                            TxFieldTag::AccessListAddressesLen,
+                            WordLoHi::from(tx.access_list_addresses_len()).into_value(),
+                        ),
+                        (
+                            TxFieldTag::AccessListStorageKeysLen,
+                            WordLoHi::from(tx.access_list_storage_keys_len()).into_value(),
+                        ),
+                        (
+                            TxFieldTag::MaxFeePerGas,
+                            WordLoHi::from(tx.max_fee_per_gas()).into_value(),
+                        ),
+                        (
+                            TxFieldTag::MaxPriorityFeePerGas,
+                            WordLoHi::from(tx.max_priority_fee_per_gas()).into_value(),
+                        ),
+                    ]
+                    .into_iter()
+                    .map(|(tag, value)| (tag, 0, value))
+                    .chain(TxTable::access_list_slots(tx)?.into_iter().map(
+                        |(tag, index, value)| (tag, index, WordLoHi::from(value).into_value()),
+                    )) {
                         let assigned_cell =
-                            config.assign_row(&mut region, offset, i + 1, tag, 0, value)?;
+                            config.assign_row(&mut region, offset, i + 1, tag, index, value)?;
                         offset += 1;
 
                         // Ref. spec 0. Copy constraints using fixed offsets between the tx rows and
@@ -261,9 +446,38 @@ impl<F: Field> TxCircuit<F> {
                                     assigned_sig_verif.msg_hash.hi().cell(),
                                 )?
                             }
+                            TxFieldTag::AccessListAddressesLen
+                            | TxFieldTag::AccessListStorageKeysLen => {
+                                access_list_len_cells.push(assigned_cell.lo())
+                            }
                             _ => (),
                         }
                     }
+
+                    // Constrain the access list slots against their lengths
+                    let [addresses_len, storage_keys_len]: [_; 2] =
+                        access_list_len_cells.try_into().unwrap();
+                    config.access_list.assign(
+                        &mut region,
+                        offset - (TX_LEN - TX_FIELDS_LEN),
+                        &[
+                            (
+                                MAX_ACCESS_LIST_ADDRESSES,
+                                tx.access_list_addresses_len(),
+                                addresses_len,
+                            ),
+                            (
+                                MAX_ACCESS_LIST_STORAGE_KEYS,
+                                tx.access_list_storage_keys_len(),
+                                storage_keys_len.clone(),
+                            ),
+                            (
+                                MAX_ACCESS_LIST_STORAGE_KEYS,
+                                tx.access_list_storage_keys_len(),
+                                storage_keys_len,
+                            ),
+                        ],
+                    )?;
                 }
 
                 // Assign call data
@@ -298,6 +512,63 @@ impl<F: Field> TxCircuit<F> {
             },
         )
     }
+
+    fn assign_sig_table(
+        &self,
+        config: &TxCircuitConfig<F>,
+        layouter: &mut impl Layouter<F>,
+        assigned_sig_verifs: &[AssignedSignatureVerify<F>],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sig table",
+            |mut region| {
+                let advice_columns =
+                    <SigTable as LookupTable<F>>::advice_columns(&config.sig_table);
+                // Copy constraints between the sig table rows and the verifications of the
+                // ecRecover signatures in the SignVerifyChip
+                for (offset, (sign_data, assigned_sig_verif)) in self
+                    .ecrecover_sign_datas
+                    .iter()
+                    .zip(assigned_sig_verifs.iter().skip(self.max_txs))
+                    .enumerate()
+                {
+                    region.assign_fixed(
+                        || format!("sig table q_enable {}", offset),
+                        config.sig_table.q_enable,
+                        offset,
+                        || Value::known(F::ONE),
+                    )?;
+                    let [msg_hash, sig_r, sig_s, address] = [
+                        &assigned_sig_verif.msg_hash,
+                        &assigned_sig_verif.sig_r,
+                        &assigned_sig_verif.sig_s,
+                        &assigned_sig_verif.address,
+                    ]
+                    .map(|word| [word.lo(), word.hi()]);
+                    let assigned_cells = msg_hash
+                        .into_iter()
+                        .chain([assigned_sig_verif.sig_v.clone()])
+                        .chain(sig_r)
+                        .chain(sig_s)
+                        .chain(address);
+                    for ((&column, value), assigned_cell) in advice_columns
+                        .iter()
+                        .zip_eq(SigTable::assignments(sign_data))
+                        .zip_eq(assigned_cells)
+                    {
+                        let cell = region.assign_advice(
+                            || format!("sig table row {}", offset),
+                            column,
+                            offset,
+                            || value,
+                        )?;
+                        region.constrain_equal(cell.cell(), assigned_cell.cell())?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
 }
 
 impl<F: Field> SubCircuit<F> for TxCircuit<F> {
@@ -316,6 +587,10 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             block.context.chain_id.as_u64(),
             block.txs.iter().map(|tx| tx.deref().clone()).collect_vec(),
         )
+        .with_ecrecover(
+            block.circuits_params.max_ecrecover,
+            block.ecrecover_events.clone(),
+        )
     }
 
     /// Return the minimum number of rows required to prove the block
@@ -324,10 +599,12 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             Self::min_num_rows(
                 block.txs.len(),
                 block.txs.iter().map(|tx| tx.call_data.len()).sum(),
+                block.ecrecover_events.len(),
             ),
             Self::min_num_rows(
                 block.circuits_params.max_txs,
                 block.circuits_params.max_calldata,
+                block.circuits_params.max_ecrecover,
             ),
         )
     }
@@ -340,6 +617,7 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         assert!(self.txs.len() <= self.max_txs);
+        assert!(self.ecrecover_sign_datas.len() <= self.max_ecrecover);
         let sign_datas: Vec<SignData> = self
             .txs
             .iter()
@@ -351,11 +629,20 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             })
             .try_collect()?;
 
+        // The signatures of the txs, padded to max_txs, then the signatures of the ecRecover calls
+        let sign_datas = sign_datas
+            .into_iter()
+            .map(Some)
+            .pad_using(self.max_txs, |_| None)
+            .chain(self.ecrecover_sign_datas.iter().cloned().map(Some))
+            .collect_vec();
+
         config.load_aux_tables(layouter)?;
         let assigned_sig_verifs =
             self.sign_verify
                 .assign(&config.sign_verify, layouter, &sign_datas, challenges)?;
-        self.assign_tx_table(config, layouter, assigned_sig_verifs)?;
+        self.assign_tx_table(config, layouter, &assigned_sig_verifs)?;
+        self.assign_sig_table(config, layouter, &assigned_sig_verifs)?;
         Ok(())
     }
 
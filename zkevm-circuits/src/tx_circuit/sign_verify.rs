@@ -86,11 +86,12 @@ impl<F: Field> SignVerifyChip<F> {
         // --all-features -- --nocapture`
         // The value rows_range_chip_table has been obtained by patching the halo2
         // library to report the number of rows used in the range chip table
-        // region. TODO: Figure out a way to get these numbers automatically.
+        // region. The rows of the r and s decompositions are estimated from the msg_hash
+        // ones. TODO: Figure out a way to get these numbers automatically.
         let rows_range_chip_table = 295188;
         let rows_ecc_chip_aux = 226;
-        let rows_ecdsa_chip_verification = 104471;
-        let rows_signature_address_verify = 76;
+        let rows_ecdsa_chip_verification = 104511;
+        let rows_signature_address_verify = 96;
         std::cmp::max(
             rows_range_chip_table,
             (rows_ecc_chip_aux + rows_ecdsa_chip_verification + rows_signature_address_verify)
@@ -288,12 +289,17 @@ pub(crate) struct AssignedECDSA<F: Field> {
     pk_x_le: [AssignedValue<F>; 32],
     pk_y_le: [AssignedValue<F>; 32],
     msg_hash_le: [AssignedValue<F>; 32],
+    sig_r_le: [AssignedValue<F>; 32],
+    sig_s_le: [AssignedValue<F>; 32],
 }
 
 #[derive(Debug)]
 pub(crate) struct AssignedSignatureVerify<F: Field> {
     pub(crate) address: WordLoHi<AssignedValue<F>>,
     pub(crate) msg_hash: WordLoHi<AssignedValue<F>>,
+    pub(crate) sig_v: AssignedValue<F>,
+    pub(crate) sig_r: WordLoHi<AssignedValue<F>>,
+    pub(crate) sig_s: WordLoHi<AssignedValue<F>>,
 }
 
 // Return an array of bytes that corresponds to the little endian representation
@@ -368,6 +374,9 @@ impl<F: Field> SignVerifyChip<F> {
 
         let r_assigned = scalar_chip.assign_integer(ctx, integer_r, Range::Remainder)?;
         let s_assigned = scalar_chip.assign_integer(ctx, integer_s, Range::Remainder)?;
+        // Convert (r, s) integers to little endian bytes
+        let sig_r_le = integer_to_bytes_le(ctx, range_chip, &r_assigned)?;
+        let sig_s_le = integer_to_bytes_le(ctx, range_chip, &s_assigned)?;
         let sig = AssignedEcdsaSig {
             r: r_assigned,
             s: s_assigned,
@@ -397,6 +406,8 @@ impl<F: Field> SignVerifyChip<F> {
             pk_x_le,
             pk_y_le,
             msg_hash_le,
+            sig_r_le,
+            sig_s_le,
         })
     }
 
@@ -568,33 +579,49 @@ impl<F: Field> SignVerifyChip<F> {
         let iz_zero_lo = main_gate.is_zero(ctx, &address_cells.lo())?;
         let is_address_zero = main_gate.and(ctx, &iz_zero_lo, &iz_zero_hi)?;
 
+        // Build the lo and hi cells of a word from its little endian byte cells.
+        let word_cells = |ctx: &mut RegionCtx<F>, bytes_le: &[AssignedValue<F>; 32]| {
+            let [lo, hi] = [&bytes_le[..16], &bytes_le[16..]].map(|bytes| {
+                main_gate
+                    .decompose(
+                        ctx,
+                        &bytes
+                            .iter()
+                            .zip_eq(&powers_of_256)
+                            .map(|(cell, coeff)| maingate::Term::Assigned(cell, *coeff))
+                            .collect_vec(),
+                        F::ZERO,
+                        |_, _| Ok(()),
+                    )
+                    .map(|(cell, _)| cell)
+            });
+            Ok::<_, Error>(WordLoHi::new([lo?, hi?]))
+        };
+
         // Ref. spec SignVerifyChip 3. Verify that the signed message in the ecdsa_chip
         // corresponds to msg_hash
-        let msg_hash_cells = {
-            let msg_hash_lo_cell_bytes = &assigned_ecdsa.msg_hash_le[..16];
-            let msg_hash_hi_cell_bytes = &assigned_ecdsa.msg_hash_le[16..];
-            let (msg_hash_cell_lo, _) = main_gate.decompose(
-                ctx,
-                &msg_hash_lo_cell_bytes
-                    .iter()
-                    .zip_eq(&powers_of_256)
-                    .map(|(cell, coeff)| maingate::Term::Assigned(cell, *coeff))
-                    .collect_vec(),
-                F::ZERO,
-                |_, _| Ok(()),
-            )?;
-            let (msg_hash_cell_hi, _) = main_gate.decompose(
+        let msg_hash_cells = word_cells(ctx, &assigned_ecdsa.msg_hash_le)?;
+        // The signature words, for the sig table of the ecRecover precompile
+        let sig_r_cells = word_cells(ctx, &assigned_ecdsa.sig_r_le)?;
+        let sig_s_cells = word_cells(ctx, &assigned_ecdsa.sig_s_le)?;
+        // The recovery id of the signature, for the same table: the parity of the y coordinate of
+        // the public key, taken from its lowest byte as `pk_y_le[0] = 2 * pk_y_half + sig_v`.
+        let sig_v_cell = {
+            let pk_y_byte = sign_data.pk.y.to_repr()[0];
+            let sig_v = main_gate.assign_bit(ctx, Value::known(F::from((pk_y_byte & 1) as u64)))?;
+            let (pk_y_half, _) =
+                range_chip.decompose(ctx, Value::known(F::from((pk_y_byte >> 1) as u64)), 8, 8)?;
+            let (pk_y_byte_cell, _) = main_gate.decompose(
                 ctx,
-                &msg_hash_hi_cell_bytes
-                    .iter()
-                    .zip_eq(&powers_of_256)
-                    .map(|(cell, coeff)| maingate::Term::Assigned(cell, *coeff))
-                    .collect_vec(),
+                &[
+                    maingate::Term::Assigned(&pk_y_half, F::from(2)),
+                    maingate::Term::Assigned(&sig_v, F::ONE),
+                ],
                 F::ZERO,
                 |_, _| Ok(()),
             )?;
-
-            WordLoHi::new([msg_hash_cell_lo, msg_hash_cell_hi])
+            main_gate.assert_equal(ctx, &pk_y_byte_cell, &assigned_ecdsa.pk_y_le[0])?;
+            sig_v
         };
 
         let pk_rlc = {
@@ -622,14 +649,19 @@ impl<F: Field> SignVerifyChip<F> {
         Ok(AssignedSignatureVerify {
             address: address_cells,
             msg_hash: msg_hash_cells,
+            sig_v: sig_v_cell,
+            sig_r: sig_r_cells,
+            sig_s: sig_s_cells,
         })
     }
 
+    /// Assign the verifications of the signatures, `None` being a padding verification (of the
+    /// zero address).
     pub(crate) fn assign(
         &self,
         config: &SignVerifyConfig,
         layouter: &mut impl Layouter<F>,
-        signatures: &[SignData],
+        signatures: &[Option<SignData>],
         challenges: &Challenges<Value<F>>,
     ) -> Result<Vec<AssignedSignatureVerify<F>>, Error> {
         if signatures.len() > self.max_verif {
@@ -674,12 +706,8 @@ impl<F: Field> SignVerifyChip<F> {
                 let mut assigned_ecdsas = Vec::new();
                 let mut ctx = RegionCtx::new(region, 0);
                 for i in 0..self.max_verif {
-                    let signature = if i < signatures.len() {
-                        signatures[i].clone()
-                    } else {
-                        // padding (enabled when address == 0)
-                        SignData::default()
-                    };
+                    // padding (enabled when address == 0)
+                    let signature = signatures.get(i).cloned().flatten().unwrap_or_default();
                     let assigned_ecdsa = self.assign_ecdsa(&mut ctx, &chips, &signature)?;
                     assigned_ecdsas.push(assigned_ecdsa);
                 }
@@ -694,7 +722,8 @@ impl<F: Field> SignVerifyChip<F> {
                 let mut assigned_sig_verifs = Vec::new();
                 let mut ctx = RegionCtx::new(region, 0);
                 for (i, assigned_ecdsa) in assigned_ecdsas.iter().enumerate() {
-                    let sign_data = signatures.get(i); // None when padding (enabled when address == 0)
+                    // None when padding (enabled when address == 0)
+                    let sign_data = signatures.get(i).and_then(Option::as_ref);
                     let assigned_sig_verif = self.assign_signature_verify(
                         config,
                         &mut ctx,
@@ -784,7 +813,7 @@ mod sign_verify_tests {
             self.sign_verify.assign(
                 &config.sign_verify,
                 &mut layouter,
-                &self.signatures,
+                &self.signatures.iter().cloned().map(Some).collect_vec(),
                 &challenges,
             )?;
             config.sign_verify._keccak_table.dev_load(
@@ -0,0 +1,144 @@
+//! Extension point to add columns and gates to a [`SubCircuit`] from outside of this crate.
+//!
+//! A downstream fork that needs extra constraints in one of the sub-circuits (for example an L2
+//! fee rule on top of the tx circuit) implements [`SubCircuitExtension`] for it and uses
+//! [`ExtendedCircuit`] in place of the original sub-circuit.  The extension is configured after
+//! the original sub-circuit, can use the columns of its config and of the tables passed in its
+//! `ConfigArgs`, and is assigned right after it.
+
+use super::{Challenges, SubCircuit, SubCircuitConfig};
+use crate::witness;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error},
+};
+use std::marker::PhantomData;
+
+/// Extra columns and gates added to the sub-circuit `C`.
+pub trait SubCircuitExtension<F: Field, C: SubCircuit<F>> {
+    /// Configuration of the extension.
+    type Config;
+    /// Arguments of the extension configuration, typically the tables it looks up.
+    type ConfigArgs;
+
+    /// Configure the extension.  `base` is the configuration of the extended sub-circuit.
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        base: &C::Config,
+        args: Self::ConfigArgs,
+    ) -> Self::Config;
+
+    /// Create a new extension from a witness Block
+    fn new_from_block(block: &witness::Block<F>) -> Self;
+
+    /// Returns the instance columns required for the extension.  They are placed after the
+    /// instance columns of the extended sub-circuit.
+    fn instance(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+
+    /// Assign the columns of the extension.
+    fn synthesize_extension(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error>;
+
+    /// Return the minimum number of rows required by the extension to prove the block.
+    /// Row numbers without/with padding are both returned.
+    fn min_num_rows_block(_block: &witness::Block<F>) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+/// Configuration of an [`ExtendedCircuit`].
+#[derive(Clone, Debug)]
+pub struct ExtendedCircuitConfig<F: Field, C: SubCircuit<F>, E: SubCircuitExtension<F, C>> {
+    /// Configuration of the extended sub-circuit
+    pub base: C::Config,
+    /// Configuration of the extension
+    pub extension: E::Config,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, C: SubCircuit<F>, E: SubCircuitExtension<F, C>> SubCircuitConfig<F>
+    for ExtendedCircuitConfig<F, C, E>
+{
+    type ConfigArgs = (
+        <C::Config as SubCircuitConfig<F>>::ConfigArgs,
+        E::ConfigArgs,
+    );
+
+    fn new(meta: &mut ConstraintSystem<F>, (base_args, extension_args): Self::ConfigArgs) -> Self {
+        let base = C::Config::new(meta, base_args);
+        let extension = E::configure(meta, &base, extension_args);
+        Self {
+            base,
+            extension,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Sub-circuit `C` with the columns and gates of the extension `E`.
+#[derive(Clone, Debug, Default)]
+pub struct ExtendedCircuit<F: Field, C: SubCircuit<F>, E: SubCircuitExtension<F, C>> {
+    /// Extended sub-circuit
+    pub base: C,
+    /// Extension
+    pub extension: E,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, C: SubCircuit<F>, E: SubCircuitExtension<F, C>> ExtendedCircuit<F, C, E> {
+    /// Return a new ExtendedCircuit
+    pub fn new(base: C, extension: E) -> Self {
+        Self {
+            base,
+            extension,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: SubCircuit<F>, E: SubCircuitExtension<F, C>> SubCircuit<F>
+    for ExtendedCircuit<F, C, E>
+{
+    type Config = ExtendedCircuitConfig<F, C, E>;
+
+    /// The extension shares the rows of the extended sub-circuit, so it must not query its
+    /// columns at more rotations than the sub-circuit does.
+    fn unusable_rows() -> usize {
+        C::unusable_rows()
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(C::new_from_block(block), E::new_from_block(block))
+    }
+
+    fn instance(&self) -> Vec<Vec<F>> {
+        let mut instance = self.base.instance();
+        instance.extend(self.extension.instance());
+        instance
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.base
+            .synthesize_sub(&config.base, challenges, layouter)?;
+        self.extension
+            .synthesize_extension(&config.extension, challenges, layouter)
+    }
+
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let (base, base_padded) = C::min_num_rows_block(block);
+        let (extension, extension_padded) = E::min_num_rows_block(block);
+        (base.max(extension), base_padded.max(extension_padded))
+    }
+}
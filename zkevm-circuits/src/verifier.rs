@@ -0,0 +1,174 @@
+//! Verification of proofs from their bytes, without a prover or a filesystem.
+//!
+//! [`verify_bytes`] takes the verifying key, the params, the proof and the public inputs as byte
+//! slices, as a browser or a wasm runtime receives them, and is built for `wasm32` along with the
+//! configurations of the circuits, which reading a verifying key needs.  The public inputs of a
+//! column are the 32-byte little-endian representations of its field elements, as written by
+//! [`encode_instance`].
+//!
+//! [`verify`] checks a proof of [`prove`](crate::local_prover::prove) with any of the commitment
+//! schemes of halo2.
+
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        ff::{FromUniformBytes, PrimeField},
+    },
+    plonk::{self, verify_proof, Circuit, VerifyingKey},
+    poly::{
+        commitment::{CommitmentScheme, ParamsProver, Verifier},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{EncodedChallenge, TranscriptReadBuffer},
+    SerdeFormat,
+};
+use std::io;
+use thiserror::Error;
+
+/// Size of an encoded public input, in bytes.
+pub const INSTANCE_BYTES: usize = 32;
+
+/// Error verifying a proof from its bytes.
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    /// The verifying key or the params can't be decoded.
+    #[error("invalid verifying key or params: {0}")]
+    Io(#[from] io::Error),
+    /// The column of public inputs isn't a sequence of canonical field elements.
+    #[error("invalid public inputs in column {0}")]
+    Instance(usize),
+    /// The proof doesn't verify for the public inputs.
+    #[error("invalid proof: {0}")]
+    Proof(#[from] plonk::Error),
+}
+
+/// Encode a column of public inputs for [`verify_bytes`].
+pub fn encode_instance(column: &[Fr]) -> Vec<u8> {
+    column.iter().flat_map(|value| value.to_repr()).collect()
+}
+
+/// Decode a column of public inputs encoded by [`encode_instance`].  Returns `None` if the bytes
+/// aren't a sequence of canonical field elements.
+pub fn decode_instance(bytes: &[u8]) -> Option<Vec<Fr>> {
+    if bytes.len() % INSTANCE_BYTES != 0 {
+        return None;
+    }
+    bytes
+        .chunks(INSTANCE_BYTES)
+        .map(|chunk| Option::from(Fr::from_repr(chunk.try_into().unwrap())))
+        .collect()
+}
+
+/// References to the columns of the public inputs of each instance, as halo2 takes them.
+pub(crate) fn instance_refs(instances: &[Vec<Vec<Fr>>]) -> Vec<Vec<&[Fr]>> {
+    instances
+        .iter()
+        .map(|instance| instance.iter().map(Vec::as_slice).collect())
+        .collect()
+}
+
+/// Verify a proof of a circuit with the commitment scheme `Scheme`, its multiopen verifier `V`,
+/// the transcript `T` and the verification `strategy`, with its public inputs, one vector per
+/// instance column.
+pub fn verify<'params, 'a, Scheme, V, T, E, S>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: S,
+    instance: &[Vec<Scheme::Scalar>],
+    proof: &'a [u8],
+) -> Result<S::Output, plonk::Error>
+where
+    Scheme: CommitmentScheme,
+    Scheme::Scalar: FromUniformBytes<64>,
+    V: Verifier<'params, Scheme>,
+    T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
+    E: EncodedChallenge<Scheme::Curve>,
+    S: VerificationStrategy<'params, Scheme, V>,
+{
+    let instance = instance.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = T::init(proof);
+    verify_proof::<Scheme, V, _, _, _>(params, vk, strategy, &[&instance], &mut transcript)
+}
+
+/// Verify a SHPLONK proof with the transcript `T` of several instances of a circuit, with the
+/// public inputs of each instance, one vector per instance column.
+pub fn verify_instances<'a, T, E>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[Vec<Vec<Fr>>],
+    proof: &'a [u8],
+) -> Result<(), plonk::Error>
+where
+    T: TranscriptReadBuffer<&'a [u8], G1Affine, E>,
+    E: EncodedChallenge<G1Affine>,
+{
+    if instances.is_empty() {
+        return Err(plonk::Error::InvalidInstances);
+    }
+    let instance_refs = instance_refs(instances);
+    let instance_refs = instance_refs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = T::init(proof);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        params.verifier_params(),
+        vk,
+        SingleStrategy::new(params),
+        &instance_refs,
+        &mut transcript,
+    )
+}
+
+/// Verify a SHPLONK proof of the circuit `C` with the transcript `T`, from the verifying key and
+/// the params in `SerdeFormat::RawBytes`, and the encoded columns of public inputs.
+/// `circuit_params` are the params of the configuration of the circuit, `()` for most circuits.
+pub fn verify_bytes<'a, C, T, E>(
+    vk: &[u8],
+    params: &[u8],
+    proof: &'a [u8],
+    instances: &[&[u8]],
+    circuit_params: C::Params,
+) -> Result<(), VerifyError>
+where
+    C: Circuit<Fr>,
+    T: TranscriptReadBuffer<&'a [u8], G1Affine, E>,
+    E: EncodedChallenge<G1Affine>,
+{
+    let vk = VerifyingKey::<G1Affine>::read::<_, C>(
+        &mut &vk[..],
+        SerdeFormat::RawBytes,
+        circuit_params,
+    )?;
+    let params = ParamsKZG::<Bn256>::read_custom(&mut &params[..], SerdeFormat::RawBytes)?;
+    let instance = instances
+        .iter()
+        .enumerate()
+        .map(|(column, bytes)| decode_instance(bytes).ok_or(VerifyError::Instance(column)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(verify_instances::<T, E>(&params, &vk, &[instance], proof)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_instance() {
+        let column = vec![Fr::from(1), -Fr::from(1), Fr::from(u64::MAX)];
+        let bytes = encode_instance(&column);
+        assert_eq!(bytes.len(), 3 * INSTANCE_BYTES);
+        assert_eq!(decode_instance(&bytes), Some(column));
+
+        assert_eq!(decode_instance(&bytes[1..]), None);
+        // The modulus isn't canonical
+        let modulus = encode_instance(&[-Fr::from(1)])
+            .into_iter()
+            .enumerate()
+            .map(|(i, byte)| if i == 0 { byte + 1 } else { byte })
+            .collect::<Vec<_>>();
+        assert_eq!(decode_instance(&modulus), None);
+    }
+}
@@ -0,0 +1,586 @@
+//! The withdrawal circuit credits the withdrawals (EIP-4895) of a block to their accounts.
+//!
+//! Each row of the circuit is a withdrawal of the [`WdTable`], with the rw table lookups of its
+//! credit: the read of the code hash of the account, which is 0 when the account doesn't exist,
+//! the creation of the account in that case, and the increase of its balance by the amount in
+//! Wei.  These rws are done by the last EndBlock step, after the transactions of the block, at
+//! consecutive rw counters, and the [`WdCreditTable`] exposes their range to the EndBlock step.
+//! Withdrawals with a zero amount don't do any rw.
+//!
+//! The withdrawals precede the padding rows, with consecutive ids, so that each one is credited
+//! once, and every withdrawal of the [`WdTable`] is in the circuit.  The withdrawals and their
+//! root are committed to the public input by the PI circuit.
+//!
+//! The withdrawals root is NOT constrained: no circuit checks that it is the root of the trie
+//! of the RLP encoded withdrawals, so a proof is only sound for a verifier that recomputes the
+//! root from the withdrawals of the public input.
+
+#[cfg(any(test, feature = "test-circuits"))]
+mod dev;
+#[cfg(test)]
+mod test;
+#[cfg(feature = "test-circuits")]
+pub use dev::WithdrawalCircuit as TestWithdrawalCircuit;
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::{AccountFieldTag, LookupTable, RwTable, WdCreditTable, WdTable},
+    util::{word::WordLoHi, Challenges, SubCircuit, SubCircuitConfig},
+    witness::{self, Rw, RwMap, Withdrawal},
+};
+use bus_mapping::{operation::Target, state_db::CodeDB};
+use eth_types::{Field, Word};
+use gadgets::{
+    batched_is_zero::{BatchedIsZeroChip, BatchedIsZeroConfig},
+    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
+    util::{and, not, Expr},
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, FirstPhase, Selector, VirtualCells,
+    },
+    poly::Rotation,
+};
+use itertools::Itertools;
+use std::marker::PhantomData;
+
+/// Wei in a Gwei, the unit of the withdrawal amounts.
+const GWEI: u64 = 1_000_000_000;
+
+/// Config for WithdrawalCircuit
+#[derive(Clone, Debug)]
+pub struct WithdrawalCircuitConfig<F> {
+    /// Whether the row is a withdrawal, or the padding of one.
+    q_enable: Selector,
+    /// Whether the row is a withdrawal of the block.
+    is_withdrawal: Column<Advice>,
+    id: Column<Advice>,
+    validator_id: Column<Advice>,
+    address: WordLoHi<Column<Advice>>,
+    /// Amount in Gwei
+    amount: Column<Advice>,
+    amount_is_zero: IsZeroConfig<F>,
+    /// rw counter of the first rw of the credit.  The row following the last withdrawal or
+    /// padding has the rw counter following the credits.
+    rw_counter: Column<Advice>,
+    /// Code hash of the account before the credit, 0 if the account doesn't exist
+    code_hash: WordLoHi<Column<Advice>>,
+    code_hash_is_zero: BatchedIsZeroConfig,
+    balance_prev: WordLoHi<Column<Advice>>,
+    /// WdTable
+    pub wd_table: WdTable,
+    /// WdCreditTable
+    pub wd_credit_table: WdCreditTable,
+    /// RwTable
+    pub rw_table: RwTable,
+}
+
+/// Circuit configuration arguments
+pub struct WithdrawalCircuitConfigArgs {
+    /// WdTable
+    pub wd_table: WdTable,
+    /// WdCreditTable
+    pub wd_credit_table: WdCreditTable,
+    /// RwTable
+    pub rw_table: RwTable,
+}
+
+impl<F: Field> SubCircuitConfig<F> for WithdrawalCircuitConfig<F> {
+    type ConfigArgs = WithdrawalCircuitConfigArgs;
+
+    /// Return a new WithdrawalCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            wd_table,
+            wd_credit_table,
+            rw_table,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = meta.complex_selector();
+        let is_withdrawal = meta.advice_column();
+        let id = meta.advice_column();
+        let validator_id = meta.advice_column();
+        let address = WordLoHi::new([meta.advice_column(), meta.advice_column()]);
+        let amount = meta.advice_column();
+        let amount_inv = meta.advice_column();
+        let rw_counter = meta.advice_column();
+        let code_hash = WordLoHi::new([meta.advice_column(), meta.advice_column()]);
+        let balance_prev = WordLoHi::new([meta.advice_column(), meta.advice_column()]);
+        meta.enable_equality(rw_counter);
+        for column in <WdCreditTable as LookupTable<F>>::advice_columns(&wd_credit_table) {
+            meta.enable_equality(column);
+        }
+
+        let amount_is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_enable),
+            |meta| meta.query_advice(amount, Rotation::cur()),
+            amount_inv,
+        );
+        let code_hash_is_zero = BatchedIsZeroChip::configure(
+            meta,
+            (FirstPhase, FirstPhase),
+            |meta| meta.query_selector(q_enable),
+            |meta| {
+                [
+                    meta.query_advice(code_hash.lo(), Rotation::cur()),
+                    meta.query_advice(code_hash.hi(), Rotation::cur()),
+                ]
+            },
+        );
+        // The account is created when the credit is done to an account that doesn't exist.
+        let is_create = |meta: &mut VirtualCells<F>| {
+            not::expr(amount_is_zero.expr())
+                * meta.query_advice(code_hash_is_zero.is_zero, Rotation::cur())
+        };
+
+        meta.create_gate("withdrawal order", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_withdrawal_cur = meta.query_advice(is_withdrawal, Rotation::cur());
+            let is_withdrawal_next = meta.query_advice(is_withdrawal, Rotation::next());
+            cb.require_boolean("is_withdrawal is boolean", is_withdrawal_cur.clone());
+            cb.condition(not::expr(is_withdrawal_cur.clone()), |cb| {
+                cb.require_zero(
+                    "a padding row doesn't credit anything",
+                    meta.query_advice(amount, Rotation::cur()),
+                );
+                cb.require_zero(
+                    "the withdrawals precede the padding rows",
+                    is_withdrawal_next.clone(),
+                );
+            });
+            cb.condition(and::expr([is_withdrawal_cur, is_withdrawal_next]), |cb| {
+                cb.require_equal(
+                    "id::next == id::cur + 1",
+                    meta.query_advice(id, Rotation::next()),
+                    meta.query_advice(id, Rotation::cur()) + 1.expr(),
+                );
+            });
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        meta.create_gate("rw counter of the next credit", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let num_rws = not::expr(amount_is_zero.expr()) * 2.expr() + is_create(meta);
+            cb.require_equal(
+                "rw_counter::next == rw_counter::cur + rws of the credit",
+                meta.query_advice(rw_counter, Rotation::next()),
+                meta.query_advice(rw_counter, Rotation::cur()) + num_rws,
+            );
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        let withdrawal_exprs = |meta: &mut VirtualCells<F>| {
+            let address = address.query_advice(meta, Rotation::cur());
+            vec![
+                meta.query_advice(id, Rotation::cur()),
+                meta.query_advice(validator_id, Rotation::cur()),
+                address.lo(),
+                address.hi(),
+                meta.query_advice(amount, Rotation::cur()),
+            ]
+        };
+
+        meta.lookup_any("withdrawal in wd table", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            withdrawal_exprs(meta)
+                .into_iter()
+                .zip_eq(wd_table.table_exprs(meta))
+                .map(|(arg, table)| (q_enable.clone() * arg, table))
+                .collect()
+        });
+
+        // Every row of the wd table, including its padding with empty withdrawals, which match
+        // the disabled rows, is a row of the circuit.
+        meta.lookup_any("wd table row in withdrawal circuit", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            wd_table
+                .table_exprs(meta)
+                .into_iter()
+                .zip_eq(withdrawal_exprs(meta))
+                .map(|(arg, table)| (arg, q_enable.clone() * table))
+                .collect()
+        });
+
+        meta.lookup_any("account code hash in rw table", |meta| {
+            let cond = meta.query_selector(q_enable) * not::expr(amount_is_zero.expr());
+            let code_hash = code_hash.query_advice(meta, Rotation::cur());
+            vec![
+                meta.query_advice(rw_counter, Rotation::cur()),
+                0.expr(),
+                Target::Account.expr(),
+                0.expr(),
+                address.query_advice(meta, Rotation::cur()).compress(),
+                AccountFieldTag::CodeHash.expr(),
+                0.expr(),
+                0.expr(),
+                code_hash.lo(),
+                code_hash.hi(),
+                code_hash.lo(),
+                code_hash.hi(),
+                0.expr(),
+                0.expr(),
+            ]
+            .into_iter()
+            .zip_eq(rw_table.table_exprs(meta))
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup_any("account creation in rw table", |meta| {
+            let cond = meta.query_selector(q_enable) * is_create(meta);
+            let empty_code_hash = WordLoHi::<F>::from(CodeDB::empty_code_hash());
+            vec![
+                meta.query_advice(rw_counter, Rotation::cur()) + 1.expr(),
+                1.expr(),
+                Target::Account.expr(),
+                0.expr(),
+                address.query_advice(meta, Rotation::cur()).compress(),
+                AccountFieldTag::CodeHash.expr(),
+                0.expr(),
+                0.expr(),
+                Expression::Constant(empty_code_hash.lo()),
+                Expression::Constant(empty_code_hash.hi()),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ]
+            .into_iter()
+            .zip_eq(rw_table.table_exprs(meta))
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup_any("balance credit in rw table", |meta| {
+            let cond = meta.query_selector(q_enable) * not::expr(amount_is_zero.expr());
+            let balance_prev = balance_prev.query_advice(meta, Rotation::cur());
+            // The credit of at most 2^64 Gwei can't overflow the lo limb of a balance, as the
+            // total supply of Ether is far below 2^128 Wei.
+            let balance_lo =
+                balance_prev.lo() + meta.query_advice(amount, Rotation::cur()) * GWEI.expr();
+            vec![
+                meta.query_advice(rw_counter, Rotation::cur()) + 1.expr() + is_create(meta),
+                1.expr(),
+                Target::Account.expr(),
+                0.expr(),
+                address.query_advice(meta, Rotation::cur()).compress(),
+                AccountFieldTag::Balance.expr(),
+                0.expr(),
+                0.expr(),
+                balance_lo,
+                balance_prev.hi(),
+                balance_prev.lo(),
+                balance_prev.hi(),
+                0.expr(),
+                0.expr(),
+            ]
+            .into_iter()
+            .zip_eq(rw_table.table_exprs(meta))
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        Self {
+            q_enable,
+            is_withdrawal,
+            id,
+            validator_id,
+            address,
+            amount,
+            amount_is_zero,
+            rw_counter,
+            code_hash,
+            code_hash_is_zero,
+            balance_prev,
+            wd_table,
+            wd_credit_table,
+            rw_table,
+        }
+    }
+}
+
+impl<F: Field> WithdrawalCircuitConfig<F> {
+    /// Assign the credits of the withdrawals, starting at `rw_counter` and padded to
+    /// `max_withdrawals` rows, and the wd credit table.
+    pub fn assign_credits(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        credits: &[WithdrawalCredit],
+        rw_counter: usize,
+        max_withdrawals: usize,
+    ) -> Result<(), Error> {
+        assert!(
+            credits.len() <= max_withdrawals,
+            "credits.len() <= max_withdrawals: credits.len()={}, max_withdrawals={}",
+            credits.len(),
+            max_withdrawals
+        );
+        // The padding rows, and the row that follows them, carry the rw counter of the last
+        // credit over.
+        let rw_counter_end = credits
+            .last()
+            .map_or(rw_counter, |credit| credit.next_rw_counter());
+        let padding = WithdrawalCredit {
+            rw_counter: rw_counter_end,
+            ..Default::default()
+        };
+        let amount_is_zero = IsZeroChip::construct(self.amount_is_zero.clone());
+        let code_hash_is_zero = BatchedIsZeroChip::construct(self.code_hash_is_zero.clone());
+
+        let rw_counter_cells = layouter.assign_region(
+            || "withdrawal credits",
+            |mut region| {
+                let mut rw_counter_cells = Vec::with_capacity(max_withdrawals + 1);
+                for (offset, credit) in credits
+                    .iter()
+                    .chain(std::iter::repeat(&padding))
+                    .take(max_withdrawals)
+                    .enumerate()
+                {
+                    self.q_enable.enable(&mut region, offset)?;
+
+                    let wd = &credit.withdrawal;
+                    for (name, column, value) in [
+                        (
+                            "is_withdrawal",
+                            self.is_withdrawal,
+                            F::from((offset < credits.len()) as u64),
+                        ),
+                        ("id", self.id, F::from(wd.id)),
+                        ("validator_id", self.validator_id, F::from(wd.validator_id)),
+                        ("amount", self.amount, F::from(wd.amount)),
+                    ] {
+                        region.assign_advice(|| name, column, offset, || Value::known(value))?;
+                    }
+                    rw_counter_cells.push(region.assign_advice(
+                        || "rw_counter",
+                        self.rw_counter,
+                        offset,
+                        || Value::known(F::from(credit.rw_counter as u64)),
+                    )?);
+                    WordLoHi::<F>::from(wd.address).into_value().assign_advice(
+                        &mut region,
+                        || "address",
+                        self.address,
+                        offset,
+                    )?;
+                    let code_hash = WordLoHi::<F>::from(credit.code_hash);
+                    code_hash.into_value().assign_advice(
+                        &mut region,
+                        || "code_hash",
+                        self.code_hash,
+                        offset,
+                    )?;
+                    WordLoHi::<F>::from(credit.balance_prev)
+                        .into_value()
+                        .assign_advice(&mut region, || "balance_prev", self.balance_prev, offset)?;
+                    amount_is_zero.assign(&mut region, offset, Value::known(F::from(wd.amount)))?;
+                    code_hash_is_zero.assign(
+                        &mut region,
+                        offset,
+                        Value::known([code_hash.lo(), code_hash.hi()]),
+                    )?;
+                }
+                rw_counter_cells.push(region.assign_advice(
+                    || "rw_counter end",
+                    self.rw_counter,
+                    max_withdrawals,
+                    || Value::known(F::from(rw_counter_end as u64)),
+                )?);
+                Ok(rw_counter_cells)
+            },
+        )?;
+
+        self.assign_wd_credit_table(
+            layouter,
+            [
+                rw_counter_cells.first().unwrap(),
+                rw_counter_cells.last().unwrap(),
+            ],
+        )
+    }
+
+    fn assign_wd_credit_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rw_counter_cells: [&AssignedCell<F, F>; 2],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "wd credit table",
+            |mut region| {
+                region.assign_fixed(
+                    || "wd credit table q_enable",
+                    self.wd_credit_table.q_enable,
+                    0,
+                    || Value::known(F::ONE),
+                )?;
+                // Copy constraints between the wd credit table and the rw counters of the first
+                // credit and of the row following the credits
+                for (&column, assigned_cell) in
+                    <WdCreditTable as LookupTable<F>>::advice_columns(&self.wd_credit_table)
+                        .iter()
+                        .zip_eq(rw_counter_cells)
+                {
+                    assigned_cell.copy_advice(|| "wd credit table row", &mut region, column, 0)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Balance credit of a withdrawal.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithdrawalCredit {
+    /// Credited withdrawal
+    pub withdrawal: Withdrawal,
+    /// rw counter of the first rw of the credit
+    pub rw_counter: usize,
+    /// Code hash of the account before the credit, 0 if the account doesn't exist
+    pub code_hash: Word,
+    /// Balance of the account before the credit
+    pub balance_prev: Word,
+}
+
+impl WithdrawalCredit {
+    /// Return whether the credit creates the account.
+    pub fn is_create(&self) -> bool {
+        self.withdrawal.amount != 0 && self.code_hash.is_zero()
+    }
+
+    /// Return the rw counter of the credit that follows this one.
+    fn next_rw_counter(&self) -> usize {
+        if self.withdrawal.amount == 0 {
+            self.rw_counter
+        } else {
+            self.rw_counter + 2 + self.is_create() as usize
+        }
+    }
+
+    /// Return the credits of the withdrawals of the block, from the rws of its last EndBlock
+    /// step.
+    pub fn from_block<F: Field>(block: &witness::Block<F>) -> Vec<Self> {
+        let step = &block.end_block_last;
+        let mut account_rws = (0..step.rw_indices_len())
+            .map(|idx| block.get_rws(step, idx))
+            .filter(|rw| matches!(rw, Rw::Account { .. }));
+        let (mut rw_counter, _) = block.withdrawal_rw_counters();
+
+        block
+            .withdrawals()
+            .into_iter()
+            .map(|withdrawal| {
+                let mut credit = Self {
+                    withdrawal,
+                    rw_counter,
+                    ..Default::default()
+                };
+                if withdrawal.amount != 0 {
+                    if let Some(Rw::Account { value, .. }) = account_rws.next() {
+                        credit.code_hash = value;
+                    }
+                    if credit.is_create() {
+                        account_rws.next();
+                    }
+                    if let Some(Rw::Account { value_prev, .. }) = account_rws.next() {
+                        credit.balance_prev = value_prev;
+                    }
+                }
+                rw_counter = credit.next_rw_counter();
+                credit
+            })
+            .collect()
+    }
+}
+
+/// Withdrawal Circuit
+#[derive(Clone, Debug, Default)]
+pub struct WithdrawalCircuit<F: Field> {
+    /// Credits of the withdrawals of the block
+    pub credits: Vec<WithdrawalCredit>,
+    /// rw counter of the first credit
+    pub rw_counter: usize,
+    /// Max number of withdrawals
+    pub max_withdrawals: usize,
+    /// Withdrawals of the block, for the wd table of the dev circuit
+    pub withdrawals: Vec<Withdrawal>,
+    /// Rws of the block, for the rw table of the dev circuit
+    pub rws: RwMap,
+    /// Max number of rws, for the rw table of the dev circuit
+    pub max_rws: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> WithdrawalCircuit<F> {
+    /// Return a new WithdrawalCircuit
+    pub fn new(
+        credits: Vec<WithdrawalCredit>,
+        rw_counter: usize,
+        max_withdrawals: usize,
+        withdrawals: Vec<Withdrawal>,
+        rws: RwMap,
+        max_rws: usize,
+    ) -> Self {
+        Self {
+            credits,
+            rw_counter,
+            max_withdrawals,
+            withdrawals,
+            rws,
+            max_rws,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> SubCircuit<F> for WithdrawalCircuit<F> {
+    type Config = WithdrawalCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            WithdrawalCredit::from_block(block),
+            block.withdrawal_rw_counters().0,
+            block.circuits_params.max_withdrawals,
+            block.withdrawals(),
+            block.rws.clone(),
+            block.circuits_params.max_rws,
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        // The credits are followed by a row with the rw counter of their end.
+        (
+            block.withdrawals().len() + 1,
+            block.circuits_params.max_withdrawals + 1,
+        )
+    }
+
+    /// Make the assignments to the WithdrawalCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        _challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.assign_credits(
+            layouter,
+            &self.credits,
+            self.rw_counter,
+            self.max_withdrawals,
+        )
+    }
+}
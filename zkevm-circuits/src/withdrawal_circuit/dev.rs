@@ -0,0 +1,60 @@
+pub use super::WithdrawalCircuit;
+
+use crate::{
+    table::{RwTable, WdCreditTable, WdTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    withdrawal_circuit::{WithdrawalCircuitConfig, WithdrawalCircuitConfigArgs},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Challenge, Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for WithdrawalCircuit<F> {
+    type Config = (WithdrawalCircuitConfig<F>, Challenges<Challenge>);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let wd_table = WdTable::construct(meta);
+        let wd_credit_table = WdCreditTable::construct(meta);
+        let rw_table = RwTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        (
+            WithdrawalCircuitConfig::new(
+                meta,
+                WithdrawalCircuitConfigArgs {
+                    wd_table,
+                    wd_credit_table,
+                    rw_table,
+                },
+            ),
+            challenges,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenge_values = config.1.values(&mut layouter);
+
+        config
+            .0
+            .wd_table
+            .load(&mut layouter, &self.withdrawals, self.max_withdrawals)?;
+        config
+            .0
+            .rw_table
+            .load(&mut layouter, &self.rws.table_assignments(), self.max_rws)?;
+
+        self.synthesize_sub(&config.0, &challenge_values, &mut layouter)
+    }
+}
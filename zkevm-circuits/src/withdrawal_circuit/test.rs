@@ -0,0 +1,105 @@
+use crate::{
+    test_util::CircuitTestBuilder,
+    util::{log2_ceil, unusable_rows, SubCircuit},
+    withdrawal_circuit::WithdrawalCircuit,
+    witness::{block_convert, Block},
+};
+use bus_mapping::{mock::BlockData, state_db::CodeDB};
+use eth_types::{geth_types::GethData, Address, ToWord, Word};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+};
+use mock::{eth, test_ctx::helpers::tx_from_1_to_0, TestContext2, MOCK_ACCOUNTS};
+
+#[test]
+fn withdrawal_circuit_unusable_rows() {
+    assert_eq!(
+        WithdrawalCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, WithdrawalCircuit::<Fr>>(()),
+    )
+}
+
+/// Block with withdrawals to an existing account, to a new account, and of a zero amount.
+fn withdrawals_block() -> Block<Fr> {
+    let block: GethData = TestContext2::<2, 1, 4>::new(
+        None,
+        |accs| {
+            accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(1));
+            accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+        },
+        tx_from_1_to_0,
+        |mut wds| {
+            let new_account = Address::repeat_byte(0xad);
+            wds[0]
+                .id(10)
+                .validator_id(1)
+                .address(MOCK_ACCOUNTS[0])
+                .amount(5);
+            wds[1].id(11).validator_id(2).address(new_account).amount(7);
+            wds[2].id(12).validator_id(2).address(new_account).amount(3);
+            wds[3]
+                .id(13)
+                .validator_id(3)
+                .address(MOCK_ACCOUNTS[1])
+                .amount(0);
+        },
+        |block, _tx| block,
+    )
+    .unwrap()
+    .into();
+    let builder = BlockData::new_from_geth_data(block.clone())
+        .new_circuit_input_builder()
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    block_convert(&builder).unwrap()
+}
+
+fn run(circuit: &WithdrawalCircuit<Fr>) -> Result<(), Vec<VerifyFailure>> {
+    let rows = circuit.max_rws.max(circuit.max_withdrawals + 1);
+    let k = log2_ceil(rows + WithdrawalCircuit::<Fr>::unusable_rows()).max(6);
+    let prover = MockProver::<Fr>::run(k, circuit, vec![]).unwrap();
+    prover.verify()
+}
+
+#[test]
+fn withdrawal_circuit_credits() {
+    let block = withdrawals_block();
+    let circuit = WithdrawalCircuit::<Fr>::new_from_block(&block);
+    assert_eq!(
+        circuit
+            .credits
+            .iter()
+            .map(|credit| credit.is_create())
+            .collect::<Vec<_>>(),
+        vec![false, true, false, false]
+    );
+    assert_eq!(run(&circuit), Ok(()));
+
+    // The credits are also consistent with the EndBlock step and the state circuit.
+    CircuitTestBuilder::<2, 1>::new_from_block(block).run();
+}
+
+#[test]
+fn withdrawal_circuit_wrong_balance() {
+    let block = withdrawals_block();
+    let mut circuit = WithdrawalCircuit::<Fr>::new_from_block(&block);
+    circuit.credits[0].balance_prev = circuit.credits[0].balance_prev + Word::one();
+    assert!(run(&circuit).is_err());
+}
+
+#[test]
+fn withdrawal_circuit_missing_creation() {
+    let block = withdrawals_block();
+    let mut circuit = WithdrawalCircuit::<Fr>::new_from_block(&block);
+    circuit.credits[1].code_hash = CodeDB::empty_code_hash().to_word();
+    assert!(run(&circuit).is_err());
+}
+
+#[test]
+fn withdrawal_circuit_missing_credit() {
+    let block = withdrawals_block();
+    let mut circuit = WithdrawalCircuit::<Fr>::new_from_block(&block);
+    circuit.credits.truncate(1);
+    assert!(run(&circuit).is_err());
+}
@@ -3,7 +3,13 @@
 //! used to generate witnesses for circuits.
 
 mod block;
-pub use block::{block_convert, Block, BlockContext};
+pub use block::{
+    block_convert, block_convert_batch, block_convert_with_prev_pi_digest, Block, BlockContext,
+};
+mod dump;
+pub use dump::{TableDiff, WitnessDump, WITNESS_DUMP_VERSION};
+mod lookups;
+pub use lookups::{check_lookups, DanglingLookup};
 mod mpt;
 pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates};
 mod rw;
@@ -1,17 +1,21 @@
 use super::{ExecStep, Rw, RwMap, Transaction};
+#[cfg(feature = "evm-circuit")]
 use crate::{
     evm_circuit::{detect_fixed_table_tags, EvmCircuit},
-    exp_circuit::param::OFFSET_INCREMENT,
-    instance::public_data_convert,
+    table::exp_table::OFFSET_INCREMENT,
+    util::{log2_ceil, SubCircuit},
+};
+use crate::{
+    instance::{public_data_convert, PublicInputs},
     table::BlockContextFieldTag,
-    util::{log2_ceil, word::WordLoHi, SubCircuit},
+    util::word::WordLoHi,
 };
 use bus_mapping::{
     circuit_input_builder::{self, CopyEvent, ExpEvent, FeatureConfig, FixedCParams, Withdrawal},
     state_db::CodeDB,
     Error,
 };
-use eth_types::{Address, Field, ToScalar, Word, H256};
+use eth_types::{sign_types::SignData, Address, Field, ToScalar, Word, H256};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
 
@@ -39,6 +43,8 @@ pub struct Block<F> {
     pub copy_events: Vec<CopyEvent>,
     /// Exponentiation traces for the exponentiation circuit's table.
     pub exp_events: Vec<ExpEvent>,
+    /// Signatures verified by the ecRecover calls, for the sig table.
+    pub ecrecover_events: Vec<SignData>,
     /// Pad exponentiation circuit to make selectors fixed.
     pub exp_circuit_pad_to: usize,
     /// Circuit Setup Parameters
@@ -49,6 +55,8 @@ pub struct Block<F> {
     pub sha3_inputs: Vec<Vec<u8>>,
     /// State root of the previous block
     pub prev_state_root: Word, // TODO: Make this H256
+    /// Public inputs digest of the previous block, used to chain proofs
+    pub prev_pi_digest: H256,
     /// Keccak inputs
     pub keccak_inputs: Vec<Vec<u8>>,
     /// Original Block from geth
@@ -102,9 +110,22 @@ impl<F: Field> Block<F> {
         self.eth_block.withdrawals_root.unwrap_or_default()
     }
 
+    /// Return the rw counter of the first withdrawal credit of this block, and the one following
+    /// the last credit.  The credits are the account rws of the last EndBlock step, done after
+    /// its call context lookup of the last tx id.
+    pub fn withdrawal_rw_counters(&self) -> (usize, usize) {
+        let step = &self.end_block_last;
+        let start = step.rwc.0 + !self.txs.is_empty() as usize;
+        let num_rws = (0..step.rw_indices_len())
+            .filter(|&idx| matches!(self.get_rws(step, idx), Rw::Account { .. }))
+            .count();
+        (start, start + num_rws)
+    }
+
     /// Obtains the expected Circuit degree needed in order to be able to test
     /// the EvmCircuit with this block without needing to configure the
     /// `ConstraintSystem`.
+    #[cfg(feature = "evm-circuit")]
     pub fn get_test_degree(&self) -> u32 {
         let num_rows_required_for_execution_steps: usize =
             EvmCircuit::<F>::get_num_rows_required(self);
@@ -272,6 +293,17 @@ impl From<&circuit_input_builder::Block> for BlockContext {
 /// Convert a block struct in bus-mapping to a witness block used in circuits
 pub fn block_convert<F: Field>(
     builder: &circuit_input_builder::CircuitInputBuilder<FixedCParams>,
+) -> Result<Block<F>, Error> {
+    block_convert_with_prev_pi_digest(builder, H256::zero())
+}
+
+/// Convert a block struct in bus-mapping to a witness block whose public inputs are chained to
+/// the previous block through its public inputs digest, see [`PublicData::get_rpi_digest`].
+///
+/// [`PublicData::get_rpi_digest`]: crate::instance::PublicData::get_rpi_digest
+pub fn block_convert_with_prev_pi_digest<F: Field>(
+    builder: &circuit_input_builder::CircuitInputBuilder<FixedCParams>,
+    prev_pi_digest: H256,
 ) -> Result<Block<F>, Error> {
     let block = &builder.block;
     let code_db = &builder.code_db;
@@ -288,11 +320,13 @@ pub fn block_convert<F: Field>(
         bytecodes: code_db.clone(),
         copy_events: block.copy_events.clone(),
         exp_events: block.exp_events.clone(),
+        ecrecover_events: block.ecrecover_events.clone(),
         sha3_inputs: block.sha3_inputs.clone(),
         circuits_params: builder.circuits_params,
         feature_config: builder.feature_config,
         exp_circuit_pad_to: <usize>::default(),
         prev_state_root: block.prev_state_root,
+        prev_pi_digest,
         keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
         eth_block: block.eth_block.clone(),
     };
@@ -302,7 +336,91 @@ pub fn block_convert<F: Field>(
         block.circuits_params.max_withdrawals,
         block.circuits_params.max_calldata,
     );
+    let tx_bytes = public_data.get_tx_bytes(
+        block.circuits_params.max_txs,
+        block.circuits_params.max_calldata,
+    );
     // PI Circuit
-    block.keccak_inputs.extend_from_slice(&[rpi_bytes]);
+    block
+        .keccak_inputs
+        .extend_from_slice(&[rpi_bytes, tx_bytes]);
     Ok(block)
 }
+
+/// Convert a batch of consecutive blocks to witness blocks, chaining the public inputs digest of
+/// each block to the previous one.  Each block must start from the state root of the previous
+/// block and its parent hash must be the hash of the previous block, so that the proofs of the
+/// batch can be aggregated by the `BatchCircuit`.
+pub fn block_convert_batch<F: Field>(
+    builders: &[circuit_input_builder::CircuitInputBuilder<FixedCParams>],
+) -> Result<Vec<Block<F>>, Error> {
+    let mut blocks = Vec::with_capacity(builders.len());
+    let mut prev_inputs: Option<PublicInputs> = None;
+    for builder in builders {
+        let prev_pi_digest = prev_inputs
+            .as_ref()
+            .map_or(H256::zero(), |prev| prev.rpi_digest);
+        let block: Block<F> = block_convert_with_prev_pi_digest(builder, prev_pi_digest)?;
+        let params = block.circuits_params;
+        let inputs = public_data_convert(&block).get_public_inputs(
+            params.max_txs,
+            params.max_withdrawals,
+            params.max_calldata,
+        );
+        if let Some(prev) = prev_inputs {
+            if inputs.prev_state_root != prev.state_root || inputs.parent_hash != prev.block_hash {
+                return Err(Error::UnchainedBlock(builder.block.number));
+            }
+        }
+        prev_inputs = Some(inputs);
+        blocks.push(block);
+    }
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    fn builder() -> circuit_input_builder::CircuitInputBuilder<FixedCParams> {
+        let code = bytecode! {
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        BlockData::new_from_geth_data(block.clone())
+            .new_circuit_input_builder()
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap()
+    }
+
+    #[test]
+    fn block_convert_batch_chains_blocks() {
+        let mut builders = [builder(), builder()];
+        builders[0].block.eth_block.hash = Some(H256::repeat_byte(0xaa));
+        builders[0].block.eth_block.state_root = H256::repeat_byte(0x11);
+        assert!(matches!(
+            block_convert_batch::<Fr>(&builders),
+            Err(Error::UnchainedBlock(_))
+        ));
+
+        builders[1].block.history_hashes = vec![Word::from_big_endian(&[0xaa; 32])];
+        builders[1].block.prev_state_root = Word::from_big_endian(&[0x11; 32]);
+        let blocks = block_convert_batch::<Fr>(&builders).unwrap();
+        let params = blocks[0].circuits_params;
+        assert_eq!(blocks[0].prev_pi_digest, H256::zero());
+        assert_eq!(
+            blocks[1].prev_pi_digest,
+            public_data_convert(&blocks[0]).get_rpi_digest(
+                params.max_txs,
+                params.max_withdrawals,
+                params.max_calldata
+            )
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Stable dump of the witness tables.
+//!
+//! A [`WitnessDump`] contains the rows of the tables produced by the witness generation, encoded
+//! as big-endian hex strings.  The encoding doesn't depend on the in-memory representation of the
+//! witness, so dumps produced by two versions of this crate from the same trace can be compared
+//! row by row with [`WitnessDump::diff`].
+
+use super::Block;
+use crate::{
+    table::{BytecodeFieldTag, CopyTable, ExpTable, TxTable},
+    util::{word::WordLoHi, Challenges},
+};
+use eth_types::Field;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Write},
+};
+
+/// Version of the dump format.  Increase it whenever the layout of a dumped table changes, so
+/// that dumps from incompatible versions are not compared row by row.
+pub const WITNESS_DUMP_VERSION: u32 = 1;
+
+/// Serializable dump of the witness tables of a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessDump {
+    /// Version of the dump format
+    pub version: u32,
+    /// Rows of each table, indexed by table name
+    pub tables: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+/// Difference found between two [`WitnessDump`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableDiff {
+    /// The dumps have different format versions
+    Version {
+        /// Version of the left dump
+        left: u32,
+        /// Version of the right dump
+        right: u32,
+    },
+    /// The table only exists in one of the dumps
+    MissingTable {
+        /// Table name
+        table: String,
+        /// Whether the table exists in the left dump
+        in_left: bool,
+    },
+    /// The table has a different number of rows in each dump
+    RowCount {
+        /// Table name
+        table: String,
+        /// Number of rows in the left dump
+        left: usize,
+        /// Number of rows in the right dump
+        right: usize,
+    },
+    /// The row at `index` differs between the dumps
+    Row {
+        /// Table name
+        table: String,
+        /// Row index
+        index: usize,
+        /// Row in the left dump
+        left: Vec<String>,
+        /// Row in the right dump
+        right: Vec<String>,
+    },
+}
+
+impl fmt::Display for TableDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version { left, right } => {
+                write!(f, "dump version mismatch: {} != {}", left, right)
+            }
+            Self::MissingTable { table, in_left } => write!(
+                f,
+                "table {} only in {} dump",
+                table,
+                if *in_left { "left" } else { "right" }
+            ),
+            Self::RowCount { table, left, right } => {
+                write!(f, "table {} rows: {} != {}", table, left, right)
+            }
+            Self::Row {
+                table,
+                index,
+                left,
+                right,
+            } => write!(
+                f,
+                "table {} row {}:\n  - {}\n  + {}",
+                table,
+                index,
+                left.join(" "),
+                right.join(" ")
+            ),
+        }
+    }
+}
+
+fn value_to_hex<F: Field>(value: Value<F>) -> String {
+    let mut hex = String::new();
+    value.map(|v| hex = field_to_hex(v));
+    hex
+}
+
+fn field_to_hex<F: Field>(value: F) -> String {
+    let mut bytes = value.to_repr();
+    bytes.reverse();
+    hex::encode(bytes)
+}
+
+impl WitnessDump {
+    /// Dump the witness tables of a block.  It fails on a tx that doesn't fit in the tx table.
+    pub fn from_block<F: Field>(block: &Block<F>) -> Result<Self, Error> {
+        let mut tables = BTreeMap::new();
+
+        let rw_rows = block
+            .rws
+            .table_assignments()
+            .iter()
+            .map(|rw| {
+                rw.table_assignment::<F>()
+                    .unwrap()
+                    .values()
+                    .into_iter()
+                    .map(field_to_hex)
+                    .collect_vec()
+            })
+            .collect_vec();
+        tables.insert("rw".to_string(), rw_rows);
+
+        let block_rows = block
+            .context
+            .table_assignments::<F>()
+            .into_iter()
+            .map(|row| row.into_iter().map(value_to_hex).collect_vec())
+            .collect_vec();
+        tables.insert("block".to_string(), block_rows);
+
+        // The rows of the calldata follow the rows of the fields of all the txs, as in the table.
+        let (tx_rows, calldata_rows): (Vec<_>, Vec<_>) = block
+            .txs
+            .iter()
+            .map(TxTable::assignments::<F>)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        let tx_rows = tx_rows
+            .into_iter()
+            .chain(calldata_rows)
+            .flatten()
+            .map(|row| row.into_iter().map(value_to_hex).collect_vec())
+            .collect_vec();
+        tables.insert("tx".to_string(), tx_rows);
+
+        // CodeDB is a HashMap, sort by code hash to get a deterministic order.
+        let bytecode_rows = block
+            .bytecodes
+            .clone()
+            .into_iter()
+            .sorted_by_key(|bytecode| bytecode.hash())
+            .flat_map(|bytecode| {
+                let code_hash = WordLoHi::<F>::from(bytecode.hash());
+                std::iter::once([
+                    code_hash.lo(),
+                    code_hash.hi(),
+                    F::from(BytecodeFieldTag::Header as u64),
+                    F::ZERO,
+                    F::ZERO,
+                    F::from(bytecode.codesize() as u64),
+                ])
+                .chain(bytecode.code_vec().into_iter().enumerate().map(
+                    move |(index, (byte, is_code))| {
+                        [
+                            code_hash.lo(),
+                            code_hash.hi(),
+                            F::from(BytecodeFieldTag::Byte as u64),
+                            F::from(index as u64),
+                            F::from(is_code.into()),
+                            F::from(byte.into()),
+                        ]
+                    },
+                ))
+                .map(|row| row.into_iter().map(field_to_hex).collect_vec())
+                .collect_vec()
+            })
+            .collect_vec();
+        tables.insert("bytecode".to_string(), bytecode_rows);
+
+        let challenges = Challenges::mock(
+            Value::known(block.randomness),
+            Value::known(block.randomness),
+        );
+        let copy_rows = block
+            .copy_events
+            .iter()
+            .flat_map(|copy_event| CopyTable::assignments(copy_event, challenges))
+            .map(|(tag, table_row, circuit_row)| {
+                std::iter::once(field_to_hex(F::from(tag as u64)))
+                    .chain(table_row.into_iter().map(|(value, _)| value_to_hex(value)))
+                    .chain(
+                        circuit_row
+                            .into_iter()
+                            .map(|(value, _)| value_to_hex(value)),
+                    )
+                    .collect_vec()
+            })
+            .collect_vec();
+        tables.insert("copy".to_string(), copy_rows);
+
+        let exp_rows = block
+            .exp_events
+            .iter()
+            .flat_map(ExpTable::assignments::<F>)
+            .map(|row| row.into_iter().map(field_to_hex).collect_vec())
+            .collect_vec();
+        tables.insert("exp".to_string(), exp_rows);
+
+        // The keccak inputs are collected from a HashSet, so sort them.
+        let keccak_rows = block
+            .keccak_inputs
+            .iter()
+            .map(hex::encode)
+            .sorted()
+            .map(|input| vec![input])
+            .collect_vec();
+        tables.insert("keccak_inputs".to_string(), keccak_rows);
+
+        Ok(Self {
+            version: WITNESS_DUMP_VERSION,
+            tables,
+        })
+    }
+
+    /// Write the dump as JSON.
+    pub fn write<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Read a dump from JSON.
+    pub fn read<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Compare two dumps and return all the differences found.  Rows of each table are compared
+    /// by index.  If the dump versions differ no table is compared.
+    pub fn diff(&self, other: &Self) -> Vec<TableDiff> {
+        if self.version != other.version {
+            return vec![TableDiff::Version {
+                left: self.version,
+                right: other.version,
+            }];
+        }
+        let mut diffs = Vec::new();
+        for name in self.tables.keys().chain(other.tables.keys()).unique() {
+            let (left, right) = match (self.tables.get(name), other.tables.get(name)) {
+                (Some(left), Some(right)) => (left, right),
+                (left, _) => {
+                    diffs.push(TableDiff::MissingTable {
+                        table: name.clone(),
+                        in_left: left.is_some(),
+                    });
+                    continue;
+                }
+            };
+            if left.len() != right.len() {
+                diffs.push(TableDiff::RowCount {
+                    table: name.clone(),
+                    left: left.len(),
+                    right: right.len(),
+                });
+            }
+            for (index, (left_row, right_row)) in left.iter().zip(right.iter()).enumerate() {
+                if left_row != right_row {
+                    diffs.push(TableDiff::Row {
+                        table: name.clone(),
+                        index,
+                        left: left_row.clone(),
+                        right: right_row.clone(),
+                    });
+                }
+            }
+        }
+        diffs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::bytecode;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    fn dump() -> WitnessDump {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SHA3
+            STOP
+        };
+        let block = CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .build_block()
+        .unwrap();
+        WitnessDump::from_block(&block).unwrap()
+    }
+
+    #[test]
+    fn witness_dump_roundtrip() {
+        let dump = dump();
+        assert!(!dump.tables["rw"].is_empty());
+        assert!(!dump.tables["copy"].is_empty());
+        assert!(!dump.tables["tx"].is_empty());
+
+        let mut buf = Vec::new();
+        dump.write(&mut buf).unwrap();
+        let read = WitnessDump::read(buf.as_slice()).unwrap();
+        assert_eq!(read, dump);
+        assert_eq!(dump.diff(&dump()), vec![]);
+    }
+
+    #[test]
+    fn witness_dump_diff() {
+        let left = dump();
+        let mut right = left.clone();
+        right.tables.get_mut("rw").unwrap()[3][8] = field_to_hex(Fr::from(0xdead_beef));
+        right.tables.get_mut("exp").unwrap().push(vec![]);
+        right.tables.remove("keccak_inputs");
+
+        let diffs = left.diff(&right);
+        assert_eq!(diffs.len(), 3);
+        assert!(matches!(&diffs[0], TableDiff::RowCount { table, .. } if table == "exp"));
+        assert!(matches!(
+            &diffs[1],
+            TableDiff::MissingTable { table, in_left: true } if table == "keccak_inputs"
+        ));
+        assert!(matches!(&diffs[2], TableDiff::Row { table, index: 3, .. } if table == "rw"));
+    }
+}
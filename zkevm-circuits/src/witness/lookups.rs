@@ -0,0 +1,340 @@
+//! Check of the lookups between the circuits on the witness of a block.
+//!
+//! A lookup into the table of another circuit only fails when proving, as an unsatisfied lookup
+//! of the whole block that doesn't say which row is missing.  [`check_lookups`] recomputes the
+//! lookups from the witness, and reports the ones whose row isn't in the producing table:
+//!
+//! - the EVM circuit looks up the opcodes it executes in the bytecode table, and the
+//!   exponentiations in the exp table
+//! - the copy circuit looks up the bytes it copies in the bytecode, tx and rw tables, and the bytes
+//!   of a `SHA3` in the keccak table
+//! - the bytecode circuit looks up the hash of each bytecode in the keccak table
+
+use super::{Block, Rw};
+use crate::table::TxLogFieldTag;
+use bus_mapping::circuit_input_builder::{CopyDataType, ExecState, NumberOrHash};
+use eth_types::{evm_types::OpcodeId, Field, Word, H256};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+/// Lookup of a circuit whose row isn't in the table of the producing circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingLookup {
+    /// Circuit performing the lookup
+    pub circuit: &'static str,
+    /// Table looked up
+    pub table: &'static str,
+    /// Looked up row
+    pub row: String,
+}
+
+impl fmt::Display for DanglingLookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} circuit lookup of {} not in the {} table",
+            self.circuit, self.row, self.table
+        )
+    }
+}
+
+/// Check the lookups between the circuits of the block, and return the dangling ones.
+pub fn check_lookups<F: Field>(block: &Block<F>) -> Vec<DanglingLookup> {
+    // CodeDB is a HashMap, sort by code hash to report in a deterministic order.
+    let bytecodes: BTreeMap<H256, Vec<(u8, bool)>> = block
+        .bytecodes
+        .clone()
+        .into_iter()
+        .map(|bytecode| (bytecode.hash_h256(), bytecode.code_vec()))
+        .collect();
+    let calldata: HashMap<usize, &[u8]> = block
+        .txs
+        .iter()
+        .map(|tx| (tx.id as usize, tx.call_data.as_ref()))
+        .collect();
+    let rws: HashMap<usize, &Rw> = block
+        .rws
+        .0
+        .values()
+        .flatten()
+        .map(|rw| (rw.rw_counter(), rw))
+        .collect();
+    let exp_events: HashMap<usize, (Word, Word)> = block
+        .exp_events
+        .iter()
+        .map(|event| (event.identifier, (event.base, event.exponent)))
+        .collect();
+    let keccak_inputs: HashSet<&[u8]> = block.keccak_inputs.iter().map(Vec::as_slice).collect();
+
+    let mut dangling = Vec::new();
+    let mut push = |circuit, table, row| {
+        dangling.push(DanglingLookup {
+            circuit,
+            table,
+            row,
+        })
+    };
+
+    for tx in block.txs.iter() {
+        for step in tx.steps() {
+            let ExecState::Op(opcode) = step.exec_state else {
+                continue;
+            };
+            let code_hash = tx.calls()[step.call_index].code_hash;
+            let pc = step.pc as usize;
+            match bytecodes.get(&code_hash).map(|code| code.get(pc)) {
+                Some(Some(&(byte, true))) if byte == opcode.as_u8() => {}
+                // Running past the end of the code is a STOP that isn't looked up
+                Some(None) if opcode == OpcodeId::STOP => {}
+                _ => push(
+                    "EVM",
+                    "bytecode",
+                    format!("{:?} at pc {} of code {:?}", opcode, pc, code_hash),
+                ),
+            }
+
+            if opcode == OpcodeId::EXP && step.error.is_none() {
+                let operands = step
+                    .bus_mapping_instance
+                    .iter()
+                    .map(|op_ref| &block.rws[*op_ref])
+                    .filter(|rw| {
+                        matches!(
+                            rw,
+                            Rw::Stack {
+                                is_write: false,
+                                ..
+                            }
+                        )
+                    })
+                    .map(|rw| rw.stack_value())
+                    .collect::<Vec<_>>();
+                // The exponentiation is identified by the rw counter after the step
+                let identifier = step.rwc.0 + step.bus_mapping_instance.len();
+                if operands.len() != 2
+                    || exp_events.get(&identifier) != Some(&(operands[0], operands[1]))
+                {
+                    push(
+                        "EVM",
+                        "exp",
+                        format!("{:?} at rw counter {}", operands, identifier),
+                    );
+                }
+            }
+        }
+    }
+
+    for event in block.copy_events.iter() {
+        for (index, &(byte, is_code)) in event.bytes.iter().enumerate() {
+            let src_addr = event.src_addr + index as u64;
+            // The bytes after the end of the source are padding, not read
+            if src_addr < event.src_addr_end {
+                let read_rw_counter = event.rw_counter(2 * index) as usize;
+                match (event.src_type, &event.src_id) {
+                    (CopyDataType::Bytecode, NumberOrHash::Hash(code_hash)) => {
+                        let code_byte = bytecodes
+                            .get(code_hash)
+                            .and_then(|code| code.get(src_addr as usize));
+                        if code_byte != Some(&(byte, is_code)) {
+                            push(
+                                "copy",
+                                "bytecode",
+                                format!("byte {} of code {:?}", src_addr, code_hash),
+                            );
+                        }
+                    }
+                    (CopyDataType::TxCalldata, NumberOrHash::Number(tx_id)) => {
+                        let calldata_byte = calldata
+                            .get(tx_id)
+                            .and_then(|calldata| calldata.get(src_addr as usize));
+                        if calldata_byte != Some(&byte) {
+                            push(
+                                "copy",
+                                "tx",
+                                format!("calldata byte {} of tx {}", src_addr, tx_id),
+                            );
+                        }
+                    }
+                    (CopyDataType::Memory, NumberOrHash::Number(call_id)) => {
+                        match rws.get(&read_rw_counter) {
+                            Some(Rw::Memory {
+                                is_write: false,
+                                call_id: rw_call_id,
+                                memory_address,
+                                byte: rw_byte,
+                                ..
+                            }) if rw_call_id == call_id
+                                && *memory_address == src_addr
+                                && *rw_byte == byte => {}
+                            _ => push(
+                                "copy",
+                                "rw",
+                                format!(
+                                    "memory read of byte {} of call {} at rw counter {}",
+                                    src_addr, call_id, read_rw_counter
+                                ),
+                            ),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let dst_addr = event.dst_addr + index as u64;
+            let write_rw_counter = event.rw_counter(2 * index + 1) as usize;
+            match (event.dst_type, &event.dst_id) {
+                (CopyDataType::Memory, NumberOrHash::Number(call_id)) => {
+                    match rws.get(&write_rw_counter) {
+                        Some(Rw::Memory {
+                            is_write: true,
+                            call_id: rw_call_id,
+                            memory_address,
+                            byte: rw_byte,
+                            ..
+                        }) if rw_call_id == call_id
+                            && *memory_address == dst_addr
+                            && *rw_byte == byte => {}
+                        _ => push(
+                            "copy",
+                            "rw",
+                            format!(
+                                "memory write of byte {} of call {} at rw counter {}",
+                                dst_addr, call_id, write_rw_counter
+                            ),
+                        ),
+                    }
+                }
+                (CopyDataType::TxLog, NumberOrHash::Number(tx_id)) => {
+                    match rws.get(&write_rw_counter) {
+                        Some(Rw::TxLog {
+                            is_write: true,
+                            tx_id: rw_tx_id,
+                            log_id,
+                            field_tag: TxLogFieldTag::Data,
+                            index: rw_index,
+                            value,
+                            ..
+                        }) if rw_tx_id == tx_id
+                            && Some(*log_id) == event.log_id
+                            && *rw_index as u64 == dst_addr
+                            && *value == Word::from(byte) => {}
+                        _ => push(
+                            "copy",
+                            "rw",
+                            format!(
+                                "log data byte {} of tx {} at rw counter {}",
+                                dst_addr, tx_id, write_rw_counter
+                            ),
+                        ),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if event.dst_type == CopyDataType::RlcAcc {
+            let input = event
+                .bytes
+                .iter()
+                .map(|(byte, _)| *byte)
+                .collect::<Vec<_>>();
+            if !keccak_inputs.contains(input.as_slice()) {
+                push(
+                    "copy",
+                    "keccak",
+                    format!(
+                        "hash of {} bytes at rw counter {}",
+                        input.len(),
+                        event.rw_counter_start.0
+                    ),
+                );
+            }
+        }
+    }
+
+    for (code_hash, code) in bytecodes.iter() {
+        let code = code.iter().map(|(byte, _)| *byte).collect::<Vec<_>>();
+        if !keccak_inputs.contains(code.as_slice()) {
+            push(
+                "bytecode",
+                "keccak",
+                format!("hash of code {:?}", code_hash),
+            );
+        }
+    }
+
+    dangling
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::bytecode;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    fn block() -> Block<Fr> {
+        let code = bytecode! {
+            PUSH32(Word::from(0x1234))
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SHA3
+            PUSH1(0x20)
+            PUSH1(0x00)
+            LOG0
+            PUSH1(0x10)
+            PUSH1(0x00)
+            PUSH1(0x40)
+            CODECOPY
+            PUSH1(0x03)
+            PUSH1(0x02)
+            EXP
+            STOP
+        };
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .build_block()
+        .unwrap()
+    }
+
+    #[test]
+    fn lookups_consistent() {
+        assert_eq!(check_lookups(&block()), vec![]);
+    }
+
+    #[test]
+    fn lookups_dangling() {
+        let mut block = block();
+        // The bytes hashed by the SHA3 aren't the ones in memory
+        let sha3 = block
+            .copy_events
+            .iter_mut()
+            .find(|event| event.dst_type == CopyDataType::RlcAcc)
+            .unwrap();
+        sha3.bytes[31].0 ^= 1;
+        block.exp_events.clear();
+        block.keccak_inputs.retain(|input| input.len() == 32);
+
+        let mut lookups = check_lookups(&block)
+            .into_iter()
+            .map(|lookup| (lookup.circuit, lookup.table))
+            .collect::<Vec<_>>();
+        // All the bytecodes are dangling
+        lookups.dedup();
+        assert_eq!(
+            lookups,
+            vec![
+                ("EVM", "exp"),
+                ("copy", "rw"),
+                ("copy", "keccak"),
+                ("bytecode", "keccak"),
+            ]
+        );
+    }
+}
@@ -28,6 +28,12 @@ impl MptUpdate {
                     MPTProofType::StorageChanged
                 }
             }
+            // The code_hash is only set to 0 when the account is destructed, as 0 -> 0 is a
+            // non-existing proof.
+            Key::Account {
+                field_tag: AccountFieldTag::CodeHash,
+                ..
+            } if self.new_value.is_zero() => MPTProofType::AccountDestructed,
             Key::Account { field_tag, .. } => field_tag.into(),
         };
         F::from(proof_type as u64)
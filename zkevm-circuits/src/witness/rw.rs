@@ -125,19 +125,34 @@ impl RwMap {
     }
     /// Prepad Rw::Start rows to target length
     pub fn table_assignments_prepad(rows: &[Rw], target_len: usize) -> (Vec<Rw>, usize) {
+        let (rows, padding_length) = Self::table_assignments_prepad_iter(rows, target_len);
+        (rows.collect(), padding_length)
+    }
+    /// Prepad Rw::Start rows to target length, as an iterator over the rows that doesn't copy
+    /// them, so that a table is assigned without a second copy of its rows in memory.
+    pub fn table_assignments_prepad_iter(
+        rows: &[Rw],
+        target_len: usize,
+    ) -> (impl Iterator<Item = Rw> + '_, usize) {
         // Remove Start rows as we will add them from scratch.
-        let rows: Vec<Rw> = rows
+        let start_len = rows
             .iter()
-            .skip_while(|rw| matches!(rw, Rw::Start { .. }))
-            .cloned()
-            .collect();
+            .take_while(|rw| matches!(rw, Rw::Start { .. }))
+            .count();
+        let rows = &rows[start_len..];
         let padding_length = Self::padding_len(rows.len(), target_len);
         let padding = (1..=padding_length).map(|rw_counter| Rw::Start { rw_counter });
-        (padding.chain(rows.into_iter()).collect(), padding_length)
+        (padding.chain(rows.iter().copied()), padding_length)
     }
     /// Build Rws for assignment
     pub fn table_assignments(&self) -> Vec<Rw> {
-        let mut rows: Vec<Rw> = self.0.values().flatten().cloned().collect();
+        Self::sorted(self.0.values().flatten().cloned().collect())
+    }
+    /// Build Rws for assignment, moving them out of the map instead of copying them.
+    pub fn into_table_assignments(self) -> Vec<Rw> {
+        Self::sorted(self.0.into_values().flatten().collect())
+    }
+    fn sorted(mut rows: Vec<Rw>) -> Vec<Rw> {
         rows.sort_by_key(|row| {
             (
                 row.tag() as u64,
@@ -171,6 +186,15 @@ pub enum Rw {
         is_warm: bool,
         is_warm_prev: bool,
     },
+    /// TxDestructedAccount
+    TxDestructedAccount {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        is_destructed: bool,
+        is_destructed_prev: bool,
+    },
     /// TxAccessListAccountStorage
     TxAccessListAccountStorage {
         rw_counter: usize,
@@ -348,6 +372,17 @@ impl Rw {
         }
     }
 
+    pub(crate) fn tx_destructed_account_value_pair(&self) -> (bool, bool) {
+        match self {
+            Self::TxDestructedAccount {
+                is_destructed,
+                is_destructed_prev,
+                ..
+            } => (*is_destructed, *is_destructed_prev),
+            _ => unreachable!(),
+        }
+    }
+
     pub(crate) fn tx_refund_value_pair(&self) -> (u64, u64) {
         match self {
             Self::TxRefund {
@@ -478,6 +513,7 @@ impl Rw {
             | Self::Stack { rw_counter, .. }
             | Self::AccountStorage { rw_counter, .. }
             | Self::TxAccessListAccount { rw_counter, .. }
+            | Self::TxDestructedAccount { rw_counter, .. }
             | Self::TxAccessListAccountStorage { rw_counter, .. }
             | Self::TxRefund { rw_counter, .. }
             | Self::Account { rw_counter, .. }
@@ -494,6 +530,7 @@ impl Rw {
             | Self::Stack { is_write, .. }
             | Self::AccountStorage { is_write, .. }
             | Self::TxAccessListAccount { is_write, .. }
+            | Self::TxDestructedAccount { is_write, .. }
             | Self::TxAccessListAccountStorage { is_write, .. }
             | Self::TxRefund { is_write, .. }
             | Self::Account { is_write, .. }
@@ -510,6 +547,7 @@ impl Rw {
             Self::Stack { .. } => Target::Stack,
             Self::AccountStorage { .. } => Target::Storage,
             Self::TxAccessListAccount { .. } => Target::TxAccessListAccount,
+            Self::TxDestructedAccount { .. } => Target::TxDestructedAccount,
             Self::TxAccessListAccountStorage { .. } => Target::TxAccessListAccountStorage,
             Self::TxRefund { .. } => Target::TxRefund,
             Self::Account { .. } => Target::Account,
@@ -523,6 +561,7 @@ impl Rw {
         match self {
             Self::AccountStorage { tx_id, .. }
             | Self::TxAccessListAccount { tx_id, .. }
+            | Self::TxDestructedAccount { tx_id, .. }
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
             | Self::TxLog { tx_id, .. }
@@ -539,6 +578,9 @@ impl Rw {
             Self::TxAccessListAccount {
                 account_address, ..
             }
+            | Self::TxDestructedAccount {
+                account_address, ..
+            }
             | Self::TxAccessListAccountStorage {
                 account_address, ..
             }
@@ -578,6 +620,7 @@ impl Rw {
             | Self::Stack { .. }
             | Self::AccountStorage { .. }
             | Self::TxAccessListAccount { .. }
+            | Self::TxDestructedAccount { .. }
             | Self::TxAccessListAccountStorage { .. }
             | Self::TxRefund { .. }
             | Self::TxLog { .. } => None,
@@ -595,6 +638,7 @@ impl Rw {
             | Self::TxRefund { .. }
             | Self::Account { .. }
             | Self::TxAccessListAccount { .. }
+            | Self::TxDestructedAccount { .. }
             | Self::TxLog { .. }
             | Self::TxReceipt { .. } => None,
         }
@@ -610,6 +654,7 @@ impl Rw {
             | Self::TxLog { value, .. } => *value,
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => U256::from(*is_warm as u64),
+            Self::TxDestructedAccount { is_destructed, .. } => U256::from(*is_destructed as u64),
             Self::Memory { byte, .. } => U256::from(u64::from(*byte)),
             Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => U256::from(*value),
         }
@@ -624,6 +669,9 @@ impl Rw {
             | Self::TxAccessListAccountStorage { is_warm_prev, .. } => {
                 Some(U256::from(*is_warm_prev as u64))
             }
+            Self::TxDestructedAccount {
+                is_destructed_prev, ..
+            } => Some(U256::from(*is_destructed_prev as u64)),
             Self::TxRefund { value_prev, .. } => Some(U256::from(*value_prev)),
             Self::Start { .. }
             | Self::Stack { .. }
@@ -673,6 +721,21 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            Target::TxDestructedAccount,
+            container
+                .tx_destructed_account
+                .iter()
+                .map(|op| Rw::TxDestructedAccount {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    is_destructed: op.op().is_destructed,
+                    is_destructed_prev: op.op().is_destructed_prev,
+                })
+                .collect(),
+        );
         rws.insert(
             Target::TxAccessListAccountStorage,
             container